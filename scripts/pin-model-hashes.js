@@ -0,0 +1,62 @@
+#!/usr/bin/env node
+
+// Fill in the SHA-256 of every model in the bundled manifest from the
+// checksums Hugging Face publishes for its LFS files, and check the sizes.
+// Re-sign the manifest afterwards with sign-model-manifest.js.
+import fs from 'fs';
+import path from 'path';
+import { fileURLToPath } from 'url';
+
+const __filename = fileURLToPath(import.meta.url);
+const __dirname = path.dirname(__filename);
+
+const manifestPath = path.join(__dirname, '..', 'src-tauri', 'src', 'whisper', 'model_manifest.json');
+const manifest = JSON.parse(fs.readFileSync(manifestPath, 'utf8'));
+
+// https://huggingface.co/<repo>/resolve/<revision>/<file>
+function parseUrl(url) {
+  const match = url.match(/^https:\/\/huggingface\.co\/(.+?)\/resolve\/([^/]+)\/(.+)$/);
+  if (!match) {
+    throw new Error(`Not a Hugging Face download URL: ${url}`);
+  }
+  return { repo: match[1], revision: match[2], file: match[3] };
+}
+
+async function publishedFiles(repo, revision) {
+  const response = await fetch(`https://huggingface.co/api/models/${repo}/tree/${revision}`);
+  if (!response.ok) {
+    throw new Error(`Failed to list ${repo}: HTTP ${response.status}`);
+  }
+  return response.json();
+}
+
+const listings = new Map();
+let failed = false;
+
+for (const model of manifest.models) {
+  const { repo, revision, file } = parseUrl(model.url);
+  const key = `${repo}@${revision}`;
+  if (!listings.has(key)) {
+    listings.set(key, await publishedFiles(repo, revision));
+  }
+
+  const entry = listings.get(key).find((f) => f.path === file);
+  if (!entry?.lfs?.oid) {
+    console.error(`❌ ${model.name}: ${file} has no published LFS checksum`);
+    failed = true;
+    continue;
+  }
+  if (entry.lfs.size !== model.size) {
+    console.warn(`⚠️  ${model.name}: size ${model.size} updated to ${entry.lfs.size}`);
+    model.size = entry.lfs.size;
+  }
+  model.sha256 = entry.lfs.oid;
+  console.log(`✅ ${model.name}: ${model.sha256}`);
+}
+
+if (failed) {
+  process.exit(1);
+}
+
+fs.writeFileSync(manifestPath, JSON.stringify(manifest, null, 2) + '\n');
+console.log(`Updated ${manifestPath}`);
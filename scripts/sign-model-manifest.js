@@ -0,0 +1,76 @@
+#!/usr/bin/env node
+
+// Sign a model manifest with the project's Ed25519 key, writing <manifest>.sig.
+// Run after every change to the bundled manifest (e.g. pin-model-hashes.js);
+// the app refuses a bundled manifest whose signature doesn't verify.
+//
+//   node scripts/sign-model-manifest.js --generate <private-key.pem>
+//   node scripts/sign-model-manifest.js <private-key.pem> [manifest.json]
+//
+// --generate creates a new key pair and writes the public key the app embeds.
+// Keep the private key out of the repository.
+import crypto from 'crypto';
+import fs from 'fs';
+import path from 'path';
+import { fileURLToPath } from 'url';
+
+const __filename = fileURLToPath(import.meta.url);
+const __dirname = path.dirname(__filename);
+
+const whisperDir = path.join(__dirname, '..', 'src-tauri', 'src', 'whisper');
+const publicKeyPath = path.join(whisperDir, 'manifest_signing_key.pub');
+const bundledManifestPath = path.join(whisperDir, 'model_manifest.json');
+
+const args = process.argv.slice(2);
+
+if (args[0] === '--generate') {
+  const privateKeyPath = args[1];
+  if (!privateKeyPath) {
+    console.error('Usage: node sign-model-manifest.js --generate <private-key.pem>');
+    process.exit(1);
+  }
+  if (fs.existsSync(privateKeyPath)) {
+    console.error(`❌ ${privateKeyPath} already exists; refusing to overwrite it`);
+    process.exit(1);
+  }
+
+  const { publicKey, privateKey } = crypto.generateKeyPairSync('ed25519');
+  fs.mkdirSync(path.dirname(privateKeyPath), { recursive: true });
+  fs.writeFileSync(privateKeyPath, privateKey.export({ type: 'pkcs8', format: 'pem' }), { mode: 0o600 });
+
+  // The app expects the raw 32-byte key, which is the tail of the SPKI encoding
+  const raw = publicKey.export({ type: 'spki', format: 'der' }).subarray(-32);
+  fs.writeFileSync(publicKeyPath, raw.toString('base64') + '\n');
+
+  console.log(`✅ Private key written to ${privateKeyPath}`);
+  console.log(`✅ Public key written to ${publicKeyPath}`);
+  console.log('Sign the bundled manifest with the new key before building.');
+  process.exit(0);
+}
+
+const privateKeyPath = args[0] || process.env.MANIFEST_SIGNING_KEY;
+const manifestPath = args[1] || bundledManifestPath;
+
+if (!privateKeyPath) {
+  console.error('Usage: node sign-model-manifest.js <private-key.pem> [manifest.json]');
+  process.exit(1);
+}
+
+const privateKey = crypto.createPrivateKey(fs.readFileSync(privateKeyPath));
+const content = fs.readFileSync(manifestPath);
+const signature = crypto.sign(null, content, privateKey).toString('base64');
+
+// Check against the key the app embeds before publishing anything
+const embedded = fs.readFileSync(publicKeyPath, 'utf8').trim();
+const derived = crypto
+  .createPublicKey(privateKey)
+  .export({ type: 'spki', format: 'der' })
+  .subarray(-32)
+  .toString('base64');
+if (derived !== embedded) {
+  console.error(`❌ ${privateKeyPath} does not match the public key in ${publicKeyPath}`);
+  process.exit(1);
+}
+
+fs.writeFileSync(`${manifestPath}.sig`, signature + '\n');
+console.log(`✅ Signed ${manifestPath}`);
@@ -1,3 +1,4 @@
+import AVFoundation
 import Foundation
 import FluidAudio
 
@@ -25,6 +26,16 @@ struct TranscriptionResponse: Encodable {
 
 struct Segment: Encodable {
     let text: String
+    let start: Double?
+    let end: Double?
+    let tokens: [Token]
+}
+
+struct Token: Encodable {
+    let text: String
+    let start: Double
+    let end: Double
+    let confidence: Float
 }
 
 struct StatusResponse: Encodable {
@@ -36,6 +47,28 @@ struct StatusResponse: Encodable {
     let attention: String? = nil
 }
 
+struct HelloResponse: Encodable {
+    let type: String = "hello"
+    let protocolVersion: Int
+    let engine: String = "parakeet"
+    let engineVersion: String = "fluidaudio"
+    let capabilities: [String] = [
+        "load_model", "unload_model", "delete_model", "transcribe", "status", "shutdown", "progress"
+    ]
+
+    enum CodingKeys: String, CodingKey {
+        case type, engine, capabilities
+        case protocolVersion = "protocol_version"
+        case engineVersion = "engine_version"
+    }
+}
+
+struct ProgressResponse: Encodable {
+    let type: String = "progress"
+    let progress: Double
+    let message: String
+}
+
 struct ErrorResponse: Encodable {
     let type: String = "error"
     let code: String
@@ -72,6 +105,18 @@ var isModelLoaded = false
 var loadedModelVersion: SupportedModelVersion?
 var downloadedVersions = Set<SupportedModelVersion>()
 
+// Recordings longer than chunkDuration are transcribed in chunks sharing
+// overlapDuration seconds. Set by load_model; transcribe may override them.
+var chunkDuration: Double = 120
+var overlapDuration: Double = 15
+let sampleRate = 16_000
+
+// Versioned protocol state: 0 until the host's `hello` negotiates a version.
+// Every reply echoes the id of the request being handled.
+let supportedProtocolVersions = [1]
+var protocolVersion = 0
+var currentRequestId: Int?
+
 @main
 struct ParakeetSidecar {
     static func main() async {
@@ -108,8 +153,30 @@ struct ParakeetSidecar {
                     continue
                 }
 
+                currentRequestId = json["id"] as? Int
+
                 switch json["type"] as? String {
+                case "hello":
+                    let offered = json["protocol_versions"] as? [Int] ?? []
+                    if let version = offered.filter({ supportedProtocolVersions.contains($0) }).max() {
+                        protocolVersion = version
+                        sendResponse(HelloResponse(protocolVersion: version), encoder: encoder)
+                    } else {
+                        sendError("unsupported_protocol", message: "No common protocol version in \(offered)", encoder: encoder)
+                    }
+
                 case "load_model", "download_model":
+                    // Core ML runs the models at fp16 with full attention; nothing else can be honoured
+                    if let precision = json["precision"] as? String, precision != "fp16" {
+                        sendError("unsupported_option", message: "Parakeet cannot run at \(precision) precision on macOS", encoder: encoder)
+                        continue
+                    }
+                    if let attention = json["attention"] as? String, attention != "full" {
+                        sendError("unsupported_option", message: "Parakeet cannot use \(attention) attention on macOS", encoder: encoder)
+                        continue
+                    }
+                    chunkDuration = json["chunk_duration"] as? Double ?? chunkDuration
+                    overlapDuration = json["overlap_duration"] as? Double ?? overlapDuration
                     let version = parseModelVersion(json["model_version"], fallbackModelId: json["model_id"] as? String)
                     let forceDownload: Bool
                     if let explicit = json["force_download"] as? Bool {
@@ -136,7 +203,14 @@ struct ParakeetSidecar {
                         // Extract optional parameters from Rust backend
                         let language = json["language"] as? String
                         let translateToEnglish = json["translate_to_english"] as? Bool ?? false
-                        await transcribeFile(audioPath, language: language, translateToEnglish: translateToEnglish, encoder: encoder)
+                        await transcribeFile(
+                            audioPath,
+                            language: language,
+                            translateToEnglish: translateToEnglish,
+                            chunk: json["chunk_duration"] as? Double ?? chunkDuration,
+                            overlap: json["overlap_duration"] as? Double ?? overlapDuration,
+                            encoder: encoder
+                        )
                     } else {
                         sendError("missing_audio_path", message: "audio_path is required", encoder: encoder)
                     }
@@ -178,8 +252,10 @@ struct ParakeetSidecar {
             if forceDownload {
                 log("📥 Force-downloading Parakeet \(version.rawValue.uppercased()) via FluidAudio...")
                 log("🌐 This will download ~500MB. Please wait...")
+                sendProgress(0.0, message: "Downloading Parakeet \(version.rawValue.uppercased())", encoder: encoder)
                 models = try await AsrModels.downloadAndLoad(version: version.asrVersion)
                 downloadedVersions.insert(version)
+                sendProgress(0.9, message: "Initializing model", encoder: encoder)
             } else {
                 log("🔍 Attempting to load Parakeet \(version.rawValue.uppercased()) from cache...")
                 do {
@@ -247,7 +323,14 @@ struct ParakeetSidecar {
         downloadedVersions.remove(version)
     }
 
-    static func transcribeFile(_ audioPath: String, language: String? = nil, translateToEnglish: Bool = false, encoder: JSONEncoder) async {
+    static func transcribeFile(
+        _ audioPath: String,
+        language: String? = nil,
+        translateToEnglish: Bool = false,
+        chunk: Double = chunkDuration,
+        overlap: Double = overlapDuration,
+        encoder: JSONEncoder
+    ) async {
         // Check if model is loaded - DO NOT auto-download
         guard isModelLoaded else {
             sendError("model_not_loaded", message: "Parakeet model not loaded. Please download it first from Settings.", encoder: encoder)
@@ -268,13 +351,28 @@ struct ParakeetSidecar {
         }
 
         do {
+            if let samples = try readLongRecording(fileURL, longerThan: chunk) {
+                let tokens = try await transcribeChunked(samples, manager: manager, chunk: chunk, overlap: overlap)
+                let text = tokens.map(\.text).joined()
+                    .split(whereSeparator: \.isWhitespace)
+                    .joined(separator: " ")
+                let response = TranscriptionResponse(
+                    text: text,
+                    segments: buildSegments(tokens),
+                    language: language,
+                    duration: Float(samples.count) / Float(sampleRate)
+                )
+                sendResponse(response, encoder: encoder)
+                return
+            }
+
             // Transcribe the audio file (returns ASRResult)
             let result = try await manager.transcribe(fileURL)
 
             // Send transcription response
             let response = TranscriptionResponse(
                 text: result.text,
-                segments: [],
+                segments: buildSegments(tokens(from: result.tokenTimings ?? [])),
                 language: language,
                 duration: Float(result.duration)
             )
@@ -285,9 +383,103 @@ struct ParakeetSidecar {
         }
     }
 
+    /// Samples of a 16 kHz mono recording longer than `seconds`; nil when it is
+    /// short enough for FluidAudio to take in one go
+    static func readLongRecording(_ url: URL, longerThan seconds: Double) throws -> [Float]? {
+        let file = try AVAudioFile(forReading: url)
+        let format = file.processingFormat
+        guard Double(file.length) / format.sampleRate > seconds else { return nil }
+        guard format.sampleRate == Double(sampleRate), format.channelCount == 1 else {
+            log("⚠️  Not chunking \(format.sampleRate) Hz / \(format.channelCount) channel audio")
+            return nil
+        }
+        guard let buffer = AVAudioPCMBuffer(pcmFormat: format, frameCapacity: AVAudioFrameCount(file.length)) else {
+            return nil
+        }
+        try file.read(into: buffer)
+        guard let channel = buffer.floatChannelData?[0] else { return nil }
+        return Array(UnsafeBufferPointer(start: channel, count: Int(buffer.frameLength)))
+    }
+
+    /// Transcribe in overlapping chunks. Each overlap is split down the middle:
+    /// tokens starting in the first half belong to the earlier chunk, the rest
+    /// to the later one, so words at the seams are kept exactly once.
+    static func transcribeChunked(_ samples: [Float], manager: AsrManager, chunk: Double, overlap: Double) async throws -> [Token] {
+        let chunkLength = max(Int(chunk * Double(sampleRate)), sampleRate)
+        let overlapLength = min(Int(max(overlap, 0) * Double(sampleRate)), chunkLength / 2)
+        let step = chunkLength - overlapLength
+        let rate = Double(sampleRate)
+
+        var merged: [Token] = []
+        var start = 0
+        while true {
+            let end = min(start + chunkLength, samples.count)
+            let last = end == samples.count
+            let keepFrom = start == 0 ? 0 : Double(start + overlapLength / 2) / rate
+            let keepUntil = last ? Double.infinity : Double(end - overlapLength + overlapLength / 2) / rate
+
+            let offset = Double(start) / rate
+            let result = try await manager.transcribe(Array(samples[start..<end]))
+            var chunkTokens = tokens(from: result.tokenTimings ?? [], offset: offset)
+            if chunkTokens.isEmpty, !result.text.isEmpty {
+                chunkTokens = [Token(text: " " + result.text, start: offset, end: Double(end) / rate, confidence: result.confidence)]
+            }
+            merged += chunkTokens.filter { $0.start >= keepFrom && $0.start < keepUntil }
+
+            if last { return merged }
+            start += step
+        }
+    }
+
+    /// FluidAudio's token timings shifted `offset` seconds along the recording
+    static func tokens(from timings: [TokenTiming], offset: Double = 0) -> [Token] {
+        timings.map { timing in
+            // SentencePiece marks word starts with U+2581
+            Token(
+                text: timing.token.replacingOccurrences(of: "\u{2581}", with: " "),
+                start: timing.startTime + offset,
+                end: timing.endTime + offset,
+                confidence: timing.confidence
+            )
+        }
+    }
+
+    /// Group tokens into sentence-sized segments for subtitles and playback
+    static func buildSegments(_ timed: [Token]) -> [Segment] {
+        var segments: [Segment] = []
+        var tokens: [Token] = []
+
+        func flush() {
+            let text = tokens.map(\.text).joined().trimmingCharacters(in: .whitespaces)
+            if !text.isEmpty {
+                segments.append(Segment(text: text, start: tokens.first?.start, end: tokens.last?.end, tokens: tokens))
+            }
+            tokens.removeAll()
+        }
+
+        for token in timed {
+            if let previous = tokens.last, token.start - previous.end > 1.0 {
+                flush()
+            }
+            tokens.append(token)
+            if let last = token.text.trimmingCharacters(in: .whitespaces).last, ".?!".contains(last) {
+                flush()
+            }
+        }
+        flush()
+        return segments
+    }
+
     static func sendResponse<T: Encodable>(_ response: T, encoder: JSONEncoder) {
         do {
-            let data = try encoder.encode(response)
+            var data = try encoder.encode(response)
+            // Versioned protocol: tag the reply with the request it answers
+            if protocolVersion > 0, let id = currentRequestId,
+               var object = try JSONSerialization.jsonObject(with: data) as? [String: Any] {
+                object["v"] = protocolVersion
+                object["id"] = id
+                data = try JSONSerialization.data(withJSONObject: object)
+            }
             if let jsonString = String(data: data, encoding: .utf8) {
                 print(jsonString)
                 fflush(stdout)
@@ -298,6 +490,12 @@ struct ParakeetSidecar {
         }
     }
 
+    /// Streamed progress for the current request; legacy hosts don't expect these
+    static func sendProgress(_ progress: Double, message: String, encoder: JSONEncoder) {
+        guard protocolVersion > 0, currentRequestId != nil else { return }
+        sendResponse(ProgressResponse(progress: progress, message: message), encoder: encoder)
+    }
+
     static func sendError(_ code: String, message: String, encoder: JSONEncoder) {
         sendResponse(ErrorResponse(code: code, message: message), encoder: encoder)
     }
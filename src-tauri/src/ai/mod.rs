@@ -21,7 +21,7 @@ pub static AI_HTTP_CLIENT: Lazy<Client> = Lazy::new(|| {
 });
 
 pub use config::MAX_TEXT_LENGTH;
-pub use prompts::EnhancementOptions;
+pub use prompts::{EnhancementOptions, EnhancementPreset};
 
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,318 @@
+// Microphone test: records a short sample from one device and measures how
+// usable it is for dictation. The result is kept per device and seeds the voice
+// threshold and the automatic gain of later recordings on that device.
+
+use cpal::traits::{DeviceTrait, StreamTrait};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::capture_buffer::{CaptureBuffer, CAPTURE_SAMPLE_RATE};
+use super::capture_format::{resolve_capture_config, CaptureFormat};
+use super::dsp::DeviceLevels;
+use super::recorder::{build_input_stream, select_input_device};
+use super::silence_detector::voice_threshold_for_noise;
+
+/// Length of a test recording
+pub const TEST_DURATION: Duration = Duration::from_secs(5);
+/// Shortest sample worth measuring
+const MIN_TEST_DURATION: Duration = Duration::from_secs(1);
+/// Levels are measured over 20 ms frames
+const FRAME_SAMPLES: usize = CAPTURE_SAMPLE_RATE as usize / 50;
+/// Samples at or above this magnitude count as clipped
+const CLIP_LEVEL: f32 = 0.99;
+/// The quietest frames measure the room, the loudest the voice
+const NOISE_PERCENTILE: f32 = 0.1;
+const SPEECH_PERCENTILE: f32 = 0.9;
+/// Lowest threshold a device calibration may pick (very quiet, very clean mics)
+const MIN_VOICE_THRESHOLD: f32 = 0.0005;
+/// Keeps the SNR finite on digitally silent input
+const MIN_NOISE_FLOOR: f32 = 0.00001;
+/// SNR at which a microphone scores full marks
+const GOOD_SNR_DB: f32 = 30.0;
+/// Below this the loudest frames are just more room noise
+const NO_SPEECH_SNR_DB: f32 = 6.0;
+/// Below this the transcription quality drops noticeably
+const MIN_SNR_DB: f32 = 15.0;
+/// Normal conversation level (see the level meter's scale)
+const NORMAL_SPEECH: f32 = 0.02;
+/// Share of clipped samples that makes a sample unusable
+const MAX_CLIPPING_PERCENT: f32 = 5.0;
+
+/// Peak and clipping, measured on the device samples before resampling
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RawLevels {
+    pub peak: f32,
+    pub clipped: usize,
+    pub samples: usize,
+}
+
+impl RawLevels {
+    pub fn push(&mut self, samples: &[f32]) {
+        for &sample in samples {
+            let magnitude = sample.abs();
+            self.peak = self.peak.max(magnitude);
+            if magnitude >= CLIP_LEVEL {
+                self.clipped += 1;
+            }
+        }
+        self.samples += samples.len();
+    }
+}
+
+/// What a test recording says about a microphone
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MicMeasurement {
+    pub peak: f32,
+    pub rms: f32,
+    pub noise_floor: f32,
+    /// Typical RMS while speaking
+    pub speech_level: f32,
+    pub clipping_percent: f32,
+    /// Estimated signal-to-noise ratio in dB
+    pub snr_db: f32,
+}
+
+impl MicMeasurement {
+    /// Measure 16kHz mono `audio`; peak and clipping come from `raw`
+    pub fn from_samples(audio: &[f32], raw: &RawLevels) -> Result<Self, String> {
+        let min_samples = (MIN_TEST_DURATION.as_secs_f32() * CAPTURE_SAMPLE_RATE as f32) as usize;
+        if audio.len() < min_samples {
+            return Err("The test recording is too short to measure".to_string());
+        }
+
+        let mut frames: Vec<f32> = audio.chunks(FRAME_SAMPLES).map(rms).collect();
+        frames.sort_by(|a, b| a.total_cmp(b));
+        let percentile = |p: f32| frames[((frames.len() - 1) as f32 * p).round() as usize];
+        let noise_floor = percentile(NOISE_PERCENTILE);
+        let speech_level = percentile(SPEECH_PERCENTILE);
+
+        let snr_db =
+            20.0 * (speech_level.max(MIN_NOISE_FLOOR) / noise_floor.max(MIN_NOISE_FLOOR)).log10();
+        let clipping_percent = if raw.samples == 0 {
+            0.0
+        } else {
+            raw.clipped as f32 * 100.0 / raw.samples as f32
+        };
+
+        Ok(Self {
+            peak: raw.peak,
+            rms: rms(audio),
+            noise_floor,
+            speech_level,
+            clipping_percent,
+            snr_db,
+        })
+    }
+
+    /// Whether the loudest part of the sample stands out from the room at all
+    pub fn heard_speech(&self) -> bool {
+        self.snr_db >= NO_SPEECH_SNR_DB
+    }
+
+    /// Voice threshold for this microphone: above the room, below the voice
+    pub fn voice_threshold(&self) -> f32 {
+        let between = (self.noise_floor * self.speech_level).sqrt();
+        voice_threshold_for_noise(self.noise_floor, MIN_VOICE_THRESHOLD)
+            .min(between)
+            .max(MIN_VOICE_THRESHOLD)
+    }
+
+    /// Problems worth telling the user about, most serious first
+    pub fn issues(&self) -> Vec<String> {
+        let mut issues = Vec::new();
+        if !self.heard_speech() {
+            issues.push("No speech was heard during the test".to_string());
+        } else if self.snr_db < MIN_SNR_DB {
+            issues.push("Background noise is close to the level of your voice".to_string());
+        }
+        if self.clipping_percent > 0.1 {
+            issues.push("The input is clipping; lower the microphone gain".to_string());
+        }
+        if self.speech_level < NORMAL_SPEECH / 4.0 {
+            issues.push("Your voice is very quiet; raise the microphone gain".to_string());
+        }
+        issues
+    }
+
+    /// 0-100 rating used to compare microphones; `word_accuracy` is 0.0-1.0
+    pub fn score(&self, word_accuracy: Option<f32>) -> f32 {
+        let snr = (self.snr_db / GOOD_SNR_DB).clamp(0.0, 1.0);
+        let level = (self.speech_level / NORMAL_SPEECH).min(1.0);
+        let clipping = (1.0 - self.clipping_percent / MAX_CLIPPING_PERCENT).clamp(0.0, 1.0);
+        let accuracy = word_accuracy.map_or(1.0, |a| 0.5 + 0.5 * a.clamp(0.0, 1.0));
+        100.0 * snr * level * clipping * accuracy
+    }
+}
+
+/// Stored result of a microphone test, keyed by device under `mic_calibration`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MicCalibration {
+    pub device: String,
+    pub measured_at: String,
+    pub measurement: MicMeasurement,
+    /// Used for silence detection and AGC on this device
+    pub voice_threshold: f32,
+    /// The phrase the user was asked to read, and what the model heard
+    pub phrase: Option<String>,
+    pub transcript: Option<String>,
+    pub word_accuracy: Option<f32>,
+    pub score: f32,
+    pub issues: Vec<String>,
+}
+
+impl MicCalibration {
+    pub fn device_levels(&self) -> DeviceLevels {
+        DeviceLevels {
+            speech_rms: self.measurement.speech_level,
+            voice_threshold: self.voice_threshold,
+        }
+    }
+}
+
+/// A test recording, resampled to 16kHz mono
+pub struct MicSample {
+    /// The device that was actually opened (after falling back to the default)
+    pub device: String,
+    pub audio: Vec<f32>,
+    pub raw: RawLevels,
+}
+
+struct SampleCapture {
+    buffer: CaptureBuffer,
+    raw: RawLevels,
+}
+
+/// Record `duration` of audio from a device in its capture format. Blocks the calling thread.
+pub fn record_sample(
+    device_name: Option<&str>,
+    format: &CaptureFormat,
+    duration: Duration,
+) -> Result<MicSample, String> {
+    let host = cpal::default_host();
+    let device = select_input_device(&host, device_name)?;
+    let name = device.name().unwrap_or_else(|_| "Unknown".to_string());
+    let config = resolve_capture_config(&device, format)?;
+    let sample_rate = config.sample_rate();
+    let channels = config.channels();
+    log::info!(
+        "Microphone test on {} ({} Hz, {} ch)",
+        name,
+        sample_rate,
+        channels
+    );
+
+    let capture = Arc::new(Mutex::new(SampleCapture {
+        buffer: CaptureBuffer::with_max_seconds(
+            sample_rate,
+            channels,
+            duration.as_secs() as usize + 1,
+        )?,
+        raw: RawLevels::default(),
+    }));
+    let capture_in_callback = capture.clone();
+    let process = move |samples: &[f32], _: &[i16]| {
+        if let Ok(mut capture) = capture_in_callback.lock() {
+            capture.raw.push(samples);
+            if let Err(e) = capture.buffer.push_interleaved(samples) {
+                log::warn!("Microphone test capture failed: {}", e);
+            }
+        }
+    };
+
+    let err_fn = |err| log::error!("Microphone test stream error: {}", err);
+    let stream = build_input_stream(&device, &config, process, err_fn)?;
+
+    stream.play().map_err(|e| e.to_string())?;
+    std::thread::sleep(duration);
+    drop(stream);
+
+    let mut capture = capture
+        .lock()
+        .map_err(|e| format!("Failed to read test recording: {}", e))?;
+    Ok(MicSample {
+        device: name,
+        audio: capture.buffer.take(),
+        raw: capture.raw,
+    })
+}
+
+fn rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    (samples.iter().map(|x| x * x).sum::<f32>() / samples.len() as f32).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `seconds` of 16kHz audio: quiet noise with a louder tone in the middle
+    fn sample(seconds: usize, noise: f32, voice: f32) -> Vec<f32> {
+        let len = seconds * CAPTURE_SAMPLE_RATE as usize;
+        (0..len)
+            .map(|i| {
+                let level = if (len / 4..len * 3 / 4).contains(&i) {
+                    voice
+                } else {
+                    noise
+                };
+                let phase =
+                    i as f32 * 2.0 * std::f32::consts::PI * 220.0 / CAPTURE_SAMPLE_RATE as f32;
+                level * std::f32::consts::SQRT_2 * phase.sin()
+            })
+            .collect()
+    }
+
+    fn measure(audio: &[f32]) -> MicMeasurement {
+        let mut raw = RawLevels::default();
+        raw.push(audio);
+        MicMeasurement::from_samples(audio, &raw).unwrap()
+    }
+
+    #[test]
+    fn test_measurement_separates_room_and_voice() {
+        let m = measure(&sample(5, 0.001, 0.05));
+        assert!((m.noise_floor - 0.001).abs() < 0.0002);
+        assert!((m.speech_level - 0.05).abs() < 0.005);
+        assert!((m.snr_db - 34.0).abs() < 1.0);
+        assert_eq!(m.clipping_percent, 0.0);
+        assert!(m.issues().is_empty());
+        assert!(m.score(Some(1.0)) > 99.0);
+
+        // Three times the noise floor, well below the voice
+        assert!((m.voice_threshold() - 0.003).abs() < 0.0005);
+    }
+
+    #[test]
+    fn test_quiet_noisy_and_clipping_mics_rank_lower() {
+        let good = measure(&sample(5, 0.001, 0.05)).score(None);
+        let quiet = measure(&sample(5, 0.0001, 0.003));
+        let noisy = measure(&sample(5, 0.01, 0.03));
+        let clipping = measure(&sample(5, 0.001, 1.0));
+
+        assert!(quiet.score(None) < good);
+        assert!(noisy.score(None) < good);
+        assert!(clipping.score(None) < good);
+        assert!(clipping.clipping_percent > 1.0);
+        assert!(clipping.peak >= CLIP_LEVEL);
+
+        // A quiet mic gets a threshold it can actually reach
+        assert!(quiet.voice_threshold() < 0.003);
+        assert!(!quiet.issues().is_empty());
+        assert!(!noisy.issues().is_empty());
+    }
+
+    #[test]
+    fn test_silence_is_reported_and_short_samples_rejected() {
+        let silent = measure(&vec![0.0; CAPTURE_SAMPLE_RATE as usize * 2]);
+        assert_eq!(silent.snr_db, 0.0);
+        assert_eq!(silent.score(None), 0.0);
+        assert_eq!(silent.voice_threshold(), MIN_VOICE_THRESHOLD);
+        assert!(silent.issues()[0].contains("No speech"));
+
+        let short = vec![0.0; 100];
+        assert!(MicMeasurement::from_samples(&short, &RawLevels::default()).is_err());
+    }
+}
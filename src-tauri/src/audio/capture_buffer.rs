@@ -0,0 +1,181 @@
+use std::collections::VecDeque;
+use std::path::Path;
+
+use super::resampler::StreamingResampler;
+
+/// Sample rate Whisper expects and the in-memory buffer is kept at
+pub const CAPTURE_SAMPLE_RATE: u32 = 16_000;
+
+/// Upper bound for the in-memory buffer (30 minutes, ~115MB of f32)
+const MAX_CAPTURE_SECONDS: usize = 30 * 60;
+
+/// In-memory 16kHz mono capture, filled straight from the cpal callback.
+///
+/// Device audio is downmixed and resampled on the fly, so at stop time the
+/// samples are ready for whisper-rs without a WAV round-trip. The buffer is a
+/// ring: once full, the oldest audio is dropped.
+pub struct CaptureBuffer {
+    channels: usize,
+    resampler: StreamingResampler,
+    samples: VecDeque<f32>,
+    max_samples: usize,
+    dropped_samples: usize,
+    mono_scratch: Vec<f32>,
+}
+
+impl CaptureBuffer {
+    pub fn new(input_sample_rate: u32, channels: u16) -> Result<Self, String> {
+        Self::with_max_seconds(input_sample_rate, channels, MAX_CAPTURE_SECONDS)
+    }
+
+    pub fn with_max_seconds(
+        input_sample_rate: u32,
+        channels: u16,
+        max_seconds: usize,
+    ) -> Result<Self, String> {
+        if channels == 0 {
+            return Err("Channel count cannot be zero".to_string());
+        }
+
+        let max_samples = max_seconds.max(1) * CAPTURE_SAMPLE_RATE as usize;
+        Ok(Self {
+            channels: channels as usize,
+            resampler: StreamingResampler::new(input_sample_rate)?,
+            // Start with room for a typical dictation, grow as needed
+            samples: VecDeque::with_capacity(max_samples.min(60 * CAPTURE_SAMPLE_RATE as usize)),
+            max_samples,
+            dropped_samples: 0,
+            mono_scratch: Vec::new(),
+        })
+    }
+
+    /// Push interleaved device samples
+    pub fn push_interleaved(&mut self, samples: &[f32]) -> Result<(), String> {
+        self.mono_scratch.clear();
+        if self.channels == 1 {
+            self.mono_scratch.extend_from_slice(samples);
+        } else {
+            let scale = 1.0 / self.channels as f32;
+            self.mono_scratch.extend(
+                samples
+                    .chunks_exact(self.channels)
+                    .map(|frame| frame.iter().sum::<f32>() * scale),
+            );
+        }
+
+        let resampled = self.resampler.process(&self.mono_scratch)?;
+        self.append(&resampled);
+        Ok(())
+    }
+
+    /// Flush the resampler and return the captured 16kHz mono audio
+    pub fn finish(mut self) -> Result<Vec<f32>, String> {
+        let tail = self.resampler.finish()?;
+        self.append(&tail);
+
+        if self.dropped_samples > 0 {
+            log::warn!(
+                "In-memory capture exceeded {}s, dropped {:.1}s of the oldest audio",
+                self.max_samples / CAPTURE_SAMPLE_RATE as usize,
+                self.dropped_samples as f32 / CAPTURE_SAMPLE_RATE as f32
+            );
+        }
+
+        Ok(self.samples.into())
+    }
+
+    /// Hand over the 16kHz audio captured so far and keep capturing into an empty buffer
+    pub fn take(&mut self) -> Vec<f32> {
+        self.dropped_samples = 0;
+        self.samples.drain(..).collect()
+    }
+
+    pub fn duration_secs(&self) -> f32 {
+        self.samples.len() as f32 / CAPTURE_SAMPLE_RATE as f32
+    }
+
+    fn append(&mut self, samples: &[f32]) {
+        self.samples.extend(samples.iter().copied());
+        if self.samples.len() > self.max_samples {
+            let overflow = self.samples.len() - self.max_samples;
+            self.samples.drain(..overflow);
+            self.dropped_samples += overflow;
+        }
+    }
+}
+
+/// Write 16kHz mono samples to a 16-bit WAV file
+/// Used when captured audio has to hit the disk after all (retention, file-based engines)
+pub fn write_capture_wav(path: &Path, samples: &[f32]) -> Result<(), String> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: CAPTURE_SAMPLE_RATE,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+
+    let mut writer =
+        hound::WavWriter::create(path, spec).map_err(|e| format!("Failed to create WAV: {}", e))?;
+    for &sample in samples {
+        writer
+            .write_sample((sample.clamp(-1.0, 1.0) * 32767.0) as i16)
+            .map_err(|e| format!("Failed to write audio sample: {}", e))?;
+    }
+    writer
+        .finalize()
+        .map_err(|e| format!("Failed to finalize WAV: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_stereo_48khz_is_downmixed_and_resampled() {
+        let mut buffer = CaptureBuffer::new(48_000, 2).unwrap();
+        // One second of stereo audio in 10ms callbacks
+        for block in vec![0.25f32; 48_000 * 2].chunks(960) {
+            buffer.push_interleaved(block).unwrap();
+        }
+        let samples = buffer.finish().unwrap();
+        assert_eq!(samples.len(), 16_000);
+    }
+
+    #[test]
+    fn test_ring_drops_oldest_audio() {
+        let mut buffer = CaptureBuffer::with_max_seconds(16_000, 1, 1).unwrap();
+        buffer.push_interleaved(&vec![0.1f32; 16_000]).unwrap();
+        buffer.push_interleaved(&vec![0.9f32; 8_000]).unwrap();
+        assert!((buffer.duration_secs() - 1.0).abs() < f32::EPSILON);
+
+        let samples = buffer.finish().unwrap();
+        assert_eq!(samples.len(), 16_000);
+        assert_eq!(*samples.last().unwrap(), 0.9);
+        assert_eq!(samples[0], 0.1);
+        assert_eq!(samples[8_000], 0.9);
+    }
+
+    #[test]
+    fn test_take_empties_the_buffer() {
+        let mut buffer = CaptureBuffer::new(16_000, 1).unwrap();
+        buffer.push_interleaved(&vec![0.2f32; 4_000]).unwrap();
+        assert_eq!(buffer.take().len(), 4_000);
+        assert_eq!(buffer.duration_secs(), 0.0);
+
+        buffer.push_interleaved(&vec![0.2f32; 1_600]).unwrap();
+        assert_eq!(buffer.take().len(), 1_600);
+    }
+
+    #[test]
+    fn test_write_capture_wav() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("capture.wav");
+        write_capture_wav(&path, &vec![0.5f32; 1600]).unwrap();
+
+        let reader = hound::WavReader::open(&path).unwrap();
+        assert_eq!(reader.spec().sample_rate, CAPTURE_SAMPLE_RATE);
+        assert_eq!(reader.spec().channels, 1);
+        assert_eq!(reader.duration(), 1600);
+    }
+}
@@ -0,0 +1,482 @@
+// Per-device capture format: sample rate, buffer size, sample format and which
+// input channels carry the voice. Multi-channel interfaces often have the mic on
+// one channel only; picking or mixing happens right after capture so the writer,
+// level meter and detectors only ever see the voice channels.
+
+use cpal::traits::DeviceTrait;
+use serde::{Deserialize, Serialize};
+
+/// Sample rates a capture format may ask for
+const SAMPLE_RATE_RANGE: std::ops::RangeInclusive<u32> = 8_000..=192_000;
+/// Buffer sizes (frames per callback) a capture format may ask for
+const BUFFER_SIZE_RANGE: std::ops::RangeInclusive<u32> = 16..=16_384;
+/// Highest channel count a capture format may refer to
+const MAX_CHANNELS: u16 = 64;
+
+/// Sample formats the recorder can read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CaptureSampleFormat {
+    F32,
+    I16,
+    U16,
+}
+
+impl CaptureSampleFormat {
+    fn name(self) -> &'static str {
+        match self {
+            Self::F32 => "f32",
+            Self::I16 => "i16",
+            Self::U16 => "u16",
+        }
+    }
+}
+
+/// Name of a cpal sample format as reported to the UI ("f32", "i16", ...)
+fn format_name(format: cpal::SampleFormat) -> String {
+    format!("{:?}", format).to_lowercase()
+}
+
+/// Which device channels are recorded
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum ChannelSelection {
+    /// Every device channel, interleaved
+    #[default]
+    All,
+    /// One channel (0-based), recorded as mono
+    Pick { channel: u16 },
+    /// The average of the listed channels (0-based, all when empty) as mono
+    Mix { channels: Vec<u16> },
+}
+
+impl ChannelSelection {
+    /// Channels the device must have for this selection
+    fn required_channels(&self) -> u32 {
+        match self {
+            Self::All => 1,
+            Self::Pick { channel } => u32::from(*channel) + 1,
+            Self::Mix { channels } => channels.iter().max().map_or(1, |&c| u32::from(c) + 1),
+        }
+    }
+}
+
+/// Capture settings for one device, stored per device under `capture_formats`.
+/// Unset fields keep the device default.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CaptureFormat {
+    pub sample_rate: Option<u32>,
+    /// Frames per callback; smaller is more responsive, larger survives load better
+    pub buffer_size: Option<u32>,
+    pub sample_format: Option<CaptureSampleFormat>,
+    pub channels: ChannelSelection,
+}
+
+impl CaptureFormat {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(rate) = self.sample_rate {
+            if !SAMPLE_RATE_RANGE.contains(&rate) {
+                return Err(format!(
+                    "Sample rate must be between {} and {} Hz (got {})",
+                    SAMPLE_RATE_RANGE.start(),
+                    SAMPLE_RATE_RANGE.end(),
+                    rate
+                ));
+            }
+        }
+        if let Some(size) = self.buffer_size {
+            if !BUFFER_SIZE_RANGE.contains(&size) {
+                return Err(format!(
+                    "Buffer size must be between {} and {} frames (got {})",
+                    BUFFER_SIZE_RANGE.start(),
+                    BUFFER_SIZE_RANGE.end(),
+                    size
+                ));
+            }
+        }
+        if self.channels.required_channels() > u32::from(MAX_CHANNELS) {
+            return Err(format!("Channels are numbered 0 to {}", MAX_CHANNELS - 1));
+        }
+        if let ChannelSelection::Mix { channels } = &self.channels {
+            let mut sorted = channels.clone();
+            sorted.sort_unstable();
+            sorted.dedup();
+            if sorted.len() != channels.len() {
+                return Err("Each channel can only be mixed in once".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// One entry of the device's `supported_input_configs`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SupportedFormat {
+    pub channels: u16,
+    pub min_sample_rate: u32,
+    pub max_sample_rate: u32,
+    pub sample_format: String,
+    /// Frames per callback the driver accepts, when it says
+    pub min_buffer_size: Option<u32>,
+    pub max_buffer_size: Option<u32>,
+}
+
+impl SupportedFormat {
+    fn from_range(range: &cpal::SupportedStreamConfigRange) -> Self {
+        let (min_buffer_size, max_buffer_size) = buffer_range(range.buffer_size());
+        Self {
+            channels: range.channels(),
+            min_sample_rate: range.min_sample_rate().0,
+            max_sample_rate: range.max_sample_rate().0,
+            sample_format: format_name(range.sample_format()),
+            min_buffer_size,
+            max_buffer_size,
+        }
+    }
+
+    fn accepts_buffer_size(&self, size: Option<u32>) -> bool {
+        match (size, self.min_buffer_size, self.max_buffer_size) {
+            (Some(size), Some(min), Some(max)) => (min..=max).contains(&size),
+            _ => true,
+        }
+    }
+}
+
+fn buffer_range(size: &cpal::SupportedBufferSize) -> (Option<u32>, Option<u32>) {
+    match size {
+        cpal::SupportedBufferSize::Range { min, max } => (Some(*min), Some(*max)),
+        cpal::SupportedBufferSize::Unknown => (None, None),
+    }
+}
+
+/// What a device can capture, for the format settings UI
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceCapabilities {
+    pub device: String,
+    pub default_sample_rate: u32,
+    pub default_channels: u16,
+    pub default_sample_format: String,
+    pub supported: Vec<SupportedFormat>,
+}
+
+pub fn device_capabilities(device: &cpal::Device) -> Result<DeviceCapabilities, String> {
+    let default = device
+        .default_input_config()
+        .map_err(|e| format!("Failed to read the default input format: {}", e))?;
+    let supported = device
+        .supported_input_configs()
+        .map_err(|e| format!("Failed to list supported input formats: {}", e))?
+        .map(|range| SupportedFormat::from_range(&range))
+        .collect();
+    Ok(DeviceCapabilities {
+        device: device.name().unwrap_or_else(|_| "Unknown".to_string()),
+        default_sample_rate: default.sample_rate().0,
+        default_channels: default.channels(),
+        default_sample_format: format_name(default.sample_format()),
+        supported,
+    })
+}
+
+/// Pick the supported format that satisfies `format`; returns its index and the sample rate.
+/// Among matches the device's default channel count wins, then the widest format.
+fn choose_format(
+    supported: &[SupportedFormat],
+    default_rate: u32,
+    default_channels: u16,
+    default_sample_format: &str,
+    format: &CaptureFormat,
+) -> Result<(usize, u32), String> {
+    let rate = format.sample_rate.unwrap_or(default_rate);
+    let sample_format = format
+        .sample_format
+        .map_or(default_sample_format, |f| f.name());
+    let required_channels = format.channels.required_channels();
+
+    supported
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| {
+            s.sample_format == sample_format
+                && (s.min_sample_rate..=s.max_sample_rate).contains(&rate)
+                && u32::from(s.channels) >= required_channels
+                && s.accepts_buffer_size(format.buffer_size)
+        })
+        .max_by_key(|(_, s)| (s.channels == default_channels, s.channels))
+        .map(|(index, _)| (index, rate))
+        .ok_or_else(|| {
+            format!(
+                "The device cannot capture {} Hz {} with {} channel(s){}",
+                rate,
+                sample_format,
+                required_channels,
+                format
+                    .buffer_size
+                    .map(|size| format!(" in {}-frame buffers", size))
+                    .unwrap_or_default()
+            )
+        })
+}
+
+/// Picks or mixes device channels right after capture
+#[derive(Debug, Clone)]
+pub struct ChannelMapper {
+    input_channels: usize,
+    /// Channels averaged into mono; `None` passes every channel through
+    sources: Option<Vec<usize>>,
+}
+
+impl ChannelMapper {
+    pub fn new(selection: &ChannelSelection, input_channels: u16) -> Result<Self, String> {
+        if selection.required_channels() > u32::from(input_channels) {
+            return Err(format!(
+                "Channel {} is not available; the device has {} channel(s)",
+                selection.required_channels(),
+                input_channels
+            ));
+        }
+        let sources = match selection {
+            ChannelSelection::All => None,
+            ChannelSelection::Pick { channel } => Some(vec![*channel as usize]),
+            ChannelSelection::Mix { channels } if channels.is_empty() => {
+                Some((0..input_channels as usize).collect())
+            }
+            ChannelSelection::Mix { channels } => {
+                Some(channels.iter().map(|&c| c as usize).collect())
+            }
+        };
+        Ok(Self {
+            input_channels: input_channels.max(1) as usize,
+            // Mixing every channel of a mono device changes nothing
+            sources: sources.filter(|s| input_channels > 1 || s.as_slice() != [0]),
+        })
+    }
+
+    /// Channel count after mapping
+    pub fn output_channels(&self) -> u16 {
+        match self.sources {
+            Some(_) => 1,
+            None => self.input_channels as u16,
+        }
+    }
+
+    /// Map interleaved device samples; `None` when they pass through unchanged
+    pub fn map(&self, samples: &[f32]) -> Option<Vec<f32>> {
+        let sources = self.sources.as_ref()?;
+        let scale = 1.0 / sources.len() as f32;
+        Some(
+            samples
+                .chunks_exact(self.input_channels)
+                .map(|frame| sources.iter().map(|&c| frame[c]).sum::<f32>() * scale)
+                .collect(),
+        )
+    }
+}
+
+/// A device's stream settings with its capture format applied
+pub struct CaptureConfig {
+    pub stream: cpal::StreamConfig,
+    sample_format: cpal::SampleFormat,
+    pub mapper: ChannelMapper,
+}
+
+impl CaptureConfig {
+    pub fn sample_rate(&self) -> u32 {
+        self.stream.sample_rate.0
+    }
+
+    /// Channels after picking or mixing, as seen by everything after capture
+    pub fn channels(&self) -> u16 {
+        self.mapper.output_channels()
+    }
+
+    pub fn sample_format(&self) -> cpal::SampleFormat {
+        self.sample_format
+    }
+}
+
+/// Stream settings for `device` honouring `format`; the default format opens the
+/// device exactly as `default_input_config` describes it
+pub fn resolve_capture_config(
+    device: &cpal::Device,
+    format: &CaptureFormat,
+) -> Result<CaptureConfig, String> {
+    let default = device.default_input_config().map_err(|e| e.to_string())?;
+
+    // The default config only fits when it has every channel the selection reads
+    let config = if format.sample_rate.is_none()
+        && format.sample_format.is_none()
+        && format.channels.required_channels() <= u32::from(default.channels())
+    {
+        let (min, max) = buffer_range(default.buffer_size());
+        if let (Some(size), Some(min), Some(max)) = (format.buffer_size, min, max) {
+            if !(min..=max).contains(&size) {
+                return Err(format!(
+                    "Buffer size must be between {} and {} frames for this device",
+                    min, max
+                ));
+            }
+        }
+        default
+    } else {
+        let ranges: Vec<cpal::SupportedStreamConfigRange> = device
+            .supported_input_configs()
+            .map_err(|e| format!("Failed to list supported input formats: {}", e))?
+            .collect();
+        let supported: Vec<SupportedFormat> =
+            ranges.iter().map(SupportedFormat::from_range).collect();
+        let (index, rate) = choose_format(
+            &supported,
+            default.sample_rate().0,
+            default.channels(),
+            &format_name(default.sample_format()),
+            format,
+        )?;
+        ranges[index]
+            .clone()
+            .with_sample_rate(cpal::SampleRate(rate))
+    };
+
+    let mapper = ChannelMapper::new(&format.channels, config.channels())?;
+    let mut stream = config.config();
+    if let Some(size) = format.buffer_size {
+        stream.buffer_size = cpal::BufferSize::Fixed(size);
+    }
+    Ok(CaptureConfig {
+        stream,
+        sample_format: config.sample_format(),
+        mapper,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn supported(channels: u16, rates: (u32, u32), format: &str) -> SupportedFormat {
+        SupportedFormat {
+            channels,
+            min_sample_rate: rates.0,
+            max_sample_rate: rates.1,
+            sample_format: format.to_string(),
+            min_buffer_size: Some(64),
+            max_buffer_size: Some(4096),
+        }
+    }
+
+    #[test]
+    fn test_channel_mapper_picks_and_mixes() {
+        // Two frames of a four-channel interface
+        let frames = [0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8];
+
+        let all = ChannelMapper::new(&ChannelSelection::All, 4).unwrap();
+        assert_eq!(all.output_channels(), 4);
+        assert_eq!(all.map(&frames), None);
+
+        let pick = ChannelMapper::new(&ChannelSelection::Pick { channel: 2 }, 4).unwrap();
+        assert_eq!(pick.output_channels(), 1);
+        assert_eq!(pick.map(&frames), Some(vec![0.3, 0.7]));
+
+        let mix = ChannelMapper::new(
+            &ChannelSelection::Mix {
+                channels: vec![0, 3],
+            },
+            4,
+        )
+        .unwrap();
+        let mixed = mix.map(&frames).unwrap();
+        assert!((mixed[0] - 0.25).abs() < 1e-6 && (mixed[1] - 0.65).abs() < 1e-6);
+
+        let mix_all = ChannelMapper::new(&ChannelSelection::Mix { channels: vec![] }, 2).unwrap();
+        assert_eq!(mix_all.map(&[0.2, 0.4]).map(|m| m.len()), Some(1));
+
+        assert!(ChannelMapper::new(&ChannelSelection::Pick { channel: 2 }, 2).is_err());
+        // Picking the only channel of a mono mic is a no-op
+        let mono = ChannelMapper::new(&ChannelSelection::Pick { channel: 0 }, 1).unwrap();
+        assert_eq!(mono.map(&[0.5]), None);
+    }
+
+    #[test]
+    fn test_choose_format_matches_request() {
+        let formats = vec![
+            supported(2, (44_100, 48_000), "f32"),
+            supported(8, (44_100, 96_000), "f32"),
+            supported(2, (8_000, 48_000), "i16"),
+        ];
+
+        // Default channel count wins when it can do the job
+        let stereo = CaptureFormat {
+            sample_rate: Some(44_100),
+            ..CaptureFormat::default()
+        };
+        assert_eq!(
+            choose_format(&formats, 48_000, 2, "f32", &stereo),
+            Ok((0, 44_100))
+        );
+
+        // Channel 5 needs the eight-channel format
+        let fifth = CaptureFormat {
+            sample_rate: Some(96_000),
+            channels: ChannelSelection::Pick { channel: 5 },
+            ..CaptureFormat::default()
+        };
+        assert_eq!(
+            choose_format(&formats, 48_000, 2, "f32", &fifth),
+            Ok((1, 96_000))
+        );
+
+        let int16 = CaptureFormat {
+            sample_rate: Some(16_000),
+            sample_format: Some(CaptureSampleFormat::I16),
+            ..CaptureFormat::default()
+        };
+        assert_eq!(
+            choose_format(&formats, 48_000, 2, "f32", &int16),
+            Ok((2, 16_000))
+        );
+
+        let impossible = CaptureFormat {
+            sample_rate: Some(48_000),
+            buffer_size: Some(8192),
+            ..CaptureFormat::default()
+        };
+        assert!(choose_format(&formats, 48_000, 2, "f32", &impossible).is_err());
+    }
+
+    #[test]
+    fn test_capture_format_validation() {
+        assert!(CaptureFormat::default().is_default());
+        assert!(CaptureFormat::default().validate().is_ok());
+
+        let slow = CaptureFormat {
+            sample_rate: Some(4_000),
+            ..CaptureFormat::default()
+        };
+        assert!(slow.validate().is_err());
+        let twice = CaptureFormat {
+            channels: ChannelSelection::Mix {
+                channels: vec![1, 1],
+            },
+            ..CaptureFormat::default()
+        };
+        assert!(twice.validate().is_err());
+        let out_of_range = CaptureFormat {
+            channels: ChannelSelection::Pick { channel: u16::MAX },
+            ..CaptureFormat::default()
+        };
+        assert!(out_of_range.validate().is_err());
+        assert_eq!(
+            ChannelSelection::Pick { channel: u16::MAX }.required_channels(),
+            65_536
+        );
+
+        let parsed: CaptureFormat =
+            serde_json::from_str(r#"{"channels":{"mode":"pick","channel":2}}"#).unwrap();
+        assert_eq!(parsed.channels, ChannelSelection::Pick { channel: 2 });
+        assert_eq!(parsed.sample_rate, None);
+    }
+}
@@ -0,0 +1,743 @@
+use nnnoiseless::DenoiseState;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use super::level_meter::voice_level_to_rms;
+use super::resampler::StreamingResampler;
+
+/// Sample rate the RNNoise model was trained on
+const DENOISE_SAMPLE_RATE: u32 = 48_000;
+
+/// Below this RMS the input is treated as silence and AGC holds its gain
+/// (matches the SilenceDetector voice threshold) unless the device is calibrated
+const AGC_VOICE_THRESHOLD: f32 = 0.005;
+
+/// User-facing configuration for the capture DSP chain.
+/// Every stage is optional and the whole chain is off by default.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AudioProcessingOptions {
+    #[serde(default)]
+    pub high_pass_enabled: bool,
+    #[serde(default = "default_high_pass_cutoff_hz")]
+    pub high_pass_cutoff_hz: f32,
+    #[serde(default)]
+    pub noise_suppression_enabled: bool,
+    #[serde(default)]
+    pub auto_gain_enabled: bool,
+    /// AGC target on the same 0.0-0.95 scale reported by `AudioLevelMeter`
+    #[serde(default = "default_auto_gain_target_level")]
+    pub auto_gain_target_level: f64,
+    #[serde(default = "default_auto_gain_max_gain")]
+    pub auto_gain_max_gain: f32,
+    /// Keep a 16kHz mono buffer in memory and hand it straight to Whisper
+    /// instead of round-tripping through a temp WAV file
+    #[serde(default)]
+    pub in_memory_capture: bool,
+}
+
+fn default_high_pass_cutoff_hz() -> f32 {
+    80.0
+}
+
+fn default_auto_gain_target_level() -> f64 {
+    0.8 // Comfortably inside the "loud speech" band of the level meter
+}
+
+fn default_auto_gain_max_gain() -> f32 {
+    10.0
+}
+
+impl Default for AudioProcessingOptions {
+    fn default() -> Self {
+        Self {
+            high_pass_enabled: false,
+            high_pass_cutoff_hz: default_high_pass_cutoff_hz(),
+            noise_suppression_enabled: false,
+            auto_gain_enabled: false,
+            auto_gain_target_level: default_auto_gain_target_level(),
+            auto_gain_max_gain: default_auto_gain_max_gain(),
+            in_memory_capture: false,
+        }
+    }
+}
+
+impl AudioProcessingOptions {
+    /// True when at least one stage would touch the signal
+    pub fn any_enabled(&self) -> bool {
+        self.high_pass_enabled || self.noise_suppression_enabled || self.auto_gain_enabled
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !(20.0..=500.0).contains(&self.high_pass_cutoff_hz) {
+            return Err(format!(
+                "High-pass cutoff must be between 20 and 500 Hz (got {})",
+                self.high_pass_cutoff_hz
+            ));
+        }
+        if !(0.1..=0.95).contains(&self.auto_gain_target_level) {
+            return Err(format!(
+                "Auto gain target level must be between 0.1 and 0.95 (got {})",
+                self.auto_gain_target_level
+            ));
+        }
+        if !(1.0..=40.0).contains(&self.auto_gain_max_gain) {
+            return Err(format!(
+                "Auto gain max gain must be between 1 and 40 (got {})",
+                self.auto_gain_max_gain
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Levels measured by the microphone test for the device being recorded
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DeviceLevels {
+    /// Typical RMS of the user's voice on this device
+    pub speech_rms: f32,
+    pub voice_threshold: f32,
+}
+
+/// Second-order high-pass (RBJ cookbook biquad, Butterworth Q)
+struct HighPassFilter {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+    /// Per-channel state: x[n-1], x[n-2], y[n-1], y[n-2]
+    state: Vec<[f32; 4]>,
+}
+
+impl HighPassFilter {
+    fn new(sample_rate: u32, channels: usize, cutoff_hz: f32) -> Self {
+        let omega = 2.0 * std::f32::consts::PI * cutoff_hz / sample_rate as f32;
+        let (sin, cos) = omega.sin_cos();
+        let alpha = sin / (2.0 * std::f32::consts::FRAC_1_SQRT_2);
+        let a0 = 1.0 + alpha;
+
+        Self {
+            b0: ((1.0 + cos) / 2.0) / a0,
+            b1: (-(1.0 + cos)) / a0,
+            b2: ((1.0 + cos) / 2.0) / a0,
+            a1: (-2.0 * cos) / a0,
+            a2: (1.0 - alpha) / a0,
+            state: vec![[0.0; 4]; channels.max(1)],
+        }
+    }
+
+    fn process(&mut self, samples: &mut [f32]) {
+        let channels = self.state.len();
+        for (i, sample) in samples.iter_mut().enumerate() {
+            let s = &mut self.state[i % channels];
+            let x = *sample;
+            let y = self.b0 * x + self.b1 * s[0] + self.b2 * s[1] - self.a1 * s[2] - self.a2 * s[3];
+            s[1] = s[0];
+            s[0] = x;
+            s[3] = s[2];
+            s[2] = y;
+            *sample = y;
+        }
+    }
+}
+
+/// Per-channel resamplers taking device audio to the denoiser's rate and back
+struct RateBridge {
+    to_model: Vec<StreamingResampler>,
+    from_model: Vec<StreamingResampler>,
+    /// Device frames fed in and handed back, so the flushed tail restores the length
+    frames_in: usize,
+    frames_out: usize,
+}
+
+impl RateBridge {
+    fn new(sample_rate: u32, channels: usize) -> Result<Self, String> {
+        let mut to_model = Vec::with_capacity(channels);
+        let mut from_model = Vec::with_capacity(channels);
+        for _ in 0..channels {
+            to_model.push(StreamingResampler::with_output_rate(
+                sample_rate,
+                DENOISE_SAMPLE_RATE,
+            )?);
+            from_model.push(StreamingResampler::with_output_rate(
+                DENOISE_SAMPLE_RATE,
+                sample_rate,
+            )?);
+        }
+        Ok(Self {
+            to_model,
+            from_model,
+            frames_in: 0,
+            frames_out: 0,
+        })
+    }
+
+    /// Run each channel of `samples` through its resampler and re-interleave
+    fn convert(
+        resamplers: &mut [StreamingResampler],
+        samples: &[f32],
+        finish: bool,
+    ) -> Result<Vec<f32>, String> {
+        let channels = resamplers.len();
+        let mut converted = Vec::with_capacity(channels);
+        for (ch, resampler) in resamplers.iter_mut().enumerate() {
+            let channel: Vec<f32> = samples.iter().skip(ch).step_by(channels).copied().collect();
+            let mut output = resampler.process(&channel)?;
+            if finish {
+                output.extend(resampler.finish()?);
+            }
+            converted.push(output);
+        }
+        // Every channel sees the same number of frames, so the lengths agree
+        let frames = converted.iter().map(Vec::len).min().unwrap_or(0);
+        Ok((0..frames)
+            .flat_map(|i| converted.iter().map(move |channel| channel[i]))
+            .collect())
+    }
+}
+
+/// RNNoise denoiser (nnnoiseless), one state per channel.
+/// Works on fixed 480-sample frames, so it buffers input between callbacks.
+/// Devices that don't run at 48kHz are resampled to it and back.
+struct Denoiser {
+    channels: usize,
+    states: Vec<Box<DenoiseState<'static>>>,
+    bridge: Option<RateBridge>,
+    /// Interleaved samples waiting for a full frame
+    pending: Vec<f32>,
+    frame_in: Vec<f32>,
+    frame_out: Vec<f32>,
+}
+
+impl Denoiser {
+    fn new(sample_rate: u32, channels: usize) -> Result<Self, String> {
+        let channels = channels.max(1);
+        let bridge = if sample_rate == DENOISE_SAMPLE_RATE {
+            None
+        } else {
+            Some(RateBridge::new(sample_rate, channels)?)
+        };
+        Ok(Self {
+            channels,
+            states: (0..channels).map(|_| DenoiseState::new()).collect(),
+            bridge,
+            pending: Vec::new(),
+            frame_in: vec![0.0; DenoiseState::FRAME_SIZE],
+            frame_out: vec![0.0; DenoiseState::FRAME_SIZE],
+        })
+    }
+
+    /// Push samples in, get back everything denoised so far
+    fn process(&mut self, samples: &[f32]) -> Vec<f32> {
+        match self.bridge.take() {
+            Some(mut bridge) => {
+                bridge.frames_in += samples.len() / self.channels;
+                let output = self.denoise_bridged(&mut bridge, samples, false);
+                self.bridge = Some(bridge);
+                output
+            }
+            None => self.denoise(samples),
+        }
+    }
+
+    /// Drain everything still buffered, zero-padding the final frame
+    fn flush(&mut self) -> Vec<f32> {
+        match self.bridge.take() {
+            Some(mut bridge) => {
+                let remaining = bridge.frames_in.saturating_sub(bridge.frames_out);
+                let mut output = self.denoise_bridged(&mut bridge, &[], true);
+                // Rounding in the two conversions can leave the tail a frame off
+                output.resize(remaining * self.channels, 0.0);
+                bridge.frames_out = bridge.frames_in;
+                self.bridge = Some(bridge);
+                output
+            }
+            None => self.flush_frames(),
+        }
+    }
+
+    /// Resample to 48kHz, denoise and resample back. A resampling failure drops
+    /// the block rather than stopping the recording.
+    fn denoise_bridged(
+        &mut self,
+        bridge: &mut RateBridge,
+        samples: &[f32],
+        finish: bool,
+    ) -> Vec<f32> {
+        let result = RateBridge::convert(&mut bridge.to_model, samples, finish).and_then(|up| {
+            let mut denoised = self.denoise(&up);
+            if finish {
+                denoised.extend(self.flush_frames());
+            }
+            RateBridge::convert(&mut bridge.from_model, &denoised, finish)
+        });
+        let output = result.unwrap_or_else(|e| {
+            log::error!("Noise suppression resampling failed: {}", e);
+            Vec::new()
+        });
+        bridge.frames_out += output.len() / self.channels;
+        output
+    }
+
+    /// Denoise 48kHz samples, returning every complete frame processed so far
+    fn denoise(&mut self, samples: &[f32]) -> Vec<f32> {
+        self.pending.extend_from_slice(samples);
+
+        let block = DenoiseState::FRAME_SIZE * self.channels;
+        let full_blocks = self.pending.len() / block;
+        let mut output = Vec::with_capacity(full_blocks * block);
+
+        for block_idx in 0..full_blocks {
+            let start = block_idx * block;
+            let mut processed = vec![0.0f32; block];
+            for ch in 0..self.channels {
+                self.run_frame(ch, start, &mut processed);
+            }
+            output.extend_from_slice(&processed);
+        }
+
+        self.pending.drain(..full_blocks * block);
+        output
+    }
+
+    /// Process whatever is left by zero-padding the final frame
+    fn flush_frames(&mut self) -> Vec<f32> {
+        if self.pending.is_empty() {
+            return Vec::new();
+        }
+
+        let remaining = self.pending.len();
+        let block = DenoiseState::FRAME_SIZE * self.channels;
+        self.pending.resize(block, 0.0);
+        let mut processed = vec![0.0f32; block];
+        for ch in 0..self.channels {
+            self.run_frame(ch, 0, &mut processed);
+        }
+        self.pending.clear();
+
+        processed.truncate(remaining);
+        processed
+    }
+
+    fn run_frame(&mut self, ch: usize, start: usize, processed: &mut [f32]) {
+        // RNNoise expects samples in i16 range
+        for i in 0..DenoiseState::FRAME_SIZE {
+            self.frame_in[i] = self.pending[start + i * self.channels + ch] * 32768.0;
+        }
+        self.states[ch].process_frame(&mut self.frame_out, &self.frame_in);
+        for i in 0..DenoiseState::FRAME_SIZE {
+            processed[i * self.channels + ch] = (self.frame_out[i] / 32768.0).clamp(-1.0, 1.0);
+        }
+    }
+}
+
+/// Automatic gain control driven by a smoothed RMS measurement
+struct AutoGain {
+    target_rms: f32,
+    max_gain: f32,
+    gain: f32,
+    voice_threshold: f32,
+}
+
+impl AutoGain {
+    /// How quickly gain drops when the signal gets louder (per block)
+    const ATTACK: f32 = 0.5;
+    /// How quickly gain recovers when the signal gets quieter (per block)
+    const RELEASE: f32 = 0.05;
+    const MIN_GAIN: f32 = 0.25;
+
+    fn new(target_level: f64, max_gain: f32) -> Self {
+        Self {
+            target_rms: voice_level_to_rms(target_level),
+            max_gain,
+            gain: 1.0,
+            voice_threshold: AGC_VOICE_THRESHOLD,
+        }
+    }
+
+    /// Start from the gain the device is known to need instead of ramping up from 1.0
+    fn calibrate(&mut self, levels: DeviceLevels) {
+        if levels.speech_rms > 0.0 {
+            self.gain = (self.target_rms / levels.speech_rms).clamp(Self::MIN_GAIN, self.max_gain);
+        }
+        self.voice_threshold = levels.voice_threshold;
+    }
+
+    fn process(&mut self, samples: &mut [f32], measured_rms: f32) {
+        let previous_gain = self.gain;
+
+        // Hold gain during silence so noise isn't pumped up between words
+        if measured_rms > self.voice_threshold {
+            let desired = (self.target_rms / measured_rms).clamp(Self::MIN_GAIN, self.max_gain);
+            let rate = if desired < self.gain {
+                Self::ATTACK
+            } else {
+                Self::RELEASE
+            };
+            self.gain += (desired - self.gain) * rate;
+        }
+
+        // Ramp across the block to avoid zipper noise
+        let len = samples.len().max(1) as f32;
+        for (i, sample) in samples.iter_mut().enumerate() {
+            let g = previous_gain + (self.gain - previous_gain) * (i as f32 / len);
+            *sample = (*sample * g).clamp(-1.0, 1.0);
+        }
+    }
+}
+
+/// Optional DSP chain applied between the cpal callback and the WAV writer:
+/// high-pass → noise suppression → (level metering) → automatic gain control
+pub struct DspChain {
+    high_pass: Option<HighPassFilter>,
+    denoiser: Option<Denoiser>,
+    auto_gain: Option<AutoGain>,
+    /// Smoothed RMS used when no external level measurement is supplied
+    smoothed_rms: f32,
+}
+
+impl DspChain {
+    pub fn new(sample_rate: u32, channels: u16, options: &AudioProcessingOptions) -> Self {
+        let channels = channels as usize;
+
+        let denoiser = if options.noise_suppression_enabled {
+            match Denoiser::new(sample_rate, channels) {
+                Ok(denoiser) => Some(denoiser),
+                Err(e) => {
+                    log::warn!("Noise suppression unavailable at {} Hz: {}", sample_rate, e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        Self {
+            high_pass: options
+                .high_pass_enabled
+                .then(|| HighPassFilter::new(sample_rate, channels, options.high_pass_cutoff_hz)),
+            denoiser,
+            auto_gain: options
+                .auto_gain_enabled
+                .then(|| AutoGain::new(options.auto_gain_target_level, options.auto_gain_max_gain)),
+            smoothed_rms: 0.0,
+        }
+    }
+
+    /// Seed AGC with the levels measured for this device by the microphone test
+    pub fn calibrate(&mut self, levels: DeviceLevels) {
+        if let Some(agc) = self.auto_gain.as_mut() {
+            agc.calibrate(levels);
+        }
+    }
+
+    /// Run the filtering stages (high-pass and denoise).
+    /// The returned buffer may be shorter or longer than the input because
+    /// the denoiser works on fixed-size frames.
+    pub fn filter(&mut self, samples: &[f32]) -> Vec<f32> {
+        let mut output = samples.to_vec();
+        if let Some(hp) = self.high_pass.as_mut() {
+            hp.process(&mut output);
+        }
+        match self.denoiser.as_mut() {
+            Some(denoiser) => denoiser.process(&output),
+            None => output,
+        }
+    }
+
+    /// Apply AGC using an externally measured (smoothed) RMS, e.g. from `AudioLevelMeter`
+    pub fn apply_gain(&mut self, samples: &mut [f32], measured_rms: f32) {
+        if let Some(agc) = self.auto_gain.as_mut() {
+            agc.process(samples, measured_rms);
+        }
+    }
+
+    /// Full chain for offline use, measuring loudness the same way the level meter does
+    pub fn process(&mut self, samples: &[f32]) -> Vec<f32> {
+        let mut output = self.filter(samples);
+        if !output.is_empty() {
+            let rms = (output.iter().map(|x| x * x).sum::<f32>() / output.len() as f32).sqrt();
+            self.smoothed_rms = self.smoothed_rms * 0.7 + rms * 0.3;
+            let measured = self.smoothed_rms;
+            self.apply_gain(&mut output, measured);
+        }
+        output
+    }
+
+    /// Drain samples still buffered inside the denoiser (gain is applied at the current level)
+    pub fn flush(&mut self) -> Vec<f32> {
+        let mut output = match self.denoiser.as_mut() {
+            Some(denoiser) => denoiser.flush(),
+            None => Vec::new(),
+        };
+        if let Some(agc) = self.auto_gain.as_mut() {
+            let gain = agc.gain;
+            for sample in output.iter_mut() {
+                *sample = (*sample * gain).clamp(-1.0, 1.0);
+            }
+        }
+        output
+    }
+}
+
+/// Run a WAV file through the DSP chain and write the processed result.
+/// Used for offline tuning and testing of the chain on fixture recordings.
+pub fn process_wav_file(
+    input_path: &Path,
+    output_path: &Path,
+    options: &AudioProcessingOptions,
+) -> Result<(), String> {
+    let mut reader =
+        hound::WavReader::open(input_path).map_err(|e| format!("Failed to open WAV: {}", e))?;
+    let spec = reader.spec();
+
+    let samples: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Int => reader
+            .samples::<i16>()
+            .map(|s| s.map(|v| v as f32 / i16::MAX as f32))
+            .collect::<Result<_, _>>(),
+        hound::SampleFormat::Float => reader.samples::<f32>().collect::<Result<_, _>>(),
+    }
+    .map_err(|e| format!("Failed to read samples: {}", e))?;
+
+    let mut chain = DspChain::new(spec.sample_rate, spec.channels, options);
+    let mut processed = Vec::with_capacity(samples.len());
+    // Feed in callback-sized blocks (~10ms) to mirror live capture
+    let block = (spec.sample_rate as usize / 100).max(1) * spec.channels as usize;
+    for chunk in samples.chunks(block) {
+        processed.extend(chain.process(chunk));
+    }
+    processed.extend(chain.flush());
+
+    let out_spec = hound::WavSpec {
+        channels: spec.channels,
+        sample_rate: spec.sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(output_path, out_spec)
+        .map_err(|e| format!("Failed to create WAV: {}", e))?;
+    for sample in processed {
+        writer
+            .write_sample((sample.clamp(-1.0, 1.0) * 32767.0) as i16)
+            .map_err(|e| format!("Failed to write sample: {}", e))?;
+    }
+    writer
+        .finalize()
+        .map_err(|e| format!("Failed to finalize WAV: {}", e))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_fixture(path: &Path, sample_rate: u32, samples: &[f32]) {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(path, spec).unwrap();
+        for &s in samples {
+            writer.write_sample((s * 32767.0) as i16).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    fn read_fixture(path: &Path) -> Vec<f32> {
+        hound::WavReader::open(path)
+            .unwrap()
+            .samples::<i16>()
+            .map(|s| s.unwrap() as f32 / i16::MAX as f32)
+            .collect()
+    }
+
+    fn sine(freq: f32, sample_rate: u32, seconds: f32, amplitude: f32) -> Vec<f32> {
+        (0..(sample_rate as f32 * seconds) as usize)
+            .map(|i| {
+                amplitude
+                    * (2.0 * std::f32::consts::PI * freq * i as f32 / sample_rate as f32).sin()
+            })
+            .collect()
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|x| x * x).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn test_disabled_chain_is_passthrough() {
+        let input = sine(440.0, 16000, 0.1, 0.3);
+        let mut chain = DspChain::new(16000, 1, &AudioProcessingOptions::default());
+        let output = chain.process(&input);
+        assert_eq!(output, input);
+        assert!(chain.flush().is_empty());
+    }
+
+    #[test]
+    fn test_high_pass_removes_rumble_keeps_voice_band() {
+        let options = AudioProcessingOptions {
+            high_pass_enabled: true,
+            ..Default::default()
+        };
+
+        let mut chain = DspChain::new(16000, 1, &options);
+        let rumble = chain.process(&sine(20.0, 16000, 1.0, 0.5));
+        let mut chain = DspChain::new(16000, 1, &options);
+        let voice = chain.process(&sine(1000.0, 16000, 1.0, 0.5));
+
+        // Skip the filter's settling time
+        assert!(rms(&rumble[8000..]) < 0.05);
+        assert!((rms(&voice[8000..]) - rms(&sine(1000.0, 16000, 0.5, 0.5))).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_auto_gain_raises_quiet_speech_towards_target() {
+        let options = AudioProcessingOptions {
+            auto_gain_enabled: true,
+            ..Default::default()
+        };
+        let target = voice_level_to_rms(options.auto_gain_target_level);
+
+        let mut chain = DspChain::new(16000, 1, &options);
+        let input = sine(300.0, 16000, 3.0, 0.01);
+        let output: Vec<f32> = input.chunks(160).flat_map(|c| chain.process(c)).collect();
+
+        let tail = &output[output.len() - 8000..];
+        assert!(rms(tail) > rms(&input) * 2.0);
+        assert!((rms(tail) - target).abs() < target * 0.25);
+    }
+
+    #[test]
+    fn test_calibrated_auto_gain_starts_at_device_gain() {
+        let options = AudioProcessingOptions {
+            auto_gain_enabled: true,
+            ..Default::default()
+        };
+        // A quiet microphone: speech stays below the default AGC voice threshold
+        let input = sine(300.0, 16000, 0.5, 0.004);
+
+        let mut plain = DspChain::new(16000, 1, &options);
+        let untouched: Vec<f32> = input.chunks(160).flat_map(|c| plain.process(c)).collect();
+        assert!((rms(&untouched) - rms(&input)).abs() < 1e-4);
+
+        let mut calibrated = DspChain::new(16000, 1, &options);
+        calibrated.calibrate(DeviceLevels {
+            speech_rms: 0.003,
+            voice_threshold: 0.001,
+        });
+        let first = calibrated.process(&input[..160]);
+        assert!(rms(&first) > rms(&input[..160]) * 5.0);
+    }
+
+    #[test]
+    fn test_auto_gain_ignores_silence() {
+        let options = AudioProcessingOptions {
+            auto_gain_enabled: true,
+            ..Default::default()
+        };
+        let mut chain = DspChain::new(16000, 1, &options);
+        let noise_floor = sine(300.0, 16000, 1.0, 0.001);
+        let output: Vec<f32> = noise_floor
+            .chunks(160)
+            .flat_map(|c| chain.process(c))
+            .collect();
+        assert!((rms(&output) - rms(&noise_floor)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_denoiser_preserves_length_after_flush() {
+        let options = AudioProcessingOptions {
+            noise_suppression_enabled: true,
+            ..Default::default()
+        };
+        let input = sine(440.0, 48000, 0.5, 0.2);
+        let mut chain = DspChain::new(48000, 1, &options);
+        let mut output: Vec<f32> = input.chunks(441).flat_map(|c| chain.process(c)).collect();
+        output.extend(chain.flush());
+        assert_eq!(output.len(), input.len());
+    }
+
+    #[test]
+    fn test_denoiser_resamples_other_rates() {
+        let options = AudioProcessingOptions {
+            noise_suppression_enabled: true,
+            ..Default::default()
+        };
+        for (sample_rate, channels) in [(16000, 1), (44100, 2)] {
+            let input: Vec<f32> = sine(440.0, sample_rate, 0.5, 0.2)
+                .into_iter()
+                .flat_map(|s| std::iter::repeat_n(s, channels))
+                .collect();
+            let mut chain = DspChain::new(sample_rate, channels as u16, &options);
+            let block = (sample_rate as usize / 100) * channels;
+            let mut output: Vec<f32> = input.chunks(block).flat_map(|c| chain.process(c)).collect();
+            output.extend(chain.flush());
+
+            assert_eq!(output.len(), input.len());
+            // Denoised, not passed through
+            assert_ne!(output, input);
+            // Channels stay aligned through the per-channel resamplers
+            if channels == 2 {
+                assert!(output
+                    .chunks(2)
+                    .all(|frame| (frame[0] - frame[1]).abs() < 1e-6));
+            }
+        }
+    }
+
+    #[test]
+    fn test_process_wav_fixture() {
+        let temp_dir = TempDir::new().unwrap();
+        let input_path = temp_dir.path().join("input.wav");
+        let output_path = temp_dir.path().join("output.wav");
+
+        // Quiet tone with low-frequency hum on top
+        let tone = sine(500.0, 48000, 1.0, 0.02);
+        let hum = sine(30.0, 48000, 1.0, 0.2);
+        let mixed: Vec<f32> = tone.iter().zip(&hum).map(|(a, b)| a + b).collect();
+        write_fixture(&input_path, 48000, &mixed);
+
+        let options = AudioProcessingOptions {
+            high_pass_enabled: true,
+            high_pass_cutoff_hz: 120.0,
+            noise_suppression_enabled: true,
+            auto_gain_enabled: true,
+            ..Default::default()
+        };
+        process_wav_file(&input_path, &output_path, &options).unwrap();
+
+        let output = read_fixture(&output_path);
+        assert_eq!(output.len(), mixed.len());
+        assert!(output.iter().all(|s| s.abs() <= 1.0));
+    }
+
+    #[test]
+    fn test_options_validation() {
+        assert!(AudioProcessingOptions::default().validate().is_ok());
+
+        let bad_cutoff = AudioProcessingOptions {
+            high_pass_cutoff_hz: 5.0,
+            ..Default::default()
+        };
+        assert!(bad_cutoff.validate().is_err());
+
+        let bad_target = AudioProcessingOptions {
+            auto_gain_target_level: 1.5,
+            ..Default::default()
+        };
+        assert!(bad_target.validate().is_err());
+    }
+
+    #[test]
+    fn test_options_deserialize_with_missing_fields() {
+        let options: AudioProcessingOptions =
+            serde_json::from_value(serde_json::json!({ "auto_gain_enabled": true })).unwrap();
+        assert!(options.auto_gain_enabled);
+        assert_eq!(options.high_pass_cutoff_hz, 80.0);
+    }
+}
@@ -0,0 +1,270 @@
+// Hot-plug handling: which microphone takes over when the one being recorded
+// disappears, and how its audio is made to look like the original device's so
+// a recording can continue in the same file and buffer.
+
+use serde::{Deserialize, Serialize};
+
+use super::recorder::normalize_device_name;
+use super::resampler::StreamingResampler;
+
+/// Failover settings, stored under `microphone_failover` in the settings store
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MicrophoneFailoverSettings {
+    /// Keep recording on another microphone when the current one disconnects
+    pub enabled: bool,
+    /// Microphones to try first, most preferred first; the system default comes after.
+    /// Also used when the selected microphone is missing as a recording starts.
+    pub priority: Vec<String>,
+}
+
+impl Default for MicrophoneFailoverSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            priority: Vec::new(),
+        }
+    }
+}
+
+impl MicrophoneFailoverSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.priority.iter().any(|name| name.trim().is_empty()) {
+            return Err("Microphone priority list contains an empty name".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// A recording moved to another microphone, or lost its only one
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeviceSwitch {
+    pub from: String,
+    /// `None` when no microphone was left and the recording stopped
+    pub to: Option<String>,
+    /// True when the original microphone came back and took over again
+    pub restored: bool,
+}
+
+/// Microphones that appeared and disappeared between two listings
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DeviceListChange {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl DeviceListChange {
+    pub fn between(before: &[String], after: &[String]) -> Self {
+        let missing_from = |list: &[String], name: &String| {
+            let name = normalize_device_name(name);
+            !list
+                .iter()
+                .any(|other| normalize_device_name(other) == name)
+        };
+        Self {
+            added: after
+                .iter()
+                .filter(|name| missing_from(before, name))
+                .cloned()
+                .collect(),
+            removed: before
+                .iter()
+                .filter(|name| missing_from(after, name))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Pick the microphone that takes over from `lost`: the first connected one in
+/// `priority`, otherwise the system default. Returns the name as listed in `available`.
+pub fn next_device(
+    lost: &str,
+    priority: &[String],
+    available: &[String],
+    default: Option<&str>,
+) -> Option<String> {
+    let lost = normalize_device_name(lost);
+    let connected = |name: &str| {
+        let name = normalize_device_name(name);
+        if name == lost {
+            return None;
+        }
+        available
+            .iter()
+            .find(|device| normalize_device_name(device) == name)
+            .cloned()
+    };
+
+    priority
+        .iter()
+        .find_map(|name| connected(name))
+        .or_else(|| default.and_then(connected))
+}
+
+/// Whether `name` is among the `available` devices
+pub fn is_connected(name: &str, available: &[String]) -> bool {
+    let name = normalize_device_name(name);
+    available
+        .iter()
+        .any(|device| normalize_device_name(device) == name)
+}
+
+/// Converts a replacement microphone's audio to the sample rate and channel count
+/// the recording started with. Speech survives the mono downmix well enough for
+/// the rest of the recording.
+pub struct FormatAdapter {
+    input_channels: usize,
+    output_channels: usize,
+    resampler: Option<StreamingResampler>,
+    mono: Vec<f32>,
+}
+
+impl FormatAdapter {
+    pub fn new(
+        input_rate: u32,
+        input_channels: u16,
+        output_rate: u32,
+        output_channels: u16,
+    ) -> Result<Self, String> {
+        let resampler = if input_rate != output_rate {
+            Some(StreamingResampler::with_output_rate(
+                input_rate,
+                output_rate,
+            )?)
+        } else {
+            None
+        };
+        Ok(Self {
+            input_channels: input_channels.max(1) as usize,
+            output_channels: output_channels.max(1) as usize,
+            resampler,
+            mono: Vec::new(),
+        })
+    }
+
+    /// Convert interleaved input samples to interleaved output samples
+    pub fn process(&mut self, samples: &[f32]) -> Vec<f32> {
+        if self.resampler.is_none() && self.input_channels == self.output_channels {
+            return samples.to_vec();
+        }
+
+        self.mono.clear();
+        let scale = 1.0 / self.input_channels as f32;
+        self.mono.extend(
+            samples
+                .chunks_exact(self.input_channels)
+                .map(|frame| frame.iter().sum::<f32>() * scale),
+        );
+
+        let resampled;
+        let mono = match self.resampler.as_mut() {
+            Some(resampler) => {
+                resampled = resampler.process(&self.mono).unwrap_or_else(|e| {
+                    log::warn!("Dropping replacement microphone audio: {}", e);
+                    Vec::new()
+                });
+                &resampled
+            }
+            None => &self.mono,
+        };
+        mono.iter()
+            .flat_map(|&sample| std::iter::repeat_n(sample, self.output_channels))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_next_device_follows_priority_then_default() {
+        let available = names(&["MacBook Pro Microphone", "USB Audio_Device"]);
+        let priority = names(&["AirPods Pro", "usb audio device", "MacBook Pro Microphone"]);
+
+        // The headset is gone; the USB mic is next in line and keeps its listed name
+        assert_eq!(
+            next_device("AirPods Pro", &priority, &available, None),
+            Some("USB Audio_Device".to_string())
+        );
+        // Without a priority list the system default takes over
+        assert_eq!(
+            next_device(
+                "AirPods Pro",
+                &[],
+                &available,
+                Some("MacBook Pro Microphone")
+            ),
+            Some("MacBook Pro Microphone".to_string())
+        );
+        // The lost device is never picked, even while it is still listed
+        assert_eq!(
+            next_device(
+                "usb audio device",
+                &[],
+                &available,
+                Some("USB Audio_Device")
+            ),
+            None
+        );
+        assert_eq!(next_device("AirPods Pro", &priority, &[], None), None);
+    }
+
+    #[test]
+    fn test_device_list_change() {
+        let before = names(&["Built-in", "AirPods"]);
+        let after = names(&["Built-in", "USB Mic"]);
+        let change = DeviceListChange::between(&before, &after);
+        assert_eq!(change.added, names(&["USB Mic"]));
+        assert_eq!(change.removed, names(&["AirPods"]));
+        assert!(DeviceListChange::between(&after, &after).is_empty());
+        assert!(is_connected("usb  mic", &after));
+        assert!(!is_connected("AirPods", &after));
+    }
+
+    #[test]
+    fn test_format_adapter_converts_rate_and_channels() {
+        // 48 kHz mono replacement for a 16 kHz stereo recording
+        let mut adapter = FormatAdapter::new(48_000, 1, 16_000, 2).unwrap();
+        let input: Vec<f32> = (0..48_000)
+            .map(|i| 0.5 * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / 48_000.0).sin())
+            .collect();
+        let output: Vec<f32> = input.chunks(441).flat_map(|c| adapter.process(c)).collect();
+
+        // Every frame is duplicated across both channels
+        assert!(output.chunks(2).all(|frame| frame[0] == frame[1]));
+        // One second in; only the last resampler chunk is still buffered
+        let left: Vec<f32> = output.iter().step_by(2).copied().collect();
+        assert!(left.len() > 15_000 && left.len() <= 16_000);
+        // The tone keeps its pitch and level at the new rate: 880 zero crossings a
+        // second and the RMS of a 0.5 sine
+        let steady = &left[1_000..15_000];
+        let crossings = steady
+            .windows(2)
+            .filter(|w| (w[0] < 0.0) != (w[1] < 0.0))
+            .count();
+        assert!(
+            (crossings as i32 - 770).abs() <= 4,
+            "{} crossings",
+            crossings
+        );
+        let rms = (steady.iter().map(|s| s * s).sum::<f32>() / steady.len() as f32).sqrt();
+        assert!((rms - 0.5 / 2f32.sqrt()).abs() < 0.01, "rms {}", rms);
+
+        // Stereo to mono at the same rate averages the channels
+        let mut downmix = FormatAdapter::new(16_000, 2, 16_000, 1).unwrap();
+        assert_eq!(downmix.process(&[0.2, 0.4, -0.5, 0.5]), vec![0.3, 0.0]);
+
+        let mut same = FormatAdapter::new(44_100, 2, 44_100, 2).unwrap();
+        assert_eq!(same.process(&[0.1, 0.2]), vec![0.1, 0.2]);
+    }
+}
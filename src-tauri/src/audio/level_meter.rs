@@ -12,14 +12,15 @@ pub struct AudioLevelMeter {
 impl AudioLevelMeter {
     pub fn new(
         sample_rate: u32,
-        _channels: u32, // Not needed for simple RMS
+        channels: u32, // Channels after capture-format mapping; samples are interleaved
         audio_level_tx: Sender<f64>,
     ) -> Result<Self, String> {
         Ok(Self {
             audio_level_tx,
             smoothed_level: 0.0,
             sample_count: 0,
-            update_interval: (sample_rate as usize) / 10, // Update 10 times per second
+            // Update 10 times per second
+            update_interval: (sample_rate as usize * channels.max(1) as usize) / 10,
         })
     }
 
@@ -51,16 +52,21 @@ impl AudioLevelMeter {
 
         Ok(())
     }
+
+    /// Smoothed RMS of the samples processed so far
+    pub fn current_rms(&self) -> f32 {
+        self.smoothed_level
+    }
 }
 
+// These thresholds are tuned for typical speaking voice
+const SILENCE_THRESHOLD: f32 = 0.001; // Below this is silence
+const WHISPER_LEVEL: f32 = 0.005; // Quiet speech
+const NORMAL_SPEECH: f32 = 0.02; // Normal conversation
+const LOUD_SPEECH: f32 = 0.1; // Raised voice
+
 /// Map RMS level to display level optimized for voice
 fn map_voice_level(rms: f32) -> f64 {
-    // These thresholds are tuned for typical speaking voice
-    const SILENCE_THRESHOLD: f32 = 0.001; // Below this is silence
-    const WHISPER_LEVEL: f32 = 0.005; // Quiet speech
-    const NORMAL_SPEECH: f32 = 0.02; // Normal conversation
-    const LOUD_SPEECH: f32 = 0.1; // Raised voice
-
     if rms < SILENCE_THRESHOLD {
         0.0
     } else if rms < WHISPER_LEVEL {
@@ -81,6 +87,19 @@ fn map_voice_level(rms: f32) -> f64 {
     }
 }
 
+/// Inverse of `map_voice_level`: the RMS that shows up as `level` on the meter.
+/// Lets gain control target the same loudness scale the user sees.
+pub(crate) fn voice_level_to_rms(level: f64) -> f32 {
+    let level = level.clamp(0.0, 0.95) as f32;
+    if level < 0.3 {
+        SILENCE_THRESHOLD + (level / 0.3) * (WHISPER_LEVEL - SILENCE_THRESHOLD)
+    } else if level < 0.7 {
+        WHISPER_LEVEL + ((level - 0.3) / 0.4) * (NORMAL_SPEECH - WHISPER_LEVEL)
+    } else {
+        NORMAL_SPEECH + ((level - 0.7) / 0.25) * (LOUD_SPEECH - NORMAL_SPEECH)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -104,4 +123,12 @@ mod tests {
         // Very loud
         assert_eq!(map_voice_level(0.2), 0.95);
     }
+
+    #[test]
+    fn test_voice_level_to_rms_round_trip() {
+        for level in [0.15, 0.5, 0.7, 0.8, 0.9] {
+            let rms = voice_level_to_rms(level);
+            assert!((map_voice_level(rms) - level).abs() < 0.01);
+        }
+    }
 }
@@ -1,8 +1,15 @@
+pub mod calibration;
+pub mod capture_buffer;
+pub mod capture_format;
 pub mod converter;
+pub mod dsp;
+pub mod failover;
 pub mod level_meter;
 pub mod recorder;
 pub mod resampler;
+pub mod retention;
 pub mod silence_detector;
+pub mod wake_word;
 
 #[cfg(test)]
 mod converter_tests;
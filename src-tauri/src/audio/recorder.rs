@@ -1,4 +1,5 @@
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::mpsc;
@@ -6,8 +7,12 @@ use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 
+use super::capture_buffer::CaptureBuffer;
+use super::capture_format::{resolve_capture_config, CaptureConfig, CaptureFormat};
+use super::dsp::{AudioProcessingOptions, DeviceLevels, DspChain};
+use super::failover::{is_connected, next_device, DeviceSwitch, FormatAdapter};
 use super::level_meter::AudioLevelMeter;
-use super::silence_detector::SilenceDetector;
+use super::silence_detector::{SilenceConfig, SilenceDetector};
 
 // Type-safe recording size limits
 pub struct RecordingSize;
@@ -27,6 +32,9 @@ impl RecordingSize {
     }
 }
 
+/// How often a recording checks that its microphone is still connected
+const DEVICE_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
 pub(crate) fn normalize_device_name(name: &str) -> String {
     name.replace('_', " ")
         .split_whitespace()
@@ -35,9 +43,214 @@ pub(crate) fn normalize_device_name(name: &str) -> String {
         .to_lowercase()
 }
 
+/// Find the named input device, falling back to the system default when it is gone
+pub(crate) fn select_input_device(
+    host: &cpal::Host,
+    device_name: Option<&str>,
+) -> Result<cpal::Device, String> {
+    if let Some(device_name) = device_name {
+        let normalized_target = normalize_device_name(device_name);
+        let found = host
+            .input_devices()
+            .map_err(|e| format!("Failed to enumerate input devices: {}", e))?
+            .find(|d| {
+                d.name()
+                    .ok()
+                    .map(|n| normalize_device_name(&n) == normalized_target)
+                    .unwrap_or(false)
+            });
+        if let Some(device) = found {
+            return Ok(device);
+        }
+        log::warn!(
+            "Specified device '{}' not found, falling back to default",
+            device_name
+        );
+    }
+    host.default_input_device()
+        .ok_or_else(|| "No input device available".to_string())
+}
+
+/// Open an input stream on `device` that hands `process` the interleaved audio as f32
+/// and i16, after picking or mixing channels as the capture format asks
+pub(crate) fn build_input_stream<F, E>(
+    device: &cpal::Device,
+    config: &CaptureConfig,
+    mut process: F,
+    err_fn: E,
+) -> Result<cpal::Stream, String>
+where
+    F: FnMut(&[f32], &[i16]) + Send + 'static,
+    E: FnMut(cpal::StreamError) + Send + 'static,
+{
+    let mapper = config.mapper.clone();
+    let mut process = move |raw_f32: &[f32], raw_i16: &[i16]| match mapper.map(raw_f32) {
+        Some(mapped) => {
+            let mapped_i16: Vec<i16> = mapped
+                .iter()
+                .map(|&s| (s.clamp(-1.0, 1.0) * 32767.0) as i16)
+                .collect();
+            process(&mapped, &mapped_i16);
+        }
+        None => process(raw_f32, raw_i16),
+    };
+
+    let stream = match config.sample_format() {
+        cpal::SampleFormat::F32 => {
+            device
+                .build_input_stream(
+                    &config.stream,
+                    move |data: &[f32], _: &_| {
+                        // Convert F32 to I16 with proper clamping to avoid distortion
+                        let i16_samples: Vec<i16> = data
+                            .iter()
+                            .map(|&sample| {
+                                // Clamp to avoid overflow and use 32767.0 for symmetric conversion
+                                let clamped = sample.clamp(-1.0, 1.0);
+                                (clamped * 32767.0) as i16
+                            })
+                            .collect();
+
+                        // Process audio
+                        process(data, &i16_samples);
+                    },
+                    err_fn,
+                    None,
+                )
+                .map_err(|e| e.to_string())?
+        }
+        cpal::SampleFormat::I16 => {
+            device
+                .build_input_stream(
+                    &config.stream,
+                    move |data: &[i16], _: &_| {
+                        // Convert I16 to F32 for processing
+                        let f32_samples: Vec<f32> =
+                            data.iter().map(|&x| x as f32 / i16::MAX as f32).collect();
+
+                        // Process audio
+                        process(&f32_samples, data);
+                    },
+                    err_fn,
+                    None,
+                )
+                .map_err(|e| e.to_string())?
+        }
+        cpal::SampleFormat::U16 => {
+            device
+                .build_input_stream(
+                    &config.stream,
+                    move |data: &[u16], _: &_| {
+                        // Convert U16 to F32 for processing
+                        let f32_samples: Vec<f32> = data
+                            .iter()
+                            .map(|&x| (x as f32 - 32768.0) / 32768.0)
+                            .collect();
+
+                        // Convert U16 to I16 for writing
+                        let i16_samples: Vec<i16> =
+                            data.iter().map(|&x| (x as i32 - 32768) as i16).collect();
+
+                        // Process audio
+                        process(&f32_samples, &i16_samples);
+                    },
+                    err_fn,
+                    None,
+                )
+                .map_err(|e| e.to_string())?
+        }
+        _ => {
+            return Err(format!(
+                "Unsupported sample format: {:?}",
+                config.sample_format()
+            ))
+        }
+    };
+    Ok(stream)
+}
+
+/// Stream error callback; a vanished device is reported to the recording thread
+fn stream_error_handler(
+    stop_tx: mpsc::Sender<RecorderCommand>,
+    generation: u32,
+) -> impl FnMut(cpal::StreamError) + Send + 'static {
+    move |err| {
+        log::error!("Stream error: {}", err);
+        if matches!(err, cpal::StreamError::DeviceNotAvailable) {
+            let _ = stop_tx.send(RecorderCommand::DeviceLost(generation));
+        }
+    }
+}
+
+/// Open `name` in its stored `capture_format` for a recording that started in
+/// `format`, converting its audio to that format so the writer, capture buffer and
+/// detectors carry on unchanged
+fn open_replacement<F, E>(
+    host: &cpal::Host,
+    name: &str,
+    capture_format: &CaptureFormat,
+    format: &CaptureConfig,
+    mut process: F,
+    err_fn: E,
+) -> Result<(cpal::Stream, String), String>
+where
+    F: FnMut(&[f32], &[i16]) + Send + 'static,
+    E: FnMut(cpal::StreamError) + Send + 'static,
+{
+    let device = select_input_device(host, Some(name))?;
+    let device_name = device.name().unwrap_or_else(|_| name.to_string());
+    // A stored format the device no longer supports should not cost the recording
+    let config = resolve_capture_config(&device, capture_format).or_else(|e| {
+        log::warn!("Opening {} in its default format: {}", device_name, e);
+        resolve_capture_config(&device, &CaptureFormat::default())
+    })?;
+    let mut adapter = FormatAdapter::new(
+        config.sample_rate(),
+        config.channels(),
+        format.sample_rate(),
+        format.channels(),
+    )?;
+    let adapted = move |samples: &[f32], _: &[i16]| {
+        let samples = adapter.process(samples);
+        if samples.is_empty() {
+            return;
+        }
+        let i16_samples: Vec<i16> = samples
+            .iter()
+            .map(|&s| (s.clamp(-1.0, 1.0) * 32767.0) as i16)
+            .collect();
+        process(&samples, &i16_samples);
+    };
+
+    let stream = build_input_stream(&device, &config, adapted, err_fn)?;
+    stream.play().map_err(|e| e.to_string())?;
+    Ok((stream, device_name))
+}
+
+/// Names of the connected input devices
+pub(crate) fn input_device_names(host: &cpal::Host) -> Vec<String> {
+    host.input_devices()
+        .map(|devices| devices.filter_map(|device| device.name().ok()).collect())
+        .unwrap_or_else(|_| Vec::new())
+}
+
 pub struct AudioRecorder {
     recording_handle: Arc<Mutex<Option<RecordingHandle>>>,
     audio_level_receiver: Arc<Mutex<Option<mpsc::Receiver<f64>>>>,
+    processing_options: AudioProcessingOptions,
+    /// When the recording stops itself on silence
+    silence_config: SilenceConfig,
+    /// Microphone test results for the device, seeding AGC
+    device_levels: Option<DeviceLevels>,
+    /// Microphones to move to when the current one disconnects; `None` stops instead
+    failover_priority: Option<Vec<String>>,
+    /// Sample rate, buffer size, sample format and channels for the device
+    capture_format: CaptureFormat,
+    /// Stored capture formats by normalized device name, for failover targets
+    device_formats: HashMap<String, CaptureFormat>,
+    device_switch_receiver: Arc<Mutex<Option<mpsc::Receiver<DeviceSwitch>>>>,
+    /// 16kHz mono audio from the last in-memory recording
+    captured_audio: Arc<Mutex<Option<Vec<f32>>>>,
 }
 
 impl Drop for AudioRecorder {
@@ -73,6 +286,10 @@ struct RecordingHandle {
 enum RecorderCommand {
     Stop,
     StopSilence,
+    /// The stream of the given generation lost its device
+    DeviceLost(u32),
+    /// The microphone went away and no other one could take over
+    Disconnected,
 }
 
 impl AudioRecorder {
@@ -80,9 +297,48 @@ impl AudioRecorder {
         Self {
             recording_handle: Arc::new(Mutex::new(None)),
             audio_level_receiver: Arc::new(Mutex::new(None)),
+            processing_options: AudioProcessingOptions::default(),
+            silence_config: SilenceConfig::default(),
+            device_levels: None,
+            failover_priority: None,
+            capture_format: CaptureFormat::default(),
+            device_formats: HashMap::new(),
+            device_switch_receiver: Arc::new(Mutex::new(None)),
+            captured_audio: Arc::new(Mutex::new(None)),
         }
     }
 
+    /// Configure the DSP chain used by the next recording
+    pub fn set_processing_options(&mut self, options: AudioProcessingOptions) {
+        self.processing_options = options;
+    }
+
+    /// Configure when the next recording stops itself on silence
+    pub fn set_silence_config(&mut self, config: SilenceConfig) {
+        self.silence_config = config;
+    }
+
+    /// Levels measured by the microphone test for the device of the next recording
+    pub fn set_device_levels(&mut self, levels: Option<DeviceLevels>) {
+        self.device_levels = levels;
+    }
+
+    /// Capture format for the device of the next recording
+    pub fn set_capture_format(&mut self, format: CaptureFormat) {
+        self.capture_format = format;
+    }
+
+    /// Capture formats for the microphones the next recording may move to
+    pub fn set_device_formats(&mut self, formats: HashMap<String, CaptureFormat>) {
+        self.device_formats = formats;
+    }
+
+    /// Keep the next recording going on another microphone if its own disconnects.
+    /// `priority` is tried first, then the system default; `None` disables failover.
+    pub fn set_failover(&mut self, priority: Option<Vec<String>>) {
+        self.failover_priority = priority;
+    }
+
     pub fn start_recording(
         &mut self,
         output_path: &str,
@@ -108,6 +364,14 @@ impl AudioRecorder {
         if let Ok(mut guard) = self.audio_level_receiver.lock() {
             guard.take();
         }
+        if let Ok(mut guard) = self.device_switch_receiver.lock() {
+            guard.take();
+        }
+
+        // Drop in-memory audio from a previous recording that was never collected
+        if let Ok(mut guard) = self.captured_audio.lock() {
+            guard.take();
+        }
 
         let output_path = PathBuf::from(output_path);
         let (stop_tx, stop_rx) = mpsc::channel();
@@ -115,54 +379,37 @@ impl AudioRecorder {
 
         // Create audio level channel (f64 for EBU R128 loudness values)
         let (audio_level_tx, audio_level_rx) = mpsc::channel::<f64>();
+        let (switch_tx, switch_rx) = mpsc::channel::<DeviceSwitch>();
+        let failover = self.failover_priority.clone();
+        let capture_format = self.capture_format.clone();
+        let device_formats = self.device_formats.clone();
 
         // Silence detection config for VAD
-        let silence_duration = Duration::from_secs(10); // 10 seconds of continuous silence
+        let silence_config = self.silence_config;
+
+        let processing_options = self.processing_options.clone();
+        let device_levels = self.device_levels;
+        let in_memory = processing_options.in_memory_capture;
+        let captured_audio = self.captured_audio.clone();
 
         // Spawn recording thread
         let thread_handle = thread::spawn(move || -> Result<String, String> {
             let host = cpal::default_host();
-            let device = if let Some(device_name) = device_name {
-                let normalized_target = normalize_device_name(&device_name);
-                // Try to find the specified device
-                host.input_devices()
-                    .map_err(|e| format!("Failed to enumerate input devices: {}", e))?
-                    .find(|d| {
-                        d.name()
-                            .ok()
-                            .map(|n| normalize_device_name(&n) == normalized_target)
-                            .unwrap_or(false)
-                    })
-                    .ok_or_else(|| {
-                        log::warn!(
-                            "Specified device '{}' not found, falling back to default",
-                            device_name
-                        );
-                        format!("Device '{}' not found", device_name)
-                    })
-                    .or_else(|_| {
-                        // Fallback to default device if specified device not found
-                        host.default_input_device()
-                            .ok_or("No input device available".to_string())
-                    })?
-            } else {
-                // Use default device
-                host.default_input_device()
-                    .ok_or("No input device available")?
-            };
+            let device = select_input_device(&host, device_name.as_deref())?;
 
             let device_name = device.name().unwrap_or_else(|_| "Unknown".to_string());
             log::info!("======================================");
             log::info!("🎤 AUDIO DEVICE SELECTED: {}", device_name);
             log::info!("======================================");
 
-            let config = device.default_input_config().map_err(|e| e.to_string())?;
+            let config = resolve_capture_config(&device, &capture_format)?;
 
             log::info!(
-                "Audio config: sample_rate={} Hz, channels={}, format={:?}",
-                config.sample_rate().0,
+                "Audio config: sample_rate={} Hz, channels={}, format={:?}, buffer={:?}",
+                config.sample_rate(),
                 config.channels(),
-                config.sample_format()
+                config.sample_format(),
+                config.stream.buffer_size
             );
 
             // List all available input devices for debugging
@@ -176,29 +423,54 @@ impl AudioRecorder {
             }
 
             // Initialize silence detector and level meter
-            let silence_detector = Arc::new(Mutex::new(SilenceDetector::new(silence_duration)));
+            let silence_detector = Arc::new(Mutex::new(SilenceDetector::new(silence_config)));
+            // Wall-clock length of one frame, for the silence detector's timing
+            let frame_duration = Duration::from_secs(1) / config.sample_rate();
+            let frame_channels = config.channels() as usize;
 
             let level_meter = Arc::new(Mutex::new(
                 AudioLevelMeter::new(
-                    config.sample_rate().0,
+                    config.sample_rate(),
                     config.channels() as u32,
                     audio_level_tx.clone(),
                 )
                 .map_err(|e| format!("Failed to create level meter: {}", e))?,
             ));
 
+            // Optional DSP chain (high-pass, denoise, AGC) between capture and writer
+            let dsp_chain = Arc::new(Mutex::new(if processing_options.any_enabled() {
+                log::info!("Audio processing enabled: {:?}", processing_options);
+                let mut chain =
+                    DspChain::new(config.sample_rate(), config.channels(), &processing_options);
+                if let Some(levels) = device_levels {
+                    chain.calibrate(levels);
+                }
+                Some(chain)
+            } else {
+                None
+            }));
+
+            // In-memory mode keeps 16kHz mono in RAM; the WAV is only written later if needed
+            let capture_buffer = Arc::new(Mutex::new(if in_memory {
+                log::info!("In-memory capture enabled, skipping WAV writer");
+                Some(CaptureBuffer::new(config.sample_rate(), config.channels())?)
+            } else {
+                None
+            }));
+
             // Record with native settings, Whisper will handle resampling
             let spec = hound::WavSpec {
                 channels: config.channels(),
-                sample_rate: config.sample_rate().0,
+                sample_rate: config.sample_rate(),
                 bits_per_sample: 16,
                 sample_format: hound::SampleFormat::Int,
             };
 
-            let writer = Arc::new(Mutex::new(Some(
-                hound::WavWriter::create(&output_path, spec).map_err(|e| e.to_string())?,
-            )));
-            let err_fn = |err| log::error!("Stream error: {}", err);
+            let writer = Arc::new(Mutex::new(if in_memory {
+                None
+            } else {
+                Some(hound::WavWriter::create(&output_path, spec).map_err(|e| e.to_string())?)
+            }));
             let error_occurred = Arc::new(Mutex::new(None::<String>));
 
             // Shared state for size tracking - use atomic for lock-free updates
@@ -213,25 +485,64 @@ impl AudioRecorder {
                 let stop_tx_for_silence = stop_tx_clone.clone();
                 let silence_detector_clone = silence_detector.clone();
                 let level_meter_clone = level_meter.clone();
+                let dsp_clone = dsp_chain.clone();
+                let capture_clone = capture_buffer.clone();
+
+                move |raw_f32: &[f32], raw_i16: &[i16]| {
+                    // Run the DSP chain when enabled; it replaces the raw samples
+                    let mut dsp_guard = dsp_clone.lock().ok();
+                    let processed = dsp_guard
+                        .as_mut()
+                        .and_then(|g| g.as_mut())
+                        .map(|chain| chain.filter(raw_f32));
+                    // The denoiser hands back audio in whole frames, so some callbacks
+                    // get nothing; meter the raw block then to keep levels and
+                    // silence detection running
+                    let f32_samples = match processed.as_deref() {
+                        Some(filtered) if !filtered.is_empty() => filtered,
+                        _ => raw_f32,
+                    };
 
-                move |f32_samples: &[f32], i16_samples: &[i16]| {
                     // Calculate RMS for both level meter and silence detection
                     let sum: f32 = f32_samples.iter().map(|x| x * x).sum();
                     let rms = (sum / f32_samples.len() as f32).sqrt();
 
                     // Process with level meter
+                    let mut measured_rms = rms;
                     if let Ok(mut meter) = level_meter_clone.try_lock() {
                         let _ = meter.process_samples(f32_samples);
+                        measured_rms = meter.current_rms();
                     }
 
                     // Check for silence
                     if let Ok(mut detector) = silence_detector_clone.try_lock() {
-                        if detector.update(rms) {
+                        // Time this callback covered, whatever the chain returned
+                        let frames = (raw_f32.len() / frame_channels) as u32;
+                        if detector.update(rms, frame_duration * frames) {
                             // Silence duration exceeded, stop recording
                             let _ = stop_tx_for_silence.send(RecorderCommand::StopSilence);
                         }
                     }
 
+                    // Gain is applied after metering so AGC tracks the loudness the user sees
+                    let gained: Option<Vec<f32>> =
+                        match (processed, dsp_guard.as_mut().and_then(|g| g.as_mut())) {
+                            (Some(mut samples), Some(chain)) => {
+                                chain.apply_gain(&mut samples, measured_rms);
+                                Some(samples)
+                            }
+                            _ => None,
+                        };
+                    drop(dsp_guard);
+                    let f32_samples = gained.as_deref().unwrap_or(raw_f32);
+                    let gained_i16: Option<Vec<i16>> = gained.as_ref().map(|samples| {
+                        samples
+                            .iter()
+                            .map(|&s| (s.clamp(-1.0, 1.0) * 32767.0) as i16)
+                            .collect()
+                    });
+                    let i16_samples = gained_i16.as_deref().unwrap_or(raw_i16);
+
                     // Check size before writing - lock-free atomic update
                     let sample_bytes = i16_samples.len() * 2; // 2 bytes per i16 sample
                     let new_total = bytes_clone.fetch_add(sample_bytes as u64, Ordering::Relaxed) + sample_bytes as u64;
@@ -240,6 +551,17 @@ impl AudioRecorder {
                         return;
                     }
 
+                    // Keep the in-memory copy (resampled to 16kHz mono on the fly)
+                    if let Ok(mut guard) = capture_clone.lock() {
+                        if let Some(capture) = guard.as_mut() {
+                            if let Err(e) = capture.push_interleaved(f32_samples) {
+                                if let Ok(mut error_guard) = error_clone.lock() {
+                                    *error_guard = Some(e);
+                                }
+                            }
+                        }
+                    }
+
                     // Write audio data (i16 format)
                     if let Ok(mut guard) = writer_clone.try_lock() {
                         if let Some(writer) = guard.as_mut() {
@@ -257,79 +579,12 @@ impl AudioRecorder {
                 }
             };
 
-            let stream = match config.sample_format() {
-                cpal::SampleFormat::F32 => {
-                    let process_clone = process_audio.clone();
-                    device
-                        .build_input_stream(
-                            &config.config(),
-                            move |data: &[f32], _: &_| {
-                                // Convert F32 to I16 with proper clamping to avoid distortion
-                                let i16_samples: Vec<i16> = data
-                                    .iter()
-                                    .map(|&sample| {
-                                        // Clamp to avoid overflow and use 32767.0 for symmetric conversion
-                                        let clamped = sample.clamp(-1.0, 1.0);
-                                        (clamped * 32767.0) as i16
-                                    })
-                                    .collect();
-
-                                // Process audio
-                                process_clone(data, &i16_samples);
-                            },
-                            err_fn,
-                            None,
-                        )
-                        .map_err(|e| e.to_string())?
-                }
-                cpal::SampleFormat::I16 => {
-                    let process_clone = process_audio.clone();
-                    device
-                        .build_input_stream(
-                            &config.config(),
-                            move |data: &[i16], _: &_| {
-                                // Convert I16 to F32 for processing
-                                let f32_samples: Vec<f32> =
-                                    data.iter().map(|&x| x as f32 / i16::MAX as f32).collect();
-
-                                // Process audio
-                                process_clone(&f32_samples, data);
-                            },
-                            err_fn,
-                            None,
-                        )
-                        .map_err(|e| e.to_string())?
-                }
-                cpal::SampleFormat::U16 => {
-                    device
-                        .build_input_stream(
-                            &config.config(),
-                            move |data: &[u16], _: &_| {
-                                // Convert U16 to F32 for processing
-                                let f32_samples: Vec<f32> = data
-                                    .iter()
-                                    .map(|&x| (x as f32 - 32768.0) / 32768.0)
-                                    .collect();
-
-                                // Convert U16 to I16 for writing
-                                let i16_samples: Vec<i16> =
-                                    data.iter().map(|&x| (x as i32 - 32768) as i16).collect();
-
-                                // Process audio
-                                process_audio(&f32_samples, &i16_samples);
-                            },
-                            err_fn,
-                            None,
-                        )
-                        .map_err(|e| e.to_string())?
-                }
-                _ => {
-                    return Err(format!(
-                        "Unsupported sample format: {:?}",
-                        config.sample_format()
-                    ))
-                }
-            };
+            let stream = build_input_stream(
+                &device,
+                &config,
+                process_audio.clone(),
+                stream_error_handler(stop_tx_clone.clone(), 0),
+            )?;
 
             stream.play().map_err(|e| {
                 log::error!("Failed to start audio stream: {}", e);
@@ -338,8 +593,103 @@ impl AudioRecorder {
 
             log::info!("Audio stream started successfully");
 
-            // Wait for stop signal
-            let stop_reason = stop_rx.recv().ok();
+            // Wait for stop signal, moving to another microphone if this one goes away
+            let mut stream = Some(stream);
+            let mut current_device = device_name.clone();
+            let mut generation = 0;
+            let stop_reason = loop {
+                // Without failover a lost device only needs its stream error; with it,
+                // the device list shows when the original microphone is back
+                let command = match failover {
+                    Some(_) => stop_rx.recv_timeout(DEVICE_CHECK_INTERVAL),
+                    None => stop_rx
+                        .recv()
+                        .map_err(|_| mpsc::RecvTimeoutError::Disconnected),
+                };
+                // Either the current microphone is gone, or the original one is back
+                let restore = match command {
+                    Ok(RecorderCommand::DeviceLost(lost)) if lost == generation => false,
+                    Ok(RecorderCommand::DeviceLost(_)) => continue,
+                    Ok(command) => break Some(command),
+                    Err(mpsc::RecvTimeoutError::Disconnected) => break None,
+                    Err(mpsc::RecvTimeoutError::Timeout) => {
+                        let available = input_device_names(&host);
+                        if available.is_empty() {
+                            // Listing failed; stream errors still report a lost device
+                            continue;
+                        } else if !is_connected(&current_device, &available) {
+                            false
+                        } else if current_device != device_name
+                            && is_connected(&device_name, &available)
+                        {
+                            true
+                        } else {
+                            continue;
+                        }
+                    }
+                };
+
+                let target = match failover.as_deref() {
+                    Some(_) if restore => Some(device_name.clone()),
+                    Some(priority) => {
+                        let default = host.default_input_device().and_then(|d| d.name().ok());
+                        let available = input_device_names(&host);
+                        next_device(&current_device, priority, &available, default.as_deref())
+                    }
+                    None => None,
+                };
+                drop(stream.take());
+
+                // A failed restore goes back to the microphone that was working
+                let mut candidates: Vec<String> = target.into_iter().collect();
+                if restore {
+                    candidates.push(current_device.clone());
+                }
+                generation += 1;
+                let replacement = candidates.iter().find_map(|name| {
+                    let capture_format = device_formats
+                        .get(&normalize_device_name(name))
+                        .cloned()
+                        .unwrap_or_default();
+                    open_replacement(
+                        &host,
+                        name,
+                        &capture_format,
+                        &config,
+                        process_audio.clone(),
+                        stream_error_handler(stop_tx_clone.clone(), generation),
+                    )
+                    .map_err(|e| log::warn!("Could not switch recording to {}: {}", name, e))
+                    .ok()
+                });
+
+                match replacement {
+                    Some((replacement, name)) => {
+                        log::info!("🎤 Recording moved from {} to {}", current_device, name);
+                        stream = Some(replacement);
+                        if name != current_device {
+                            let _ = switch_tx.send(DeviceSwitch {
+                                from: current_device.clone(),
+                                to: Some(name.clone()),
+                                restored: restore,
+                            });
+                            current_device = name;
+                        }
+                    }
+                    None => {
+                        log::warn!(
+                            "Microphone {} disconnected and no other one is available",
+                            current_device
+                        );
+                        let _ = switch_tx.send(DeviceSwitch {
+                            from: current_device.clone(),
+                            to: None,
+                            restored: false,
+                        });
+                        break Some(RecorderCommand::Disconnected);
+                    }
+                }
+            };
 
             // Stop and finalize
             drop(stream);
@@ -351,20 +701,48 @@ impl AudioRecorder {
                 }
             }
 
+            // Samples still buffered in the DSP chain
+            let dsp_tail = dsp_chain
+                .lock()
+                .ok()
+                .and_then(|mut g| g.take())
+                .map(|mut chain| chain.flush())
+                .unwrap_or_default();
+
             // Take the writer out of the mutex to finalize it
             if let Ok(mut guard) = writer.lock() {
-                if let Some(w) = guard.take() {
+                if let Some(mut w) = guard.take() {
+                    for &sample in &dsp_tail {
+                        w.write_sample((sample.clamp(-1.0, 1.0) * 32767.0) as i16)
+                            .map_err(|e| e.to_string())?;
+                    }
                     w.finalize().map_err(|e| e.to_string())?;
                 }
             }
 
+            // Hand the in-memory audio over to the recorder
+            if let Some(mut capture) = capture_buffer.lock().ok().and_then(|mut g| g.take()) {
+                capture.push_interleaved(&dsp_tail)?;
+                let samples = capture.finish()?;
+                log::info!(
+                    "In-memory capture complete: {:.2}s at 16kHz",
+                    samples.len() as f32 / 16_000.0
+                );
+                if let Ok(mut guard) = captured_audio.lock() {
+                    *guard = Some(samples);
+                }
+            }
+
             // Return appropriate message based on stop reason
             match stop_reason {
                 Some(RecorderCommand::StopSilence) => {
                     Ok("Recording stopped due to silence".to_string())
                 }
                 Some(RecorderCommand::Stop) => Ok("Recording stopped by user".to_string()),
-                None => Ok("Recording stopped".to_string()),
+                Some(RecorderCommand::Disconnected) => {
+                    Ok("Recording stopped: microphone disconnected".to_string())
+                }
+                Some(RecorderCommand::DeviceLost(_)) | None => Ok("Recording stopped".to_string()),
             }
         });
 
@@ -379,6 +757,10 @@ impl AudioRecorder {
             .audio_level_receiver
             .lock()
             .map_err(|e| format!("Failed to acquire lock: {}", e))? = Some(audio_level_rx);
+        *self
+            .device_switch_receiver
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock: {}", e))? = Some(switch_rx);
 
         Ok(())
     }
@@ -423,6 +805,19 @@ impl AudioRecorder {
         }
     }
 
+    /// Whether the recording thread ended on its own (silence, size limit, error)
+    /// and is waiting for `stop_recording` to collect it
+    pub fn has_stopped(&self) -> bool {
+        self.recording_handle
+            .lock()
+            .map(|guard| {
+                guard
+                    .as_ref()
+                    .is_some_and(|handle| handle.thread_handle.is_finished())
+            })
+            .unwrap_or(false)
+    }
+
     pub fn is_recording(&self) -> bool {
         self.recording_handle
             .lock()
@@ -430,6 +825,14 @@ impl AudioRecorder {
             .unwrap_or(false)
     }
 
+    /// Take the 16kHz mono audio of the last recording made with in-memory capture
+    pub fn take_captured_audio(&mut self) -> Option<Vec<f32>> {
+        self.captured_audio
+            .lock()
+            .ok()
+            .and_then(|mut guard| guard.take())
+    }
+
     pub fn take_audio_level_receiver(&mut self) -> Option<mpsc::Receiver<f64>> {
         self.audio_level_receiver
             .lock()
@@ -437,10 +840,22 @@ impl AudioRecorder {
             .and_then(|mut guard| guard.take())
     }
 
+    /// Name of the system default input device
+    pub fn default_device_name() -> Option<String> {
+        cpal::default_host()
+            .default_input_device()
+            .and_then(|device| device.name().ok())
+    }
+
+    /// Microphone switches of the current recording, for the UI and the tray
+    pub fn take_device_switch_receiver(&mut self) -> Option<mpsc::Receiver<DeviceSwitch>> {
+        self.device_switch_receiver
+            .lock()
+            .ok()
+            .and_then(|mut guard| guard.take())
+    }
+
     pub fn get_devices() -> Vec<String> {
-        let host = cpal::default_host();
-        host.input_devices()
-            .map(|devices| devices.filter_map(|device| device.name().ok()).collect())
-            .unwrap_or_else(|_| Vec::new())
+        input_device_names(&cpal::default_host())
     }
 }
@@ -2,6 +2,19 @@ use rubato::{
     Resampler, SincFixedIn, SincInterpolationParameters, SincInterpolationType, WindowFunction,
 };
 
+/// Configure resampler parameters optimized for speech
+/// Speech has limited frequency content (85-8000 Hz), so we can use
+/// lower quality settings without audible degradation
+fn speech_sinc_params() -> SincInterpolationParameters {
+    SincInterpolationParameters {
+        sinc_len: 64,   // Reduced from 256 - sufficient for speech frequencies
+        f_cutoff: 0.95, // Prevent aliasing
+        interpolation: SincInterpolationType::Linear,
+        oversampling_factor: 128, // Reduced from 256 - sufficient for speech
+        window: WindowFunction::BlackmanHarris2,
+    }
+}
+
 /// Resample audio from any sample rate to 16kHz for Whisper
 pub fn resample_to_16khz(input: &[f32], input_sample_rate: u32) -> Result<Vec<f32>, String> {
     // If already at 16kHz, just return a copy
@@ -15,16 +28,7 @@ pub fn resample_to_16khz(input: &[f32], input_sample_rate: u32) -> Result<Vec<f3
     // Calculate resampling ratio
     let resample_ratio = 16_000_f64 / input_sample_rate as f64;
 
-    // Configure resampler parameters optimized for speech
-    // Speech has limited frequency content (85-8000 Hz), so we can use
-    // lower quality settings without audible degradation
-    let params = SincInterpolationParameters {
-        sinc_len: 64,   // Reduced from 256 - sufficient for speech frequencies
-        f_cutoff: 0.95, // Prevent aliasing
-        interpolation: SincInterpolationType::Linear,
-        oversampling_factor: 128, // Reduced from 256 - sufficient for speech
-        window: WindowFunction::BlackmanHarris2,
-    };
+    let params = speech_sinc_params();
 
     // Create resampler with fixed input chunk size
     // We'll process the entire audio at once for simplicity
@@ -76,6 +80,118 @@ pub fn resample_to_16khz(input: &[f32], input_sample_rate: u32) -> Result<Vec<f3
     Ok(output)
 }
 
+/// Incremental mono resampler, to 16kHz unless asked otherwise, fed from the
+/// capture callback.
+///
+/// Input arrives in arbitrary block sizes; it is buffered into fixed chunks for
+/// rubato and the filter delay is trimmed so output lines up with the input.
+pub struct StreamingResampler {
+    resampler: Option<SincFixedIn<f32>>,
+    pending: Vec<f32>,
+    chunk_size: usize,
+    ratio: f64,
+    delay_remaining: usize,
+    frames_in: usize,
+    frames_out: usize,
+}
+
+impl StreamingResampler {
+    /// ~20ms at 48kHz, small enough to keep the capture callback light
+    const CHUNK_SIZE: usize = 1024;
+
+    pub fn new(input_sample_rate: u32) -> Result<Self, String> {
+        Self::with_output_rate(input_sample_rate, 16_000)
+    }
+
+    pub fn with_output_rate(
+        input_sample_rate: u32,
+        output_sample_rate: u32,
+    ) -> Result<Self, String> {
+        let ratio = output_sample_rate as f64 / input_sample_rate as f64;
+
+        let resampler = if input_sample_rate == output_sample_rate {
+            None
+        } else {
+            Some(
+                SincFixedIn::<f32>::new(ratio, 2.0, speech_sinc_params(), Self::CHUNK_SIZE, 1)
+                    .map_err(|e| format!("Failed to create resampler: {:?}", e))?,
+            )
+        };
+        let delay_remaining = resampler.as_ref().map(|r| r.output_delay()).unwrap_or(0);
+
+        Ok(Self {
+            resampler,
+            pending: Vec::with_capacity(Self::CHUNK_SIZE * 2),
+            chunk_size: Self::CHUNK_SIZE,
+            ratio,
+            delay_remaining,
+            frames_in: 0,
+            frames_out: 0,
+        })
+    }
+
+    /// Feed mono samples and return whatever resampled output is ready
+    pub fn process(&mut self, input: &[f32]) -> Result<Vec<f32>, String> {
+        self.frames_in += input.len();
+
+        let Some(resampler) = self.resampler.as_mut() else {
+            self.frames_out += input.len();
+            return Ok(input.to_vec());
+        };
+
+        self.pending.extend_from_slice(input);
+        let mut output = Vec::new();
+        while self.pending.len() >= self.chunk_size {
+            let chunk = resampler
+                .process(&[&self.pending[..self.chunk_size]], None)
+                .map_err(|e| format!("Resampling failed: {:?}", e))?;
+            self.pending.drain(..self.chunk_size);
+            Self::append_trimmed(&mut output, &chunk[0], &mut self.delay_remaining);
+        }
+
+        self.frames_out += output.len();
+        Ok(output)
+    }
+
+    /// Flush buffered input and the filter tail
+    pub fn finish(&mut self) -> Result<Vec<f32>, String> {
+        let Some(resampler) = self.resampler.as_mut() else {
+            return Ok(Vec::new());
+        };
+
+        let mut output = Vec::new();
+        if !self.pending.is_empty() {
+            let chunk = resampler
+                .process_partial(Some(&[&self.pending[..]][..]), None)
+                .map_err(|e| format!("Resampling failed: {:?}", e))?;
+            self.pending.clear();
+            Self::append_trimmed(&mut output, &chunk[0], &mut self.delay_remaining);
+        }
+
+        // Push silence through until the delayed samples come out
+        let expected_total = (self.frames_in as f64 * self.ratio).round() as usize;
+        while self.frames_out + output.len() < expected_total {
+            let chunk = resampler
+                .process_partial::<&[f32]>(None, None)
+                .map_err(|e| format!("Resampling failed: {:?}", e))?;
+            if chunk[0].is_empty() {
+                break;
+            }
+            Self::append_trimmed(&mut output, &chunk[0], &mut self.delay_remaining);
+        }
+
+        output.truncate(expected_total.saturating_sub(self.frames_out));
+        self.frames_out += output.len();
+        Ok(output)
+    }
+
+    fn append_trimmed(output: &mut Vec<f32>, chunk: &[f32], delay_remaining: &mut usize) {
+        let skip = (*delay_remaining).min(chunk.len());
+        *delay_remaining -= skip;
+        output.extend_from_slice(&chunk[skip..]);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -105,4 +221,35 @@ mod tests {
         // Should be approximately 2/3 the size
         assert!((result.len() as f32 - 16_000.0).abs() < 100.0);
     }
+
+    #[test]
+    fn test_streaming_resampler_matches_expected_length() {
+        let mut resampler = StreamingResampler::new(48_000).unwrap();
+        let mut output = Vec::new();
+        // Feed uneven callback-sized blocks
+        for block in vec![0.5f32; 48_000].chunks(441) {
+            output.extend(resampler.process(block).unwrap());
+        }
+        output.extend(resampler.finish().unwrap());
+        assert_eq!(output.len(), 16_000);
+    }
+
+    #[test]
+    fn test_streaming_resampler_upsamples() {
+        let mut resampler = StreamingResampler::with_output_rate(16_000, 48_000).unwrap();
+        let mut output = Vec::new();
+        for block in vec![0.5f32; 16_000].chunks(160) {
+            output.extend(resampler.process(block).unwrap());
+        }
+        output.extend(resampler.finish().unwrap());
+        assert_eq!(output.len(), 48_000);
+    }
+
+    #[test]
+    fn test_streaming_resampler_passthrough_at_16khz() {
+        let mut resampler = StreamingResampler::new(16_000).unwrap();
+        let input: Vec<f32> = (0..1000).map(|i| i as f32 / 1000.0).collect();
+        assert_eq!(resampler.process(&input).unwrap(), input);
+        assert!(resampler.finish().unwrap().is_empty());
+    }
 }
@@ -0,0 +1,272 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Subdirectory of `recordings/` holding audio kept for history entries
+const RETAINED_AUDIO_DIR: &str = "retained";
+
+/// Compressed format for retained recordings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RetentionFormat {
+    /// Lossless, roughly half the size of the WAV
+    Flac,
+    /// Lossy speech-tuned, ~10x smaller than FLAC
+    Opus,
+}
+
+impl RetentionFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            RetentionFormat::Flac => "flac",
+            RetentionFormat::Opus => "opus",
+        }
+    }
+
+    /// ffmpeg codec name and optional bitrate
+    pub fn codec(&self) -> (&'static str, Option<&'static str>) {
+        match self {
+            RetentionFormat::Flac => ("flac", None),
+            RetentionFormat::Opus => ("libopus", Some("24k")),
+        }
+    }
+}
+
+/// Opt-in policy for keeping recorded audio alongside transcription history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AudioRetentionPolicy {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_format")]
+    pub format: RetentionFormat,
+    /// Total size budget for retained audio; oldest files are removed first
+    #[serde(default = "default_max_total_mb")]
+    pub max_total_mb: u64,
+    /// Files older than this are removed (0 = keep regardless of age)
+    #[serde(default = "default_max_age_days")]
+    pub max_age_days: u32,
+}
+
+fn default_format() -> RetentionFormat {
+    RetentionFormat::Opus
+}
+
+fn default_max_total_mb() -> u64 {
+    500
+}
+
+fn default_max_age_days() -> u32 {
+    30
+}
+
+impl Default for AudioRetentionPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            format: default_format(),
+            max_total_mb: default_max_total_mb(),
+            max_age_days: default_max_age_days(),
+        }
+    }
+}
+
+impl AudioRetentionPolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_total_mb == 0 {
+            return Err("Retention size quota must be at least 1 MB".to_string());
+        }
+        Ok(())
+    }
+}
+
+pub fn retained_audio_dir(recordings_dir: &Path) -> PathBuf {
+    recordings_dir.join(RETAINED_AUDIO_DIR)
+}
+
+/// Apply age and size quotas to the retained audio directory.
+/// `keep` is never removed (the file that was just written).
+/// Returns the files that were deleted.
+pub fn enforce_quotas(
+    dir: &Path,
+    policy: &AudioRetentionPolicy,
+    keep: Option<&Path>,
+) -> Result<Vec<PathBuf>, String> {
+    let mut files: Vec<(PathBuf, SystemTime, u64)> = std::fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read retained audio directory: {}", e))?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            if !metadata.is_file() {
+                return None;
+            }
+            let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            Some((entry.path(), modified, metadata.len()))
+        })
+        .collect();
+
+    // Oldest first
+    files.sort_by_key(|(_, modified, _)| *modified);
+
+    let mut removed = Vec::new();
+    let is_kept = |path: &Path| keep.map(|k| k == path).unwrap_or(false);
+
+    // Age quota
+    if policy.max_age_days > 0 {
+        let max_age = Duration::from_secs(policy.max_age_days as u64 * 24 * 60 * 60);
+        let now = SystemTime::now();
+        files.retain(|(path, modified, _)| {
+            let expired = now
+                .duration_since(*modified)
+                .map(|age| age > max_age)
+                .unwrap_or(false);
+            if expired && !is_kept(path) && std::fs::remove_file(path).is_ok() {
+                removed.push(path.clone());
+                return false;
+            }
+            true
+        });
+    }
+
+    // Size quota
+    let budget = policy.max_total_mb * 1024 * 1024;
+    let mut total: u64 = files.iter().map(|(_, _, size)| size).sum();
+    for (path, _, size) in &files {
+        if total <= budget {
+            break;
+        }
+        if is_kept(path) {
+            continue;
+        }
+        if std::fs::remove_file(path).is_ok() {
+            total = total.saturating_sub(*size);
+            removed.push(path.clone());
+        }
+    }
+
+    if !removed.is_empty() {
+        log::info!(
+            "Retention quota removed {} audio file(s), {:.1}MB retained",
+            removed.len(),
+            total as f64 / 1024.0 / 1024.0
+        );
+    }
+
+    Ok(removed)
+}
+
+/// Remove `entry`'s `audio_file` link if it names one of the `removed` files.
+/// Returns whether the entry changed.
+pub fn clear_audio_link(entry: &mut serde_json::Value, removed: &[PathBuf]) -> bool {
+    let linked = match entry.get("audio_file").and_then(|v| v.as_str()) {
+        Some(name) => removed
+            .iter()
+            .any(|path| path.file_name().is_some_and(|file| file == name)),
+        None => false,
+    };
+    if linked {
+        if let Some(entry) = entry.as_object_mut() {
+            entry.remove("audio_file");
+        }
+    }
+    linked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn write_file(dir: &Path, name: &str, size: usize, age_secs: u64) -> PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, vec![0u8; size]).unwrap();
+        let mtime = SystemTime::now() - Duration::from_secs(age_secs);
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(mtime)
+            .unwrap();
+        path
+    }
+
+    #[test]
+    fn test_size_quota_removes_oldest_first() {
+        let temp_dir = TempDir::new().unwrap();
+        let mb = 1024 * 1024;
+        let oldest = write_file(temp_dir.path(), "a.opus", mb, 300);
+        let middle = write_file(temp_dir.path(), "b.opus", mb, 200);
+        let newest = write_file(temp_dir.path(), "c.opus", mb, 100);
+
+        let policy = AudioRetentionPolicy {
+            enabled: true,
+            max_total_mb: 2,
+            max_age_days: 0,
+            ..Default::default()
+        };
+        let removed = enforce_quotas(temp_dir.path(), &policy, Some(&newest)).unwrap();
+
+        assert_eq!(removed, vec![oldest.clone()]);
+        assert!(!oldest.exists());
+        assert!(middle.exists());
+        assert!(newest.exists());
+    }
+
+    #[test]
+    fn test_age_quota_removes_expired_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let expired = write_file(temp_dir.path(), "old.flac", 10, 3 * 24 * 60 * 60);
+        let fresh = write_file(temp_dir.path(), "new.flac", 10, 60);
+
+        let policy = AudioRetentionPolicy {
+            enabled: true,
+            max_age_days: 2,
+            ..Default::default()
+        };
+        let removed = enforce_quotas(temp_dir.path(), &policy, None).unwrap();
+
+        assert_eq!(removed, vec![expired]);
+        assert!(fresh.exists());
+    }
+
+    #[test]
+    fn test_kept_file_survives_quota() {
+        let temp_dir = TempDir::new().unwrap();
+        let just_written = write_file(temp_dir.path(), "big.flac", 2 * 1024 * 1024, 0);
+
+        let policy = AudioRetentionPolicy {
+            enabled: true,
+            max_total_mb: 1,
+            ..Default::default()
+        };
+        let removed = enforce_quotas(temp_dir.path(), &policy, Some(&just_written)).unwrap();
+
+        assert!(removed.is_empty());
+        assert!(just_written.exists());
+    }
+
+    #[test]
+    fn test_clear_audio_link_only_touches_removed_files() {
+        let removed = vec![PathBuf::from("/recordings/retained/a.opus")];
+        let mut linked = serde_json::json!({ "text": "hi", "audio_file": "a.opus" });
+        let mut other = serde_json::json!({ "text": "hi", "audio_file": "b.opus" });
+        let mut plain = serde_json::json!({ "text": "hi" });
+
+        assert!(clear_audio_link(&mut linked, &removed));
+        assert_eq!(linked, serde_json::json!({ "text": "hi" }));
+        assert!(!clear_audio_link(&mut other, &removed));
+        assert_eq!(other["audio_file"], "b.opus");
+        assert!(!clear_audio_link(&mut plain, &removed));
+    }
+
+    #[test]
+    fn test_policy_defaults_and_format_serialization() {
+        let policy: AudioRetentionPolicy =
+            serde_json::from_value(serde_json::json!({ "enabled": true, "format": "flac" }))
+                .unwrap();
+        assert!(policy.enabled);
+        assert_eq!(policy.format, RetentionFormat::Flac);
+        assert_eq!(policy.max_total_mb, 500);
+        assert_eq!(policy.format.extension(), "flac");
+    }
+}
@@ -1,33 +1,290 @@
-use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
-/// Simple silence detector based on audio level
+/// RMS treated as voice unless calibration says otherwise (matches original whisper.cpp threshold)
+pub const DEFAULT_VOICE_THRESHOLD: f32 = 0.005;
+/// Ambient noise is measured over the start of the recording
+const CALIBRATION_PERIOD: Duration = Duration::from_millis(500);
+/// Voice has to be this much louder than the room (~10 dB)
+const NOISE_MARGIN: f32 = 3.0;
+/// A calibrated threshold never goes above normal speech level
+const MAX_CALIBRATED_THRESHOLD: f32 = 0.02;
+/// Silence that ends any recording, also when nothing has been said yet
+const NO_SPEECH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// When a recording stops by itself
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SilenceConfig {
+    /// Silence after speech that stops the recording
+    pub silence_duration: Duration,
+    /// Speech needed before a pause counts; shorter noises do not arm the stop
+    pub min_speech: Duration,
+    /// Voice threshold, or the lowest one calibration may pick.
+    /// `None` until the user or a microphone test sets one.
+    pub threshold: Option<f32>,
+    pub calibrate: bool,
+}
+
+impl SilenceConfig {
+    /// Use the threshold a microphone test measured for this device, unless the user set one
+    pub fn with_device_threshold(mut self, voice_threshold: f32) -> Self {
+        self.threshold.get_or_insert(voice_threshold);
+        self
+    }
+
+    fn voice_threshold(&self) -> f32 {
+        self.threshold.unwrap_or(DEFAULT_VOICE_THRESHOLD)
+    }
+}
+
+impl Default for SilenceConfig {
+    /// Safety net for manual recordings: only a long silence ends them
+    fn default() -> Self {
+        Self {
+            silence_duration: NO_SPEECH_TIMEOUT,
+            min_speech: Duration::ZERO,
+            threshold: None,
+            calibrate: false,
+        }
+    }
+}
+
+/// Auto-stop settings, stored under `auto_stop` in the settings store
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoStopSettings {
+    /// "Dictate until I pause": stop and transcribe at the first pause after speech
+    pub enabled: bool,
+    pub silence_duration_ms: u32,
+    pub min_speech_ms: u32,
+    /// Set the threshold from the room noise in the first 500 ms
+    pub calibrate: bool,
+    /// `None` uses the microphone test's threshold, or the default without one
+    pub threshold: Option<f32>,
+}
+
+impl Default for AutoStopSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            silence_duration_ms: 1500,
+            min_speech_ms: 400,
+            calibrate: true,
+            threshold: None,
+        }
+    }
+}
+
+impl AutoStopSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(300..=60_000).contains(&self.silence_duration_ms) {
+            return Err("Silence duration must be between 0.3 and 60 seconds".to_string());
+        }
+        if self.min_speech_ms > 10_000 {
+            return Err("Minimum speech must be at most 10 seconds".to_string());
+        }
+        if matches!(self.threshold, Some(threshold) if !(0.0005..=0.1).contains(&threshold)) {
+            return Err("Silence threshold must be between 0.0005 and 0.1".to_string());
+        }
+        Ok(())
+    }
+
+    /// Detector for a toggle-mode recording; without auto-stop only the safety net applies
+    pub fn silence_config(&self) -> SilenceConfig {
+        if self.enabled {
+            self.pause_detection(Duration::from_millis(self.silence_duration_ms as u64))
+        } else {
+            SilenceConfig::default()
+        }
+    }
+
+    /// Stop at the first `silence_duration` pause after speech, whatever `enabled` says
+    pub fn pause_detection(&self, silence_duration: Duration) -> SilenceConfig {
+        SilenceConfig {
+            silence_duration,
+            min_speech: Duration::from_millis(self.min_speech_ms as u64),
+            threshold: self.threshold,
+            calibrate: self.calibrate,
+        }
+    }
+}
+
+/// Voice threshold for a room with the given noise floor, never below `floor`
+pub fn voice_threshold_for_noise(noise_floor: f32, floor: f32) -> f32 {
+    (noise_floor * NOISE_MARGIN)
+        .min(MAX_CALIBRATED_THRESHOLD)
+        .max(floor)
+}
+
+/// Silence detector based on audio level
 pub struct SilenceDetector {
-    /// Last time voice was detected
-    last_voice_time: Instant,
-    /// How long silence before stopping
-    silence_duration: Duration,
-    /// RMS threshold for voice detection
-    voice_threshold: f32,
+    config: SilenceConfig,
+    threshold: f32,
+    /// RMS levels seen during calibration; `None` once the threshold is set
+    calibration: Option<Vec<f32>>,
+    calibration_elapsed: Duration,
+    speech: Duration,
+    silence: Duration,
 }
 
 impl SilenceDetector {
-    pub fn new(silence_duration: Duration) -> Self {
+    pub fn new(config: SilenceConfig) -> Self {
         Self {
-            last_voice_time: Instant::now(),
-            silence_duration,
-            voice_threshold: 0.005, // 0.5% - matches original whisper.cpp threshold
+            config,
+            threshold: config.voice_threshold(),
+            calibration: config.calibrate.then(Vec::new),
+            calibration_elapsed: Duration::ZERO,
+            speech: Duration::ZERO,
+            silence: Duration::ZERO,
         }
     }
 
-    /// Update with current RMS level and check if should stop
-    pub fn update(&mut self, rms: f32) -> bool {
-        if rms > self.voice_threshold {
-            // Voice detected, update timestamp
-            self.last_voice_time = Instant::now();
-            false // Don't stop
+    pub fn threshold(&self) -> f32 {
+        self.threshold
+    }
+
+    /// Update with the RMS of the next `chunk` of audio and check if should stop
+    pub fn update(&mut self, rms: f32, chunk: Duration) -> bool {
+        if let Some(levels) = self.calibration.as_mut() {
+            levels.push(rms);
+            self.calibration_elapsed += chunk;
+            if self.calibration_elapsed >= CALIBRATION_PERIOD {
+                self.finish_calibration();
+            }
+            return false;
+        }
+
+        if rms > self.threshold {
+            // Voice detected
+            self.speech += chunk;
+            self.silence = Duration::ZERO;
+            return false;
+        }
+
+        self.silence += chunk;
+        if self.speech >= self.config.min_speech {
+            self.silence > self.config.silence_duration
         } else {
-            // Check if silence duration exceeded
-            self.last_voice_time.elapsed() > self.silence_duration
+            self.silence > self.config.silence_duration.max(NO_SPEECH_TIMEOUT)
+        }
+    }
+
+    fn finish_calibration(&mut self) {
+        let Some(mut levels) = self.calibration.take() else {
+            return;
+        };
+        // The median ignores a word or a click during the measurement
+        levels.sort_by(|a, b| a.total_cmp(b));
+        let noise_floor = levels[levels.len() / 2];
+        self.threshold = voice_threshold_for_noise(noise_floor, self.config.voice_threshold());
+        log::info!(
+            "Silence threshold calibrated: noise floor {:.4}, threshold {:.4}",
+            noise_floor,
+            self.threshold
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHUNK: Duration = Duration::from_millis(100);
+
+    fn feed(detector: &mut SilenceDetector, rms: f32, chunks: usize) -> bool {
+        (0..chunks).any(|_| detector.update(rms, CHUNK))
+    }
+
+    #[test]
+    fn test_pause_after_speech_stops() {
+        let settings = AutoStopSettings {
+            enabled: true,
+            calibrate: false,
+            ..AutoStopSettings::default()
+        };
+        let mut detector = SilenceDetector::new(settings.silence_config());
+
+        // Hesitating before speaking does not stop the recording
+        assert!(!feed(&mut detector, 0.0, 30));
+        // Too little speech to arm the stop
+        assert!(!feed(&mut detector, 0.05, 2));
+        assert!(!feed(&mut detector, 0.0, 16));
+
+        assert!(!feed(&mut detector, 0.05, 3));
+        assert!(!feed(&mut detector, 0.0, 15));
+        assert!(detector.update(0.0, CHUNK));
+    }
+
+    #[test]
+    fn test_safety_net_without_speech() {
+        let mut detector = SilenceDetector::new(SilenceConfig::default());
+        assert!(!feed(&mut detector, 0.0, 100));
+        assert!(detector.update(0.0, CHUNK));
+    }
+
+    #[test]
+    fn test_calibration_raises_threshold_above_room_noise() {
+        let config = AutoStopSettings::default().pause_detection(Duration::from_secs(1));
+        let mut detector = SilenceDetector::new(config);
+
+        // A noisy room with one loud click
+        assert!(!feed(&mut detector, 0.004, 4));
+        assert!(!detector.update(0.5, CHUNK));
+        assert!((detector.threshold() - 0.012).abs() < 1e-6);
+
+        // Room noise now counts as silence: speech, then noise until the stop
+        assert!(!feed(&mut detector, 0.05, 5));
+        assert!(feed(&mut detector, 0.004, 11));
+
+        // A quiet room keeps the configured floor
+        let mut quiet = SilenceDetector::new(config);
+        feed(&mut quiet, 0.0001, 5);
+        assert_eq!(quiet.threshold(), DEFAULT_VOICE_THRESHOLD);
+    }
+
+    #[test]
+    fn test_settings_validation() {
+        assert!(AutoStopSettings::default().validate().is_ok());
+        assert_eq!(
+            AutoStopSettings::default().silence_config(),
+            SilenceConfig::default()
+        );
+
+        let short = AutoStopSettings {
+            silence_duration_ms: 100,
+            ..AutoStopSettings::default()
+        };
+        assert!(short.validate().is_err());
+        let deaf = AutoStopSettings {
+            threshold: Some(0.5),
+            ..AutoStopSettings::default()
+        };
+        assert!(deaf.validate().is_err());
+    }
+
+    #[test]
+    fn test_device_threshold_only_replaces_an_unset_one() {
+        let measured = 0.002;
+        let config = AutoStopSettings::default()
+            .pause_detection(Duration::from_secs(1))
+            .with_device_threshold(measured);
+        assert_eq!(SilenceDetector::new(config).threshold(), measured);
+
+        let chosen = AutoStopSettings {
+            threshold: Some(0.01),
+            ..AutoStopSettings::default()
         }
+        .pause_detection(Duration::from_secs(1))
+        .with_device_threshold(measured);
+        assert_eq!(SilenceDetector::new(chosen).threshold(), 0.01);
+
+        // Saved settings without a threshold leave it to the microphone test
+        let saved: AutoStopSettings =
+            serde_json::from_value(serde_json::json!({ "enabled": true })).unwrap();
+        assert_eq!(saved.threshold, None);
+        assert_eq!(
+            SilenceDetector::new(saved.silence_config()).threshold(),
+            DEFAULT_VOICE_THRESHOLD
+        );
     }
 }
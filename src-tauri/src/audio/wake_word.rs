@@ -0,0 +1,453 @@
+// Hands-free activation: a low-rate listener that waits for a short utterance,
+// checks it for the wake phrase and reports a match. Audio is only kept in a few
+// seconds of RAM and never written to disk. Detection itself is supplied by the
+// caller so this module does not depend on a particular speech engine.
+
+use cpal::traits::{DeviceTrait, StreamTrait};
+use serde::{Deserialize, Serialize};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::capture_buffer::{CaptureBuffer, CAPTURE_SAMPLE_RATE};
+use super::capture_format::{resolve_capture_config, CaptureFormat};
+use super::recorder::{build_input_stream, select_input_device};
+use super::silence_detector::DEFAULT_VOICE_THRESHOLD;
+
+/// Longest utterance kept for detection; wake phrases are a second or two
+const WINDOW_SECONDS: usize = 3;
+/// Detection needs at least this much audio (Whisper rejects shorter input)
+const MIN_DETECTION_SAMPLES: usize = CAPTURE_SAMPLE_RATE as usize;
+
+/// Stored under `wake_word` in the settings store
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WakeWordSettings {
+    pub enabled: bool,
+    pub phrase: String,
+    /// Whisper model used to recognise the phrase; the smallest one is plenty
+    pub model: String,
+    /// Share of one CPU core detection may use on average
+    pub cpu_budget_percent: u8,
+    /// Silence that ends a wake-activated recording
+    pub silence_timeout_secs: f32,
+}
+
+impl Default for WakeWordSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            phrase: "hey verity".to_string(),
+            model: "base.en".to_string(),
+            cpu_budget_percent: 10,
+            silence_timeout_secs: 2.0,
+        }
+    }
+}
+
+impl WakeWordSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        let phrase = normalize(&self.phrase);
+        if phrase.split(' ').count() < 2 || phrase.len() > 40 {
+            return Err(
+                "The wake phrase must be two or more words, at most 40 characters".to_string(),
+            );
+        }
+        if self.model.trim().is_empty() {
+            return Err("Choose a model for wake phrase detection".to_string());
+        }
+        if !(1..=50).contains(&self.cpu_budget_percent) {
+            return Err("CPU budget must be between 1% and 50%".to_string());
+        }
+        if !(0.5..=30.0).contains(&self.silence_timeout_secs) {
+            return Err("Silence timeout must be between 0.5 and 30 seconds".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn silence_timeout(&self) -> Duration {
+        Duration::from_secs_f32(self.silence_timeout_secs)
+    }
+}
+
+/// Lowercase words without punctuation, single-spaced
+fn normalize(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Whether `heard` contains the wake phrase, allowing for punctuation, spacing and
+/// roughly one misheard letter in six ("hey ferity", "heyverity")
+pub fn phrase_matches(heard: &str, phrase: &str) -> bool {
+    let phrase: Vec<char> = normalize(phrase).chars().filter(|c| *c != ' ').collect();
+    let heard: Vec<char> = normalize(heard).chars().filter(|c| *c != ' ').collect();
+    if phrase.is_empty() || heard.is_empty() {
+        return false;
+    }
+    let allowed = (phrase.len() / 6).max(1);
+
+    // Edit distance of the phrase against the best-matching stretch of `heard`
+    let mut previous = (0..=phrase.len()).collect::<Vec<_>>();
+    let mut best = previous[phrase.len()];
+    for h in &heard {
+        let mut current = vec![0; phrase.len() + 1];
+        for (i, p) in phrase.iter().enumerate() {
+            let substitution = previous[i] + usize::from(p != h);
+            current[i + 1] = substitution.min(previous[i + 1] + 1).min(current[i] + 1);
+        }
+        best = best.min(current[phrase.len()]);
+        previous = current;
+    }
+    best <= allowed
+}
+
+/// Spaces detection runs out so they average at most `percent` of one core
+pub struct CpuBudget {
+    fraction: f32,
+    next_allowed: Option<Instant>,
+}
+
+impl CpuBudget {
+    pub fn new(percent: u8) -> Self {
+        Self {
+            fraction: percent.clamp(1, 100) as f32 / 100.0,
+            next_allowed: None,
+        }
+    }
+
+    pub fn ready(&self, now: Instant) -> bool {
+        match self.next_allowed {
+            Some(next) => now >= next,
+            None => true,
+        }
+    }
+
+    /// Record a detection that started at `started` and took `elapsed`
+    pub fn spent(&mut self, started: Instant, elapsed: Duration) {
+        self.next_allowed = Some(started + elapsed.div_f32(self.fraction));
+    }
+}
+
+/// Finds short utterances in the level stream: some speech followed by a pause.
+/// Longer stretches of speech are ignored so conversation does not cost CPU.
+pub struct VoiceGate {
+    threshold: f32,
+    voiced: Duration,
+    quiet: Duration,
+}
+
+impl VoiceGate {
+    /// Speech shorter than this is a click or a cough
+    const MIN_VOICED: Duration = Duration::from_millis(250);
+    /// Pause that ends an utterance
+    const END_PAUSE: Duration = Duration::from_millis(300);
+    const MAX_VOICED: Duration = Duration::from_secs(WINDOW_SECONDS as u64);
+
+    pub fn new(threshold: f32) -> Self {
+        Self {
+            threshold,
+            voiced: Duration::ZERO,
+            quiet: Duration::ZERO,
+        }
+    }
+
+    /// Feed the RMS of the next `chunk` of audio; true when an utterance just ended
+    pub fn update(&mut self, rms: f32, chunk: Duration) -> bool {
+        if rms > self.threshold {
+            self.voiced += chunk;
+            self.quiet = Duration::ZERO;
+            return false;
+        }
+        if self.voiced.is_zero() {
+            return false;
+        }
+        self.quiet += chunk;
+        if self.quiet < Self::END_PAUSE {
+            return false;
+        }
+        let utterance = (Self::MIN_VOICED..=Self::MAX_VOICED).contains(&self.voiced);
+        self.reset();
+        utterance
+    }
+
+    pub fn reset(&mut self) {
+        self.voiced = Duration::ZERO;
+        self.quiet = Duration::ZERO;
+    }
+}
+
+enum ListenerMessage {
+    Utterance(Vec<f32>),
+    /// The detection worker heard the wake phrase
+    Heard(String),
+    Stop,
+}
+
+/// Capture state shared with the cpal callback
+struct ListenerCapture {
+    buffer: CaptureBuffer,
+    gate: VoiceGate,
+    frame_duration: Duration,
+    channels: usize,
+}
+
+impl ListenerCapture {
+    fn push(&mut self, samples: &[f32]) -> Option<Vec<f32>> {
+        if samples.is_empty() {
+            return None;
+        }
+        let sum: f32 = samples.iter().map(|x| x * x).sum();
+        let rms = (sum / samples.len() as f32).sqrt();
+        if let Err(e) = self.buffer.push_interleaved(samples) {
+            log::debug!("Wake listener dropped audio: {}", e);
+            return None;
+        }
+        let frames = (samples.len() / self.channels) as u32;
+        if self.gate.update(rms, self.frame_duration * frames) {
+            return Some(self.buffer.take());
+        }
+        None
+    }
+}
+
+/// Runs until the phrase is heard once or `stop` is called; either way the
+/// microphone is released when the listener thread ends. Detection runs on its
+/// own worker thread so the listener thread is always free to close the stream.
+pub struct WakeWordListener {
+    tx: mpsc::Sender<ListenerMessage>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl WakeWordListener {
+    /// `detect` turns 16kHz mono audio into text; `on_wake` gets what was heard and
+    /// runs on the listener thread once the microphone has been released
+    pub fn start<D, W>(
+        device_name: Option<String>,
+        format: CaptureFormat,
+        settings: &WakeWordSettings,
+        mut detect: D,
+        on_wake: W,
+    ) -> Result<Self, String>
+    where
+        D: FnMut(&[f32]) -> Result<String, String> + Send + 'static,
+        W: FnOnce(String) + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel();
+        let (ready_tx, ready_rx) = mpsc::channel::<Result<(), String>>();
+        let utterance_tx = tx.clone();
+        let heard_tx = tx.clone();
+        let phrase = settings.phrase.clone();
+        let cpu_budget_percent = settings.cpu_budget_percent;
+
+        let thread = thread::spawn(move || {
+            let stream = match open_stream(device_name.as_deref(), &format, utterance_tx) {
+                Ok(stream) => stream,
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e));
+                    return;
+                }
+            };
+            let _ = ready_tx.send(Ok(()));
+
+            // One utterance waits at most; newer ones are dropped while a check runs
+            let (detect_tx, detect_rx) = mpsc::sync_channel::<Vec<f32>>(1);
+            thread::spawn(move || {
+                let mut budget = CpuBudget::new(cpu_budget_percent);
+                for mut audio in detect_rx {
+                    let now = Instant::now();
+                    if !budget.ready(now) {
+                        log::debug!("Wake phrase check skipped to stay within the CPU budget");
+                        continue;
+                    }
+                    if audio.len() < MIN_DETECTION_SAMPLES {
+                        audio.resize(MIN_DETECTION_SAMPLES, 0.0);
+                    }
+
+                    let heard = detect(&audio);
+                    budget.spent(now, now.elapsed());
+                    match heard {
+                        Ok(text) if phrase_matches(&text, &phrase) => {
+                            let _ = heard_tx.send(ListenerMessage::Heard(text));
+                            return;
+                        }
+                        Ok(text) => log::debug!("Not the wake phrase: {:?}", text.trim()),
+                        Err(e) => log::debug!("Wake phrase check failed: {}", e),
+                    }
+                }
+            });
+
+            loop {
+                match rx.recv() {
+                    Ok(ListenerMessage::Utterance(audio)) => {
+                        let _ = detect_tx.try_send(audio);
+                    }
+                    Ok(ListenerMessage::Heard(text)) => {
+                        log::info!("Wake phrase detected: {:?}", text.trim());
+                        drop(stream);
+                        on_wake(text);
+                        return;
+                    }
+                    Ok(ListenerMessage::Stop) | Err(_) => break,
+                }
+            }
+            // Closing the stream here releases the microphone even while a check
+            // is still running; the worker exits once it finishes
+            drop(stream);
+            log::info!("Wake phrase listener stopped");
+        });
+
+        match ready_rx.recv() {
+            Ok(Ok(())) => Ok(Self {
+                tx,
+                thread: Some(thread),
+            }),
+            Ok(Err(e)) => Err(e),
+            Err(_) => Err("Wake phrase listener exited during start".to_string()),
+        }
+    }
+
+    /// Whether the listener gave up the microphone (phrase heard or stopped)
+    pub fn is_finished(&self) -> bool {
+        match &self.thread {
+            Some(thread) => thread.is_finished(),
+            None => true,
+        }
+    }
+
+    /// Release the microphone right away; a check that is already running is
+    /// left to finish in the background
+    pub fn stop(mut self) {
+        let _ = self.tx.send(ListenerMessage::Stop);
+        self.thread.take();
+    }
+}
+
+impl Drop for WakeWordListener {
+    fn drop(&mut self) {
+        let _ = self.tx.send(ListenerMessage::Stop);
+    }
+}
+
+fn open_stream(
+    device_name: Option<&str>,
+    format: &CaptureFormat,
+    utterance_tx: mpsc::Sender<ListenerMessage>,
+) -> Result<cpal::Stream, String> {
+    let host = cpal::default_host();
+    let device = select_input_device(&host, device_name)?;
+    let config = resolve_capture_config(&device, format)?;
+    let sample_rate = config.sample_rate();
+    let channels = config.channels();
+    log::info!(
+        "Wake phrase listener on {} ({} Hz, {} ch)",
+        device.name().unwrap_or_else(|_| "Unknown".to_string()),
+        sample_rate,
+        channels
+    );
+
+    let capture = Arc::new(Mutex::new(ListenerCapture {
+        buffer: CaptureBuffer::with_max_seconds(sample_rate, channels, WINDOW_SECONDS)?,
+        gate: VoiceGate::new(DEFAULT_VOICE_THRESHOLD),
+        frame_duration: Duration::from_secs(1) / sample_rate,
+        channels: channels as usize,
+    }));
+    let process = move |samples: &[f32], _: &[i16]| {
+        let utterance = capture.lock().ok().and_then(|mut c| c.push(samples));
+        if let Some(audio) = utterance {
+            let _ = utterance_tx.send(ListenerMessage::Utterance(audio));
+        }
+    };
+
+    let err_fn = |err| log::error!("Wake listener stream error: {}", err);
+    let stream = build_input_stream(&device, &config, process, err_fn)?;
+
+    stream.play().map_err(|e| e.to_string())?;
+    Ok(stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phrase_matching() {
+        let phrase = "hey verity";
+        assert!(phrase_matches("Hey, Verity!", phrase));
+        assert!(phrase_matches(" hey verity start a note", phrase));
+        assert!(phrase_matches("Heyverity.", phrase));
+        assert!(phrase_matches("Hey Ferity", phrase));
+        assert!(!phrase_matches("Hey there", phrase));
+        assert!(!phrase_matches("very tea", phrase));
+        assert!(!phrase_matches("", phrase));
+    }
+
+    #[test]
+    fn test_cpu_budget_spaces_out_checks() {
+        let mut budget = CpuBudget::new(10);
+        let start = Instant::now();
+        assert!(budget.ready(start));
+
+        // 200ms of work at 10% buys the next check two seconds after this one began
+        budget.spent(start, Duration::from_millis(200));
+        assert!(!budget.ready(start + Duration::from_millis(1_999)));
+        assert!(budget.ready(start + Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn test_voice_gate_reports_short_utterances() {
+        let chunk = Duration::from_millis(50);
+        let mut gate = VoiceGate::new(0.005);
+
+        // Silence never triggers
+        assert!((0..40).all(|_| !gate.update(0.0, chunk)));
+
+        // 800ms of speech then a pause
+        assert!((0..16).all(|_| !gate.update(0.05, chunk)));
+        let ended: Vec<bool> = (0..6).map(|_| gate.update(0.0, chunk)).collect();
+        assert_eq!(ended.iter().filter(|e| **e).count(), 1);
+        assert!(ended[5]);
+
+        // A click is too short
+        gate.update(0.05, chunk);
+        assert!((0..10).all(|_| !gate.update(0.0, chunk)));
+
+        // Continuous conversation is too long to be a wake phrase
+        (0..100).for_each(|_| {
+            gate.update(0.05, chunk);
+        });
+        assert!((0..10).all(|_| !gate.update(0.0, chunk)));
+    }
+
+    #[test]
+    fn test_settings_validation() {
+        assert!(WakeWordSettings::default().validate().is_ok());
+
+        let invalid = [
+            WakeWordSettings {
+                phrase: "verity".to_string(),
+                ..WakeWordSettings::default()
+            },
+            WakeWordSettings {
+                cpu_budget_percent: 0,
+                ..WakeWordSettings::default()
+            },
+            WakeWordSettings {
+                silence_timeout_secs: 0.1,
+                ..WakeWordSettings::default()
+            },
+            WakeWordSettings {
+                model: String::new(),
+                ..WakeWordSettings::default()
+            },
+        ];
+        for settings in invalid {
+            assert!(settings.validate().is_err(), "{:?}", settings);
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+
+/// Edit operations needed to turn the reference into the hypothesis
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorCounts {
+    pub substitutions: usize,
+    pub deletions: usize,
+    pub insertions: usize,
+    /// Length of the reference (words for WER, characters for CER)
+    pub reference_len: usize,
+}
+
+impl ErrorCounts {
+    pub fn errors(&self) -> usize {
+        self.substitutions + self.deletions + self.insertions
+    }
+
+    /// Error rate; an empty reference scores 0 if the hypothesis is empty, else 1
+    pub fn rate(&self) -> f64 {
+        if self.reference_len == 0 {
+            return if self.insertions == 0 { 0.0 } else { 1.0 };
+        }
+        self.errors() as f64 / self.reference_len as f64
+    }
+
+    pub fn add(&mut self, other: &ErrorCounts) {
+        self.substitutions += other.substitutions;
+        self.deletions += other.deletions;
+        self.insertions += other.insertions;
+        self.reference_len += other.reference_len;
+    }
+}
+
+/// Normalize a transcript before scoring so formatting differences don't count as errors:
+/// lowercase, hyphens and dashes become spaces, other punctuation is dropped
+/// (apostrophes inside words are kept), whitespace is collapsed.
+pub fn normalize_text(text: &str) -> String {
+    let mut cleaned = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c.is_alphanumeric() || c == '\'' {
+            cleaned.extend(c.to_lowercase());
+        } else if c.is_whitespace() || c == '-' || c == '–' || c == '—' || c == '/' {
+            cleaned.push(' ');
+        }
+    }
+
+    cleaned
+        .split_whitespace()
+        .map(|word| word.trim_matches('\''))
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Word-level error counts on normalized text
+pub fn word_errors(reference: &str, hypothesis: &str) -> ErrorCounts {
+    let reference = normalize_text(reference);
+    let hypothesis = normalize_text(hypothesis);
+    let ref_words: Vec<&str> = reference.split_whitespace().collect();
+    let hyp_words: Vec<&str> = hypothesis.split_whitespace().collect();
+    align(&ref_words, &hyp_words)
+}
+
+/// Character-level error counts on normalized text (spaces included)
+pub fn char_errors(reference: &str, hypothesis: &str) -> ErrorCounts {
+    let ref_chars: Vec<char> = normalize_text(reference).chars().collect();
+    let hyp_chars: Vec<char> = normalize_text(hypothesis).chars().collect();
+    align(&ref_chars, &hyp_chars)
+}
+
+/// Levenshtein alignment, keeping the breakdown of edit types
+fn align<T: PartialEq>(reference: &[T], hypothesis: &[T]) -> ErrorCounts {
+    // Each cell holds (cost, substitutions, deletions, insertions); two rows are enough
+    let mut prev: Vec<(usize, usize, usize, usize)> =
+        (0..=hypothesis.len()).map(|j| (j, 0, 0, j)).collect();
+    let mut curr = vec![(0, 0, 0, 0); hypothesis.len() + 1];
+
+    for (i, r) in reference.iter().enumerate() {
+        curr[0] = (i + 1, 0, i + 1, 0);
+        for (j, h) in hypothesis.iter().enumerate() {
+            let diag = prev[j];
+            let up = prev[j + 1];
+            let left = curr[j];
+
+            curr[j + 1] = if r == h {
+                diag
+            } else if diag.0 <= up.0 && diag.0 <= left.0 {
+                (diag.0 + 1, diag.1 + 1, diag.2, diag.3)
+            } else if up.0 <= left.0 {
+                (up.0 + 1, up.1, up.2 + 1, up.3)
+            } else {
+                (left.0 + 1, left.1, left.2, left.3 + 1)
+            };
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+
+    let (_, substitutions, deletions, insertions) = prev[hypothesis.len()];
+    ErrorCounts {
+        substitutions,
+        deletions,
+        insertions,
+        reference_len: reference.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalization_ignores_formatting() {
+        assert_eq!(
+            normalize_text("Hello, World!  It's a well-known 'fact'."),
+            "hello world it's a well known fact"
+        );
+    }
+
+    #[test]
+    fn test_word_errors_breakdown() {
+        // one substitution (the -> a), one deletion (quick), one insertion (today)
+        let counts = word_errors("the quick brown fox", "a brown fox today");
+        assert_eq!(counts.substitutions, 1);
+        assert_eq!(counts.deletions, 1);
+        assert_eq!(counts.insertions, 1);
+        assert_eq!(counts.reference_len, 4);
+        assert!((counts.rate() - 0.75).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_perfect_match_after_normalization() {
+        let counts = word_errors("Send it to Bob.", "send it to bob");
+        assert_eq!(counts.errors(), 0);
+        assert_eq!(counts.rate(), 0.0);
+    }
+
+    #[test]
+    fn test_char_errors() {
+        let counts = char_errors("cat", "cut");
+        assert_eq!(counts.substitutions, 1);
+        assert_eq!(counts.reference_len, 3);
+    }
+
+    #[test]
+    fn test_empty_reference() {
+        assert_eq!(word_errors("", "").rate(), 0.0);
+        assert_eq!(word_errors("", "noise").rate(), 1.0);
+    }
+
+    #[test]
+    fn test_corpus_totals() {
+        let mut total = ErrorCounts::default();
+        total.add(&word_errors("one two", "one two"));
+        total.add(&word_errors("three four", "three"));
+        assert_eq!(total.errors(), 1);
+        assert_eq!(total.reference_len, 4);
+        assert!((total.rate() - 0.25).abs() < 1e-9);
+    }
+}
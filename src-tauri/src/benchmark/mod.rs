@@ -0,0 +1,492 @@
+// Offline WER benchmark: runs a folder of WAV + reference-text pairs through Whisper
+// models and decoding profiles. Headless (no Tauri runtime), so it can run on CPU-only
+// Linux CI to catch quality regressions when whisper-rs is updated.
+
+pub mod metrics;
+pub mod report;
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use crate::whisper::transcriber::{DecodingOptions, Transcriber};
+use metrics::{char_errors, word_errors, ErrorCounts};
+
+/// A named set of decoding settings to benchmark
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecodingProfile {
+    pub name: String,
+    #[serde(default)]
+    pub decoding: DecodingOptions,
+}
+
+impl DecodingProfile {
+    /// Built-in profiles selectable by name
+    pub fn builtin(name: &str) -> Option<Self> {
+        let decoding = match name {
+            // What dictation uses
+            "default" => DecodingOptions::default(),
+            "greedy" => DecodingOptions {
+                beam_size: 0,
+                best_of: 1,
+                temperature: 0.0,
+                ..Default::default()
+            },
+            "beam-8" => DecodingOptions {
+                beam_size: 8,
+                ..Default::default()
+            },
+            _ => return None,
+        };
+        Some(Self {
+            name: name.to_string(),
+            decoding,
+        })
+    }
+}
+
+/// One audio file with its reference transcript
+#[derive(Debug, Clone)]
+pub struct BenchmarkSample {
+    pub name: String,
+    pub audio_path: PathBuf,
+    pub reference: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct BenchmarkConfig {
+    pub dataset_dir: PathBuf,
+    pub models: Vec<PathBuf>,
+    pub profiles: Vec<DecodingProfile>,
+    pub language: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileResult {
+    pub file: String,
+    pub duration_secs: f64,
+    pub reference: String,
+    pub hypothesis: String,
+    pub wer: f64,
+    pub cer: f64,
+    pub word_errors: ErrorCounts,
+    pub char_errors: ErrorCounts,
+    pub inference_ms: u64,
+    pub real_time_factor: f64,
+    /// Set when the file could not be loaded or transcribed
+    pub error: Option<String>,
+}
+
+/// Results for one (model, profile) combination
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunResult {
+    pub model: String,
+    pub profile: String,
+    pub decoding: DecodingOptions,
+    /// Corpus-level rates (total errors / total reference length)
+    pub wer: f64,
+    pub cer: f64,
+    pub word_errors: ErrorCounts,
+    pub char_errors: ErrorCounts,
+    pub audio_secs: f64,
+    pub inference_ms: u64,
+    pub real_time_factor: f64,
+    pub model_load_ms: u64,
+    /// Peak resident memory during the run, where the platform reports it
+    pub peak_memory_mb: Option<f64>,
+    pub failed_files: usize,
+    pub files: Vec<FileResult>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkReport {
+    pub created_at: String,
+    pub dataset: String,
+    pub language: String,
+    pub file_count: usize,
+    pub platform: String,
+    pub threads: usize,
+    pub runs: Vec<RunResult>,
+}
+
+/// Collect `name.wav` + `name.txt` pairs from a directory (sorted by name).
+/// WAVs without a reference are skipped with a warning.
+pub fn load_dataset(dir: &Path) -> Result<Vec<BenchmarkSample>, String> {
+    let mut samples = Vec::new();
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read dataset directory {:?}: {}", dir, e))?;
+
+    for entry in entries.filter_map(|e| e.ok()) {
+        let audio_path = entry.path();
+        let is_wav = audio_path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.eq_ignore_ascii_case("wav"))
+            .unwrap_or(false);
+        if !is_wav {
+            continue;
+        }
+
+        let reference_path = audio_path.with_extension("txt");
+        let reference = match std::fs::read_to_string(&reference_path) {
+            Ok(text) => text.trim().to_string(),
+            Err(_) => {
+                log::warn!("Skipping {:?}: no reference transcript", audio_path);
+                continue;
+            }
+        };
+
+        samples.push(BenchmarkSample {
+            name: audio_path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            audio_path,
+            reference,
+        });
+    }
+
+    if samples.is_empty() {
+        return Err(format!("No WAV + .txt pairs found in {:?}", dir));
+    }
+
+    samples.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(samples)
+}
+
+/// Read a WAV file as 16kHz mono f32, the format `Transcriber` expects
+pub fn load_wav_16k_mono(path: &Path) -> Result<Vec<f32>, String> {
+    let mut reader =
+        hound::WavReader::open(path).map_err(|e| format!("Failed to open WAV: {}", e))?;
+    let spec = reader.spec();
+
+    let interleaved: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Float => reader
+            .samples::<f32>()
+            .collect::<Result<_, _>>()
+            .map_err(|e| format!("Failed to read samples: {}", e))?,
+        hound::SampleFormat::Int => {
+            let scale = 1.0 / (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|s| s.map(|v| v as f32 * scale))
+                .collect::<Result<_, _>>()
+                .map_err(|e| format!("Failed to read samples: {}", e))?
+        }
+    };
+
+    let channels = spec.channels.max(1) as usize;
+    let mono: Vec<f32> = if channels == 1 {
+        interleaved
+    } else {
+        interleaved
+            .chunks_exact(channels)
+            .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+            .collect()
+    };
+
+    if spec.sample_rate == 16_000 {
+        Ok(mono)
+    } else {
+        crate::audio::resampler::resample_to_16khz(&mono, spec.sample_rate)
+    }
+}
+
+/// Run every model × profile over the dataset
+pub fn run_benchmark(config: &BenchmarkConfig) -> Result<BenchmarkReport, String> {
+    if config.models.is_empty() {
+        return Err("No models given".to_string());
+    }
+    if config.profiles.is_empty() {
+        return Err("No decoding profiles given".to_string());
+    }
+
+    let samples = load_dataset(&config.dataset_dir)?;
+    log::info!(
+        "[BENCHMARK] {} file(s), {} model(s), {} profile(s)",
+        samples.len(),
+        config.models.len(),
+        config.profiles.len()
+    );
+
+    // Decode audio once up front; it's shared by every run
+    let audio: Vec<Result<Vec<f32>, String>> = samples
+        .iter()
+        .map(|s| load_wav_16k_mono(&s.audio_path))
+        .collect();
+
+    let mut runs = Vec::new();
+    for model_path in &config.models {
+        memory::reset_peak();
+        let load_start = Instant::now();
+        let transcriber = Transcriber::new(model_path)?;
+        let model_load_ms = load_start.elapsed().as_millis() as u64;
+
+        let model_name = model_path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| model_path.display().to_string());
+
+        for profile in &config.profiles {
+            let run = run_profile(
+                &transcriber,
+                &model_name,
+                profile,
+                &samples,
+                &audio,
+                &config.language,
+                model_load_ms,
+            );
+            log::info!(
+                "[BENCHMARK] {} / {}: WER {:.2}%, CER {:.2}%, RTF {:.3}",
+                run.model,
+                run.profile,
+                run.wer * 100.0,
+                run.cer * 100.0,
+                run.real_time_factor
+            );
+            runs.push(run);
+        }
+    }
+
+    Ok(BenchmarkReport {
+        created_at: chrono::Utc::now().to_rfc3339(),
+        dataset: config.dataset_dir.display().to_string(),
+        language: config.language.clone(),
+        file_count: samples.len(),
+        platform: format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH),
+        threads: std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1),
+        runs,
+    })
+}
+
+fn run_profile(
+    transcriber: &Transcriber,
+    model_name: &str,
+    profile: &DecodingProfile,
+    samples: &[BenchmarkSample],
+    audio: &[Result<Vec<f32>, String>],
+    language: &str,
+    model_load_ms: u64,
+) -> RunResult {
+    let mut total_words = ErrorCounts::default();
+    let mut total_chars = ErrorCounts::default();
+    let mut audio_secs = 0.0;
+    let mut inference_ms = 0;
+    let mut failed_files = 0;
+    let mut files = Vec::with_capacity(samples.len());
+
+    for (sample, samples_16k) in samples.iter().zip(audio) {
+        let duration_secs = samples_16k
+            .as_ref()
+            .map(|a| a.len() as f64 / 16_000.0)
+            .unwrap_or(0.0);
+
+        let start = Instant::now();
+        let outcome = samples_16k.as_ref().map_err(|e| e.clone()).and_then(|a| {
+            transcriber.transcribe_samples_with_options(a, Some(language), false, &profile.decoding)
+        });
+        let elapsed_ms = start.elapsed().as_millis() as u64;
+
+        let (hypothesis, error) = match outcome {
+            Ok(text) => (text, None),
+            Err(e) => {
+                failed_files += 1;
+                (String::new(), Some(e))
+            }
+        };
+
+        // Failed files still count: every reference word becomes a deletion
+        let words = word_errors(&sample.reference, &hypothesis);
+        let chars = char_errors(&sample.reference, &hypothesis);
+        total_words.add(&words);
+        total_chars.add(&chars);
+
+        if error.is_none() {
+            audio_secs += duration_secs;
+            inference_ms += elapsed_ms;
+        }
+
+        files.push(FileResult {
+            file: sample.name.clone(),
+            duration_secs,
+            reference: sample.reference.clone(),
+            hypothesis,
+            wer: words.rate(),
+            cer: chars.rate(),
+            word_errors: words,
+            char_errors: chars,
+            inference_ms: elapsed_ms,
+            real_time_factor: if duration_secs > 0.0 {
+                elapsed_ms as f64 / 1000.0 / duration_secs
+            } else {
+                0.0
+            },
+            error,
+        });
+    }
+
+    RunResult {
+        model: model_name.to_string(),
+        profile: profile.name.clone(),
+        decoding: profile.decoding.clone(),
+        wer: total_words.rate(),
+        cer: total_chars.rate(),
+        word_errors: total_words,
+        char_errors: total_chars,
+        audio_secs,
+        inference_ms,
+        real_time_factor: if audio_secs > 0.0 {
+            inference_ms as f64 / 1000.0 / audio_secs
+        } else {
+            0.0
+        },
+        model_load_ms,
+        peak_memory_mb: memory::peak_mb(),
+        failed_files,
+        files,
+    }
+}
+
+/// Peak resident memory tracking
+mod memory {
+    /// Reset the kernel's peak RSS counter so each model is measured on its own
+    #[cfg(target_os = "linux")]
+    pub fn reset_peak() {
+        // "5" resets VmHWM (Linux 4.0+); ignore failures, the peak is then process-wide
+        let _ = std::fs::write("/proc/self/clear_refs", "5");
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn reset_peak() {}
+
+    #[cfg(target_os = "linux")]
+    pub fn peak_mb() -> Option<f64> {
+        let status = std::fs::read_to_string("/proc/self/status").ok()?;
+        let line = status.lines().find(|l| l.starts_with("VmHWM:"))?;
+        let kb: f64 = line.split_whitespace().nth(1)?.parse().ok()?;
+        Some(kb / 1024.0)
+    }
+
+    /// No portable peak counter; report current usage as a lower bound
+    #[cfg(not(target_os = "linux"))]
+    pub fn peak_mb() -> Option<f64> {
+        let pid = sysinfo::get_current_pid().ok()?;
+        let mut system = sysinfo::System::new();
+        system.refresh_processes(sysinfo::ProcessesToUpdate::Some(&[pid]), true);
+        system
+            .process(pid)
+            .map(|p| p.memory() as f64 / 1024.0 / 1024.0)
+    }
+}
+
+/// Entry point for the `wer-benchmark` binary
+pub fn run_cli(args: Vec<String>) -> Result<(), String> {
+    let options = report::CliOptions::parse(&args)?;
+    let report = run_benchmark(&options.config)?;
+
+    let json = serde_json::to_string_pretty(&report)
+        .map_err(|e| format!("Failed to serialize report: {}", e))?;
+    let markdown = report::to_markdown(&report);
+
+    match &options.json_output {
+        Some(path) => {
+            std::fs::write(path, &json).map_err(|e| format!("Failed to write {:?}: {}", path, e))?
+        }
+        None => println!("{}", json),
+    }
+    if let Some(path) = &options.markdown_output {
+        std::fs::write(path, &markdown)
+            .map_err(|e| format!("Failed to write {:?}: {}", path, e))?;
+    } else if options.json_output.is_some() {
+        println!("{}", markdown);
+    }
+
+    if let Some(max_wer) = options.max_wer {
+        let failing: Vec<String> = report
+            .runs
+            .iter()
+            .filter(|run| run.wer > max_wer)
+            .map(|run| format!("{}/{} ({:.2}%)", run.model, run.profile, run.wer * 100.0))
+            .collect();
+        if !failing.is_empty() {
+            return Err(format!(
+                "WER above {:.2}%: {}",
+                max_wer * 100.0,
+                failing.join(", ")
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_wav(path: &Path, sample_rate: u32, channels: u16, frames: usize) {
+        let spec = hound::WavSpec {
+            channels,
+            sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(path, spec).unwrap();
+        for _ in 0..frames * channels as usize {
+            writer.write_sample(8192i16).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    #[test]
+    fn test_load_dataset_pairs_wav_with_reference() {
+        let temp_dir = TempDir::new().unwrap();
+        write_wav(&temp_dir.path().join("b.wav"), 16_000, 1, 100);
+        write_wav(&temp_dir.path().join("a.wav"), 16_000, 1, 100);
+        write_wav(&temp_dir.path().join("orphan.wav"), 16_000, 1, 100);
+        std::fs::write(temp_dir.path().join("a.txt"), "first\n").unwrap();
+        std::fs::write(temp_dir.path().join("b.txt"), "second").unwrap();
+
+        let samples = load_dataset(temp_dir.path()).unwrap();
+        let names: Vec<&str> = samples.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "b"]);
+        assert_eq!(samples[0].reference, "first");
+    }
+
+    #[test]
+    fn test_load_dataset_requires_pairs() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(load_dataset(temp_dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_load_wav_downmixes_and_resamples() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("stereo.wav");
+        write_wav(&path, 48_000, 2, 48_000);
+
+        let samples = load_wav_16k_mono(&path).unwrap();
+        assert!((samples.len() as i64 - 16_000).abs() < 100);
+        assert!((samples[8_000] - 0.25).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_builtin_profiles() {
+        assert_eq!(
+            DecodingProfile::builtin("default").unwrap().decoding,
+            DecodingOptions::default()
+        );
+        assert_eq!(
+            DecodingProfile::builtin("greedy")
+                .unwrap()
+                .decoding
+                .beam_size,
+            0
+        );
+        assert!(DecodingProfile::builtin("unknown").is_none());
+    }
+}
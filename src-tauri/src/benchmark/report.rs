@@ -0,0 +1,278 @@
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use super::{BenchmarkConfig, BenchmarkReport, DecodingProfile};
+
+/// Number of worst files listed per run in the Markdown report
+const WORST_FILES: usize = 5;
+
+const USAGE: &str = "\
+Usage: wer-benchmark --dataset <dir> --model <ggml.bin> [--model ...] [options]
+
+  --dataset <dir>        Folder of name.wav + name.txt reference pairs
+  --model <path>         Whisper model file (repeatable)
+  --profile <name>       Built-in decoding profile: default, greedy, beam-8 (repeatable)
+  --profiles <file>      JSON array of {\"name\", \"decoding\"} profiles
+  --language <code>      Language code (default: en)
+  --json <path>          Write the JSON report here (default: stdout)
+  --markdown <path>      Write the Markdown report here
+  --max-wer <percent>    Exit non-zero if any run's WER exceeds this";
+
+pub struct CliOptions {
+    pub config: BenchmarkConfig,
+    pub json_output: Option<PathBuf>,
+    pub markdown_output: Option<PathBuf>,
+    /// Fraction (0.0-1.0)
+    pub max_wer: Option<f64>,
+}
+
+impl CliOptions {
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut dataset_dir = None;
+        let mut models = Vec::new();
+        let mut profiles = Vec::new();
+        let mut language = "en".to_string();
+        let mut json_output = None;
+        let mut markdown_output = None;
+        let mut max_wer = None;
+
+        let mut iter = args.iter();
+        while let Some(flag) = iter.next() {
+            if flag == "--help" || flag == "-h" {
+                return Err(USAGE.to_string());
+            }
+            let value = iter
+                .next()
+                .ok_or_else(|| format!("Missing value for {}\n\n{}", flag, USAGE))?;
+
+            match flag.as_str() {
+                "--dataset" => dataset_dir = Some(PathBuf::from(value)),
+                "--model" => models.push(PathBuf::from(value)),
+                "--profile" => profiles.push(
+                    DecodingProfile::builtin(value)
+                        .ok_or_else(|| format!("Unknown decoding profile: {}", value))?,
+                ),
+                "--profiles" => {
+                    let content = std::fs::read_to_string(value)
+                        .map_err(|e| format!("Failed to read {}: {}", value, e))?;
+                    let parsed: Vec<DecodingProfile> = serde_json::from_str(&content)
+                        .map_err(|e| format!("Invalid profiles file {}: {}", value, e))?;
+                    profiles.extend(parsed);
+                }
+                "--language" => language = value.clone(),
+                "--json" => json_output = Some(PathBuf::from(value)),
+                "--markdown" => markdown_output = Some(PathBuf::from(value)),
+                "--max-wer" => {
+                    let percent: f64 = value
+                        .parse()
+                        .map_err(|_| format!("Invalid --max-wer value: {}", value))?;
+                    max_wer = Some(percent / 100.0);
+                }
+                _ => return Err(format!("Unknown argument: {}\n\n{}", flag, USAGE)),
+            }
+        }
+
+        let dataset_dir =
+            dataset_dir.ok_or_else(|| format!("--dataset is required\n\n{}", USAGE))?;
+        if models.is_empty() {
+            return Err(format!("At least one --model is required\n\n{}", USAGE));
+        }
+        if profiles.is_empty() {
+            profiles.extend(DecodingProfile::builtin("default"));
+        }
+
+        Ok(Self {
+            config: BenchmarkConfig {
+                dataset_dir,
+                models,
+                profiles,
+                language,
+            },
+            json_output,
+            markdown_output,
+            max_wer,
+        })
+    }
+}
+
+/// Render a report as Markdown: a summary table, then the worst files per run
+pub fn to_markdown(report: &BenchmarkReport) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# WER benchmark\n");
+    let _ = writeln!(
+        out,
+        "Dataset `{}` ({} files, language `{}`) on {} with {} threads, {}\n",
+        report.dataset,
+        report.file_count,
+        report.language,
+        report.platform,
+        report.threads,
+        report.created_at
+    );
+
+    let _ = writeln!(
+        out,
+        "| Model | Profile | WER | CER | S / D / I | RTF | Load | Peak memory | Failed |"
+    );
+    let _ = writeln!(out, "|---|---|---|---|---|---|---|---|---|");
+    for run in &report.runs {
+        let _ = writeln!(
+            out,
+            "| {} | {} | {:.2}% | {:.2}% | {} / {} / {} | {:.3} | {}ms | {} | {} |",
+            run.model,
+            run.profile,
+            run.wer * 100.0,
+            run.cer * 100.0,
+            run.word_errors.substitutions,
+            run.word_errors.deletions,
+            run.word_errors.insertions,
+            run.real_time_factor,
+            run.model_load_ms,
+            run.peak_memory_mb
+                .map(|mb| format!("{:.0}MB", mb))
+                .unwrap_or_else(|| "n/a".to_string()),
+            run.failed_files
+        );
+    }
+
+    for run in &report.runs {
+        let mut worst: Vec<_> = run
+            .files
+            .iter()
+            .filter(|f| f.error.is_some() || f.word_errors.errors() > 0)
+            .collect();
+        if worst.is_empty() {
+            continue;
+        }
+        worst.sort_by(|a, b| b.wer.total_cmp(&a.wer));
+
+        let _ = writeln!(out, "\n## {} / {}\n", run.model, run.profile);
+        let _ = writeln!(out, "| File | WER | Reference | Hypothesis |");
+        let _ = writeln!(out, "|---|---|---|---|");
+        for file in worst.into_iter().take(WORST_FILES) {
+            let hypothesis = match &file.error {
+                Some(e) => format!("_error: {}_", e),
+                None => file.hypothesis.clone(),
+            };
+            let _ = writeln!(
+                out,
+                "| {} | {:.1}% | {} | {} |",
+                file.file,
+                file.wer * 100.0,
+                escape_cell(&file.reference),
+                escape_cell(&hypothesis)
+            );
+        }
+    }
+
+    out
+}
+
+fn escape_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmark::metrics::{char_errors, word_errors};
+    use crate::benchmark::{FileResult, RunResult};
+    use crate::whisper::transcriber::DecodingOptions;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_defaults() {
+        let options =
+            CliOptions::parse(&args(&["--dataset", "data", "--model", "base.bin"])).unwrap();
+        assert_eq!(options.config.language, "en");
+        assert_eq!(options.config.profiles.len(), 1);
+        assert_eq!(options.config.profiles[0].name, "default");
+        assert!(options.max_wer.is_none());
+    }
+
+    #[test]
+    fn test_parse_multiple_models_and_threshold() {
+        let options = CliOptions::parse(&args(&[
+            "--dataset",
+            "data",
+            "--model",
+            "a.bin",
+            "--model",
+            "b.bin",
+            "--profile",
+            "greedy",
+            "--max-wer",
+            "12.5",
+        ]))
+        .unwrap();
+        assert_eq!(options.config.models.len(), 2);
+        assert_eq!(options.config.profiles[0].name, "greedy");
+        assert_eq!(options.max_wer, Some(0.125));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(CliOptions::parse(&args(&["--model", "a.bin"])).is_err());
+        assert!(CliOptions::parse(&args(&["--dataset", "data"])).is_err());
+        assert!(CliOptions::parse(&args(&["--dataset"])).is_err());
+        assert!(CliOptions::parse(&args(&[
+            "--dataset",
+            "d",
+            "--model",
+            "m",
+            "--profile",
+            "nope"
+        ]))
+        .is_err());
+    }
+
+    #[test]
+    fn test_markdown_lists_worst_files() {
+        let words = word_errors("hello world", "hello word");
+        let chars = char_errors("hello world", "hello word");
+        let report = BenchmarkReport {
+            created_at: "now".to_string(),
+            dataset: "data".to_string(),
+            language: "en".to_string(),
+            file_count: 1,
+            platform: "linux-x86_64".to_string(),
+            threads: 4,
+            runs: vec![RunResult {
+                model: "ggml-base.en".to_string(),
+                profile: "default".to_string(),
+                decoding: DecodingOptions::default(),
+                wer: words.rate(),
+                cer: chars.rate(),
+                word_errors: words,
+                char_errors: chars,
+                audio_secs: 2.0,
+                inference_ms: 500,
+                real_time_factor: 0.25,
+                model_load_ms: 100,
+                peak_memory_mb: None,
+                failed_files: 0,
+                files: vec![FileResult {
+                    file: "greeting".to_string(),
+                    duration_secs: 2.0,
+                    reference: "hello world".to_string(),
+                    hypothesis: "hello word".to_string(),
+                    wer: words.rate(),
+                    cer: chars.rate(),
+                    word_errors: words,
+                    char_errors: chars,
+                    inference_ms: 500,
+                    real_time_factor: 0.25,
+                    error: None,
+                }],
+            }],
+        };
+
+        let markdown = to_markdown(&report);
+        assert!(markdown.contains("| ggml-base.en | default | 50.00% |"));
+        assert!(markdown.contains("## ggml-base.en / default"));
+        assert!(markdown.contains("| greeting | 50.0% | hello world | hello word |"));
+    }
+}
@@ -0,0 +1,12 @@
+// Headless WER benchmark over a folder of WAV + reference-text pairs.
+// Build with: cargo run --features benchmark --bin wer-benchmark -- --help
+
+fn main() {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Err(e) = voicetypr_lib::benchmark::run_cli(args) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
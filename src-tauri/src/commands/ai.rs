@@ -1,4 +1,7 @@
-use crate::ai::{AIEnhancementRequest, AIProviderConfig, AIProviderFactory, EnhancementOptions};
+use crate::ai::{
+    AIEnhancementRequest, AIProviderConfig, AIProviderFactory, EnhancementOptions,
+    EnhancementPreset,
+};
 use once_cell::sync::Lazy;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
@@ -596,6 +599,16 @@ pub async fn set_thinking_mode(app: tauri::AppHandle, prefix: String) -> Result<
 
 #[tauri::command]
 pub async fn enhance_transcription(text: String, app: tauri::AppHandle) -> Result<String, String> {
+    enhance_transcription_with_preset(text, app, None).await
+}
+
+/// Enhance text with the stored options, optionally overriding the preset
+/// (used when re-running a history entry with a different preset)
+pub(crate) async fn enhance_transcription_with_preset(
+    text: String,
+    app: tauri::AppHandle,
+    preset_override: Option<EnhancementPreset>,
+) -> Result<String, String> {
     // Quick validation
     if text.trim().is_empty() {
         log::debug!("Skipping enhancement for empty text");
@@ -692,7 +705,12 @@ pub async fn enhance_transcription(text: String, app: tauri::AppHandle) -> Resul
     drop(store); // Release lock before async operation
 
     // Load enhancement options
-    let enhancement_options = get_enhancement_options(app.clone()).await.ok();
+    let mut enhancement_options = get_enhancement_options(app.clone()).await.ok();
+    if let Some(preset) = preset_override {
+        enhancement_options
+            .get_or_insert_with(EnhancementOptions::default)
+            .preset = preset;
+    }
 
     log::info!(
         "Enhancing text with {} model {} (length: {}, options: {:?})",
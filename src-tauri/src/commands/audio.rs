@@ -1,15 +1,28 @@
 use tauri::{AppHandle, Manager, State};
 
+use crate::audio::capture_buffer::{write_capture_wav, CAPTURE_SAMPLE_RATE};
+use crate::audio::dsp::AudioProcessingOptions;
+use crate::audio::failover::next_device;
 use crate::audio::recorder::{normalize_device_name, AudioRecorder};
-use crate::commands::settings::get_settings;
-use crate::parakeet::messages::ParakeetResponse;
+use crate::audio::retention::{
+    clear_audio_link, enforce_quotas, retained_audio_dir, AudioRetentionPolicy,
+};
+use crate::audio::silence_detector::{AutoStopSettings, SilenceConfig};
+use crate::commands::calibration::load_device_calibration;
+use crate::commands::devices::{
+    forward_device_switches, load_capture_format, load_capture_formats, load_failover_settings,
+};
+use crate::commands::model::load_transcriber;
+use crate::commands::settings::{get_settings, load_setting, save_setting};
+use crate::fallback::{walk_chain, FallbackChain, FallbackEntry, TranscriptionError};
 use crate::parakeet::ParakeetManager;
+use crate::transcript::Transcript;
 use crate::utils::logger::*;
 #[cfg(debug_assertions)]
 use crate::utils::system_monitor;
-use crate::whisper::cache::TranscriberCache;
 use crate::whisper::languages::validate_language;
 use crate::whisper::manager::WhisperManager;
+use crate::whisper::transcriber::MIN_DURATION_SECS;
 use crate::{
     emit_to_all, emit_to_window, update_recording_state, AppState, RecordingMode, RecordingState,
 };
@@ -18,8 +31,8 @@ use serde_json;
 use std::panic::{RefUnwindSafe, UnwindSafe};
 use std::path::{Path, PathBuf};
 use std::sync::Mutex;
-use std::time::Instant;
-use tauri::async_runtime::{Mutex as AsyncMutex, RwLock as AsyncRwLock};
+use std::time::{Duration, Instant};
+use tauri::async_runtime::RwLock as AsyncRwLock;
 use tauri_plugin_global_shortcut::GlobalShortcutExt;
 use tauri_plugin_store::StoreExt;
 
@@ -100,7 +113,7 @@ impl UnwindSafe for RecordingConfig {}
 impl RefUnwindSafe for RecordingConfig {}
 
 #[derive(Clone)]
-enum ActiveEngineSelection {
+pub(crate) enum ActiveEngineSelection {
     Whisper {
         model_name: String,
         model_path: PathBuf,
@@ -114,7 +127,7 @@ enum ActiveEngineSelection {
 }
 
 impl ActiveEngineSelection {
-    fn engine_name(&self) -> &'static str {
+    pub(crate) fn engine_name(&self) -> &'static str {
         match self {
             ActiveEngineSelection::Whisper { .. } => "whisper",
             ActiveEngineSelection::Parakeet { .. } => "parakeet",
@@ -122,7 +135,7 @@ impl ActiveEngineSelection {
         }
     }
 
-    fn model_name(&self) -> &str {
+    pub(crate) fn model_name(&self) -> &str {
         match self {
             ActiveEngineSelection::Whisper { model_name, .. } => model_name,
             ActiveEngineSelection::Parakeet { model_name } => model_name,
@@ -140,8 +153,10 @@ async fn abort_due_to_missing_model(
     log::error!("{}", log_message);
     update_recording_state(app, RecordingState::Error, Some(user_message.to_string()));
 
-    if let Err(e) = std::fs::remove_file(audio_path) {
-        log::warn!("Failed to remove audio file: {}", e);
+    if audio_path.exists() {
+        if let Err(e) = std::fs::remove_file(audio_path) {
+            log::warn!("Failed to remove audio file: {}", e);
+        }
     }
 
     let _ = emit_to_window(
@@ -164,7 +179,7 @@ async fn abort_due_to_missing_model(
     Err(log_message.to_string())
 }
 
-async fn resolve_engine_for_model(
+pub(crate) async fn resolve_engine_for_model(
     app: &AppHandle,
     model_name: &str,
     engine_hint: Option<&str>,
@@ -201,11 +216,12 @@ async fn resolve_engine_for_model(
             }
         }
         Some(ref engine) if engine == "whisper" || engine == "whisper.cpp" => {
-            let path = whisper_state
-                .read()
-                .await
-                .get_model_path(model_name)
-                .ok_or_else(|| format!("Whisper model '{}' not found", model_name))?;
+            let manager = whisper_state.read().await;
+            let path = manager.get_model_path(model_name).ok_or_else(|| {
+                manager
+                    .storage_error()
+                    .unwrap_or_else(|| format!("Whisper model '{}' not found", model_name))
+            })?;
 
             Ok(ActiveEngineSelection::Whisper {
                 model_name: model_name.to_string(),
@@ -243,6 +259,11 @@ async fn resolve_engine_for_model(
                 }
             }
 
+            // A disconnected models drive explains the missing model better
+            if let Some(error) = whisper_state.read().await.storage_error() {
+                return Err(error);
+            }
+
             Err(format!(
                 "Model '{}' not found in Whisper or Parakeet registries",
                 model_name
@@ -393,6 +414,28 @@ async fn validate_recording_requirements(app: &AppHandle) -> Result<(), String>
 pub async fn start_recording(
     app: AppHandle,
     state: State<'_, RecorderState>,
+) -> Result<(), String> {
+    // Push-to-talk ends on key release, so "dictate until I pause" only applies to toggle
+    let push_to_talk = app
+        .state::<AppState>()
+        .recording_mode
+        .lock()
+        .map(|mode| *mode == RecordingMode::PushToTalk)
+        .unwrap_or(false);
+    let silence = if push_to_talk {
+        SilenceConfig::default()
+    } else {
+        load_auto_stop_settings(&app).silence_config()
+    };
+    begin_recording(app, state, silence).await
+}
+
+/// Start a recording that stops itself on silence as `silence` describes.
+/// Hands-free (wake phrase) recordings always stop at the first pause.
+pub(crate) async fn begin_recording(
+    app: AppHandle,
+    state: State<'_, RecorderState>,
+    mut silence: SilenceConfig,
 ) -> Result<(), String> {
     let recording_start = Instant::now();
 
@@ -490,6 +533,8 @@ pub async fn start_recording(
         .map_err(|e| format!("Failed to acquire path lock: {}", e))?
         .replace(audio_path.clone());
 
+    let failover = load_failover_settings(&app);
+
     // Get selected microphone from settings (before acquiring recorder lock)
     let selected_microphone = match get_settings(app.clone()).await {
         Ok(settings) => {
@@ -504,19 +549,22 @@ pub async fn start_recording(
                     log::info!("Using selected microphone: {}", mic);
                     Some(mic)
                 } else {
+                    // Next connected microphone in the priority list, else the system default
+                    let fallback = next_device(&mic, &failover.priority, &available_devices, None);
                     log::warn!(
-                        "Selected microphone '{}' not found; using system default",
-                        mic
+                        "Selected microphone '{}' not found; using {}",
+                        mic,
+                        fallback.as_deref().unwrap_or("system default")
                     );
                     let _ = emit_to_all(
                         &app,
                         "microphone-unavailable",
                         serde_json::json!({
                             "selected": mic,
-                            "fallback": "System Default"
+                            "fallback": fallback.as_deref().unwrap_or("System Default")
                         }),
                     );
-                    None
+                    fallback
                 }
             } else {
                 log::info!("Using default microphone");
@@ -532,6 +580,19 @@ pub async fn start_recording(
         }
     };
 
+    let processing_options = load_audio_processing_options(&app);
+    let capture_format = load_capture_format(&app, selected_microphone.as_deref());
+
+    // A microphone test of this device sets the voice threshold unless the user chose one
+    let device_levels = load_device_calibration(&app, selected_microphone.as_deref())
+        .map(|calibration| calibration.device_levels());
+    if let Some(levels) = device_levels {
+        silence = silence.with_device_threshold(levels.voice_threshold);
+    }
+
+    // The wake phrase listener gives up the microphone until the app is idle again
+    crate::commands::wake_word::pause_for_recording(&app);
+
     // Start recording (scoped to release mutex before async operations)
     {
         let mut recorder = state
@@ -589,6 +650,13 @@ pub async fn start_recording(
 
         log_file_operation("RECORDING_START", audio_path_str, false, None, None);
 
+        recorder.set_processing_options(processing_options);
+        recorder.set_silence_config(silence);
+        recorder.set_device_levels(device_levels);
+        recorder.set_failover(failover.enabled.then(|| failover.priority.clone()));
+        recorder.set_capture_format(capture_format);
+        recorder.set_device_formats(load_capture_formats(&app));
+
         // Start recording and get audio level receiver
         let audio_level_rx = match recorder
             .start_recording(audio_path_str, selected_microphone.clone())
@@ -685,9 +753,16 @@ pub async fn start_recording(
             }
         };
 
+        let device_switch_rx = recorder.take_device_switch_receiver();
+
         // Release the recorder lock after successful start
         drop(recorder);
 
+        // Tell the UI and the tray when the recording moves to another microphone
+        if let Some(device_switch_rx) = device_switch_rx {
+            forward_device_switches(app.clone(), device_switch_rx);
+        }
+
         // Start audio level monitoring
         if let Some(audio_level_rx) = audio_level_rx {
             let app_for_levels = app.clone();
@@ -722,6 +797,9 @@ pub async fn start_recording(
     // Update state to recording
     update_recording_state(&app, RecordingState::Recording, None);
 
+    // Collect the recording if the recorder stops itself on silence
+    watch_for_auto_stop(app.clone());
+
     // Preload AI model in background if enhancement is enabled
     // This warms up Ollama while user is speaking, reducing latency after transcription
     if config.ai_enabled {
@@ -805,204 +883,82 @@ pub async fn start_recording(
     Ok(())
 }
 
-#[tauri::command]
-pub async fn stop_recording(
-    app: AppHandle,
-    state: State<'_, RecorderState>,
-) -> Result<String, String> {
-    let stop_start = Instant::now();
-
-    log_start("RECORDING_STOP");
-    log_with_context(
-        log::Level::Debug,
-        "Stop recording command",
-        &[
-            ("command", "stop_recording"),
-            ("timestamp", &chrono::Utc::now().to_rfc3339().as_str()),
-        ],
-    );
-
-    // Update state to stopping
-    log_state_transition("RECORDING", "recording", "stopping", true, None);
-    update_recording_state(&app, RecordingState::Stopping, None);
-    // DO NOT request cancellation here - we want transcription to complete!
-    // Cancellation should only happen in cancel_recording command
-
-    // Stop recording (lock only within this scope to stay Send)
-    log::info!("🛑 Stopping recording...");
-    {
-        let mut recorder = state
-            .inner()
-            .0
-            .lock()
-            .map_err(|e| format!("Failed to acquire recorder lock: {}", e))?;
-
-        // Check if actually recording first
-        if !recorder.is_recording() {
-            log::warn!("stop_recording called but not currently recording");
-            // Don't error - just return empty result, but make sure to reset state
-            drop(recorder); // Drop the lock before updating state
-            update_recording_state(&app, RecordingState::Idle, None);
-            return Ok("".to_string());
-        }
-
-        let stop_message = recorder
-            .stop_recording()
-            .map_err(|e| format!("Failed to stop recording: {}", e))?;
-        log::info!("{}", stop_message);
-
-        // Monitor system resources after recording stop
-        #[cfg(debug_assertions)]
-        system_monitor::log_resources_after_operation(
-            "RECORDING_STOP",
-            stop_start.elapsed().as_millis() as u64,
-        );
-
-        // Emit event if recording was stopped due to silence
-        if stop_message.contains("silence") {
-            let _ = emit_to_window(&app, "pill", "recording-stopped-silence", ());
-        }
-    } // MutexGuard dropped here BEFORE any await
-
-    // Unregister ESC key
-    match "Escape".parse::<tauri_plugin_global_shortcut::Shortcut>() {
-        Ok(escape_shortcut) => {
-            if let Err(e) = app.global_shortcut().unregister(escape_shortcut) {
-                log::debug!(
-                    "Failed to unregister ESC shortcut (might not have been registered): {}",
-                    e
-                );
-            } else {
-                log::info!("Unregistered ESC shortcut");
+/// How often a running recording is checked for having stopped itself
+const AUTO_STOP_POLL: Duration = Duration::from_millis(250);
+
+/// The recorder ends a recording by itself after a pause (auto-stop), a long
+/// silence or at the size limit; hand it to `stop_recording` so it is
+/// transcribed like a manual stop
+fn watch_for_auto_stop(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(AUTO_STOP_POLL).await;
+            if !matches!(crate::get_recording_state(&app), RecordingState::Recording) {
+                // Stopped or cancelled by hand
+                return;
             }
-        }
-        Err(e) => {
-            log::debug!("Failed to parse ESC shortcut for unregistration: {:?}", e);
-        }
-    }
-
-    // Clean up ESC state
-    let app_state = app.state::<AppState>();
-    app_state
-        .esc_pressed_once
-        .store(false, std::sync::atomic::Ordering::SeqCst);
-
-    // Cancel any ESC timeout
-    if let Ok(mut timeout_guard) = app_state.esc_timeout_handle.lock() {
-        if let Some(handle) = timeout_guard.take() {
-            handle.abort();
-        }
-    }
-
-    log::debug!("Unregistered ESC key and cleaned up state");
-
-    // Check if cancellation was requested
-    if app_state.is_cancellation_requested() {
-        log::info!("Recording was cancelled, skipping transcription");
-
-        // Clean up audio file if it exists
-        if let Ok(path_guard) = app_state.current_recording_path.lock() {
-            if let Some(audio_path) = path_guard.as_ref() {
-                log::info!("Removing cancelled recording file");
-                if let Err(e) = std::fs::remove_file(audio_path) {
-                    log::warn!("Failed to remove cancelled recording: {}", e);
+            let stopped = app
+                .state::<RecorderState>()
+                .0
+                .lock()
+                .map(|recorder| recorder.has_stopped())
+                .unwrap_or(false);
+            if stopped {
+                log::info!("Recorder stopped by itself, finishing the recording");
+                if let Err(e) = stop_recording(app.clone(), app.state::<RecorderState>()).await {
+                    log::error!("Failed to finish auto-stopped recording: {}", e);
                 }
+                return;
             }
         }
+    });
+}
 
-        // Hide pill window
-        if let Err(e) = crate::commands::window::hide_pill_widget(app.clone()).await {
-            log::error!("Failed to hide pill window: {}", e);
-        }
-
-        // Transition to idle
-        update_recording_state(&app, RecordingState::Idle, None);
-
-        return Ok("".to_string());
-    }
-
-    // Get the audio file path
-    let audio_path = app_state
-        .current_recording_path
-        .lock()
-        .map_err(|e| format!("Failed to acquire path lock: {}", e))?
-        .take();
-
-    // If no audio path, there was no recording
-    let audio_path = match audio_path {
-        Some(path) => {
-            // Check if file exists and has content
-            if let Ok(metadata) = std::fs::metadata(&path) {
-                log::debug!("Audio file size: {} bytes", metadata.len());
-            } else {
-                log::error!("Audio file does not exist at path: {:?}", path);
-            }
-            path
-        }
-        None => {
-            log::warn!("No audio file found - no recording was made");
-            // Make sure to transition back to Idle state
-            update_recording_state(&app, RecordingState::Idle, None);
-            return Ok("".to_string());
-        }
-    };
+/// Why no engine could be selected: what to log and what to tell the user
+pub(crate) struct EngineUnavailable {
+    log_message: String,
+    pub(crate) user_message: String,
+}
 
-    // Fast-path: handle header-only/empty WAV files before normalization
-    if let Ok(meta) = std::fs::metadata(&audio_path) {
-        // A valid WAV header is typically 44 bytes; <= 44 implies no audio samples were written
-        if meta.len() <= 44 {
-            let _ = emit_to_window(&app, "pill", "recording-too-short", "No audio captured");
-            if let Err(e) = std::fs::remove_file(&audio_path) {
-                log::debug!("Failed to remove empty audio file: {}", e);
-            }
-            if let Err(e) = crate::commands::window::hide_pill_widget(app.clone()).await {
-                log::error!("Failed to hide pill window: {}", e);
-            }
-            update_recording_state(&app, RecordingState::Idle, None);
-            return Ok("".to_string());
+impl EngineUnavailable {
+    fn new(log_message: impl Into<String>, user_message: impl Into<String>) -> Self {
+        Self {
+            log_message: log_message.into(),
+            user_message: user_message.into(),
         }
     }
+}
 
-    // Decide engine early to optionally skip normalization for Soniox
-    let config = get_recording_config(&app).await.map_err(|e| {
-        log::error!("Failed to load recording config: {}", e);
-        format!("Configuration error: {}", e)
-    })?;
-
+/// Pick the engine and model for a finished recording from the cached settings
+pub(crate) async fn select_recording_engine(
+    app: &AppHandle,
+    config: &RecordingConfig,
+) -> Result<ActiveEngineSelection, EngineUnavailable> {
     let whisper_manager = app.state::<AsyncRwLock<WhisperManager>>();
 
-    let engine_selection = match config.current_engine.as_str() {
+    Ok(match config.current_engine.as_str() {
         "parakeet" => {
             if config.current_model.is_empty() {
-                return abort_due_to_missing_model(
-                    &app,
-                    &audio_path,
+                return Err(EngineUnavailable::new(
                     "No Parakeet model selected",
                     "Please select a Parakeet model before recording.",
-                )
-                .await;
+                ));
             }
 
             let parakeet_manager = app.state::<ParakeetManager>();
             let models = parakeet_manager.list_models();
             if let Some(status) = models.into_iter().find(|m| m.name == config.current_model) {
                 if !status.downloaded {
-                    return abort_due_to_missing_model(
-                        &app,
-                        &audio_path,
+                    return Err(EngineUnavailable::new(
                         "Selected Parakeet model is not downloaded",
                         "Please download the selected Parakeet model before recording.",
-                    )
-                    .await;
+                    ));
                 }
             } else {
-                return abort_due_to_missing_model(
-                    &app,
-                    &audio_path,
+                return Err(EngineUnavailable::new(
                     "Selected Parakeet model is not available",
                     "The selected Parakeet model is unavailable. Please download it again.",
-                )
-                .await;
+                ));
             }
 
             ActiveEngineSelection::Parakeet {
@@ -1011,23 +967,17 @@ pub async fn stop_recording(
         }
         "soniox" => {
             if config.current_model.is_empty() {
-                return abort_due_to_missing_model(
-                    &app,
-                    &audio_path,
+                return Err(EngineUnavailable::new(
                     "No Soniox model selected",
                     "Please select the Soniox cloud model before recording.",
-                )
-                .await;
+                ));
             }
 
-            if !crate::secure_store::secure_has(&app, "stt_api_key_soniox").unwrap_or(false) {
-                return abort_due_to_missing_model(
-                    &app,
-                    &audio_path,
+            if !crate::secure_store::secure_has(app, "stt_api_key_soniox").unwrap_or(false) {
+                return Err(EngineUnavailable::new(
                     "Soniox token not configured",
                     "Please configure your Soniox token in Models before recording.",
-                )
-                .await;
+                ));
             }
 
             ActiveEngineSelection::Soniox {
@@ -1037,20 +987,28 @@ pub async fn stop_recording(
         _ => {
             // OPTIMIZATION: Batch read lock - get all needed data in one lock acquisition
             // This reduces async lock cycles from 3-4 to 2
-            let (downloaded_models, models_by_size) = {
+            let (downloaded_models, models_by_size, storage_error) = {
                 let manager = whisper_manager.read().await;
-                (manager.get_downloaded_model_names(), manager.get_models_by_size())
+                (
+                    manager.get_downloaded_model_names(),
+                    manager.get_models_by_size(),
+                    manager.storage_error(),
+                )
             };
             log::debug!("Downloaded Whisper models: {:?}", downloaded_models);
 
+            if let Some(storage_error) = storage_error {
+                return Err(EngineUnavailable::new(
+                    "Models drive not connected",
+                    &storage_error,
+                ));
+            }
+
             if downloaded_models.is_empty() {
-                return abort_due_to_missing_model(
-                    &app,
-                    &audio_path,
+                return Err(EngineUnavailable::new(
                     "No speech recognition models installed",
                     "Please download at least one speech recognition model from Models to use Verity.",
-                )
-                .await;
+                ));
             }
 
             log_start("MODEL_SELECTION");
@@ -1079,6 +1037,31 @@ pub async fn stop_recording(
                     );
                     configured_model
                 } else {
+                    // The user's fallback chain decides first; size order only when it
+                    // has nothing installed
+                    let requested = FallbackEntry::new("whisper", configured_model.clone());
+                    let chain = load_transcription_fallback(app);
+                    if let Some(selection) = first_available_fallback(app, &chain, &requested).await
+                    {
+                        log::warn!(
+                            "Whisper model {} is not downloaded; falling back to {} model {}",
+                            configured_model,
+                            selection.engine_name(),
+                            selection.model_name()
+                        );
+                        let _ = emit_to_window(
+                            app,
+                            "pill",
+                            "model-fallback",
+                            serde_json::json!({
+                                "requested": configured_model,
+                                "fallback": selection.model_name(),
+                                "engine": selection.engine_name(),
+                            }),
+                        );
+                        return Ok(selection);
+                    }
+
                     // Use pre-fetched models_by_size instead of acquiring lock again
                     let fallback_model = select_best_fallback_model(
                         &downloaded_models,
@@ -1102,7 +1085,7 @@ pub async fn stop_recording(
                     );
 
                     let _ = emit_to_window(
-                        &app,
+                        app,
                         "pill",
                         "model-fallback",
                         serde_json::json!({
@@ -1133,66 +1116,617 @@ pub async fn stop_recording(
                 best_model
             };
 
-            let model_path = whisper_manager
-                .read()
-                .await
-                .get_model_path(&chosen_model)
-                .ok_or_else(|| format!("Model '{}' path not found", chosen_model))?;
+            let model_path = {
+                let manager = whisper_manager.read().await;
+                manager.get_model_path(&chosen_model).ok_or_else(|| {
+                    let message = manager
+                        .storage_error()
+                        .unwrap_or_else(|| format!("Model '{}' path not found", chosen_model));
+                    EngineUnavailable::new("Selected Whisper model path not found", message)
+                })?
+            };
 
             ActiveEngineSelection::Whisper {
                 model_name: chosen_model,
                 model_path,
             }
         }
-    };
+    })
+}
 
-    // Determine min duration based on recording mode (PTT vs Toggle) once
-    let (min_duration_s_f32, min_duration_s_i32) = {
-        let app_state = app.state::<AppState>();
-        let mode = app_state
-            .recording_mode
-            .lock()
-            .ok()
-            .map(|g| *g)
-            .unwrap_or(RecordingMode::Toggle);
-        match mode {
-            RecordingMode::PushToTalk => (1.0f32, 1i32),
-            RecordingMode::Toggle => (3.0f32, 3i32),
+/// First model after `requested` in the fallback chain that is installed, for when
+/// the requested one is not
+async fn first_available_fallback(
+    app: &AppHandle,
+    chain: &FallbackChain,
+    requested: &FallbackEntry,
+) -> Option<ActiveEngineSelection> {
+    for entry in chain.candidates(requested) {
+        match resolve_engine_for_model(app, &entry.model, Some(&entry.engine)).await {
+            Ok(selection) => return Some(selection),
+            Err(e) => log::debug!("Skipping fallback {} {}: {}", entry.engine, entry.model, e),
         }
-    };
+    }
+    None
+}
 
-    // OPTIMIZATION: Check duration on RAW audio BEFORE any processing
-    // This saves 2-5 seconds on too-short recordings by avoiding ffmpeg
-    let too_short = (|| -> Result<bool, String> {
-        let reader = hound::WavReader::open(&audio_path)
-            .map_err(|e| format!("Failed to open raw wav: {}", e))?;
-        let spec = reader.spec();
-        let total_samples = reader.duration();
-        let frames = total_samples / spec.channels as u32;
-        let duration = frames as f32 / spec.sample_rate as f32;
-        log_with_context(
-            log::Level::Info,
-            "RAW_AUDIO_CHECK",
-            &[
-                ("path", &format!("{:?}", audio_path).as_str()),
-                ("sample_rate", &spec.sample_rate.to_string().as_str()),
-                ("channels", &spec.channels.to_string().as_str()),
-                ("duration_s", &format!("{:.2}", duration).as_str()),
-            ],
-        );
-        Ok(duration < min_duration_s_f32)
-    })();
+/// Run one engine over a finished recording. Whisper takes the in-memory capture
+/// when there is one; other engines read `audio_path`, which must already be
+/// normalized for Parakeet.
+async fn transcribe_recording(
+    app: &AppHandle,
+    selection: &ActiveEngineSelection,
+    audio_path: &Path,
+    captured_audio: Option<&[f32]>,
+    language: Option<&str>,
+    translate_to_english: bool,
+) -> Result<Transcript, TranscriptionError> {
+    let app_state = app.state::<AppState>();
+    if app_state.is_cancellation_requested() {
+        return Err(TranscriptionError::Cancelled);
+    }
 
-    if let Ok(true) = too_short {
-        // Emit friendly feedback and stop here - NO ffmpeg processing needed!
-        let _ = emit_to_window(
-            &app,
-            "pill",
+    let result = match selection {
+        ActiveEngineSelection::Whisper {
+            model_name,
+            model_path,
+        } => {
+            let transcriber = load_transcriber(app, model_name, model_path)
+                .await
+                .map_err(TranscriptionError::Failed)?;
+
+            const MAX_RETRIES: u32 = 3;
+            const RETRY_DELAY_MS: u64 = 500;
+
+            let mut result = Err("No attempt made".to_string());
+
+            for attempt in 1..=MAX_RETRIES {
+                if app_state.is_cancellation_requested() {
+                    log::info!("Transcription cancelled at attempt {}", attempt);
+                    result = Err("Transcription cancelled".to_string());
+                    break;
+                }
+
+                result = match captured_audio {
+                    Some(samples) => transcriber.transcribe_samples_with_cancellation(
+                        samples,
+                        language,
+                        translate_to_english,
+                        || app_state.is_cancellation_requested(),
+                    ),
+                    None => transcriber.transcribe_with_cancellation(
+                        audio_path,
+                        language,
+                        translate_to_english,
+                        || app_state.is_cancellation_requested(),
+                    ),
+                };
+
+                match &result {
+                    Ok(_) => {
+                        if attempt > 1 {
+                            log::info!("Transcription succeeded on attempt {}", attempt);
+                        }
+                        break;
+                    }
+                    Err(e) => {
+                        if attempt < MAX_RETRIES {
+                            log::warn!(
+                                "Transcription attempt {} failed: {}. Retrying in {}ms...",
+                                attempt,
+                                e,
+                                RETRY_DELAY_MS
+                            );
+                            tokio::time::sleep(std::time::Duration::from_millis(RETRY_DELAY_MS))
+                                .await;
+                        } else {
+                            log::error!(
+                                "Transcription failed after {} attempts: {}",
+                                MAX_RETRIES,
+                                e
+                            );
+                        }
+                    }
+                }
+            }
+
+            result
+        }
+        ActiveEngineSelection::Parakeet { model_name } => {
+            let parakeet_manager = app.state::<ParakeetManager>();
+            parakeet_manager
+                .load_model(app, model_name)
+                .await
+                .map_err(|e| {
+                    TranscriptionError::Failed(format!("Parakeet model load failed: {e}"))
+                })?;
+
+            match parakeet_manager
+                .transcribe(
+                    app,
+                    model_name,
+                    audio_path.to_path_buf(),
+                    language.map(str::to_string),
+                    translate_to_english,
+                )
+                .await
+            {
+                Ok(response) => response.into_transcript(),
+                Err(e) => Err(e.to_string()),
+            }
+        }
+        ActiveEngineSelection::Soniox { .. } => soniox_transcribe_async(app, audio_path, language)
+            .await
+            .map(Transcript::plain),
+    };
+
+    // Engines report a cancelled run as an error too
+    result.map_err(|e| {
+        if app_state.is_cancellation_requested() {
+            TranscriptionError::Cancelled
+        } else {
+            TranscriptionError::Failed(e)
+        }
+    })
+}
+
+/// Transcribe with `primary` and, if it fails, walk the fallback chain. Returns the
+/// result together with the engine that produced it; on failure that is `primary`
+/// and the error is the one it reported.
+async fn transcribe_with_fallback(
+    app: &AppHandle,
+    primary: ActiveEngineSelection,
+    chain: &FallbackChain,
+    audio_path: &Path,
+    captured_audio: Option<&[f32]>,
+    language: Option<&str>,
+    translate_to_english: bool,
+) -> (
+    Result<Transcript, TranscriptionError>,
+    ActiveEngineSelection,
+) {
+    // No engine can do anything with less audio than Whisper's minimum
+    match recording_duration_secs(audio_path, captured_audio) {
+        Ok(duration) if duration < MIN_DURATION_SECS => {
+            return (Err(TranscriptionError::TooShort), primary);
+        }
+        Ok(_) => {}
+        Err(e) => log::debug!("Could not measure the recording: {}", e),
+    }
+
+    let error = match transcribe_recording(
+        app,
+        &primary,
+        audio_path,
+        captured_audio,
+        language,
+        translate_to_english,
+    )
+    .await
+    {
+        Ok(transcript) => return (Ok(transcript), primary),
+        Err(error) => error,
+    };
+
+    let failed = FallbackEntry::new(primary.engine_name(), primary.model_name());
+    let primary_ref = &primary;
+    let fallback = run_fallback_chain(
+        app,
+        "pill",
+        &failed,
+        &error,
+        chain,
+        |selection| async move {
+            // The primary engine may have left raw audio; Parakeet needs it normalized
+            let normalized = match (primary_ref, &selection) {
+                (ActiveEngineSelection::Parakeet { .. }, _)
+                | (_, ActiveEngineSelection::Whisper { .. }) => None,
+                _ => Some(
+                    normalize_recording(app, audio_path, captured_audio)
+                        .await
+                        .map_err(TranscriptionError::Failed)?,
+                ),
+            };
+            let result = transcribe_recording(
+                app,
+                &selection,
+                normalized.as_deref().unwrap_or(audio_path),
+                captured_audio,
+                language,
+                translate_to_english,
+            )
+            .await;
+            if let Some(path) = normalized {
+                let _ = std::fs::remove_file(path);
+            }
+            result
+        },
+    )
+    .await;
+
+    match fallback {
+        Some((transcript, selection)) => (Ok(transcript), selection),
+        None => (Err(error), primary),
+    }
+}
+
+/// Walk the fallback chain after `failed` could not transcribe, running each
+/// installed entry through `run` until one succeeds. `window` learns which model
+/// produced the text.
+async fn run_fallback_chain<F, Fut>(
+    app: &AppHandle,
+    window: &str,
+    failed: &FallbackEntry,
+    error: &TranscriptionError,
+    chain: &FallbackChain,
+    run: F,
+) -> Option<(Transcript, ActiveEngineSelection)>
+where
+    F: Fn(ActiveEngineSelection) -> Fut,
+    Fut: std::future::Future<Output = Result<Transcript, TranscriptionError>>,
+{
+    // Nothing to recover from when the user cancelled or there was no speech
+    if !error.is_recoverable() {
+        return None;
+    }
+
+    let run = &run;
+    let (outcome, entry) = walk_chain(chain.candidates(failed), move |entry| async move {
+        let selection = resolve_engine_for_model(app, &entry.model, Some(&entry.engine))
+            .await
+            .map_err(TranscriptionError::Failed)?;
+        log::warn!(
+            "{} model {} failed ({}); falling back to {} model {}",
+            failed.engine,
+            failed.model,
+            error,
+            entry.engine,
+            entry.model
+        );
+        let transcript = run(selection.clone()).await?;
+        Ok((transcript, selection))
+    })
+    .await?;
+
+    let _ = emit_to_window(
+        app,
+        window,
+        "model-fallback",
+        serde_json::json!({
+            "requested": failed.model,
+            "fallback": entry.model,
+            "engine": entry.engine,
+            "reason": error.to_string(),
+        }),
+    );
+    Some(outcome)
+}
+
+/// Length of the raw recording, from the in-memory capture when there is one
+fn recording_duration_secs(
+    audio_path: &Path,
+    captured_audio: Option<&[f32]>,
+) -> Result<f32, String> {
+    if let Some(samples) = captured_audio {
+        let duration = samples.len() as f32 / CAPTURE_SAMPLE_RATE as f32;
+        log_with_context(
+            log::Level::Info,
+            "RAW_AUDIO_CHECK",
+            &[
+                ("source", "memory"),
+                ("duration_s", &format!("{:.2}", duration).as_str()),
+            ],
+        );
+        return Ok(duration);
+    }
+
+    let reader =
+        hound::WavReader::open(audio_path).map_err(|e| format!("Failed to open raw wav: {}", e))?;
+    let spec = reader.spec();
+    let total_samples = reader.duration();
+    let frames = total_samples / spec.channels as u32;
+    let duration = frames as f32 / spec.sample_rate as f32;
+    log_with_context(
+        log::Level::Info,
+        "RAW_AUDIO_CHECK",
+        &[
+            ("path", &format!("{:?}", audio_path).as_str()),
+            ("sample_rate", &spec.sample_rate.to_string().as_str()),
+            ("channels", &spec.channels.to_string().as_str()),
+            ("duration_s", &format!("{:.2}", duration).as_str()),
+        ],
+    );
+    Ok(duration)
+}
+
+/// Write the in-memory capture if needed and convert it to 16 kHz mono next to it
+async fn normalize_recording(
+    app: &AppHandle,
+    audio_path: &Path,
+    captured_audio: Option<&[f32]>,
+) -> Result<PathBuf, String> {
+    if !audio_path.exists() {
+        let samples = captured_audio.ok_or("No recorded audio to normalize")?;
+        write_capture_wav(audio_path, samples)?;
+    }
+    let parent_dir = audio_path.parent().unwrap_or_else(|| Path::new("."));
+    let ts = chrono::Local::now().format("%Y%m%d_%H%M%S");
+    let out_path = parent_dir.join(format!("normalized_fallback_{}.wav", ts));
+    crate::ffmpeg::normalize_streaming(app, audio_path, &out_path)
+        .await
+        .map_err(|e| format!("Audio normalization (ffmpeg) failed: {}", e))?;
+    Ok(out_path)
+}
+
+#[tauri::command]
+pub async fn stop_recording(
+    app: AppHandle,
+    state: State<'_, RecorderState>,
+) -> Result<String, String> {
+    let stop_start = Instant::now();
+
+    log_start("RECORDING_STOP");
+    log_with_context(
+        log::Level::Debug,
+        "Stop recording command",
+        &[
+            ("command", "stop_recording"),
+            ("timestamp", &chrono::Utc::now().to_rfc3339().as_str()),
+        ],
+    );
+
+    // Claim the stop: an auto-stop and a hotkey press can arrive together
+    let claimed = app
+        .state::<AppState>()
+        .recording_state
+        .transition_if(RecordingState::Recording, RecordingState::Stopping)
+        .unwrap_or(false);
+    if !claimed
+        && matches!(
+            crate::get_recording_state(&app),
+            RecordingState::Stopping | RecordingState::Transcribing
+        )
+    {
+        log::info!("stop_recording ignored: the recording is already being stopped");
+        return Ok(String::new());
+    }
+
+    // Update state to stopping
+    log_state_transition("RECORDING", "recording", "stopping", true, None);
+    update_recording_state(&app, RecordingState::Stopping, None);
+    // DO NOT request cancellation here - we want transcription to complete!
+    // Cancellation should only happen in cancel_recording command
+
+    // Stop recording (lock only within this scope to stay Send)
+    log::info!("🛑 Stopping recording...");
+    let (captured_audio, stopped_on_silence) = {
+        let mut recorder = state
+            .inner()
+            .0
+            .lock()
+            .map_err(|e| format!("Failed to acquire recorder lock: {}", e))?;
+
+        // Check if actually recording first
+        if !recorder.is_recording() {
+            log::warn!("stop_recording called but not currently recording");
+            // Don't error - just return empty result, but make sure to reset state
+            drop(recorder); // Drop the lock before updating state
+            update_recording_state(&app, RecordingState::Idle, None);
+            return Ok("".to_string());
+        }
+
+        let stop_message = recorder
+            .stop_recording()
+            .map_err(|e| format!("Failed to stop recording: {}", e))?;
+        log::info!("{}", stop_message);
+
+        // Monitor system resources after recording stop
+        #[cfg(debug_assertions)]
+        system_monitor::log_resources_after_operation(
+            "RECORDING_STOP",
+            stop_start.elapsed().as_millis() as u64,
+        );
+
+        // Emit event if recording was stopped due to silence
+        let stopped_on_silence = stop_message.contains("silence");
+        if stopped_on_silence {
+            let _ = emit_to_window(&app, "pill", "recording-stopped-silence", ());
+        }
+
+        // Present when in-memory capture was enabled for this recording
+        (recorder.take_captured_audio(), stopped_on_silence)
+    }; // MutexGuard dropped here BEFORE any await
+
+    // Unregister ESC key
+    match "Escape".parse::<tauri_plugin_global_shortcut::Shortcut>() {
+        Ok(escape_shortcut) => {
+            if let Err(e) = app.global_shortcut().unregister(escape_shortcut) {
+                log::debug!(
+                    "Failed to unregister ESC shortcut (might not have been registered): {}",
+                    e
+                );
+            } else {
+                log::info!("Unregistered ESC shortcut");
+            }
+        }
+        Err(e) => {
+            log::debug!("Failed to parse ESC shortcut for unregistration: {:?}", e);
+        }
+    }
+
+    // Clean up ESC state
+    let app_state = app.state::<AppState>();
+    app_state
+        .esc_pressed_once
+        .store(false, std::sync::atomic::Ordering::SeqCst);
+
+    // Cancel any ESC timeout
+    if let Ok(mut timeout_guard) = app_state.esc_timeout_handle.lock() {
+        if let Some(handle) = timeout_guard.take() {
+            handle.abort();
+        }
+    }
+
+    log::debug!("Unregistered ESC key and cleaned up state");
+
+    // Check if cancellation was requested
+    if app_state.is_cancellation_requested() {
+        log::info!("Recording was cancelled, skipping transcription");
+
+        // Clean up audio file if it exists
+        if let Ok(path_guard) = app_state.current_recording_path.lock() {
+            if let Some(audio_path) = path_guard.as_ref().filter(|p| p.exists()) {
+                log::info!("Removing cancelled recording file");
+                if let Err(e) = std::fs::remove_file(audio_path) {
+                    log::warn!("Failed to remove cancelled recording: {}", e);
+                }
+            }
+        }
+
+        // Hide pill window
+        if let Err(e) = crate::commands::window::hide_pill_widget(app.clone()).await {
+            log::error!("Failed to hide pill window: {}", e);
+        }
+
+        // Transition to idle
+        update_recording_state(&app, RecordingState::Idle, None);
+
+        return Ok("".to_string());
+    }
+
+    // Get the audio file path
+    let audio_path = app_state
+        .current_recording_path
+        .lock()
+        .map_err(|e| format!("Failed to acquire path lock: {}", e))?
+        .take();
+
+    // If no audio path, there was no recording
+    let audio_path = match audio_path {
+        Some(path) => {
+            if let Some(samples) = &captured_audio {
+                log::debug!("In-memory audio: {} samples at 16kHz", samples.len());
+            } else if let Ok(metadata) = std::fs::metadata(&path) {
+                // Check if file exists and has content
+                log::debug!("Audio file size: {} bytes", metadata.len());
+            } else {
+                log::error!("Audio file does not exist at path: {:?}", path);
+            }
+            path
+        }
+        None => {
+            log::warn!("No audio file found - no recording was made");
+            // Make sure to transition back to Idle state
+            update_recording_state(&app, RecordingState::Idle, None);
+            return Ok("".to_string());
+        }
+    };
+
+    // Fast-path: handle header-only/empty WAV files before normalization
+    let no_audio_captured = match &captured_audio {
+        Some(samples) => samples.is_empty(),
+        // A valid WAV header is typically 44 bytes; <= 44 implies no audio samples were written
+        None => std::fs::metadata(&audio_path)
+            .map(|meta| meta.len() <= 44)
+            .unwrap_or(false),
+    };
+    if no_audio_captured {
+        let _ = emit_to_window(&app, "pill", "recording-too-short", "No audio captured");
+        if audio_path.exists() {
+            if let Err(e) = std::fs::remove_file(&audio_path) {
+                log::debug!("Failed to remove empty audio file: {}", e);
+            }
+        }
+        if let Err(e) = crate::commands::window::hide_pill_widget(app.clone()).await {
+            log::error!("Failed to hide pill window: {}", e);
+        }
+        update_recording_state(&app, RecordingState::Idle, None);
+        return Ok("".to_string());
+    }
+
+    // Decide engine early to optionally skip normalization for Soniox
+    let config = get_recording_config(&app).await.map_err(|e| {
+        log::error!("Failed to load recording config: {}", e);
+        format!("Configuration error: {}", e)
+    })?;
+
+    let fallback = load_transcription_fallback(&app);
+    let engine_selection = match select_recording_engine(&app, &config).await {
+        Ok(selection) => selection,
+        Err(unavailable) => match first_available_fallback(
+            &app,
+            &fallback,
+            &FallbackEntry::new(config.current_engine.clone(), config.current_model.clone()),
+        )
+        .await
+        {
+            Some(selection) => {
+                log::warn!(
+                    "{}; falling back to {} model {}",
+                    unavailable.log_message,
+                    selection.engine_name(),
+                    selection.model_name()
+                );
+                let _ = emit_to_window(
+                    &app,
+                    "pill",
+                    "model-fallback",
+                    serde_json::json!({
+                        "requested": config.current_model,
+                        "fallback": selection.model_name(),
+                        "engine": selection.engine_name(),
+                        "reason": unavailable.user_message,
+                    }),
+                );
+                selection
+            }
+            None => {
+                return abort_due_to_missing_model(
+                    &app,
+                    &audio_path,
+                    &unavailable.log_message,
+                    &unavailable.user_message,
+                )
+                .await;
+            }
+        },
+    };
+
+    // Determine min duration based on recording mode (PTT vs Toggle) once
+    let (min_duration_s_f32, min_duration_s_i32) = {
+        let app_state = app.state::<AppState>();
+        let mode = app_state
+            .recording_mode
+            .lock()
+            .ok()
+            .map(|g| *g)
+            .unwrap_or(RecordingMode::Toggle);
+        match mode {
+            RecordingMode::PushToTalk => (1.0f32, 1i32),
+            // The 3s guard is for accidental presses; a recording that ended on a
+            // pause after speech (auto-stop, wake phrase) is deliberate
+            RecordingMode::Toggle if stopped_on_silence => (1.0f32, 1i32),
+            RecordingMode::Toggle => (3.0f32, 3i32),
+        }
+    };
+
+    // OPTIMIZATION: Check duration on RAW audio BEFORE any processing
+    // This saves 2-5 seconds on too-short recordings by avoiding ffmpeg
+    let too_short = recording_duration_secs(&audio_path, captured_audio.as_deref())
+        .map(|duration| duration < min_duration_s_f32);
+
+    if let Ok(true) = too_short {
+        // Emit friendly feedback and stop here - NO ffmpeg processing needed!
+        let _ = emit_to_window(
+            &app,
+            "pill",
             "recording-too-short",
             format!("Recording shorter than {} seconds", min_duration_s_i32),
         );
-        if let Err(e) = std::fs::remove_file(&audio_path) {
-            log::debug!("Failed to remove short raw audio: {}", e);
+        if audio_path.exists() {
+            if let Err(e) = std::fs::remove_file(&audio_path) {
+                log::debug!("Failed to remove short raw audio: {}", e);
+            }
         }
         // Hide pill and return to Idle
         if let Err(e) = crate::commands::window::hide_pill_widget(app.clone()).await {
@@ -1202,6 +1736,28 @@ pub async fn stop_recording(
         return Ok("".to_string());
     }
 
+    // In-memory capture goes straight to Whisper; other engines read from disk,
+    // so write the WAV lazily now
+    let captured_audio = match captured_audio {
+        Some(samples) if !matches!(engine_selection, ActiveEngineSelection::Whisper { .. }) => {
+            log::info!(
+                "[RECORD] Writing in-memory audio to disk for {}",
+                engine_selection.engine_name()
+            );
+            if let Err(e) = write_capture_wav(&audio_path, &samples) {
+                log::error!("Failed to write captured audio: {}", e);
+                update_recording_state(
+                    &app,
+                    RecordingState::Error,
+                    Some("Failed to save recording".to_string()),
+                );
+                return Err(e);
+            }
+            None
+        }
+        other => other,
+    };
+
     // For engines that need normalization, process the audio
     // OPTIMIZATION: Whisper's transcriber.rs already handles resampling/conversion,
     // so we skip ffmpeg for Whisper and pass raw audio directly (saves 2-5 seconds)
@@ -1290,10 +1846,13 @@ pub async fn stop_recording(
         translate_to_english
     );
 
+    let retention_policy = load_audio_retention_policy(&app);
+
     let audio_path_clone = audio_path.clone();
+    let captured_audio_for_task = captured_audio;
     let engine_selection_for_task = engine_selection;
+    let fallback_for_task = fallback;
     let language_for_task = language.clone();
-    let selected_model_name_for_task = selected_model_name.clone();
 
     // Spawn and track the transcription task
     let app_for_task = app.clone();
@@ -1312,139 +1871,37 @@ pub async fn stop_recording(
         if app_state.is_cancellation_requested() {
             log::info!("Transcription cancelled before model loading");
 
-            // Hide pill window since we're cancelling
-            if let Err(e) = crate::commands::window::hide_pill_widget(app_for_task.clone()).await {
-                log::error!("Failed to hide pill window on cancellation: {}", e);
-            }
-
-            update_recording_state(&app_for_task, RecordingState::Idle, None);
-            return;
-        }
-
-        let transcription_result: Result<String, String> = match &engine_selection_for_task {
-            ActiveEngineSelection::Whisper { model_path, .. } => {
-                let transcriber = {
-                    let cache_state = app_for_task.state::<AsyncMutex<TranscriberCache>>();
-                    let mut cache = cache_state.lock().await;
-                    match cache.get_or_create(model_path) {
-                        Ok(t) => t,
-                        Err(e) => {
-                            update_recording_state(
-                                &app_for_task,
-                                RecordingState::Error,
-                                Some(e.clone()),
-                            );
-                            let _ = crate::commands::window::hide_pill_widget(app_for_task.clone())
-                                .await;
-                            let _ = emit_to_window(&app_for_task, "pill", "transcription-error", e);
-                            return;
-                        }
-                    }
-                };
-
-                const MAX_RETRIES: u32 = 3;
-                const RETRY_DELAY_MS: u64 = 500;
-
-                let mut result = Err("No attempt made".to_string());
-
-                for attempt in 1..=MAX_RETRIES {
-                    if app_state.is_cancellation_requested() {
-                        log::info!("Transcription cancelled at attempt {}", attempt);
-                        result = Err("Transcription cancelled".to_string());
-                        break;
-                    }
-
-                    result = transcriber.transcribe_with_cancellation(
-                        &audio_path_clone,
-                        language_for_task.as_deref(),
-                        translate_to_english,
-                        || app_state.is_cancellation_requested(),
-                    );
-
-                    match &result {
-                        Ok(_) => {
-                            if attempt > 1 {
-                                log::info!("Transcription succeeded on attempt {}", attempt);
-                            }
-                            break;
-                        }
-                        Err(e) => {
-                            if attempt < MAX_RETRIES {
-                                log::warn!(
-                                    "Transcription attempt {} failed: {}. Retrying in {}ms...",
-                                    attempt,
-                                    e,
-                                    RETRY_DELAY_MS
-                                );
-                                tokio::time::sleep(std::time::Duration::from_millis(
-                                    RETRY_DELAY_MS,
-                                ))
-                                .await;
-                            } else {
-                                log::error!(
-                                    "Transcription failed after {} attempts: {}",
-                                    MAX_RETRIES,
-                                    e
-                                );
-                            }
-                        }
-                    }
-                }
-
-                result
-            }
-            ActiveEngineSelection::Parakeet { model_name } => {
-                let parakeet_manager = app_for_task.state::<ParakeetManager>();
-                if let Err(e) = parakeet_manager.load_model(&app_for_task, model_name).await {
-                    let message = format!("Parakeet model load failed: {e}");
-                    update_recording_state(
-                        &app_for_task,
-                        RecordingState::Error,
-                        Some(message.clone()),
-                    );
-                    let _ = emit_to_window(&app_for_task, "pill", "transcription-error", message);
-                    return;
-                }
-
-                match parakeet_manager
-                    .transcribe(
-                        &app_for_task,
-                        model_name,
-                        audio_path_clone.clone(),
-                        language_for_task.clone(),
-                        translate_to_english,
-                    )
-                    .await
-                {
-                    Ok(ParakeetResponse::Transcription { text, .. }) => Ok(text),
-                    Ok(other) => {
-                        let message = format!("Unexpected Parakeet response: {:?}", other);
-                        Err(message)
-                    }
-                    Err(e) => Err(e.to_string()),
-                }
-            }
-            ActiveEngineSelection::Soniox { .. } => {
-                match soniox_transcribe_async(
-                    &app_for_task,
-                    &audio_path_clone,
-                    language_for_task.as_deref(),
-                )
-                .await
-                {
-                    Ok(text) => Ok(text),
-                    Err(e) => Err(e),
-                }
+            // Hide pill window since we're cancelling
+            if let Err(e) = crate::commands::window::hide_pill_widget(app_for_task.clone()).await {
+                log::error!("Failed to hide pill window on cancellation: {}", e);
             }
-        };
 
-        // Clean up temp file regardless of outcome
-        if let Err(e) = std::fs::remove_file(&audio_path_clone) {
-            log::warn!("Failed to remove temporary audio file: {}", e);
+            update_recording_state(&app_for_task, RecordingState::Idle, None);
+            return;
         }
 
+        let (transcription_result, produced_by) = transcribe_with_fallback(
+            &app_for_task,
+            engine_selection_for_task,
+            &fallback_for_task,
+            &audio_path_clone,
+            captured_audio_for_task.as_deref(),
+            language_for_task.as_deref(),
+            translate_to_english,
+        )
+        .await;
+
+        // The temp file is removed whichever way this task ends; a delivered
+        // transcription first keeps a compressed copy for history playback
+        let recording = PendingRecording {
+            audio_path: audio_path_clone.clone(),
+            captured_audio: captured_audio_for_task,
+            policy: retention_policy,
+        };
+
         match transcription_result {
-            Ok(text) => {
+            Ok(transcript) => {
+                let text = transcript.text.clone();
                 // Final cancellation check before processing result
                 if app_state.is_cancellation_requested() {
                     log::info!("Transcription completed but was cancelled, discarding result");
@@ -1504,8 +1961,12 @@ pub async fn stop_recording(
                 // Backend handles the complete flow
                 let app_for_process = app_for_task.clone();
                 let text_for_process = text.clone();
-                let model_for_process = selected_model_name_for_task.clone();
+                // History records the model that actually produced the text
+                let model_for_process = produced_by.model_name().to_string();
                 let ai_enabled_for_task = ai_enabled; // Capture from cached config
+                let recording_for_history = recording;
+                // Timings refer to the raw transcript, so only keep them when they carry any
+                let transcript_for_history = transcript.has_timings().then_some(transcript);
 
                 tokio::spawn(async move {
                     // 1. Process the transcription and enhancement
@@ -1590,6 +2051,11 @@ pub async fn stop_recording(
                         }
                     };
 
+                    // Segment timings describe the raw transcript; enhanced text no
+                    // longer lines up with them
+                    let transcript_for_history =
+                        transcript_for_history.filter(|_| final_text == text_for_process);
+
                     // Apply output prefix if configured
                     let final_text = {
                         match crate::commands::ai::get_enhancement_options(app_for_process.clone()).await {
@@ -1674,15 +2140,21 @@ pub async fn stop_recording(
                         }
                     }
 
-                    // 5. Save transcription to history (async, non-blocking)
+                    // 5. Retain the audio now that the text is delivered, then save
+                    // the transcription to history (async, non-blocking)
                     let app_for_history = app_for_process.clone();
                     let history_text = final_text.clone();
                     let history_model = model_for_process.clone();
                     tokio::spawn(async move {
-                        match save_transcription(
+                        let audio_for_history =
+                            recording_for_history.retain(&app_for_history).await;
+                        drop(recording_for_history);
+                        match save_transcription_entry(
                             app_for_history.clone(),
                             history_text,
                             history_model,
+                            audio_for_history,
+                            transcript_for_history,
                         )
                         .await
                         {
@@ -1700,9 +2172,9 @@ pub async fn stop_recording(
                     update_recording_state(&app_for_process, RecordingState::Idle, None);
                 });
             }
-            Err(e) => {
-                // Check if this is a cancellation error
-                if e.contains("cancelled") {
+            Err(error) => {
+                let e = error.to_string();
+                if error == TranscriptionError::Cancelled {
                     log::info!("Handling transcription cancellation");
                     // For cancellation, hide pill immediately and go to Idle
                     if let Err(hide_err) =
@@ -1711,7 +2183,7 @@ pub async fn stop_recording(
                         log::error!("Failed to hide pill window on cancellation: {}", hide_err);
                     }
                     update_recording_state(&app_for_task, RecordingState::Idle, None);
-                } else if e.contains("too short") {
+                } else if error == TranscriptionError::TooShort {
                     // Handle "too short" errors with specific user feedback
                     log::info!("Recording was too short: {}", e);
 
@@ -1786,6 +2258,392 @@ pub async fn get_audio_devices() -> Result<Vec<String>, String> {
     Ok(AudioRecorder::get_devices())
 }
 
+/// Read the capture DSP options, falling back to defaults (all stages off)
+fn load_audio_processing_options(app: &AppHandle) -> AudioProcessingOptions {
+    load_setting(app, "audio_processing")
+}
+
+#[tauri::command]
+pub async fn get_audio_processing_options(
+    app: AppHandle,
+) -> Result<AudioProcessingOptions, String> {
+    Ok(load_audio_processing_options(&app))
+}
+
+#[tauri::command]
+pub async fn update_audio_processing_options(
+    app: AppHandle,
+    options: AudioProcessingOptions,
+) -> Result<(), String> {
+    options.validate()?;
+    save_setting(&app, "audio_processing", &options)?;
+
+    log::info!("Audio processing options updated: {:?}", options);
+    Ok(())
+}
+
+fn load_audio_retention_policy(app: &AppHandle) -> AudioRetentionPolicy {
+    load_setting(app, "audio_retention")
+}
+
+pub(crate) fn load_auto_stop_settings(app: &AppHandle) -> AutoStopSettings {
+    load_setting(app, "auto_stop")
+}
+
+/// Silence handling for toggle recordings ("dictate until I pause")
+#[tauri::command]
+pub async fn get_auto_stop_settings(app: AppHandle) -> Result<AutoStopSettings, String> {
+    Ok(load_auto_stop_settings(&app))
+}
+
+/// Applies from the next recording on
+#[tauri::command]
+pub async fn update_auto_stop_settings(
+    app: AppHandle,
+    settings: AutoStopSettings,
+) -> Result<(), String> {
+    settings.validate()?;
+    save_setting(&app, "auto_stop", &settings)?;
+
+    log::info!("Auto-stop settings updated: {:?}", settings);
+    Ok(())
+}
+
+fn load_transcription_fallback(app: &AppHandle) -> FallbackChain {
+    load_setting(app, "transcription_fallback")
+}
+
+/// Engines tried in order when the selected one fails
+#[tauri::command]
+pub async fn get_transcription_fallback(app: AppHandle) -> Result<FallbackChain, String> {
+    Ok(load_transcription_fallback(&app))
+}
+
+#[tauri::command]
+pub async fn update_transcription_fallback(
+    app: AppHandle,
+    chain: FallbackChain,
+) -> Result<(), String> {
+    chain.validate()?;
+    save_setting(&app, "transcription_fallback", &chain)?;
+
+    log::info!("Transcription fallback chain updated: {:?}", chain.entries);
+    Ok(())
+}
+
+pub(crate) fn recordings_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join("recordings"))
+}
+
+#[tauri::command]
+pub async fn get_audio_retention_policy(app: AppHandle) -> Result<AudioRetentionPolicy, String> {
+    Ok(load_audio_retention_policy(&app))
+}
+
+#[tauri::command]
+pub async fn update_audio_retention_policy(
+    app: AppHandle,
+    policy: AudioRetentionPolicy,
+) -> Result<(), String> {
+    policy.validate()?;
+    save_setting(&app, "audio_retention", &policy)?;
+
+    // Apply tightened quotas right away
+    let retained_dir = retained_audio_dir(&recordings_dir(&app)?);
+    if policy.enabled && retained_dir.exists() {
+        let removed = enforce_quotas(&retained_dir, &policy, None)?;
+        unlink_removed_audio(&app, &removed);
+    }
+
+    log::info!("Audio retention policy updated: {:?}", policy);
+    Ok(())
+}
+
+/// A finished recording waiting on its transcription. Dropping it deletes the
+/// temporary audio file; `retain` keeps a compressed copy first.
+struct PendingRecording {
+    audio_path: PathBuf,
+    captured_audio: Option<Vec<f32>>,
+    policy: AudioRetentionPolicy,
+}
+
+impl PendingRecording {
+    /// Runs after the text is pasted so the encode never delays delivery
+    async fn retain(&self, app: &AppHandle) -> Option<String> {
+        retain_recording_audio(
+            app,
+            &self.audio_path,
+            self.captured_audio.as_deref(),
+            &self.policy,
+        )
+        .await
+    }
+}
+
+impl Drop for PendingRecording {
+    fn drop(&mut self) {
+        if self.audio_path.exists() {
+            if let Err(e) = std::fs::remove_file(&self.audio_path) {
+                log::warn!("Failed to remove temporary audio file: {}", e);
+            }
+        }
+    }
+}
+
+/// Compress the recording into the retained audio directory when retention is on.
+/// Returns the file name to link from the history entry.
+async fn retain_recording_audio(
+    app: &AppHandle,
+    audio_path: &Path,
+    captured_audio: Option<&[f32]>,
+    policy: &AudioRetentionPolicy,
+) -> Option<String> {
+    if !policy.enabled {
+        return None;
+    }
+
+    let result: Result<String, String> = async {
+        let retained_dir = retained_audio_dir(&recordings_dir(app)?);
+        std::fs::create_dir_all(&retained_dir)
+            .map_err(|e| format!("Failed to create retained audio directory: {}", e))?;
+
+        // In-memory capture only hits the disk now that we know the audio is being kept
+        if !audio_path.exists() {
+            let samples = captured_audio.ok_or("No recorded audio to retain")?;
+            write_capture_wav(audio_path, samples)?;
+        }
+
+        let file_name = format!(
+            "{}.{}",
+            chrono::Local::now().format("%Y%m%d_%H%M%S_%3f"),
+            policy.format.extension()
+        );
+        let output_path = retained_dir.join(&file_name);
+        let (codec, bitrate) = policy.format.codec();
+        crate::ffmpeg::encode_audio(app, audio_path, &output_path, codec, bitrate).await?;
+
+        let removed = enforce_quotas(&retained_dir, policy, Some(&output_path))?;
+        unlink_removed_audio(app, &removed);
+        Ok(file_name)
+    }
+    .await;
+
+    match result {
+        Ok(file_name) => {
+            log::info!("Retained recording audio as {}", file_name);
+            Some(file_name)
+        }
+        Err(e) => {
+            log::warn!("Failed to retain recording audio: {}", e);
+            None
+        }
+    }
+}
+
+/// Resolve the retained audio file linked to a history entry, if it still exists
+pub(crate) fn entry_audio_path(app: &AppHandle, entry: &serde_json::Value) -> Option<PathBuf> {
+    retained_file_path(app, entry.get("audio_file")?.as_str()?)
+}
+
+fn retained_file_path(app: &AppHandle, file_name: &str) -> Option<PathBuf> {
+    // Only plain file names are ever stored; refuse anything path-like
+    if file_name.contains('/') || file_name.contains('\\') || file_name.contains("..") {
+        return None;
+    }
+    let path = retained_audio_dir(&recordings_dir(app).ok()?).join(file_name);
+    path.exists().then_some(path)
+}
+
+fn remove_retained_file(app: &AppHandle, file_name: &str) {
+    if let Some(path) = retained_file_path(app, file_name) {
+        if let Err(e) = std::fs::remove_file(&path) {
+            log::warn!("Failed to remove retained audio {:?}: {}", path, e);
+        }
+    }
+}
+
+fn remove_entry_audio(app: &AppHandle, entry: &serde_json::Value) {
+    if let Some(file_name) = entry.get("audio_file").and_then(|v| v.as_str()) {
+        remove_retained_file(app, file_name);
+    }
+}
+
+/// Drop the links to audio files the retention quota just deleted so history
+/// doesn't offer playback of missing files
+fn unlink_removed_audio(app: &AppHandle, removed: &[PathBuf]) {
+    if removed.is_empty() {
+        return;
+    }
+    let store = match app.store("transcriptions") {
+        Ok(store) => store,
+        Err(e) => {
+            log::warn!("Failed to unlink removed audio from history: {}", e);
+            return;
+        }
+    };
+
+    let mut changed = false;
+    for key in store.keys() {
+        if let Some(mut entry) = store.get(&key) {
+            if clear_audio_link(&mut entry, removed) {
+                store.set(&key, entry);
+                changed = true;
+            }
+        }
+    }
+    if changed {
+        if let Err(e) = store.save() {
+            log::warn!("Failed to save history after unlinking audio: {}", e);
+        }
+        let _ = emit_to_window(app, "main", "history-updated", ());
+    }
+}
+
+/// Absolute path of the audio kept for a history entry, for playback
+#[tauri::command]
+pub async fn get_transcription_audio_path(
+    app: AppHandle,
+    timestamp: String,
+) -> Result<Option<String>, String> {
+    let store = app
+        .store("transcriptions")
+        .map_err(|e| format!("Failed to get transcriptions store: {}", e))?;
+    let entry = store
+        .get(&timestamp)
+        .ok_or_else(|| format!("Transcription entry not found: {}", timestamp))?;
+
+    Ok(entry_audio_path(&app, &entry).map(|p| p.to_string_lossy().to_string()))
+}
+
+/// SubRip subtitles for a history entry, from the timed segments saved with it
+#[tauri::command]
+pub async fn get_transcription_subtitles(
+    app: AppHandle,
+    timestamp: String,
+) -> Result<String, String> {
+    let store = app
+        .store("transcriptions")
+        .map_err(|e| format!("Failed to get transcriptions store: {}", e))?;
+    let entry = store
+        .get(&timestamp)
+        .ok_or_else(|| format!("Transcription entry not found: {}", timestamp))?;
+
+    let transcript = Transcript {
+        segments: entry
+            .get("segments")
+            .cloned()
+            .and_then(|segments| serde_json::from_value(segments).ok())
+            .unwrap_or_default(),
+        ..Transcript::default()
+    };
+    if !transcript.has_timings() {
+        return Err("This transcription has no timing information".to_string());
+    }
+    Ok(transcript.to_srt())
+}
+
+/// Re-run a history entry's retained audio with a different model, language or
+/// enhancement preset, replacing the entry's text and model with the new result.
+#[tauri::command]
+pub async fn retranscribe_entry(
+    app: AppHandle,
+    timestamp: String,
+    model_name: Option<String>,
+    model_engine: Option<String>,
+    language: Option<String>,
+    enhancement_preset: Option<crate::ai::EnhancementPreset>,
+) -> Result<String, String> {
+    let store = app
+        .store("transcriptions")
+        .map_err(|e| format!("Failed to get transcriptions store: {}", e))?;
+    let entry = store
+        .get(&timestamp)
+        .ok_or_else(|| format!("Transcription entry not found: {}", timestamp))?;
+
+    let audio_path = entry_audio_path(&app, &entry)
+        .ok_or_else(|| "Audio for this entry is no longer available".to_string())?;
+
+    let model_name = match model_name {
+        Some(name) => name,
+        None => entry
+            .get("model")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+            .ok_or_else(|| "No model specified".to_string())?,
+    };
+
+    let engine_selection =
+        resolve_engine_for_model(&app, &model_name, model_engine.as_deref()).await?;
+
+    let config = get_recording_config(&app).await?;
+    let language =
+        validate_language(Some(language.as_deref().unwrap_or(&config.language))).to_string();
+
+    log::info!(
+        "Re-transcribing entry {} with {} model {} (language: {})",
+        timestamp,
+        engine_selection.engine_name(),
+        model_name,
+        language
+    );
+
+    let recordings_dir = recordings_dir(&app)?;
+    let transcript = transcribe_file_with_engine(
+        &app,
+        engine_selection,
+        &audio_path,
+        &recordings_dir,
+        &language,
+        config.translate_to_english,
+    )
+    .await?;
+    let text = transcript.text.clone();
+
+    let text = if config.ai_enabled && !text.trim().is_empty() {
+        match crate::commands::ai::enhance_transcription_with_preset(
+            text.clone(),
+            app.clone(),
+            enhancement_preset,
+        )
+        .await
+        {
+            Ok(enhanced) => enhanced,
+            Err(e) => {
+                log::warn!("AI enhancement failed during re-transcription: {}", e);
+                text
+            }
+        }
+    } else {
+        text
+    };
+
+    let mut updated = entry;
+    updated["text"] = serde_json::json!(text.clone());
+    updated["model"] = serde_json::json!(model_name);
+    // Timings from the previous model no longer match the text
+    if let Some(fields) = updated.as_object_mut() {
+        fields.remove("segments");
+        fields.remove("duration");
+    }
+    if transcript.has_timings() {
+        updated["segments"] = serde_json::json!(transcript.segments);
+        if let Some(duration) = transcript.duration {
+            updated["duration"] = serde_json::json!(duration);
+        }
+    }
+    store.set(&timestamp, updated);
+    store
+        .save()
+        .map_err(|e| format!("Failed to save transcription: {}", e))?;
+    let _ = emit_to_window(&app, "main", "history-updated", ());
+
+    Ok(text)
+}
+
 /// Get the current default audio input device
 #[tauri::command]
 pub async fn get_current_audio_device() -> Result<String, String> {
@@ -1810,6 +2668,9 @@ pub async fn cleanup_old_transcriptions(app: AppHandle, days: Option<u32>) -> Re
         for key in keys {
             if let Ok(date) = chrono::DateTime::parse_from_rfc3339(&key) {
                 if date < cutoff_date {
+                    if let Some(entry) = store.get(&key) {
+                        remove_entry_audio(&app, &entry);
+                    }
                     store.delete(&key);
                 }
             }
@@ -1823,6 +2684,18 @@ pub async fn cleanup_old_transcriptions(app: AppHandle, days: Option<u32>) -> Re
 
 #[tauri::command]
 pub async fn save_transcription(app: AppHandle, text: String, model: String) -> Result<(), String> {
+    save_transcription_entry(app, text, model, None, None).await
+}
+
+/// Save a history entry, optionally linking the retained audio file for playback
+/// and the timed transcript it was made from
+pub(crate) async fn save_transcription_entry(
+    app: AppHandle,
+    text: String,
+    model: String,
+    audio_file: Option<String>,
+    transcript: Option<Transcript>,
+) -> Result<(), String> {
     // De-dup guard: skip saving if the most recent entry matches the same text & model within a short window
     if let Ok(store) = app.store("transcriptions") {
         // Find most recent entry
@@ -1863,6 +2736,10 @@ pub async fn save_transcription(app: AppHandle, text: String, model: String) ->
                 .unwrap_or(false);
             if same_text && same_model && within_window {
                 log::info!("Skipping duplicate transcription save (same text/model within 2s)");
+                // Nothing will link the audio retained for the duplicate
+                if let Some(audio_file) = &audio_file {
+                    remove_retained_file(&app, audio_file);
+                }
                 return Ok(());
             }
         }
@@ -1874,11 +2751,20 @@ pub async fn save_transcription(app: AppHandle, text: String, model: String) ->
         .map_err(|e| format!("Failed to get transcriptions store: {}", e))?;
 
     let timestamp = chrono::Utc::now().to_rfc3339();
-    let transcription_data = serde_json::json!({
+    let mut transcription_data = serde_json::json!({
         "text": text.clone(),
         "model": model,
         "timestamp": timestamp.clone()
     });
+    if let Some(audio_file) = audio_file {
+        transcription_data["audio_file"] = serde_json::json!(audio_file);
+    }
+    if let Some(transcript) = transcript {
+        transcription_data["segments"] = serde_json::json!(transcript.segments);
+        if let Some(duration) = transcript.duration {
+            transcription_data["duration"] = serde_json::json!(duration);
+        }
+    }
 
     store.set(&timestamp, transcription_data.clone());
 
@@ -1966,14 +2852,6 @@ pub async fn transcribe_audio_file(
     let wav_path = audio_path.to_path_buf();
     log::info!("[UPLOAD] Input ready at {:?}", wav_path);
 
-    // Resolve engine (whisper/parakeet/soniox) for the requested model
-    let engine_selection =
-        resolve_engine_for_model(&app, &model_name, model_engine.as_deref()).await?;
-    log::info!(
-        "[UPLOAD] Engine resolved to: {}",
-        engine_selection.engine_name()
-    );
-
     // Get language and translation settings
     let store = app.store("settings").map_err(|e| e.to_string())?;
     let language = {
@@ -1996,85 +2874,179 @@ pub async fn transcribe_audio_file(
         translate_to_english
     );
 
+    // Resolve engine (whisper/parakeet/soniox) for the requested model; a model
+    // that is gone goes straight to the fallback chain
+    let (primary, result) =
+        match resolve_engine_for_model(&app, &model_name, model_engine.as_deref()).await {
+            Ok(engine_selection) => {
+                log::info!(
+                    "[UPLOAD] Engine resolved to: {}",
+                    engine_selection.engine_name()
+                );
+                let primary = FallbackEntry::new(
+                    engine_selection.engine_name(),
+                    engine_selection.model_name(),
+                );
+                let result = transcribe_file_with_engine(
+                    &app,
+                    engine_selection,
+                    &wav_path,
+                    &recordings_dir,
+                    &language,
+                    translate_to_english,
+                )
+                .await
+                .map_err(TranscriptionError::Failed);
+                (primary, result)
+            }
+            Err(e) => {
+                let engine = model_engine
+                    .clone()
+                    .unwrap_or_else(|| "whisper".to_string());
+                (
+                    FallbackEntry::new(engine, model_name.clone()),
+                    Err(TranscriptionError::Failed(e)),
+                )
+            }
+        };
+
+    let result = match result {
+        Ok(transcript) => Ok(transcript),
+        Err(error) => {
+            let chain = load_transcription_fallback(&app);
+            let fallback =
+                run_fallback_chain(&app, "main", &primary, &error, &chain, |selection| {
+                    let app = &app;
+                    let wav_path = &wav_path;
+                    let recordings_dir = &recordings_dir;
+                    let language = &language;
+                    async move {
+                        transcribe_file_with_engine(
+                            app,
+                            selection,
+                            wav_path,
+                            recordings_dir,
+                            language,
+                            translate_to_english,
+                        )
+                        .await
+                        .map_err(TranscriptionError::Failed)
+                    }
+                })
+                .await;
+            match fallback {
+                Some((transcript, _)) => Ok(transcript),
+                None => Err(error.to_string()),
+            }
+        }
+    };
+    let text = result?.text;
+
+    log::info!(
+        "[UPLOAD] Completed transcription, {} characters",
+        text.len()
+    );
+    Ok(text)
+}
+
+/// Normalize a file and transcribe it with an already-resolved engine
+pub(crate) async fn transcribe_file_with_engine(
+    app: &AppHandle,
+    engine_selection: ActiveEngineSelection,
+    wav_path: &Path,
+    recordings_dir: &Path,
+    language: &str,
+    translate_to_english: bool,
+) -> Result<Transcript, String> {
     // For Soniox, skip normalization and send original wav_path
-    let text = match engine_selection {
-        ActiveEngineSelection::Whisper { model_path, .. } => {
-            // Normalize to Whisper contract
-            log::debug!("[UPLOAD] Normalizing to Whisper WAV (16k mono s16)...");
-            let normalized_path = {
-                let ts = chrono::Local::now().format("%Y%m%d_%H%M%S");
-                let out_path = recordings_dir.join(format!("normalized_{}.wav", ts));
-                crate::ffmpeg::normalize_streaming(&app, &wav_path, &out_path)
-                    .await
-                    .map_err(|e| format!("Audio normalization (ffmpeg) failed: {}", e))?;
-                out_path
-            };
-            log::info!("[UPLOAD] Normalized WAV at {:?}", normalized_path);
-            let transcriber = {
-                let cache_state = app.state::<AsyncMutex<TranscriberCache>>();
-                let mut cache = cache_state.lock().await;
-                cache.get_or_create(&model_path)?
-            };
+    if let ActiveEngineSelection::Soniox { .. } = engine_selection {
+        return transcribe_normalized_with_engine(
+            app,
+            engine_selection,
+            wav_path,
+            language,
+            translate_to_english,
+        )
+        .await;
+    }
+
+    // Normalize to the Whisper/Parakeet contract first
+    log::debug!("[UPLOAD] Normalizing to Whisper WAV (16k mono s16)...");
+    let normalized_path = {
+        let ts = chrono::Local::now().format("%Y%m%d_%H%M%S");
+        let out_path = recordings_dir.join(format!("normalized_{}.wav", ts));
+        crate::ffmpeg::normalize_streaming(app, wav_path, &out_path)
+            .await
+            .map_err(|e| format!("Audio normalization (ffmpeg) failed: {}", e))?;
+        out_path
+    };
+    log::info!("[UPLOAD] Normalized WAV at {:?}", normalized_path);
+
+    let result = transcribe_normalized_with_engine(
+        app,
+        engine_selection,
+        &normalized_path,
+        language,
+        translate_to_english,
+    )
+    .await;
+    let _ = std::fs::remove_file(&normalized_path);
+    result
+}
 
-            let result = transcriber.transcribe_with_translation(
-                &normalized_path,
-                Some(&language),
+/// Transcribe a WAV that is already 16 kHz mono s16, e.g. one normalized once and
+/// shared by several engines
+pub(crate) async fn transcribe_normalized_with_engine(
+    app: &AppHandle,
+    engine_selection: ActiveEngineSelection,
+    normalized_path: &Path,
+    language: &str,
+    translate_to_english: bool,
+) -> Result<Transcript, String> {
+    let transcript = match engine_selection {
+        ActiveEngineSelection::Whisper {
+            model_name,
+            model_path,
+        } => {
+            let transcriber = load_transcriber(app, &model_name, &model_path).await?;
+
+            transcriber.transcribe_with_cancellation(
+                normalized_path,
+                Some(language),
                 translate_to_english,
-            )?;
-            let _ = std::fs::remove_file(&normalized_path);
-            result
+                || false,
+            )?
         }
         ActiveEngineSelection::Parakeet { model_name } => {
-            // Normalize to Whisper/Parakeet contract first
-            log::debug!("[UPLOAD] Normalizing to Whisper WAV (16k mono s16)...");
-            let normalized_path = {
-                let ts = chrono::Local::now().format("%Y%m%d_%H%M%S");
-                let out_path = recordings_dir.join(format!("normalized_{}.wav", ts));
-                crate::ffmpeg::normalize_streaming(&app, &wav_path, &out_path)
-                    .await
-                    .map_err(|e| format!("Audio normalization (ffmpeg) failed: {}", e))?;
-                out_path
-            };
-            log::info!("[UPLOAD] Normalized WAV at {:?}", normalized_path);
             let parakeet_manager = app.state::<ParakeetManager>();
 
             parakeet_manager
-                .load_model(&app, &model_name)
+                .load_model(app, &model_name)
                 .await
                 .map_err(|e| format!("Failed to load Parakeet model: {}", e))?;
 
             match parakeet_manager
                 .transcribe(
-                    &app,
+                    app,
                     &model_name,
-                    normalized_path.clone(),
-                    Some(language.clone()),
+                    normalized_path.to_path_buf(),
+                    Some(language.to_string()),
                     translate_to_english,
                 )
                 .await
             {
-                Ok(ParakeetResponse::Transcription { text, .. }) => {
-                    let _ = std::fs::remove_file(&normalized_path);
-                    text
-                }
-                Ok(other) => {
-                    return Err(format!("Unexpected Parakeet response: {:?}", other));
-                }
+                Ok(response) => response.into_transcript()?,
                 Err(err) => {
                     return Err(format!("Parakeet transcription failed: {}", err));
                 }
             }
         }
         ActiveEngineSelection::Soniox { .. } => {
-            soniox_transcribe_async(&app, &wav_path, Some(&language)).await?
+            Transcript::plain(soniox_transcribe_async(app, normalized_path, Some(language)).await?)
         }
     };
 
-    log::info!(
-        "[UPLOAD] Completed transcription, {} characters",
-        text.len()
-    );
-    Ok(text)
+    Ok(transcript)
 }
 
 #[tauri::command]
@@ -2134,12 +3106,11 @@ pub async fn transcribe_audio(
     );
 
     let text = match engine_selection {
-        ActiveEngineSelection::Whisper { model_path, .. } => {
-            let transcriber = {
-                let cache_state = app.state::<AsyncMutex<TranscriberCache>>();
-                let mut cache = cache_state.lock().await;
-                cache.get_or_create(&model_path)?
-            };
+        ActiveEngineSelection::Whisper {
+            model_name,
+            model_path,
+        } => {
+            let transcriber = load_transcriber(&app, &model_name, &model_path).await?;
 
             transcriber.transcribe_with_translation(
                 &temp_path,
@@ -2165,8 +3136,7 @@ pub async fn transcribe_audio(
                 )
                 .await
             {
-                Ok(ParakeetResponse::Transcription { text, .. }) => text,
-                Ok(other) => return Err(format!("Unexpected Parakeet response: {:?}", other)),
+                Ok(response) => response.into_transcript()?.text,
                 Err(err) => return Err(format!("Parakeet transcription failed: {}", err)),
             }
         }
@@ -2319,7 +3289,10 @@ pub async fn delete_transcription_entry(app: AppHandle, timestamp: String) -> Re
         .store("transcriptions")
         .map_err(|e| format!("Failed to get transcriptions store: {}", e))?;
 
-    // Delete the entry
+    // Delete the entry along with any retained audio
+    if let Some(entry) = store.get(&timestamp) {
+        remove_entry_audio(&app, &entry);
+    }
     store.delete(&timestamp);
 
     // Save the store
@@ -2352,6 +3325,9 @@ pub async fn clear_all_transcriptions(app: AppHandle) -> Result<(), String> {
     let count = keys.len();
 
     for key in keys {
+        if let Some(entry) = store.get(&key) {
+            remove_entry_audio(&app, &entry);
+        }
         store.delete(&key);
     }
 
@@ -0,0 +1,216 @@
+// Microphone test wizard: record a short sample from a device, measure it, check
+// a known phrase with the current model and keep the result per device.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use tauri::AppHandle;
+
+use crate::audio::calibration::{record_sample, MicCalibration, MicMeasurement, TEST_DURATION};
+use crate::audio::capture_buffer::write_capture_wav;
+use crate::audio::recorder::{normalize_device_name, AudioRecorder};
+use crate::benchmark::metrics::word_errors;
+use crate::commands::audio::{
+    get_recording_config, recordings_dir, select_recording_engine, transcribe_file_with_engine,
+};
+use crate::commands::devices::load_capture_format;
+use crate::commands::settings::{load_setting, save_setting};
+use crate::commands::wake_word::ListenerHold;
+use crate::{emit_to_all, get_recording_state, RecordingState};
+
+/// Read aloud during the test unless the caller supplies a phrase
+const DEFAULT_TEST_PHRASE: &str = "The quick brown fox jumps over the lazy dog";
+/// Below this word accuracy the test flags the microphone
+const MIN_WORD_ACCURACY: f32 = 0.8;
+
+/// Calibrations keyed by normalized device name
+fn load_calibrations(app: &AppHandle) -> HashMap<String, MicCalibration> {
+    load_setting(app, "mic_calibration")
+}
+
+fn save_calibrations(
+    app: &AppHandle,
+    calibrations: &HashMap<String, MicCalibration>,
+) -> Result<(), String> {
+    save_setting(app, "mic_calibration", calibrations)
+}
+
+/// Calibration for a device, or for the system default input when `device` is `None`
+pub(crate) fn load_device_calibration(
+    app: &AppHandle,
+    device: Option<&str>,
+) -> Option<MicCalibration> {
+    let device = match device {
+        Some(name) => name.to_string(),
+        None => AudioRecorder::default_device_name()?,
+    };
+    load_calibrations(app).remove(&normalize_device_name(&device))
+}
+
+/// Transcribe the test sample with the engine and model real recordings use
+async fn transcribe_sample(app: &AppHandle, audio: &[f32]) -> Result<String, String> {
+    let config = get_recording_config(app).await?;
+    let engine = select_recording_engine(app, &config)
+        .await
+        .map_err(|e| e.user_message)?;
+
+    let dir = recordings_dir(app)?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create recordings directory: {}", e))?;
+    let wav_path = dir.join("mic_test.wav");
+    write_capture_wav(&wav_path, audio)?;
+
+    let result =
+        transcribe_file_with_engine(app, engine, &wav_path, &dir, &config.language, false).await;
+    let _ = std::fs::remove_file(&wav_path);
+    Ok(result?.text)
+}
+
+/// Record a 5-second sample from `device_name` (default input when omitted) while the
+/// user reads `phrase`, then measure it and check the transcription.
+///
+/// Results with audible speech are stored for the device and used by later
+/// recordings for silence detection and automatic gain.
+#[tauri::command]
+pub async fn test_microphone(
+    app: AppHandle,
+    device_name: Option<String>,
+    phrase: Option<String>,
+) -> Result<MicCalibration, String> {
+    if !matches!(
+        get_recording_state(&app),
+        RecordingState::Idle | RecordingState::Error
+    ) {
+        return Err("Finish the current recording before testing a microphone".to_string());
+    }
+    let phrase = phrase
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+        .unwrap_or_else(|| DEFAULT_TEST_PHRASE.to_string());
+
+    let format = load_capture_format(&app, device_name.as_deref());
+    let sample = {
+        let _hold = ListenerHold::acquire(&app);
+        let _ = emit_to_all(
+            &app,
+            "mic-test-recording",
+            serde_json::json!({
+                "device": device_name,
+                "phrase": phrase,
+                "duration_secs": TEST_DURATION.as_secs(),
+            }),
+        );
+        tauri::async_runtime::spawn_blocking(move || {
+            record_sample(device_name.as_deref(), &format, TEST_DURATION)
+        })
+        .await
+        .map_err(|e| format!("Microphone test failed: {}", e))??
+    };
+
+    let measurement = MicMeasurement::from_samples(&sample.audio, &sample.raw)?;
+    let mut issues = measurement.issues();
+
+    let transcript = if measurement.heard_speech() {
+        let _ = emit_to_all(&app, "mic-test-transcribing", &sample.device);
+        match transcribe_sample(&app, &sample.audio).await {
+            Ok(text) => Some(text.trim().to_string()),
+            Err(e) => {
+                log::warn!("Microphone test transcription failed: {}", e);
+                issues.push(format!("The test phrase could not be transcribed: {}", e));
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let word_accuracy = transcript
+        .as_ref()
+        .map(|text| (1.0 - word_errors(&phrase, text).rate() as f32).max(0.0));
+    if word_accuracy.is_some_and(|accuracy| accuracy < MIN_WORD_ACCURACY) {
+        issues.push("The test phrase was not recognized well".to_string());
+    }
+
+    let calibration = MicCalibration {
+        device: sample.device,
+        measured_at: chrono::Utc::now().to_rfc3339(),
+        voice_threshold: measurement.voice_threshold(),
+        score: measurement.score(word_accuracy),
+        measurement,
+        phrase: Some(phrase),
+        transcript,
+        word_accuracy,
+        issues,
+    };
+    log::info!(
+        "Microphone test on {}: SNR {:.1} dB, noise floor {:.4}, peak {:.3}, clipping {:.2}%, score {:.0}",
+        calibration.device,
+        calibration.measurement.snr_db,
+        calibration.measurement.noise_floor,
+        calibration.measurement.peak,
+        calibration.measurement.clipping_percent,
+        calibration.score
+    );
+
+    // Without speech the levels say nothing about the voice; keep the previous result
+    if calibration.measurement.heard_speech() {
+        let mut calibrations = load_calibrations(&app);
+        calibrations.insert(
+            normalize_device_name(&calibration.device),
+            calibration.clone(),
+        );
+        save_calibrations(&app, &calibrations)?;
+    }
+    Ok(calibration)
+}
+
+#[tauri::command]
+pub async fn get_mic_calibrations(app: AppHandle) -> Result<Vec<MicCalibration>, String> {
+    let mut calibrations: Vec<MicCalibration> = load_calibrations(&app).into_values().collect();
+    calibrations.sort_by(|a, b| a.device.cmp(&b.device));
+    Ok(calibrations)
+}
+
+#[tauri::command]
+pub async fn clear_mic_calibration(app: AppHandle, device_name: String) -> Result<(), String> {
+    let mut calibrations = load_calibrations(&app);
+    if calibrations
+        .remove(&normalize_device_name(&device_name))
+        .is_some()
+    {
+        save_calibrations(&app, &calibrations)?;
+        log::info!("Cleared microphone calibration for {}", device_name);
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MicrophoneSuggestion {
+    /// Best tested microphone that is currently connected
+    pub best: Option<MicCalibration>,
+    /// Connected microphones without a test result
+    pub untested: Vec<String>,
+}
+
+/// Pick the best of the connected microphones from their test results
+#[tauri::command]
+pub async fn suggest_microphone(app: AppHandle) -> Result<MicrophoneSuggestion, String> {
+    let mut calibrations = load_calibrations(&app);
+    let mut best: Option<MicCalibration> = None;
+    let mut untested = Vec::new();
+
+    for device in AudioRecorder::get_devices() {
+        match calibrations.remove(&normalize_device_name(&device)) {
+            Some(calibration) => {
+                let better = match &best {
+                    Some(current) => calibration.score > current.score,
+                    None => true,
+                };
+                if better {
+                    best = Some(calibration);
+                }
+            }
+            None => untested.push(device),
+        }
+    }
+
+    Ok(MicrophoneSuggestion { best, untested })
+}
@@ -0,0 +1,260 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_store::StoreExt;
+
+use crate::commands::audio::{
+    entry_audio_path, recordings_dir, resolve_engine_for_model, transcribe_normalized_with_engine,
+    ActiveEngineSelection,
+};
+use crate::commands::model::{load_transcriber, record_model_timing};
+use crate::parakeet::ParakeetManager;
+use crate::utils::word_diff::{changed_word_count, diff_words, WordDiff};
+use crate::whisper::languages::validate_language;
+
+/// A model to include in a comparison run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompareModel {
+    pub name: String,
+    /// Engine hint ("whisper" / "parakeet"); resolved from the name when omitted
+    #[serde(default)]
+    pub engine: Option<String>,
+}
+
+/// One model's output on the shared audio
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelComparisonResult {
+    pub model_name: String,
+    pub engine: String,
+    pub text: String,
+    /// Inference time, excluding model load
+    pub latency_ms: u64,
+    /// latency / audio duration (below 1.0 is faster than real time)
+    pub real_time_factor: f64,
+    pub word_count: usize,
+    /// Word-level differences against the baseline (first successful model)
+    pub diff: Vec<WordDiff>,
+    pub changed_words: usize,
+    /// Set when this model failed; the rest of the comparison still runs
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelComparison {
+    pub audio_duration_secs: f64,
+    pub language: String,
+    pub baseline_model: Option<String>,
+    pub results: Vec<ModelComparisonResult>,
+}
+
+/// Run one recording through several downloaded models in sequence and compare the output.
+///
+/// The audio comes from `file_path`, or from the retained audio of the history entry at
+/// `timestamp`. Models are loaded before timing so latency reflects inference only.
+#[tauri::command]
+pub async fn compare_models(
+    app: AppHandle,
+    models: Vec<CompareModel>,
+    file_path: Option<String>,
+    timestamp: Option<String>,
+    language: Option<String>,
+) -> Result<ModelComparison, String> {
+    if models.is_empty() {
+        return Err("Select at least one model to compare".to_string());
+    }
+
+    let source_path = resolve_source_audio(&app, file_path, timestamp)?;
+
+    let language = match language {
+        Some(lang) => validate_language(Some(&lang)).to_string(),
+        None => {
+            let store = app.store("settings").map_err(|e| e.to_string())?;
+            let lang = store
+                .get("language")
+                .and_then(|v| v.as_str().map(|s| s.to_string()))
+                .unwrap_or_else(|| "en".to_string());
+            validate_language(Some(&lang)).to_string()
+        }
+    };
+
+    let recordings_dir = recordings_dir(&app)?;
+    std::fs::create_dir_all(&recordings_dir)
+        .map_err(|e| format!("Failed to create recordings directory: {}", e))?;
+
+    // Normalize once so every model sees identical input and we know the duration
+    let normalized_path = recordings_dir.join(format!(
+        "compare_{}.wav",
+        chrono::Local::now().format("%Y%m%d_%H%M%S")
+    ));
+    crate::ffmpeg::normalize_streaming(&app, &source_path, &normalized_path)
+        .await
+        .map_err(|e| format!("Audio normalization (ffmpeg) failed: {}", e))?;
+
+    let comparison = run_comparison(&app, &models, &normalized_path, &language).await;
+
+    let _ = std::fs::remove_file(&normalized_path);
+    comparison
+}
+
+fn resolve_source_audio(
+    app: &AppHandle,
+    file_path: Option<String>,
+    timestamp: Option<String>,
+) -> Result<PathBuf, String> {
+    match (file_path, timestamp) {
+        (Some(path), _) => {
+            let path = PathBuf::from(path);
+            if !path.exists() {
+                return Err(format!("Audio file not found: {:?}", path));
+            }
+            Ok(path)
+        }
+        (None, Some(timestamp)) => {
+            let store = app
+                .store("transcriptions")
+                .map_err(|e| format!("Failed to get transcriptions store: {}", e))?;
+            let entry = store
+                .get(&timestamp)
+                .ok_or_else(|| format!("Transcription entry not found: {}", timestamp))?;
+            entry_audio_path(app, &entry)
+                .ok_or_else(|| "Audio for this entry is no longer available".to_string())
+        }
+        (None, None) => Err("Provide an audio file or a history entry to compare".to_string()),
+    }
+}
+
+async fn run_comparison(
+    app: &AppHandle,
+    models: &[CompareModel],
+    wav_path: &Path,
+    language: &str,
+) -> Result<ModelComparison, String> {
+    let audio_duration_secs = {
+        let reader =
+            hound::WavReader::open(wav_path).map_err(|e| format!("Failed to read audio: {}", e))?;
+        let spec = reader.spec();
+        reader.duration() as f64 / spec.sample_rate as f64
+    };
+    if audio_duration_secs <= 0.0 {
+        return Err("Audio is empty".to_string());
+    }
+
+    log::info!(
+        "[COMPARE] Comparing {} model(s) on {:.1}s of audio (language: {})",
+        models.len(),
+        audio_duration_secs,
+        language
+    );
+
+    let mut results: Vec<ModelComparisonResult> = Vec::with_capacity(models.len());
+    let mut baseline: Option<(String, String)> = None;
+
+    for model in models {
+        let outcome = transcribe_timed(app, model, wav_path, language).await;
+        let result = match outcome {
+            Ok((engine, text, latency_ms)) => {
+                let (diff, changed_words) = match &baseline {
+                    Some((_, baseline_text)) => {
+                        let diff = diff_words(baseline_text, &text);
+                        let changed = changed_word_count(&diff);
+                        (diff, changed)
+                    }
+                    None => {
+                        baseline = Some((model.name.clone(), text.clone()));
+                        (diff_words(&text, &text), 0)
+                    }
+                };
+
+                log::info!(
+                    "[COMPARE] {} ({}) took {}ms, RTF {:.3}, {} changed word(s)",
+                    model.name,
+                    engine,
+                    latency_ms,
+                    latency_ms as f64 / 1000.0 / audio_duration_secs,
+                    changed_words
+                );
+                // Real recordings make the best latency data for model recommendations
+                if engine == "whisper" {
+                    if let Err(e) =
+                        record_model_timing(app, &model.name, latency_ms, audio_duration_secs)
+                    {
+                        log::warn!("[COMPARE] {}", e);
+                    }
+                }
+
+                ModelComparisonResult {
+                    model_name: model.name.clone(),
+                    engine,
+                    word_count: text.split_whitespace().count(),
+                    text,
+                    latency_ms,
+                    real_time_factor: latency_ms as f64 / 1000.0 / audio_duration_secs,
+                    diff,
+                    changed_words,
+                    error: None,
+                }
+            }
+            Err(e) => {
+                log::warn!("[COMPARE] {} failed: {}", model.name, e);
+                ModelComparisonResult {
+                    model_name: model.name.clone(),
+                    engine: model.engine.clone().unwrap_or_default(),
+                    text: String::new(),
+                    latency_ms: 0,
+                    real_time_factor: 0.0,
+                    word_count: 0,
+                    diff: Vec::new(),
+                    changed_words: 0,
+                    error: Some(e),
+                }
+            }
+        };
+        results.push(result);
+    }
+
+    Ok(ModelComparison {
+        audio_duration_secs,
+        language: language.to_string(),
+        baseline_model: baseline.map(|(name, _)| name),
+        results,
+    })
+}
+
+/// Resolve, warm up and run a single model on the already normalized `wav_path`.
+/// Returns (engine, text, latency_ms).
+async fn transcribe_timed(
+    app: &AppHandle,
+    model: &CompareModel,
+    wav_path: &Path,
+    language: &str,
+) -> Result<(String, String, u64), String> {
+    let engine_selection =
+        resolve_engine_for_model(app, &model.name, model.engine.as_deref()).await?;
+    let engine = engine_selection.engine_name().to_string();
+
+    // Load outside the timed section so cold and warm models are measured the same way
+    match &engine_selection {
+        ActiveEngineSelection::Whisper {
+            model_name,
+            model_path,
+        } => {
+            load_transcriber(app, model_name, model_path).await?;
+        }
+        ActiveEngineSelection::Parakeet { model_name } => {
+            app.state::<ParakeetManager>()
+                .load_model(app, model_name)
+                .await
+                .map_err(|e| format!("Failed to load Parakeet model: {}", e))?;
+        }
+        ActiveEngineSelection::Soniox { .. } => {}
+    }
+
+    let start = Instant::now();
+    let text = transcribe_normalized_with_engine(app, engine_selection, wav_path, language, false)
+        .await?
+        .text;
+    let latency_ms = start.elapsed().as_millis() as u64;
+
+    Ok((engine, text, latency_ms))
+}
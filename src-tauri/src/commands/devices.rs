@@ -0,0 +1,199 @@
+// Microphone hot-plug: watches the device list, tells the windows and the tray
+// when microphones come and go or a recording moves to another one, and holds
+// the failover and per-device capture format settings recordings use.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::mpsc;
+use std::time::Duration;
+use tauri::AppHandle;
+
+use crate::audio::capture_format::{
+    device_capabilities, resolve_capture_config, CaptureFormat, DeviceCapabilities,
+};
+use crate::audio::failover::{
+    is_connected, DeviceListChange, DeviceSwitch, MicrophoneFailoverSettings,
+};
+use crate::audio::recorder::{
+    input_device_names, normalize_device_name, select_input_device, AudioRecorder,
+};
+use crate::commands::settings::{load_setting, save_setting, update_tray_menu};
+use crate::emit_to_all;
+
+/// How often the device list is checked for microphones coming and going
+const DEVICE_POLL: Duration = Duration::from_secs(3);
+
+/// Payload of the `device-changed` event
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DeviceChange {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    /// Set when a recording moved to another microphone
+    pub switch: Option<DeviceSwitch>,
+}
+
+pub(crate) fn load_failover_settings(app: &AppHandle) -> MicrophoneFailoverSettings {
+    load_setting(app, "microphone_failover")
+}
+
+/// Capture formats keyed by normalized device name
+pub(crate) fn load_capture_formats(app: &AppHandle) -> HashMap<String, CaptureFormat> {
+    load_setting(app, "capture_formats")
+}
+
+/// Capture format for a device, or for the system default input when `device` is `None`
+pub(crate) fn load_capture_format(app: &AppHandle, device: Option<&str>) -> CaptureFormat {
+    let device = match device {
+        Some(name) => name.to_string(),
+        None => match AudioRecorder::default_device_name() {
+            Some(name) => name,
+            None => return CaptureFormat::default(),
+        },
+    };
+    load_capture_formats(app)
+        .remove(&normalize_device_name(&device))
+        .unwrap_or_default()
+}
+
+/// Tell the windows and rebuild the tray's microphone menu
+async fn publish_device_change(app: &AppHandle, change: DeviceChange) {
+    let _ = emit_to_all(app, "device-changed", change);
+    if let Err(e) = update_tray_menu(app.clone()).await {
+        log::warn!("Failed to refresh tray after device change: {}", e);
+    }
+}
+
+/// Publish the microphone switches of one recording; ends with the recording
+pub(crate) fn forward_device_switches(app: AppHandle, switches: mpsc::Receiver<DeviceSwitch>) {
+    std::thread::spawn(move || {
+        for switch in switches {
+            log::info!(
+                "Recording microphone changed: {} -> {}",
+                switch.from,
+                switch.to.as_deref().unwrap_or("none")
+            );
+            let change = DeviceChange {
+                switch: Some(switch),
+                ..DeviceChange::default()
+            };
+            tauri::async_runtime::block_on(publish_device_change(&app, change));
+        }
+    });
+}
+
+/// Watch for microphones being plugged in or removed for the lifetime of the app
+pub async fn watch_audio_devices(app: AppHandle) {
+    let mut known = AudioRecorder::get_devices();
+    loop {
+        tokio::time::sleep(DEVICE_POLL).await;
+        let devices = match tauri::async_runtime::spawn_blocking(AudioRecorder::get_devices).await {
+            Ok(devices) => devices,
+            Err(e) => {
+                log::warn!("Failed to list audio devices: {}", e);
+                continue;
+            }
+        };
+
+        let change = DeviceListChange::between(&known, &devices);
+        known = devices;
+        if change.is_empty() {
+            continue;
+        }
+        log::info!(
+            "Audio devices changed: added {:?}, removed {:?}",
+            change.added,
+            change.removed
+        );
+        let change = DeviceChange {
+            added: change.added,
+            removed: change.removed,
+            switch: None,
+        };
+        publish_device_change(&app, change).await;
+    }
+}
+
+#[tauri::command]
+pub async fn get_microphone_failover(app: AppHandle) -> Result<MicrophoneFailoverSettings, String> {
+    Ok(load_failover_settings(&app))
+}
+
+#[tauri::command]
+pub async fn update_microphone_failover(
+    app: AppHandle,
+    settings: MicrophoneFailoverSettings,
+) -> Result<(), String> {
+    settings.validate()?;
+    save_setting(&app, "microphone_failover", &settings)?;
+
+    log::info!(
+        "Microphone failover {} (priority: {:?})",
+        if settings.enabled {
+            "enabled"
+        } else {
+            "disabled"
+        },
+        settings.priority
+    );
+    Ok(())
+}
+
+/// Sample rates, channel counts, sample formats and buffer sizes a device supports
+#[tauri::command]
+pub async fn get_device_capabilities(
+    device_name: Option<String>,
+) -> Result<DeviceCapabilities, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let host = cpal::default_host();
+        let device = select_input_device(&host, device_name.as_deref())?;
+        device_capabilities(&device)
+    })
+    .await
+    .map_err(|e| format!("Failed to read device capabilities: {}", e))?
+}
+
+#[tauri::command]
+pub async fn get_capture_format(
+    app: AppHandle,
+    device_name: Option<String>,
+) -> Result<CaptureFormat, String> {
+    Ok(load_capture_format(&app, device_name.as_deref()))
+}
+
+/// Store the capture format for a device. A connected device must be able to open
+/// the format; the default format removes the device's entry.
+#[tauri::command]
+pub async fn update_capture_format(
+    app: AppHandle,
+    device_name: String,
+    format: CaptureFormat,
+) -> Result<(), String> {
+    format.validate()?;
+
+    let check_name = device_name.clone();
+    let check_format = format.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let host = cpal::default_host();
+        // A disconnected device is checked when a recording opens it
+        if !is_connected(&check_name, &input_device_names(&host)) {
+            return Ok(());
+        }
+        let device = select_input_device(&host, Some(&check_name))?;
+        resolve_capture_config(&device, &check_format).map(|_| ())
+    })
+    .await
+    .map_err(|e| format!("Failed to check capture format: {}", e))??;
+
+    let mut formats = load_capture_formats(&app);
+    let key = normalize_device_name(&device_name);
+    if format.is_default() {
+        formats.remove(&key);
+    } else {
+        formats.insert(key, format.clone());
+    }
+
+    save_setting(&app, "capture_formats", &formats)?;
+
+    log::info!("Capture format for {}: {:?}", device_name, format);
+    Ok(())
+}
@@ -1,7 +1,10 @@
 pub mod ai;
 pub mod audio;
+pub mod calibration;
 pub mod clipboard;
+pub mod compare;
 pub mod debug;
+pub mod devices;
 pub mod key_normalizer;
 pub mod keyring;
 pub mod logs;
@@ -12,4 +15,5 @@ pub mod settings;
 pub mod stt;
 pub mod text;
 pub mod utils;
+pub mod wake_word;
 pub mod window;
@@ -1,15 +1,33 @@
+use crate::commands::settings::{load_setting, save_setting};
 use crate::emit_to_all;
+use crate::parakeet::options::ParakeetRuntimeOptions;
 use crate::parakeet::{ParakeetManager, ParakeetModelStatus};
+use crate::sidecar::registry::{EngineRegistry, ExternalEngine};
+use crate::sidecar::supervisor::SidecarHealth;
 use crate::utils::onboarding_logger;
 #[cfg(debug_assertions)]
 use crate::utils::system_monitor;
-use crate::whisper::manager::{ModelInfo, WhisperManager};
+use crate::whisper::bundle::{self, BundleSource};
+use crate::whisper::cache::{self, CacheStatus, ModelMemorySettings, TranscriberCache};
+use crate::whisper::catalog::CustomModelEntry;
+use crate::whisper::integrity::{self, ExpectedChecksums, IntegrityStatus};
+use crate::whisper::manager::{ModelInfo, ModelSize, WhisperManager};
+use crate::whisper::manifest::{self, ManifestEntry, ManifestSettings, ManifestSource};
+use crate::whisper::model_file::{self, ModelFileInfo, ModelFormat};
+use crate::whisper::quantize::{self, QuantizationType};
+use crate::whisper::recommender::{
+    self, HardwareProfile, LatencyTarget, ModelCandidate, ModelTimings, Recommendation,
+};
+use crate::whisper::storage::{self, ModelStorageSettings, ModelStorageStatus, MovePhase};
+use crate::whisper::transcriber::Transcriber;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex as StdMutex};
 use std::time::Instant;
-use tauri::async_runtime::RwLock;
+use tauri::async_runtime::{Mutex as AsyncMutex, RwLock};
 use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_store::StoreExt;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum ModelEngine {
@@ -132,7 +150,8 @@ pub async fn download_model(
         }
     });
 
-    // Execute download (no retry - user can click download again if it fails)
+    // Execute download (Whisper downloads retry and resume internally; clicking download
+    // again after a failure or cancel picks up the partial file)
     let download_result = if cancel_flag.load(Ordering::Relaxed) {
         log::info!("Download cancelled for model: {}", model_name);
         Err("Download cancelled by user".to_string())
@@ -329,6 +348,8 @@ pub async fn download_model(
 #[derive(serde::Serialize)]
 pub struct ModelStatusResponse {
     pub models: Vec<UnifiedModelInfo>,
+    /// Lets the UI explain missing models when their drive is disconnected
+    pub storage: ModelStorageStatus,
 }
 
 #[derive(Clone, serde::Serialize)]
@@ -350,11 +371,9 @@ pub struct UnifiedModelInfo {
 /// Returns status of all available speech recognition models (Whisper + Parakeet).
 ///
 /// **Platform Behavior**:
-/// - **macOS**: Returns both Whisper and Parakeet models
-/// - **Windows/Linux**: Returns only Whisper models (Parakeet filtered at compile time)
-///
-/// Parakeet models are automatically excluded on non-macOS platforms since they
-/// require Apple Neural Engine via FluidAudio SDK.
+/// - **macOS**: Returns Whisper and Parakeet (CoreML via FluidAudio) models
+/// - **Linux**: Returns Whisper and Parakeet (ONNX on CPU) models
+/// - **Windows**: Returns only Whisper models (Parakeet filtered at compile time)
 #[tauri::command]
 pub async fn get_model_status(
     whisper_state: State<'_, RwLock<WhisperManager>>,
@@ -363,11 +382,13 @@ pub async fn get_model_status(
 ) -> Result<ModelStatusResponse, String> {
     log::info!("[GET_MODEL_STATUS] Refreshing downloaded status...");
 
-    let whisper_models_map = {
+    let (whisper_models_map, models_dir) = {
         let mut manager = whisper_state.write().await;
+        manager.recheck_storage();
         manager.refresh_downloaded_status();
-        manager.get_models_status()
+        (manager.get_models_status(), manager.models_dir().clone())
     };
+    let storage = ModelStorageStatus::new(&models_dir, &default_models_dir(&app)?);
 
     let mut models: Vec<UnifiedModelInfo> = whisper_models_map
         .into_iter()
@@ -389,7 +410,7 @@ pub async fn get_model_status(
 
     log::info!("[GET_MODEL_STATUS] Returning {} models", models.len());
 
-    Ok(ModelStatusResponse { models })
+    Ok(ModelStatusResponse { models, storage })
 }
 
 #[tauri::command]
@@ -437,6 +458,774 @@ pub async fn list_downloaded_models(
     Ok(manager.list_downloaded_files())
 }
 
+/// Read the header of a local ggml/gguf file so the UI can show what is being imported
+#[tauri::command]
+pub async fn inspect_model_file(file_path: String) -> Result<ModelFileInfo, String> {
+    let path = std::path::PathBuf::from(file_path);
+    tokio::task::spawn_blocking(move || model_file::inspect_model_file(&path))
+        .await
+        .map_err(|e| format!("Model inspection task failed: {}", e))?
+}
+
+/// Import a local ggml/gguf Whisper model (fine-tuned, quantized, ...) under a
+/// user-chosen display name. The file is copied into the models directory and
+/// recorded in the custom model catalog.
+#[tauri::command]
+pub async fn import_custom_model(
+    app: AppHandle,
+    file_path: String,
+    display_name: String,
+    whisper_state: State<'_, RwLock<WhisperManager>>,
+) -> Result<UnifiedModelInfo, String> {
+    let display_name = display_name.trim().to_string();
+    if display_name.is_empty() || display_name.chars().count() > 100 {
+        return Err("Display name must be between 1 and 100 characters".to_string());
+    }
+
+    let source = std::path::PathBuf::from(&file_path);
+    if !source.is_file() {
+        return Err(format!("Model file not found: {}", file_path));
+    }
+
+    let info = inspect_model_file(file_path.clone()).await?;
+    ModelSize::new(info.file_size)?;
+    log::info!(
+        "Importing {:?} model {:?}: n_vocab={}, n_audio_layer={}, multilingual={}, {}",
+        info.format,
+        source,
+        info.hyperparams.n_vocab,
+        info.hyperparams.n_audio_layer,
+        info.multilingual,
+        info.quantization
+    );
+
+    let (name, models_dir) = {
+        let manager = whisper_state.read().await;
+        (
+            manager.custom_model_name(&display_name),
+            manager.models_dir().clone(),
+        )
+    };
+    tokio::fs::create_dir_all(&models_dir)
+        .await
+        .map_err(|e| format!("Failed to create models directory: {}", e))?;
+
+    // Copy under a temporary name so a half-copied file is never picked up as a model
+    let dest = models_dir.join(format!("{}.bin", name));
+    let tmp_path = models_dir.join(format!("{}.bin.importing", name));
+    let sha256 = match copy_and_hash(&source, &tmp_path).await {
+        Ok(hash) => hash,
+        Err(e) => {
+            let _ = tokio::fs::remove_file(&tmp_path).await;
+            return Err(e);
+        }
+    };
+
+    // GGUF support depends on the bundled whisper.cpp, so prove it loads before registering
+    if info.format == ModelFormat::Gguf {
+        let check_path = tmp_path.clone();
+        let loaded = tokio::task::spawn_blocking(move || {
+            crate::whisper::transcriber::Transcriber::new(&check_path).map(|_| ())
+        })
+        .await
+        .map_err(|e| format!("Model load check failed: {}", e))?;
+        if let Err(e) = loaded {
+            let _ = tokio::fs::remove_file(&tmp_path).await;
+            return Err(format!("Whisper could not load this GGUF model: {}", e));
+        }
+    }
+
+    if let Err(e) = tokio::fs::rename(&tmp_path, &dest).await {
+        let _ = tokio::fs::remove_file(&tmp_path).await;
+        return Err(format!("Failed to move imported model into place: {}", e));
+    }
+
+    let source_file = source
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let entry = CustomModelEntry::new(name.clone(), display_name, source_file, sha256, &info);
+
+    let model = register_custom_file(&app, &whisper_state, entry, &dest, "model-imported").await?;
+    log::info!("Imported custom model '{}' to {:?}", name, dest);
+    Ok(model)
+}
+
+/// Record a model file already at `dest` in the custom catalog, announce it with
+/// `event` and refresh the tray. The file is removed if registration fails.
+async fn register_custom_file(
+    app: &AppHandle,
+    whisper_state: &RwLock<WhisperManager>,
+    entry: CustomModelEntry,
+    dest: &std::path::Path,
+    event: &str,
+) -> Result<UnifiedModelInfo, String> {
+    let name = entry.name.clone();
+    let model_info = {
+        let mut manager = whisper_state.write().await;
+        if let Err(e) = manager.register_custom_model(entry) {
+            let _ = std::fs::remove_file(dest);
+            return Err(e);
+        }
+        manager
+            .get_models_status()
+            .get(&name)
+            .cloned()
+            .ok_or_else(|| format!("Model '{}' missing after import", name))?
+    };
+
+    let _ = app.emit(
+        event,
+        serde_json::json!({
+            "model": name.clone(),
+            "engine": ModelEngine::Whisper.as_str()
+        }),
+    );
+
+    // Make the new model selectable from the tray right away
+    if let Err(e) = crate::commands::settings::update_tray_menu(app.clone()).await {
+        log::warn!("Failed to update tray menu after adding model: {}", e);
+    }
+
+    Ok(convert_whisper_model(name, model_info))
+}
+
+/// Stream `source` to `dest`, returning the SHA-256 of the copied bytes
+async fn copy_and_hash(source: &std::path::Path, dest: &std::path::Path) -> Result<String, String> {
+    use sha2::{Digest, Sha256};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let mut reader = tokio::fs::File::open(source)
+        .await
+        .map_err(|e| format!("Failed to open model file: {}", e))?;
+    let mut writer = tokio::fs::File::create(dest)
+        .await
+        .map_err(|e| format!("Failed to create model file: {}", e))?;
+
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 1024 * 1024];
+    loop {
+        let read = reader
+            .read(&mut buffer)
+            .await
+            .map_err(|e| format!("Failed to read model file: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        writer
+            .write_all(&buffer[..read])
+            .await
+            .map_err(|e| format!("Failed to write model file: {}", e))?;
+    }
+    writer
+        .sync_all()
+        .await
+        .map_err(|e| format!("Failed to flush model file: {}", e))?;
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Set while a model is being quantized; quantization is CPU and memory heavy
+static QUANTIZE_IN_PROGRESS: AtomicBool = AtomicBool::new(false);
+
+/// Produce a q5_1 or q8_0 copy of a downloaded f16/f32 Whisper model using
+/// whisper.cpp's quantization kernels. The result is registered as a separate
+/// custom model named after the source, e.g. "Large v3 (q5_1)".
+#[tauri::command]
+pub async fn quantize_model(
+    app: AppHandle,
+    model_name: String,
+    quantization: QuantizationType,
+    whisper_state: State<'_, RwLock<WhisperManager>>,
+) -> Result<UnifiedModelInfo, String> {
+    if QUANTIZE_IN_PROGRESS.swap(true, Ordering::SeqCst) {
+        return Err("Another model is already being quantized".to_string());
+    }
+    let result = quantize_and_register(&app, &model_name, quantization, &whisper_state).await;
+    QUANTIZE_IN_PROGRESS.store(false, Ordering::SeqCst);
+    result
+}
+
+async fn quantize_and_register(
+    app: &AppHandle,
+    model_name: &str,
+    quantization: QuantizationType,
+    whisper_state: &RwLock<WhisperManager>,
+) -> Result<UnifiedModelInfo, String> {
+    let (source, display_name, name, models_dir) = {
+        let manager = whisper_state.read().await;
+        let source = match manager.get_model_path(model_name) {
+            Some(path) => path,
+            None => {
+                return Err(manager
+                    .storage_error()
+                    .unwrap_or_else(|| format!("Model '{}' is not downloaded", model_name)))
+            }
+        };
+        let base_name = manager
+            .get_models_status()
+            .get(model_name)
+            .map(|info| info.display_name.clone())
+            .unwrap_or_else(|| model_name.to_string());
+        let display_name = format!("{} ({})", base_name, quantization.as_str());
+        if manager
+            .get_models_status()
+            .values()
+            .any(|info| info.display_name == display_name)
+        {
+            return Err(format!("'{}' already exists", display_name));
+        }
+        (
+            source,
+            display_name.clone(),
+            manager.custom_model_name(&display_name),
+            manager.models_dir().clone(),
+        )
+    };
+
+    log::info!(
+        "Quantizing '{}' to {} as '{}'",
+        model_name,
+        quantization.as_str(),
+        name
+    );
+    let dest = models_dir.join(format!("{}.bin", name));
+    let tmp_path = models_dir.join(format!("{}.bin.quantizing", name));
+
+    let app_handle = app.clone();
+    let (input, output) = (source.clone(), tmp_path.clone());
+    let model = model_name.to_string();
+    let quantized = tokio::task::spawn_blocking(move || {
+        let last_percent = std::sync::atomic::AtomicU64::new(u64::MAX);
+        let progress = |done: u64, total: u64| {
+            let percent = if total > 0 { done * 100 / total } else { 100 };
+            if last_percent.swap(percent, Ordering::Relaxed) != percent {
+                let _ = app_handle.emit(
+                    "model-quantize-progress",
+                    serde_json::json!({
+                        "model": model,
+                        "quantization": quantization,
+                        "processed": done,
+                        "total": total,
+                        "progress": percent
+                    }),
+                );
+            }
+        };
+        let summary = quantize::quantize_model(&input, &output, quantization, &progress)?;
+        // Prove whisper.cpp accepts the result before it shows up as a model
+        crate::whisper::transcriber::Transcriber::new(&output)
+            .map_err(|e| format!("Whisper could not load the quantized model: {}", e))?;
+        let info = model_file::inspect_model_file(&output)?;
+        let (sha256, _) = integrity::hash_file(&output)?;
+        Ok::<_, String>((summary, info, sha256))
+    })
+    .await
+    .map_err(|e| format!("Quantization task failed: {}", e))
+    .and_then(|result| result);
+
+    let (summary, info, sha256) = match quantized {
+        Ok(result) => result,
+        Err(e) => {
+            let _ = tokio::fs::remove_file(&tmp_path).await;
+            return Err(e);
+        }
+    };
+    log::info!(
+        "Quantized {} of {} tensors: {} MB -> {} MB",
+        summary.quantized_tensors,
+        summary.tensors,
+        summary.input_bytes / (1024 * 1024),
+        summary.output_bytes / (1024 * 1024)
+    );
+
+    if let Err(e) = tokio::fs::rename(&tmp_path, &dest).await {
+        let _ = tokio::fs::remove_file(&tmp_path).await;
+        return Err(format!("Failed to move quantized model into place: {}", e));
+    }
+
+    let source_file = format!("{}.bin", model_name);
+    let entry = CustomModelEntry::new(name, display_name, source_file, sha256, &info);
+    register_custom_file(app, whisper_state, entry, &dest, "model-quantized").await
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct ModelManifestStatus {
+    pub source: ManifestSource,
+    pub version: u32,
+    pub models: Vec<ManifestEntry>,
+}
+
+/// The model catalog currently in use and where it came from
+#[tauri::command]
+pub async fn get_model_manifest(
+    state: State<'_, RwLock<WhisperManager>>,
+) -> Result<ModelManifestStatus, String> {
+    let manager = state.read().await;
+    Ok(ModelManifestStatus {
+        source: manager.manifest_source(),
+        version: manager.manifest().version,
+        models: manager.manifest().models.clone(),
+    })
+}
+
+pub(crate) fn load_manifest_settings(app: &AppHandle) -> ManifestSettings {
+    load_setting(app, "model_manifest")
+}
+
+#[tauri::command]
+pub async fn get_model_manifest_settings(app: AppHandle) -> Result<ManifestSettings, String> {
+    Ok(load_manifest_settings(&app))
+}
+
+/// Point the model catalog at a signed manifest file or mirror, or back at the
+/// bundled one. The new manifest is fetched and verified before anything is saved.
+#[tauri::command]
+pub async fn update_model_manifest_settings(
+    app: AppHandle,
+    settings: ManifestSettings,
+    whisper_state: State<'_, RwLock<WhisperManager>>,
+) -> Result<ModelManifestStatus, String> {
+    settings.validate()?;
+    apply_manifest_settings(&app, &settings, &whisper_state).await?;
+    save_setting(&app, "model_manifest", &settings)?;
+
+    get_model_manifest(whisper_state).await
+}
+
+/// Re-fetch the configured manifest (e.g. after the mirror published new models)
+#[tauri::command]
+pub async fn refresh_model_manifest(
+    app: AppHandle,
+    whisper_state: State<'_, RwLock<WhisperManager>>,
+) -> Result<ModelManifestStatus, String> {
+    let settings = load_manifest_settings(&app);
+    apply_manifest_settings(&app, &settings, &whisper_state).await?;
+    get_model_manifest(whisper_state).await
+}
+
+async fn apply_manifest_settings(
+    app: &AppHandle,
+    settings: &ManifestSettings,
+    whisper_state: &State<'_, RwLock<WhisperManager>>,
+) -> Result<(), String> {
+    let models_dir = whisper_state.read().await.models_dir().clone();
+
+    let (manifest, source) = match &settings.source {
+        Some(source) => {
+            let (content, signature) = manifest::fetch_manifest(source).await?;
+            let manifest =
+                manifest::parse_signed(&content, &signature, settings.public_key.as_deref())?;
+            manifest::install_override(&models_dir, &content, &signature)?;
+            (manifest, ManifestSource::Override)
+        }
+        None => {
+            manifest::remove_override(&models_dir);
+            (manifest::ModelManifest::bundled()?, ManifestSource::Bundled)
+        }
+    };
+
+    log::info!(
+        "Applying {:?} model manifest with {} models",
+        source,
+        manifest.models.len()
+    );
+    whisper_state.write().await.apply_manifest(manifest, source);
+
+    let _ = app.emit("model-manifest-updated", source);
+    if let Err(e) = crate::commands::settings::update_tray_menu(app.clone()).await {
+        log::warn!("Failed to update tray menu after manifest change: {}", e);
+    }
+    Ok(())
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct BundleExportSummary {
+    pub path: String,
+    pub models: Vec<String>,
+    pub size: u64,
+}
+
+#[derive(Clone, Debug, Default, serde::Serialize)]
+pub struct BundleImportSummary {
+    pub installed: Vec<String>,
+    /// Models that were already installed on this machine
+    pub skipped: Vec<String>,
+    /// Model name and reason, e.g. a checksum mismatch
+    pub failed: Vec<(String, String)>,
+}
+
+/// Progress reporter for bundle operations that emits once per percent
+fn bundle_progress(app: &AppHandle, operation: &'static str) -> impl Fn(u64, u64) {
+    let app = app.clone();
+    let last_percent = std::sync::atomic::AtomicU64::new(u64::MAX);
+    move |done, total| {
+        let percent = if total > 0 { done * 100 / total } else { 100 };
+        if last_percent.swap(percent, Ordering::Relaxed) != percent {
+            let _ = app.emit(
+                "model-bundle-progress",
+                serde_json::json!({
+                    "operation": operation,
+                    "processed": done,
+                    "total": total,
+                    "progress": percent
+                }),
+            );
+        }
+    }
+}
+
+/// Package downloaded Whisper models into a single tar bundle, with a manifest
+/// of checksums, for installing on machines without internet access
+#[tauri::command]
+pub async fn export_model_bundle(
+    app: AppHandle,
+    model_names: Vec<String>,
+    output_path: String,
+    whisper_state: State<'_, RwLock<WhisperManager>>,
+) -> Result<BundleExportSummary, String> {
+    let sources = {
+        let manager = whisper_state.read().await;
+        let custom_models: HashMap<String, CustomModelEntry> = manager
+            .get_custom_models()
+            .into_iter()
+            .map(|entry| (entry.name.clone(), entry))
+            .collect();
+        let status = manager.get_models_status();
+
+        let mut sources = Vec::with_capacity(model_names.len());
+        for name in &model_names {
+            let info = status
+                .get(name)
+                .ok_or_else(|| format!("Model '{}' not found", name))?;
+            let path = manager
+                .get_model_path(name)
+                .ok_or_else(|| format!("Model '{}' is not downloaded", name))?;
+            sources.push(BundleSource {
+                info: info.clone(),
+                path,
+                custom: custom_models.get(name).cloned(),
+            });
+        }
+        sources
+    };
+
+    let output = std::path::PathBuf::from(&output_path);
+    log::info!("Exporting {} models to {:?}", sources.len(), output);
+    let manifest = bundle::write_bundle(&output, &sources, bundle_progress(&app, "export")).await?;
+
+    let size = tokio::fs::metadata(&output)
+        .await
+        .map(|m| m.len())
+        .unwrap_or(0);
+    log::info!("Model bundle written: {:?} ({} bytes)", output, size);
+
+    Ok(BundleExportSummary {
+        path: output_path,
+        models: manifest.models.into_iter().map(|m| m.name).collect(),
+        size,
+    })
+}
+
+/// Verify and install the models from a bundle created by `export_model_bundle`.
+/// Each file must match the bundle's SHA-256 and, for catalog models, this
+/// machine's catalog checksum; failures are reported per model.
+#[tauri::command]
+pub async fn import_model_bundle(
+    app: AppHandle,
+    bundle_path: String,
+    whisper_state: State<'_, RwLock<WhisperManager>>,
+) -> Result<BundleImportSummary, String> {
+    let models_dir = whisper_state.read().await.models_dir().clone();
+    let extracted = bundle::extract_bundle(
+        std::path::Path::new(&bundle_path),
+        &models_dir,
+        bundle_progress(&app, "import"),
+    )
+    .await?;
+
+    let mut summary = BundleImportSummary::default();
+    for model in &extracted.manifest.models {
+        let Some(staged) = extracted.staged.get(&model.name) else {
+            summary
+                .failed
+                .push((model.name.clone(), "Missing from bundle".to_string()));
+            continue;
+        };
+
+        let (known, downloaded, catalog_checksum) = {
+            let manager = whisper_state.read().await;
+            match manager.get_models_status().get(&model.name) {
+                Some(info) => (
+                    true,
+                    info.downloaded,
+                    Some(info.reference_checksum().to_string()),
+                ),
+                None => (false, false, None),
+            }
+        };
+
+        if downloaded {
+            let _ = tokio::fs::remove_file(staged).await;
+            summary.skipped.push(model.name.clone());
+            continue;
+        }
+        if !known && model.custom.is_none() {
+            let _ = tokio::fs::remove_file(staged).await;
+            summary.failed.push((
+                model.name.clone(),
+                "Model is not in this app's catalog".to_string(),
+            ));
+            continue;
+        }
+
+        let catalog_checksum = catalog_checksum.filter(|c| !c.is_empty());
+        if let Err(e) =
+            bundle::verify_staged_model(model, staged, catalog_checksum.as_deref()).await
+        {
+            log::warn!("Bundle model '{}' failed verification: {}", model.name, e);
+            summary.failed.push((model.name.clone(), e));
+            continue;
+        }
+
+        let dest = models_dir.join(format!("{}.bin", model.name));
+        if let Err(e) = tokio::fs::rename(staged, &dest).await {
+            let _ = tokio::fs::remove_file(staged).await;
+            summary
+                .failed
+                .push((model.name.clone(), format!("Failed to install: {}", e)));
+            continue;
+        }
+
+        // Imported models travel with their catalog entry
+        if let (false, Some(entry)) = (known, &model.custom) {
+            let mut manager = whisper_state.write().await;
+            if let Err(e) = manager.register_custom_model(entry.clone()) {
+                let _ = std::fs::remove_file(&dest);
+                summary.failed.push((model.name.clone(), e));
+                continue;
+            }
+        }
+
+        log::info!("Installed '{}' from model bundle", model.name);
+        summary.installed.push(model.name.clone());
+    }
+    extracted.discard();
+
+    whisper_state.write().await.refresh_downloaded_status();
+
+    if !summary.installed.is_empty() {
+        let _ = app.emit("model-bundle-imported", &summary.installed);
+        if let Err(e) = crate::commands::settings::update_tray_menu(app.clone()).await {
+            log::warn!("Failed to update tray menu after bundle import: {}", e);
+        }
+    }
+
+    Ok(summary)
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct ModelIntegrityReport {
+    pub model: String,
+    pub status: Option<IntegrityStatus>,
+    pub sha256: Option<String>,
+    pub checked_at: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Hash downloaded Whisper models whose size/mtime changed or whose last check
+/// is older than `REVERIFY_AFTER_DAYS`, and flag any that no longer match.
+/// `force` re-hashes every model.
+pub(crate) async fn run_integrity_scan(
+    app: &AppHandle,
+    whisper_state: &State<'_, RwLock<WhisperManager>>,
+    force: bool,
+) -> Vec<ModelIntegrityReport> {
+    let targets: Vec<(String, std::path::PathBuf, ExpectedChecksums)> = {
+        let manager = whisper_state.read().await;
+        manager
+            .get_models_status()
+            .into_iter()
+            .filter(|(_, info)| info.downloaded)
+            .filter_map(|(name, info)| {
+                let path = manager.get_model_path(&name)?;
+                Some((name, path, ExpectedChecksums::from_model(&info)))
+            })
+            .collect()
+    };
+
+    let mut reports = Vec::with_capacity(targets.len());
+    for (name, path, expected) in targets {
+        let scan_path = path.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            if force || integrity::needs_scan(&scan_path) {
+                integrity::verify_model_file(&scan_path, &expected, true)
+            } else {
+                integrity::cached_record(&scan_path)
+                    .ok_or_else(|| "No integrity record".to_string())
+            }
+        })
+        .await
+        .map_err(|e| format!("Integrity scan task failed: {}", e))
+        .and_then(|r| r);
+
+        let report = match result {
+            Ok(record) => ModelIntegrityReport {
+                model: name,
+                status: Some(record.status),
+                sha256: Some(record.sha256),
+                checked_at: Some(record.checked_at),
+                error: None,
+            },
+            Err(e) => ModelIntegrityReport {
+                model: name,
+                status: None,
+                sha256: None,
+                checked_at: None,
+                error: Some(e),
+            },
+        };
+
+        if report.status == Some(IntegrityStatus::Mismatch) {
+            log::warn!("Model '{}' failed its integrity check", report.model);
+            let _ = app.emit("model-integrity-issue", &report);
+        }
+        reports.push(report);
+    }
+
+    let _ = app.emit("model-integrity-scan-complete", &reports);
+    reports
+}
+
+/// Check downloaded models for tampering or corruption
+#[tauri::command]
+pub async fn scan_model_integrity(
+    app: AppHandle,
+    force: Option<bool>,
+    whisper_state: State<'_, RwLock<WhisperManager>>,
+) -> Result<Vec<ModelIntegrityReport>, String> {
+    Ok(run_integrity_scan(&app, &whisper_state, force.unwrap_or(false)).await)
+}
+
+/// Recommend a Whisper model for this machine. `max_latency_ms` / `clip_secs` set the
+/// target (default: under 1 s for a 10 s clip). Latency comes from earlier model
+/// comparisons and benchmarks on this machine; with `run_benchmark`, installed models
+/// that fit in free memory are timed on a synthetic clip first and the timings kept.
+#[tauri::command]
+pub async fn recommend_model(
+    app: AppHandle,
+    max_latency_ms: Option<u64>,
+    clip_secs: Option<u32>,
+    run_benchmark: Option<bool>,
+    whisper_state: State<'_, RwLock<WhisperManager>>,
+) -> Result<Recommendation, String> {
+    let defaults = LatencyTarget::default();
+    let target = LatencyTarget {
+        max_latency_ms: max_latency_ms.unwrap_or(defaults.max_latency_ms),
+        clip_secs: clip_secs.unwrap_or(defaults.clip_secs),
+    };
+    target.validate()?;
+
+    let language = app
+        .store("settings")
+        .ok()
+        .and_then(|store| store.get("language"))
+        .and_then(|v| v.as_str().map(|s| s.to_string()))
+        .unwrap_or_else(|| "en".to_string());
+    let language = crate::whisper::languages::validate_language(Some(&language)).to_string();
+
+    let hardware = tokio::task::spawn_blocking(HardwareProfile::detect)
+        .await
+        .map_err(|e| format!("Hardware detection failed: {}", e))?;
+    onboarding_logger::with_onboarding_logger(|logger| {
+        logger.log_hardware_detection(hardware.gpu_backend.is_some(), hardware.cpu_cores);
+    });
+
+    let (candidates, benchmark_targets) = {
+        let manager = whisper_state.read().await;
+        let custom: HashMap<String, CustomModelEntry> = manager
+            .get_custom_models()
+            .into_iter()
+            .map(|entry| (entry.name.clone(), entry))
+            .collect();
+
+        let mut candidates = Vec::new();
+        let mut benchmark_targets = Vec::new();
+        for (name, info) in manager.get_models_status() {
+            let (min_ram_mb, languages) =
+                match manager.manifest().models.iter().find(|m| m.name == name) {
+                    Some(entry) => (entry.min_ram_mb, entry.languages.clone()),
+                    None => (
+                        ModelCandidate::estimated_min_ram_mb(info.size),
+                        match custom.get(&name) {
+                            Some(entry) if !entry.multilingual => vec!["en".to_string()],
+                            _ => vec!["*".to_string()],
+                        },
+                    ),
+                };
+
+            if info.downloaded && min_ram_mb <= hardware.available_ram_mb {
+                if let Some(path) = manager.get_model_path(&name) {
+                    benchmark_targets.push((name.clone(), path));
+                }
+            }
+
+            candidates.push(ModelCandidate {
+                name,
+                display_name: info.display_name,
+                size: info.size,
+                min_ram_mb,
+                languages,
+                speed_score: info.speed_score,
+                accuracy_score: info.accuracy_score,
+                downloaded: info.downloaded,
+            });
+        }
+        (candidates, benchmark_targets)
+    };
+
+    let mut timings = load_model_timings(&app);
+    if run_benchmark.unwrap_or(false) {
+        for (name, path) in benchmark_targets {
+            if let Err(e) = verify_model_before_load(&app, &name, &path).await {
+                log::warn!("[RECOMMEND] Skipping benchmark of {}: {}", name, e);
+                continue;
+            }
+            let clip_secs = target.clip_secs;
+            let result =
+                tokio::task::spawn_blocking(move || recommender::measure_latency(&path, clip_secs))
+                    .await
+                    .map_err(|e| format!("Benchmark task failed: {}", e))
+                    .and_then(|r| r);
+            match result {
+                Ok(latency_ms) => {
+                    log::info!(
+                        "[RECOMMEND] {} took {}ms on a {}s clip",
+                        name,
+                        latency_ms,
+                        clip_secs
+                    );
+                    timings.record(&name, latency_ms, f64::from(clip_secs));
+                }
+                Err(e) => log::warn!("[RECOMMEND] Benchmark of {} failed: {}", name, e),
+            }
+        }
+        if let Err(e) = save_model_timings(&app, &timings) {
+            log::warn!("[RECOMMEND] {}", e);
+        }
+    }
+
+    let recommendation =
+        recommender::recommend(&candidates, &hardware, target, &language, &timings);
+    log::info!(
+        "[RECOMMEND] Recommended {:?}: {}",
+        recommendation.model,
+        recommendation.reasons.join("; ")
+    );
+    Ok(recommendation)
+}
+
 async fn identify_download_target(
     model_name: &str,
     whisper_state: &State<'_, RwLock<WhisperManager>>,
@@ -644,15 +1433,60 @@ pub async fn verify_model(
     Ok(())
 }
 
+/// Hash a Whisper model against its catalog checksums before it is loaded. Runs
+/// off the cache lock so other transcriptions are not held up while it hashes.
+pub(crate) async fn verify_model_before_load(
+    app: &AppHandle,
+    model_name: &str,
+    model_path: &Path,
+) -> Result<(), String> {
+    let expected = {
+        let whisper_state = app.state::<RwLock<WhisperManager>>();
+        let manager = whisper_state.read().await;
+        manager
+            .get_models_status()
+            .get(model_name)
+            .map(ExpectedChecksums::from_model)
+            .unwrap_or_default()
+    };
+    integrity::verify_before_load(model_path.to_path_buf(), expected).await
+}
+
+/// Verified transcriber for a Whisper model, from the cache or freshly loaded
+pub(crate) async fn load_transcriber(
+    app: &AppHandle,
+    model_name: &str,
+    model_path: &Path,
+) -> Result<Arc<Transcriber>, String> {
+    verify_model_before_load(app, model_name, model_path).await?;
+    let cache_state = app.state::<AsyncMutex<TranscriberCache>>();
+    let mut cache = cache_state.lock().await;
+    cache.get_or_create(model_path)
+}
+
+/// Verify the pinned model, then load it if it is not in memory
+async fn warm_pinned_model(app: &AppHandle) -> Result<(), String> {
+    if let Some(name) = load_model_memory_settings(app).pinned_model {
+        let path = app
+            .state::<RwLock<WhisperManager>>()
+            .read()
+            .await
+            .get_model_path(&name);
+        if let Some(path) = path.filter(|path| path.exists()) {
+            verify_model_before_load(app, &name, &path).await?;
+        }
+    }
+    let cache_state = app.state::<AsyncMutex<TranscriberCache>>();
+    let mut cache = cache_state.lock().await;
+    cache.warm_pinned()
+}
+
 #[tauri::command]
 pub async fn preload_model(
     app: AppHandle,
     model_name: String,
     state: State<'_, RwLock<WhisperManager>>,
 ) -> Result<(), String> {
-    use crate::whisper::cache::TranscriberCache;
-    use tauri::async_runtime::Mutex as AsyncMutex;
-
     // License check removed - app is fully offline/private
     log::info!("Preloading model: {}", model_name);
 
@@ -664,14 +1498,447 @@ pub async fn preload_model(
             .ok_or(format!("Model '{}' not found", model_name))?
     };
 
-    // Load into cache
+    // Verify, then load the model and cache it
+    load_transcriber(&app, &model_name, &model_path).await?;
+
+    log::info!("Model '{}' preloaded successfully", model_name);
+
+    Ok(())
+}
+
+pub(crate) fn load_model_timings(app: &AppHandle) -> ModelTimings {
+    load_setting(app, "model_timings")
+}
+
+/// Keep how long `model` took on `audio_secs` of audio for future recommendations
+pub(crate) fn record_model_timing(
+    app: &AppHandle,
+    model: &str,
+    latency_ms: u64,
+    audio_secs: f64,
+) -> Result<(), String> {
+    let mut timings = load_model_timings(app);
+    timings.record(model, latency_ms, audio_secs);
+    save_setting(app, "model_timings", &timings)
+}
+
+/// How often idle and memory-pressure unloading runs
+const CACHE_MAINTENANCE_INTERVAL_SECS: u64 = 60;
+
+pub(crate) fn load_model_memory_settings(app: &AppHandle) -> ModelMemorySettings {
+    load_setting(app, "model_memory")
+}
+
+#[tauri::command]
+pub async fn get_model_memory_settings(app: AppHandle) -> Result<ModelMemorySettings, String> {
+    Ok(load_model_memory_settings(&app))
+}
+
+/// Change the model memory budget, idle unload time or pinned model. Takes effect
+/// immediately: models over the new limits are unloaded and a pinned model is loaded.
+#[tauri::command]
+pub async fn update_model_memory_settings(
+    app: AppHandle,
+    settings: ModelMemorySettings,
+) -> Result<CacheStatus, String> {
+    settings.validate()?;
+    apply_model_memory_settings(&app, &settings).await?;
+    save_setting(&app, "model_memory", &settings)?;
+
+    if let Err(e) = warm_pinned_model(&app).await {
+        log::warn!("Failed to load pinned model: {}", e);
+    }
+    let cache_state = app.state::<AsyncMutex<TranscriberCache>>();
+    let cache = cache_state.lock().await;
+    Ok(cache.status())
+}
+
+/// Loaded models, memory use and recent load/unload events
+#[tauri::command]
+pub async fn get_model_cache_status(app: AppHandle) -> Result<CacheStatus, String> {
     let cache_state = app.state::<AsyncMutex<TranscriberCache>>();
-    let mut cache = cache_state.lock().await;
+    let cache = cache_state.lock().await;
+    Ok(cache.status())
+}
 
-    // This will load the model and cache it
-    cache.get_or_create(&model_path)?;
+/// Parakeet sidecar crash counts, restarts and last stderr lines
+#[tauri::command]
+pub async fn get_parakeet_diagnostics(
+    parakeet_manager: State<'_, ParakeetManager>,
+) -> Result<SidecarHealth, String> {
+    Ok(parakeet_manager.sidecar_health())
+}
 
-    log::info!("Model '{}' preloaded successfully", model_name);
+/// Engine binaries registered in place of the bundled sidecars
+#[tauri::command]
+pub async fn get_sidecar_engines(app: AppHandle) -> Result<EngineRegistry, String> {
+    Ok(EngineRegistry::load(&app))
+}
+
+/// Run `engine` with an external binary speaking the sidecar protocol. The running
+/// sidecar is stopped so the next request starts the new binary.
+#[tauri::command]
+pub async fn register_sidecar_engine(
+    app: AppHandle,
+    parakeet_manager: State<'_, ParakeetManager>,
+    engine: String,
+    path: String,
+    args: Option<Vec<String>>,
+) -> Result<(), String> {
+    let binary = ExternalEngine {
+        path: PathBuf::from(path),
+        args: args.unwrap_or_default(),
+    };
+    let mut registry = EngineRegistry::load(&app);
+    registry.register(&engine, binary.clone())?;
+    registry.save(&app)?;
+    parakeet_manager.restart_sidecar().await;
+
+    log::info!("Registered {} engine binary {:?}", engine, binary.path);
+    Ok(())
+}
+
+/// Go back to the bundled sidecar (or the in-process engine) for `engine`
+#[tauri::command]
+pub async fn unregister_sidecar_engine(
+    app: AppHandle,
+    parakeet_manager: State<'_, ParakeetManager>,
+    engine: String,
+) -> Result<(), String> {
+    let mut registry = EngineRegistry::load(&app);
+    if registry.unregister(&engine).is_none() {
+        return Ok(());
+    }
+    registry.save(&app)?;
+    parakeet_manager.restart_sidecar().await;
+
+    log::info!("Unregistered the {} engine binary", engine);
+    Ok(())
+}
+
+pub(crate) fn load_parakeet_options(app: &AppHandle) -> HashMap<String, ParakeetRuntimeOptions> {
+    let mut options: HashMap<String, ParakeetRuntimeOptions> =
+        load_setting(app, "parakeet_options");
+    // Overrides saved by older builds may ask for options this backend can't honour
+    options.retain(|model, options| match options.validate() {
+        Ok(()) => true,
+        Err(e) => {
+            log::warn!("Ignoring saved Parakeet options for {}: {}", model, e);
+            false
+        }
+    });
+    options
+}
+
+/// Persist the per-model overrides, hand them to the manager and reload
+/// `model_name` if it is loaded so the change takes effect right away
+async fn apply_parakeet_options(
+    app: &AppHandle,
+    parakeet_manager: &ParakeetManager,
+    model_name: &str,
+    options: HashMap<String, ParakeetRuntimeOptions>,
+) -> Result<(), String> {
+    save_setting(app, "parakeet_options", &options)?;
+
+    parakeet_manager.set_runtime_options(options);
+    parakeet_manager
+        .reapply_runtime_options(app, model_name)
+        .await
+        .map_err(|e| format!("Saved, but failed to reload {}: {}", model_name, e))
+}
+
+/// Runtime options used when loading a Parakeet model
+#[tauri::command]
+pub async fn get_parakeet_options(
+    model_name: String,
+    parakeet_manager: State<'_, ParakeetManager>,
+) -> Result<ParakeetRuntimeOptions, String> {
+    if parakeet_manager.get_model_definition(&model_name).is_none() {
+        return Err(format!("Unknown Parakeet model: {}", model_name));
+    }
+    Ok(parakeet_manager.runtime_options(&model_name))
+}
+
+/// Change a Parakeet model's precision, attention and chunking. A loaded model is
+/// reloaded with the new options.
+#[tauri::command]
+pub async fn update_parakeet_options(
+    app: AppHandle,
+    model_name: String,
+    options: ParakeetRuntimeOptions,
+    parakeet_manager: State<'_, ParakeetManager>,
+) -> Result<ParakeetRuntimeOptions, String> {
+    if parakeet_manager.get_model_definition(&model_name).is_none() {
+        return Err(format!("Unknown Parakeet model: {}", model_name));
+    }
+    options.validate()?;
+
+    let mut all = load_parakeet_options(&app);
+    all.insert(model_name.clone(), options.clone());
+    apply_parakeet_options(&app, &parakeet_manager, &model_name, all).await?;
+    Ok(options)
+}
+
+/// Drop a Parakeet model's overrides and go back to the defaults
+#[tauri::command]
+pub async fn reset_parakeet_options(
+    app: AppHandle,
+    model_name: String,
+    parakeet_manager: State<'_, ParakeetManager>,
+) -> Result<ParakeetRuntimeOptions, String> {
+    let mut all = load_parakeet_options(&app);
+    if all.remove(&model_name).is_some() {
+        apply_parakeet_options(&app, &parakeet_manager, &model_name, all).await?;
+    }
+    Ok(ParakeetRuntimeOptions::default())
+}
 
+async fn apply_model_memory_settings(
+    app: &AppHandle,
+    settings: &ModelMemorySettings,
+) -> Result<(), String> {
+    let pinned_path = match &settings.pinned_model {
+        Some(name) => {
+            let whisper_state = app.state::<RwLock<WhisperManager>>();
+            let manager = whisper_state.read().await;
+            Some(
+                manager
+                    .get_model_path(name)
+                    .ok_or_else(|| format!("Model '{}' not found", name))?,
+            )
+        }
+        None => None,
+    };
+
+    let total_ram_mb = {
+        let mut system = sysinfo::System::new();
+        system.refresh_memory();
+        system.total_memory() / (1024 * 1024)
+    };
+
+    let cache_state = app.state::<AsyncMutex<TranscriberCache>>();
+    cache_state
+        .lock()
+        .await
+        .configure(settings.policy(total_ram_mb), pinned_path);
     Ok(())
 }
+
+/// Apply the saved memory settings, then periodically unload idle models, unload
+/// unpinned ones under memory pressure, keep the pinned model warm and forward
+/// load/unload events to the frontend as `model-cache-event`.
+pub(crate) fn spawn_cache_maintenance(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let settings = load_model_memory_settings(&app);
+        if let Err(e) = apply_model_memory_settings(&app, &settings).await {
+            log::warn!("Failed to apply model memory settings: {}", e);
+        }
+
+        let mut system = sysinfo::System::new();
+        let mut last_seq = 0;
+        loop {
+            system.refresh_memory();
+            let under_pressure = cache::is_memory_pressure(
+                system.total_memory() / (1024 * 1024),
+                system.available_memory() / (1024 * 1024),
+            );
+
+            app.state::<AsyncMutex<TranscriberCache>>()
+                .lock()
+                .await
+                .maintain(under_pressure);
+            if !under_pressure {
+                if let Err(e) = warm_pinned_model(&app).await {
+                    log::warn!("Failed to keep pinned model warm: {}", e);
+                }
+            }
+            {
+                let cache_state = app.state::<AsyncMutex<TranscriberCache>>();
+                let cache = cache_state.lock().await;
+                for event in cache.events_since(last_seq) {
+                    last_seq = event.seq;
+                    let _ = app.emit("model-cache-event", &event);
+                }
+            }
+
+            tokio::time::sleep(std::time::Duration::from_secs(
+                CACHE_MAINTENANCE_INTERVAL_SECS,
+            ))
+            .await;
+        }
+    });
+}
+
+/// Set while the models directory is being moved
+static MODELS_MOVE_IN_PROGRESS: AtomicBool = AtomicBool::new(false);
+
+pub(crate) fn load_model_storage_settings(app: &AppHandle) -> ModelStorageSettings {
+    load_setting(app, "model_storage")
+}
+
+pub(crate) fn default_models_dir(app: &AppHandle) -> Result<std::path::PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join("models"))
+        .map_err(|e| format!("Failed to resolve app data directory: {}", e))
+}
+
+/// Where models are stored and whether that folder is reachable right now
+#[tauri::command]
+pub async fn get_models_storage(
+    app: AppHandle,
+    whisper_state: State<'_, RwLock<WhisperManager>>,
+) -> Result<ModelStorageStatus, String> {
+    let default_dir = default_models_dir(&app)?;
+    let (models_dir, changed) = {
+        let mut manager = whisper_state.write().await;
+        let changed = manager.recheck_storage();
+        (manager.models_dir().clone(), changed)
+    };
+
+    let status = ModelStorageStatus::new(&models_dir, &default_dir);
+    if changed {
+        let _ = app.emit("models-storage-changed", &status);
+        if let Err(e) = crate::commands::settings::update_tray_menu(app.clone()).await {
+            log::warn!("Failed to update tray menu after storage change: {}", e);
+        }
+    }
+    Ok(status)
+}
+
+/// Move all models to `target_dir` (or back to the default folder when `None`).
+/// Files are copied and verified before the setting changes and the old copies are
+/// deleted. If the current folder is unavailable the location is switched without
+/// copying, so a lost drive can be replaced by a fresh folder.
+#[tauri::command]
+pub async fn move_models_directory(
+    app: AppHandle,
+    target_dir: Option<String>,
+    whisper_state: State<'_, RwLock<WhisperManager>>,
+    parakeet_manager: State<'_, ParakeetManager>,
+    active_downloads: State<'_, Arc<StdMutex<HashMap<String, Arc<AtomicBool>>>>>,
+) -> Result<ModelStorageStatus, String> {
+    let default_dir = default_models_dir(&app)?;
+    let target = target_dir
+        .as_deref()
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(|| default_dir.clone());
+    let source = whisper_state.read().await.models_dir().clone();
+    if target == source {
+        return Ok(ModelStorageStatus::new(&source, &default_dir));
+    }
+
+    if !active_downloads
+        .lock()
+        .map(|downloads| downloads.is_empty())
+        .unwrap_or(false)
+    {
+        return Err(
+            "Wait for model downloads to finish before moving the models folder".to_string(),
+        );
+    }
+    if MODELS_MOVE_IN_PROGRESS.swap(true, Ordering::SeqCst) {
+        return Err("The models folder is already being moved".to_string());
+    }
+
+    let result = relocate_models(&app, &source, &target).await;
+    MODELS_MOVE_IN_PROGRESS.store(false, Ordering::SeqCst);
+    let moved_items = result?;
+
+    let settings = ModelStorageSettings {
+        models_dir: (target != default_dir).then(|| target.to_string_lossy().to_string()),
+    };
+    save_setting(&app, "model_storage", &settings)?;
+
+    whisper_state.write().await.set_models_dir(target.clone());
+    parakeet_manager.set_root_dir(target.join("parakeet"));
+
+    // Loaded models and the pinned path still point at the old folder
+    let memory_settings = load_model_memory_settings(&app);
+    if let Err(e) = apply_model_memory_settings(&app, &memory_settings).await {
+        log::warn!("Failed to re-apply model memory settings after move: {}", e);
+    }
+
+    if let Some(items) = moved_items {
+        tokio::task::spawn_blocking(move || storage::remove_moved_files(&source, &items))
+            .await
+            .map_err(|e| format!("Cleanup task failed: {}", e))?;
+    }
+
+    let status = ModelStorageStatus::new(&target, &default_dir);
+    let _ = app.emit("models-storage-changed", &status);
+    if let Err(e) = crate::commands::settings::update_tray_menu(app.clone()).await {
+        log::warn!("Failed to update tray menu after moving models: {}", e);
+    }
+    Ok(status)
+}
+
+/// Copy and verify the models into `target`. Returns the files to delete from the
+/// old folder, or `None` when it was unavailable and nothing was copied.
+async fn relocate_models(
+    app: &AppHandle,
+    source: &std::path::Path,
+    target: &std::path::Path,
+) -> Result<Option<Vec<storage::MoveItem>>, String> {
+    if !source.is_dir() {
+        log::warn!(
+            "Models folder {:?} is unavailable; switching to {:?} without copying",
+            source,
+            target
+        );
+        if !target.is_absolute() {
+            return Err("Choose an absolute path for the models folder".to_string());
+        }
+        std::fs::create_dir_all(target)
+            .map_err(|e| format!("Failed to create models folder: {}", e))?;
+        return Ok(None);
+    }
+
+    storage::validate_target(source, target)?;
+    let items = storage::plan_move(source)?;
+    let total: u64 = items.iter().map(|i| i.size).sum();
+    if let Some(free) = storage::free_space(target) {
+        if free < total {
+            return Err(format!(
+                "Not enough space at the new location: {} MB needed, {} MB free",
+                total / (1024 * 1024),
+                free / (1024 * 1024)
+            ));
+        }
+    }
+
+    log::info!(
+        "Moving {} files ({} MB) from {:?} to {:?}",
+        items.len(),
+        total / (1024 * 1024),
+        source,
+        target
+    );
+    let app_handle = app.clone();
+    let (source, target) = (source.to_path_buf(), target.to_path_buf());
+    tokio::task::spawn_blocking(move || {
+        let last_percent = std::sync::atomic::AtomicU64::new(u64::MAX);
+        let progress = |phase: MovePhase, done: u64, total: u64| {
+            let percent = if total > 0 { done * 100 / total } else { 100 };
+            // Copying is 0-50%, verifying 50-100%
+            let overall = match phase {
+                MovePhase::Copying => percent / 2,
+                MovePhase::Verifying => 50 + percent / 2,
+            };
+            if last_percent.swap(overall, Ordering::Relaxed) != overall {
+                let _ = app_handle.emit(
+                    "models-move-progress",
+                    serde_json::json!({
+                        "phase": phase,
+                        "processed": done,
+                        "total": total,
+                        "progress": overall
+                    }),
+                );
+            }
+        };
+        storage::copy_and_verify(&source, &target, &items, &progress).map(|_| Some(items))
+    })
+    .await
+    .map_err(|e| format!("Move task failed: {}", e))?
+}
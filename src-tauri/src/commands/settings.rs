@@ -3,6 +3,7 @@ use crate::parakeet::ParakeetManager;
 use crate::whisper::languages::{validate_language, SUPPORTED_LANGUAGES};
 use crate::whisper::manager::WhisperManager;
 use crate::AppState;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use tauri::{AppHandle, Emitter, Manager};
@@ -135,6 +136,32 @@ pub async fn get_settings(app: AppHandle) -> Result<Settings, String> {
     Ok(settings)
 }
 
+/// Read a structured setting from the settings store, falling back to its default
+/// when it is missing or no longer parses
+pub(crate) fn load_setting<T: DeserializeOwned + Default>(app: &AppHandle, key: &str) -> T {
+    app.store("settings")
+        .ok()
+        .and_then(|store| store.get(key))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+/// Write a structured setting to the settings store and persist it
+pub(crate) fn save_setting<T: Serialize>(
+    app: &AppHandle,
+    key: &str,
+    value: &T,
+) -> Result<(), String> {
+    let store = app.store("settings").map_err(|e| e.to_string())?;
+    store.set(
+        key,
+        serde_json::to_value(value).map_err(|e| format!("Failed to serialize {}: {}", key, e))?,
+    );
+    store
+        .save()
+        .map_err(|e| format!("Failed to save {}: {}", key, e))
+}
+
 #[tauri::command]
 pub async fn save_settings(app: AppHandle, settings: Settings) -> Result<(), String> {
     let store = app.store("settings").map_err(|e| e.to_string())?;
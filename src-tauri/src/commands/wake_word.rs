@@ -0,0 +1,338 @@
+// Hands-free dictation: keeps the wake phrase listener running while the app is
+// idle. Hearing the phrase starts a recording that ends at the first pause.
+
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::async_runtime::RwLock as AsyncRwLock;
+use tauri::{AppHandle, Manager};
+
+use crate::audio::wake_word::{WakeWordListener, WakeWordSettings};
+use crate::commands::audio::{begin_recording, load_auto_stop_settings, RecorderState};
+use crate::commands::devices::load_capture_format;
+use crate::commands::model::verify_model_before_load;
+use crate::commands::settings::{get_settings, load_setting, save_setting};
+use crate::whisper::manager::WhisperManager;
+use crate::whisper::transcriber::{DecodingOptions, Transcriber};
+use crate::{emit_to_all, get_recording_state, RecordingState};
+
+/// How often a paused listener checks whether the app is idle again
+const RESUME_POLL: Duration = Duration::from_millis(500);
+
+#[derive(Default)]
+pub struct WakeWordState {
+    listener: Mutex<Option<WakeWordListener>>,
+    /// Tray mute; not persisted so a restart always honours the saved setting
+    muted: AtomicBool,
+    /// A recording owns the microphone; the listener resumes once it is over
+    paused: AtomicBool,
+    /// Detection model, kept across pauses so each resume does not reload it
+    detector: Mutex<Option<(PathBuf, Arc<Transcriber>)>>,
+    last_error: Mutex<Option<String>>,
+}
+
+impl WakeWordState {
+    pub fn is_muted(&self) -> bool {
+        self.muted.load(Ordering::SeqCst)
+    }
+
+    fn is_listening(&self) -> bool {
+        self.listener
+            .lock()
+            .map(|guard| guard.as_ref().is_some_and(|l| !l.is_finished()))
+            .unwrap_or(false)
+    }
+
+    fn stop_listener(&self) {
+        if let Some(listener) = self.listener.lock().ok().and_then(|mut g| g.take()) {
+            listener.stop();
+        }
+    }
+
+    fn set_error(&self, error: Option<String>) {
+        if let Ok(mut guard) = self.last_error.lock() {
+            *guard = error;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WakeWordStatus {
+    pub enabled: bool,
+    pub listening: bool,
+    pub muted: bool,
+    pub phrase: String,
+    /// Why the listener is not running although it should be
+    pub error: Option<String>,
+}
+
+pub(crate) fn load_wake_word_settings(app: &AppHandle) -> WakeWordSettings {
+    load_setting(app, "wake_word")
+}
+
+fn wake_word_status(app: &AppHandle) -> WakeWordStatus {
+    let settings = load_wake_word_settings(app);
+    let wake = app.state::<WakeWordState>();
+    WakeWordStatus {
+        enabled: settings.enabled,
+        listening: wake.is_listening(),
+        muted: wake.is_muted(),
+        phrase: settings.phrase,
+        error: wake.last_error.lock().ok().and_then(|guard| guard.clone()),
+    }
+}
+
+/// Tell the windows and the tray whether the app is listening
+fn publish_status(app: &AppHandle) {
+    let status = wake_word_status(app);
+    let tooltip = if status.listening {
+        format!("Verity • Listening for \"{}\"", status.phrase)
+    } else if status.enabled && status.muted {
+        "Verity • Wake phrase muted".to_string()
+    } else {
+        "Verity".to_string()
+    };
+    if let Some(tray) = app.tray_by_id("main") {
+        let _ = tray.set_tooltip(Some(tooltip));
+    }
+    let _ = emit_to_all(app, "wake-word-status", status);
+}
+
+fn is_idle(app: &AppHandle) -> bool {
+    matches!(
+        get_recording_state(app),
+        RecordingState::Idle | RecordingState::Error
+    )
+}
+
+/// Start or stop the listener to match the settings, the tray mute and whether
+/// a recording is in progress
+pub async fn refresh_wake_word(app: &AppHandle) {
+    let settings = load_wake_word_settings(app);
+    let wake = app.state::<WakeWordState>();
+    let should_listen =
+        settings.enabled && !wake.is_muted() && !wake.paused.load(Ordering::SeqCst) && is_idle(app);
+
+    if !should_listen {
+        wake.stop_listener();
+    } else if !wake.is_listening() {
+        match start_listener(app, &settings).await {
+            Ok(listener) => {
+                if let Ok(mut guard) = wake.listener.lock() {
+                    *guard = Some(listener);
+                }
+                wake.set_error(None);
+            }
+            Err(e) => {
+                log::warn!("Wake phrase listener not started: {}", e);
+                wake.set_error(Some(e));
+            }
+        }
+    }
+    publish_status(app);
+}
+
+async fn start_listener(
+    app: &AppHandle,
+    settings: &WakeWordSettings,
+) -> Result<WakeWordListener, String> {
+    let model_path = {
+        let whisper_state = app.state::<AsyncRwLock<WhisperManager>>();
+        let manager = whisper_state.read().await;
+        manager.get_model_path(&settings.model).ok_or_else(|| {
+            format!(
+                "Download the {} model to use the wake phrase",
+                settings.model
+            )
+        })?
+    };
+    let transcriber = detection_model(app, &settings.model, model_path).await?;
+
+    let device_name = get_settings(app.clone())
+        .await
+        .ok()
+        .and_then(|s| s.selected_microphone);
+
+    // One greedy pass on one thread. No initial prompt: priming Whisper with the
+    // phrase makes it hallucinate the phrase on background noise.
+    let decoding = DecodingOptions {
+        beam_size: 0,
+        best_of: 1,
+        threads: Some(1),
+        ..DecodingOptions::default()
+    };
+    let detect = move |audio: &[f32]| {
+        transcriber.transcribe_samples_with_options(audio, None, false, &decoding)
+    };
+
+    // Runs on the listener thread, which has already released the microphone
+    let app_for_wake = app.clone();
+    let on_wake = move |heard: String| {
+        tauri::async_runtime::block_on(on_wake_phrase(app_for_wake, heard));
+    };
+
+    let format = load_capture_format(app, device_name.as_deref());
+    WakeWordListener::start(device_name, format, settings, detect, on_wake)
+}
+
+async fn detection_model(
+    app: &AppHandle,
+    model_name: &str,
+    model_path: PathBuf,
+) -> Result<Arc<Transcriber>, String> {
+    let wake = app.state::<WakeWordState>();
+    if let Some((path, transcriber)) = wake.detector.lock().ok().and_then(|g| g.clone()) {
+        if path == model_path {
+            return Ok(transcriber);
+        }
+    }
+
+    verify_model_before_load(app, model_name, &model_path).await?;
+    let path = model_path.clone();
+    let transcriber = tauri::async_runtime::spawn_blocking(move || Transcriber::new(&path))
+        .await
+        .map_err(|e| format!("Failed to load wake phrase model: {}", e))??;
+    let transcriber = Arc::new(transcriber);
+    if let Ok(mut guard) = wake.detector.lock() {
+        *guard = Some((model_path, transcriber.clone()));
+    }
+    Ok(transcriber)
+}
+
+async fn on_wake_phrase(app: AppHandle, heard: String) {
+    let wake = app.state::<WakeWordState>();
+    // The listener that heard the phrase is done; a fresh one starts when needed
+    wake.stop_listener();
+    if wake.is_muted() || !is_idle(&app) {
+        refresh_wake_word(&app).await;
+        return;
+    }
+
+    let _ = emit_to_all(&app, "wake-phrase-detected", heard.trim());
+    // Hands-free recordings end at the first pause, with or without auto-stop
+    let silence = load_auto_stop_settings(&app)
+        .pause_detection(load_wake_word_settings(&app).silence_timeout());
+    let recorder = app.state::<RecorderState>();
+    if let Err(e) = begin_recording(app.clone(), recorder, silence).await {
+        log::error!("Wake phrase heard but recording failed to start: {}", e);
+        refresh_wake_word(&app).await;
+    }
+}
+
+/// Release the microphone for a recording and resume listening once the app is
+/// idle again (after transcription, cancel or error)
+pub(crate) fn pause_for_recording(app: &AppHandle) {
+    let Some(wake) = app.try_state::<WakeWordState>() else {
+        return;
+    };
+    if wake.paused.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    wake.stop_listener();
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(RESUME_POLL).await;
+            if is_idle(&app) {
+                break;
+            }
+        }
+        app.state::<WakeWordState>()
+            .paused
+            .store(false, Ordering::SeqCst);
+        refresh_wake_word(&app).await;
+    });
+}
+
+/// Keeps the listener off the microphone while held, e.g. during a microphone test
+pub(crate) struct ListenerHold {
+    app: AppHandle,
+}
+
+impl ListenerHold {
+    pub(crate) fn acquire(app: &AppHandle) -> Self {
+        if let Some(wake) = app.try_state::<WakeWordState>() {
+            wake.paused.store(true, Ordering::SeqCst);
+            wake.stop_listener();
+        }
+        Self { app: app.clone() }
+    }
+}
+
+impl Drop for ListenerHold {
+    fn drop(&mut self) {
+        let app = self.app.clone();
+        tauri::async_runtime::spawn(async move {
+            app.state::<WakeWordState>()
+                .paused
+                .store(false, Ordering::SeqCst);
+            refresh_wake_word(&app).await;
+        });
+    }
+}
+
+/// Flip the tray mute; muting releases the microphone immediately
+pub async fn toggle_wake_word_mute(app: &AppHandle) {
+    let wake = app.state::<WakeWordState>();
+    let muted = !wake.is_muted();
+    wake.muted.store(muted, Ordering::SeqCst);
+    log::info!("Wake phrase {}", if muted { "muted" } else { "unmuted" });
+    refresh_wake_word(app).await;
+}
+
+#[tauri::command]
+pub async fn get_wake_word_settings(app: AppHandle) -> Result<WakeWordSettings, String> {
+    Ok(load_wake_word_settings(&app))
+}
+
+#[tauri::command]
+pub async fn update_wake_word_settings(
+    app: AppHandle,
+    settings: WakeWordSettings,
+) -> Result<WakeWordStatus, String> {
+    settings.validate()?;
+    if settings.enabled {
+        let whisper_state = app.state::<AsyncRwLock<WhisperManager>>();
+        if whisper_state
+            .read()
+            .await
+            .get_model_path(&settings.model)
+            .is_none()
+        {
+            return Err(format!(
+                "Download the {} model before enabling the wake phrase",
+                settings.model
+            ));
+        }
+    }
+
+    save_setting(&app, "wake_word", &settings)?;
+
+    // A new phrase or model needs a fresh listener
+    app.state::<WakeWordState>().stop_listener();
+    refresh_wake_word(&app).await;
+    if let Err(e) = crate::commands::settings::update_tray_menu(app.clone()).await {
+        log::warn!("Failed to refresh tray after wake phrase change: {}", e);
+    }
+    Ok(wake_word_status(&app))
+}
+
+#[tauri::command]
+pub async fn get_wake_word_status(app: AppHandle) -> Result<WakeWordStatus, String> {
+    Ok(wake_word_status(&app))
+}
+
+#[tauri::command]
+pub async fn set_wake_word_muted(app: AppHandle, muted: bool) -> Result<WakeWordStatus, String> {
+    let wake = app.state::<WakeWordState>();
+    if wake.is_muted() != muted {
+        toggle_wake_word_mute(&app).await;
+        if let Err(e) = crate::commands::settings::update_tray_menu(app.clone()).await {
+            log::warn!("Failed to refresh tray after wake phrase mute: {}", e);
+        }
+    }
+    Ok(wake_word_status(&app))
+}
@@ -0,0 +1,258 @@
+// Ordered engine/model chain tried when the selected engine cannot transcribe,
+// stored under `transcription_fallback` in the settings store.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::future::Future;
+
+const MAX_ENTRIES: usize = 5;
+const ENGINES: &[&str] = &["whisper", "parakeet"];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FallbackEntry {
+    /// "whisper" or "parakeet"
+    pub engine: String,
+    pub model: String,
+}
+
+impl FallbackEntry {
+    pub fn new(engine: impl Into<String>, model: impl Into<String>) -> Self {
+        Self {
+            engine: engine.into(),
+            model: model.into(),
+        }
+    }
+}
+
+/// Why an engine produced no text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TranscriptionError {
+    /// The user cancelled; nothing else should run
+    Cancelled,
+    /// Too little audio for any engine to transcribe
+    TooShort,
+    /// This engine failed; another one may still succeed
+    Failed(String),
+}
+
+impl TranscriptionError {
+    /// Whether trying the next engine in the chain can help
+    pub fn is_recoverable(&self) -> bool {
+        matches!(self, TranscriptionError::Failed(_))
+    }
+}
+
+impl fmt::Display for TranscriptionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TranscriptionError::Cancelled => f.write_str("Transcription cancelled"),
+            TranscriptionError::TooShort => f.write_str("Recording too short"),
+            TranscriptionError::Failed(message) => f.write_str(message),
+        }
+    }
+}
+
+/// e.g. parakeet-tdt-0.6b-v3 → large-v3-turbo → base.en; empty disables fallback
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FallbackChain {
+    pub entries: Vec<FallbackEntry>,
+}
+
+impl FallbackChain {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.entries.len() > MAX_ENTRIES {
+            return Err(format!(
+                "The fallback chain can hold at most {} models",
+                MAX_ENTRIES
+            ));
+        }
+        for (index, entry) in self.entries.iter().enumerate() {
+            if !ENGINES.contains(&entry.engine.as_str()) {
+                return Err(format!("Unsupported fallback engine '{}'", entry.engine));
+            }
+            if entry.model.trim().is_empty() {
+                return Err("Fallback entries need a model".to_string());
+            }
+            if self.entries[..index].contains(entry) {
+                return Err(format!("'{}' is in the fallback chain twice", entry.model));
+            }
+        }
+        Ok(())
+    }
+
+    /// Entries to try, in order, once `failed` could not produce text: the ones after
+    /// it, or the whole chain when it isn't in it. Entries ahead of `failed` were
+    /// either tried already or passed over by the user's ordering.
+    pub fn candidates(&self, failed: &FallbackEntry) -> Vec<FallbackEntry> {
+        // Model names are unique across engines, and an unresolved selection may not
+        // know its engine
+        let start = self
+            .entries
+            .iter()
+            .position(|entry| entry.model == failed.model)
+            .map_or(0, |index| index + 1);
+        self.entries[start..].to_vec()
+    }
+}
+
+/// Try `candidates` in order until one succeeds, returning its result and the
+/// entry that produced it. Stops at the first error no other engine can fix.
+pub async fn walk_chain<T, F, Fut>(
+    candidates: Vec<FallbackEntry>,
+    mut attempt: F,
+) -> Option<(T, FallbackEntry)>
+where
+    F: FnMut(FallbackEntry) -> Fut,
+    Fut: Future<Output = Result<T, TranscriptionError>>,
+{
+    for entry in candidates {
+        match attempt(entry.clone()).await {
+            Ok(value) => return Some((value, entry)),
+            Err(e) if e.is_recoverable() => {
+                log::warn!("Fallback {} {} failed: {}", entry.engine, entry.model, e)
+            }
+            Err(e) => {
+                log::info!("Stopping the fallback chain: {}", e);
+                return None;
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain() -> FallbackChain {
+        FallbackChain {
+            entries: vec![
+                FallbackEntry::new("parakeet", "parakeet-tdt-0.6b-v3"),
+                FallbackEntry::new("whisper", "large-v3-turbo"),
+                FallbackEntry::new("whisper", "base.en"),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_candidates_follow_the_failed_entry() {
+        let chain = chain();
+        assert!(chain.validate().is_ok());
+
+        let after_parakeet =
+            chain.candidates(&FallbackEntry::new("parakeet", "parakeet-tdt-0.6b-v3"));
+        assert_eq!(
+            after_parakeet,
+            vec![
+                FallbackEntry::new("whisper", "large-v3-turbo"),
+                FallbackEntry::new("whisper", "base.en"),
+            ]
+        );
+
+        // Entries ahead of the failed one are not retried
+        assert_eq!(
+            chain.candidates(&FallbackEntry::new("whisper", "large-v3-turbo")),
+            vec![FallbackEntry::new("whisper", "base.en")]
+        );
+        assert!(chain
+            .candidates(&FallbackEntry::new("whisper", "base.en"))
+            .is_empty());
+
+        // A selection outside the chain falls back to all of it
+        assert_eq!(
+            chain
+                .candidates(&FallbackEntry::new("whisper", "small"))
+                .len(),
+            3
+        );
+        assert!(FallbackChain::default()
+            .candidates(&FallbackEntry::new("whisper", "small"))
+            .is_empty());
+    }
+
+    /// Stand-in engine: fails, cancels or transcribes depending on the model
+    async fn stub_engine(entry: FallbackEntry) -> Result<String, TranscriptionError> {
+        match entry.model.as_str() {
+            "broken" => Err(TranscriptionError::Failed("model crashed".to_string())),
+            "cancelled" => Err(TranscriptionError::Cancelled),
+            model => Ok(format!("text from {}", model)),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_walk_chain_stops_at_first_success() {
+        let chain = FallbackChain {
+            entries: vec![
+                FallbackEntry::new("parakeet", "parakeet-tdt-0.6b-v3"),
+                FallbackEntry::new("whisper", "broken"),
+                FallbackEntry::new("whisper", "base.en"),
+                FallbackEntry::new("whisper", "tiny"),
+            ],
+        };
+        let mut tried = Vec::new();
+        let result = walk_chain(
+            chain.candidates(&FallbackEntry::new("parakeet", "parakeet-tdt-0.6b-v3")),
+            |entry| {
+                tried.push(entry.model.clone());
+                stub_engine(entry)
+            },
+        )
+        .await;
+
+        let (text, entry) = result.unwrap();
+        assert_eq!(text, "text from base.en");
+        assert_eq!(entry, FallbackEntry::new("whisper", "base.en"));
+        assert_eq!(tried, vec!["broken", "base.en"]);
+    }
+
+    #[tokio::test]
+    async fn test_walk_chain_stops_on_cancellation() {
+        let chain = FallbackChain {
+            entries: vec![
+                FallbackEntry::new("whisper", "broken"),
+                FallbackEntry::new("whisper", "cancelled"),
+                FallbackEntry::new("whisper", "base.en"),
+            ],
+        };
+        let mut tried = Vec::new();
+        let result = walk_chain(
+            chain.candidates(&FallbackEntry::new("whisper", "small")),
+            |entry| {
+                tried.push(entry.model.clone());
+                stub_engine(entry)
+            },
+        )
+        .await;
+
+        assert!(result.is_none());
+        assert_eq!(tried, vec!["broken", "cancelled"]);
+        assert!(!TranscriptionError::TooShort.is_recoverable());
+    }
+
+    #[test]
+    fn test_validation() {
+        let mut duplicate = chain();
+        duplicate
+            .entries
+            .push(FallbackEntry::new("whisper", "base.en"));
+        assert!(duplicate.validate().unwrap_err().contains("twice"));
+
+        let cloud = FallbackChain {
+            entries: vec![FallbackEntry::new("soniox", "soniox")],
+        };
+        assert!(cloud.validate().is_err());
+
+        let empty_model = FallbackChain {
+            entries: vec![FallbackEntry::new("whisper", " ")],
+        };
+        assert!(empty_model.validate().is_err());
+
+        let too_long = FallbackChain {
+            entries: (0..6)
+                .map(|i| FallbackEntry::new("whisper", format!("model-{i}")))
+                .collect(),
+        };
+        assert!(too_long.validate().is_err());
+    }
+}
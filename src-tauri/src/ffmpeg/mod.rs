@@ -199,6 +199,34 @@ pub async fn normalize_streaming(
     to_wav_streaming(app, input, output).await
 }
 
+pub async fn encode_audio(
+    app: &AppHandle,
+    input: &Path,
+    output: &Path,
+    codec: &str,
+    bitrate: Option<&str>,
+) -> Result<(), String> {
+    // ffmpeg -y -loglevel error -i input -ac 1 -c:a <codec> [-b:a <bitrate>] output
+    let mut args: Vec<String> = vec![
+        "-y".into(),
+        "-loglevel".into(),
+        "error".into(),
+        "-hide_banner".into(),
+        "-i".into(),
+        input.to_string_lossy().to_string(),
+        "-ac".into(),
+        "1".into(),
+        "-c:a".into(),
+        codec.into(),
+    ];
+    if let Some(bitrate) = bitrate {
+        args.push("-b:a".into());
+        args.push(bitrate.into());
+    }
+    args.push(output.to_string_lossy().to_string());
+    run_ffmpeg_command(app, FFMPEG_CANDIDATES, &args, "ffmpeg").await
+}
+
 pub async fn segment(
     app: &AppHandle,
     input: &Path,
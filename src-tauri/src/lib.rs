@@ -19,13 +19,17 @@ use crate::utils::logger::*;
 
 mod ai;
 mod audio;
+pub mod benchmark;
 mod commands;
+mod fallback;
 mod ffmpeg;
 mod parakeet;
 mod secure_store;
+mod sidecar;
 mod simple_cache;
 mod state;
 mod state_machine;
+mod transcript;
 mod utils;
 mod whisper;
 mod window_manager;
@@ -42,12 +46,27 @@ use commands::{
         update_enhancement_options, validate_and_cache_api_key,
     },
     audio::*,
+    calibration::{
+        clear_mic_calibration, get_mic_calibrations, suggest_microphone, test_microphone,
+    },
     clipboard::{copy_image_to_clipboard, save_image_to_file},
+    compare::compare_models,
     debug::{debug_transcription_flow, test_transcription_event},
+    devices::{
+        get_capture_format, get_device_capabilities, get_microphone_failover,
+        update_capture_format, update_microphone_failover,
+    },
     keyring::{keyring_delete, keyring_get, keyring_has, keyring_set},
     logs::{clear_old_logs, open_logs_folder},
     model::{
-        cancel_download, delete_model, download_model, get_model_status, list_downloaded_models,
+        cancel_download, delete_model, download_model, export_model_bundle, get_model_cache_status,
+        get_model_manifest, get_model_manifest_settings, get_model_memory_settings,
+        get_model_status, get_models_storage, get_parakeet_diagnostics, get_parakeet_options,
+        get_sidecar_engines, import_custom_model, import_model_bundle, inspect_model_file,
+        list_downloaded_models, move_models_directory, quantize_model, recommend_model,
+        refresh_model_manifest, register_sidecar_engine, reset_parakeet_options,
+        scan_model_integrity, unregister_sidecar_engine, update_model_manifest_settings,
+        update_model_memory_settings, update_parakeet_options,
     },
     permissions::{
         check_accessibility_permission, check_microphone_permission,
@@ -59,6 +78,10 @@ use commands::{
     stt::{clear_soniox_key_cache, validate_and_cache_soniox_key},
     text::*,
     utils::export_transcriptions,
+    wake_word::{
+        get_wake_word_settings, get_wake_word_status, set_wake_word_muted,
+        update_wake_word_settings,
+    },
     window::*,
 };
 use state::unified_state::UnifiedRecordingState;
@@ -521,6 +544,30 @@ async fn build_tray_menu<R: tauri::Runtime>(
     let thinking_submenu = Submenu::with_id_and_items(app, "thinking_mode", thinking_display, true, &thinking_items)?;
     menu_builder = menu_builder.item(&thinking_submenu);
 
+    // Instant mute for the wake phrase listener, shown only when it is enabled
+    let wake_word_enabled = match app.store("settings") {
+        Ok(store) => store
+            .get("wake_word")
+            .and_then(|v| v.get("enabled").and_then(|e| e.as_bool()))
+            .unwrap_or(false),
+        Err(_) => false,
+    };
+    let wake_word_muted = app
+        .try_state::<commands::wake_word::WakeWordState>()
+        .map(|wake| wake.is_muted())
+        .unwrap_or(false);
+    let wake_mute_item = CheckMenuItem::with_id(
+        app,
+        "wake_word_mute",
+        "Mute Wake Phrase",
+        true,
+        wake_word_muted,
+        None::<&str>,
+    )?;
+    if wake_word_enabled {
+        menu_builder = menu_builder.item(&wake_mute_item);
+    }
+
     let menu = menu_builder
         .item(&separator1)
         .item(&settings_i)
@@ -1238,8 +1285,10 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
                 perform_startup_checks(app_handle).await;
             });
 
-            // Initialize whisper manager
-            let models_dir = app.path().app_data_dir()?.join("models");
+            // Initialize whisper manager; the models folder may have been moved to another drive
+            let default_models_dir = app.path().app_data_dir()?.join("models");
+            let models_dir = commands::model::load_model_storage_settings(app.handle())
+                .resolve(&default_models_dir);
             log::info!("🗂️  Models directory: {:?}", models_dir);
 
             log_start("WHISPER_MANAGER_INIT");
@@ -1247,35 +1296,84 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
                 ("models_dir", &format!("{:?}", models_dir).as_str())
             ]);
 
-            // Ensure the models directory exists
-            match std::fs::create_dir_all(&models_dir) {
-                Ok(_) => {
-                    log_file_operation("CREATE_DIR", &format!("{:?}", models_dir), true, None, None);
+            // Ensure the default models directory exists. A custom one is never created here:
+            // if its drive is disconnected the UI reports it as unavailable instead.
+            if models_dir != default_models_dir {
+                if !models_dir.is_dir() {
+                    log::warn!("🗂️  Models directory {:?} is unavailable (drive disconnected?)", models_dir);
                 }
-                Err(e) => {
-                    let error_msg = format!("Failed to create models directory: {}", e);
-                    log_file_operation("CREATE_DIR", &format!("{:?}", models_dir), false, None, Some(&e.to_string()));
-                    return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, error_msg)));
+            } else {
+                match std::fs::create_dir_all(&models_dir) {
+                    Ok(_) => {
+                        log_file_operation("CREATE_DIR", &format!("{:?}", models_dir), true, None, None);
+                    }
+                    Err(e) => {
+                        let error_msg = format!("Failed to create models directory: {}", e);
+                        log_file_operation("CREATE_DIR", &format!("{:?}", models_dir), false, None, Some(&e.to_string()));
+                        return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, error_msg)));
+                    }
                 }
             }
 
-            let whisper_manager = whisper::manager::WhisperManager::new(models_dir.clone());
+            // Model catalog: verified override manifest if configured, else the bundled one
+            let manifest_settings = commands::model::load_manifest_settings(app.handle());
+            let (manifest, manifest_source) = whisper::manifest::load_active_manifest(
+                &models_dir,
+                manifest_settings.public_key.as_deref(),
+            );
+            let whisper_manager = whisper::manager::WhisperManager::with_manifest(
+                models_dir.clone(),
+                manifest,
+                manifest_source,
+            );
             app.manage(AsyncRwLock::new(whisper_manager));
 
+            // Background integrity scan, delayed so it doesn't compete with startup
+            let app_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+                let state = app_handle.state::<AsyncRwLock<whisper::manager::WhisperManager>>();
+                let reports =
+                    commands::model::run_integrity_scan(&app_handle, &state, false).await;
+                log::info!("Model integrity scan checked {} models", reports.len());
+            });
+
+            // Pick up catalog changes from a configured mirror in the background
+            if manifest_settings
+                .source
+                .as_deref()
+                .is_some_and(|source| source.starts_with("https://"))
+            {
+                let app_handle = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    let state = app_handle.state::<AsyncRwLock<whisper::manager::WhisperManager>>();
+                    if let Err(e) =
+                        commands::model::refresh_model_manifest(app_handle.clone(), state).await
+                    {
+                        log::warn!("Failed to refresh model manifest from mirror: {}", e);
+                    }
+                });
+            }
+
             log::info!("✅ Whisper manager initialized and managed");
 
             // Initialize Parakeet manager and cache directory
             let parakeet_dir = models_dir.join("parakeet");
-            if let Err(e) = std::fs::create_dir_all(&parakeet_dir) {
-                let error_msg = format!("Failed to create parakeet models directory: {}", e);
-                log_file_operation("CREATE_DIR", &format!("{:?}", parakeet_dir), false, None, Some(&e.to_string()));
-                return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, error_msg)));
-            }
+            if models_dir.is_dir() {
+                if let Err(e) = std::fs::create_dir_all(&parakeet_dir) {
+                    let error_msg = format!("Failed to create parakeet models directory: {}", e);
+                    log_file_operation("CREATE_DIR", &format!("{:?}", parakeet_dir), false, None, Some(&e.to_string()));
+                    return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, error_msg)));
+                }
 
-            log_file_operation("CREATE_DIR", &format!("{:?}", parakeet_dir), true, None, None);
+                log_file_operation("CREATE_DIR", &format!("{:?}", parakeet_dir), true, None, None);
+            }
             let parakeet_manager = parakeet::ParakeetManager::new(parakeet_dir);
+            parakeet_manager
+                .set_runtime_options(commands::model::load_parakeet_options(app.handle()));
             app.manage(parakeet_manager);
             log::info!("🦜 Parakeet manager initialized");
+            parakeet::manager::spawn_health_monitor(app.app_handle().clone());
 
             // Manage active downloads for cancellation
             app.manage(Arc::new(Mutex::new(HashMap::<String, Arc<AtomicBool>>::new())));
@@ -1285,6 +1383,9 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
             // When user switches models, old one is unloaded immediately
             app.manage(AsyncMutex::new(TranscriberCache::new()));
 
+            // Apply memory limits and start idle/pressure unloading
+            commands::model::spawn_cache_maintenance(app.app_handle().clone());
+
             // Initialize unified application state
             app.manage(AppState::new());
 
@@ -1314,6 +1415,7 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
 
             // Initialize recorder state (kept separate for backwards compatibility)
             app.manage(RecorderState(Mutex::new(AudioRecorder::new())));
+            app.manage(commands::wake_word::WakeWordState::default());
 
             // Create tray icon
             use tauri::tray::{TrayIconBuilder, TrayIconEvent};
@@ -1371,6 +1473,14 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
                         app.exit(0);
                     } else if event_id == "restart" {
                         app.request_restart();
+                    } else if event_id == "wake_word_mute" {
+                        let app_handle = app.app_handle().clone();
+                        tauri::async_runtime::spawn(async move {
+                            crate::commands::wake_word::toggle_wake_word_mute(&app_handle).await;
+                            if let Err(e) = crate::commands::settings::update_tray_menu(app_handle.clone()).await {
+                                log::warn!("Failed to refresh tray after wake phrase mute: {}", e);
+                            }
+                        });
                     } else if event_id.starts_with("model_") {
                         // Handle model selection
                         let model_name = match event_id.strip_prefix("model_") {
@@ -1539,6 +1649,16 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
                 })
                 .build(app)?;
 
+            // Start listening for the wake phrase if it is enabled
+            let app_for_wake = app.app_handle().clone();
+            tauri::async_runtime::spawn(async move {
+                commands::wake_word::refresh_wake_word(&app_for_wake).await;
+            });
+
+            // Keep the tray's microphone menu in step with devices coming and going
+            let app_for_devices = app.app_handle().clone();
+            tauri::async_runtime::spawn(commands::devices::watch_audio_devices(app_for_devices));
+
             // Load hotkey from settings store with graceful degradation
             log_start("HOTKEY_SETUP");
             log_with_context(log::Level::Debug, "Setting up hotkey", &[
@@ -1746,11 +1866,8 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
                         };
 
                         if let Some(model_path) = model_path {
-                            // Load model into cache
-                            let cache_state = app_handle.state::<AsyncMutex<TranscriberCache>>();
-                            let mut cache = cache_state.lock().await;
-
-                            match cache.get_or_create(&model_path) {
+                            // Verify and load model into cache
+                            match commands::model::load_transcriber(&app_handle, &current_model, &model_path).await {
                                 Ok(_) => {
                                     log::info!("Successfully preloaded model '{}' into cache", current_model);
                                 }
@@ -1876,6 +1993,31 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
             save_transcription,
             get_audio_devices,
             get_current_audio_device,
+            get_audio_processing_options,
+            update_audio_processing_options,
+            get_audio_retention_policy,
+            update_audio_retention_policy,
+            get_transcription_fallback,
+            update_transcription_fallback,
+            get_auto_stop_settings,
+            update_auto_stop_settings,
+            get_wake_word_settings,
+            update_wake_word_settings,
+            get_wake_word_status,
+            set_wake_word_muted,
+            test_microphone,
+            get_mic_calibrations,
+            clear_mic_calibration,
+            suggest_microphone,
+            get_microphone_failover,
+            update_microphone_failover,
+            get_device_capabilities,
+            get_capture_format,
+            update_capture_format,
+            get_transcription_audio_path,
+            get_transcription_subtitles,
+            retranscribe_entry,
+            compare_models,
             download_model,
             get_model_status,
             transcribe_audio,
@@ -1890,6 +2032,29 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
             insert_text,
             delete_model,
             list_downloaded_models,
+            inspect_model_file,
+            import_custom_model,
+            get_model_manifest,
+            get_model_manifest_settings,
+            update_model_manifest_settings,
+            refresh_model_manifest,
+            export_model_bundle,
+            import_model_bundle,
+            scan_model_integrity,
+            recommend_model,
+            get_model_memory_settings,
+            update_model_memory_settings,
+            get_model_cache_status,
+            get_parakeet_diagnostics,
+            get_sidecar_engines,
+            register_sidecar_engine,
+            unregister_sidecar_engine,
+            get_parakeet_options,
+            update_parakeet_options,
+            reset_parakeet_options,
+            get_models_storage,
+            move_models_directory,
+            quantize_model,
             cancel_download,
             cleanup_old_transcriptions,
             get_transcription_history,
@@ -1,3 +1,4 @@
+use crate::sidecar::SidecarError;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -17,3 +18,20 @@ pub enum ParakeetError {
     #[error("{0}")]
     Unavailable(String),
 }
+
+impl From<SidecarError> for ParakeetError {
+    fn from(err: SidecarError) -> Self {
+        match err {
+            SidecarError::Write(details) | SidecarError::Pipe(details) => {
+                ParakeetError::SpawnError(details)
+            }
+            SidecarError::Encode(err) => ParakeetError::EncodeError(err),
+            SidecarError::Remote { code, message } => ParakeetError::SidecarError { code, message },
+            SidecarError::Terminated => ParakeetError::Terminated,
+            SidecarError::InvalidResponse => ParakeetError::InvalidResponse,
+            other @ (SidecarError::Timeout(_) | SidecarError::Incompatible(_)) => {
+                ParakeetError::Unavailable(other.to_string())
+            }
+        }
+    }
+}
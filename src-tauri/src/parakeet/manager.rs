@@ -1,16 +1,25 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::atomic::AtomicBool;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
 
 use log::{info, warn};
 use reqwest::Client;
 use serde::Serialize;
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Manager};
 
 use super::error::ParakeetError;
 use super::messages::{ParakeetCommand, ParakeetResponse};
-use super::models::{ParakeetModelDefinition, AVAILABLE_MODELS};
+use super::models::{platform_models, ParakeetModelDefinition};
+#[cfg(target_os = "linux")]
+use super::onnx::{self, OnnxParakeet};
+use super::options::ParakeetRuntimeOptions;
 use super::sidecar::ParakeetClient;
+use crate::sidecar::registry::EngineBinary;
+use crate::sidecar::supervisor::SidecarHealth;
+use crate::sidecar::SidecarEvent;
+use crate::{AppState, RecordingState};
 
 #[derive(Debug, Clone, Serialize)]
 pub struct ParakeetModelStatus {
@@ -28,17 +37,32 @@ pub struct ParakeetModelStatus {
 
 pub struct ParakeetManager {
     client: ParakeetClient,
-    root_dir: PathBuf,
+    /// In-process ONNX backend used on Linux unless an external engine is registered
+    #[cfg(target_os = "linux")]
+    onnx: Arc<OnnxParakeet>,
+    /// Follows the models directory when it is moved
+    root_dir: RwLock<PathBuf>,
+    /// User overrides by model name; models without one use the defaults
+    runtime_options: RwLock<HashMap<String, ParakeetRuntimeOptions>>,
     http: Client,
 }
 
 const PARAKEET_UNAVAILABLE_EVENT: &str = "parakeet-unavailable";
+const PARAKEET_PARTIAL_EVENT: &str = "transcription-partial";
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(30);
 
 impl ParakeetManager {
     pub fn new(root_dir: PathBuf) -> Self {
         Self {
-            client: ParakeetClient::new("parakeet-sidecar"),
-            root_dir,
+            // Linux has no bundled sidecar, only ONNX or a registered engine
+            #[cfg(target_os = "linux")]
+            client: ParakeetClient::new(None),
+            #[cfg(not(target_os = "linux"))]
+            client: ParakeetClient::new(Some("parakeet-sidecar")),
+            #[cfg(target_os = "linux")]
+            onnx: Arc::new(OnnxParakeet::new(onnx::load_sherpa)),
+            root_dir: RwLock::new(root_dir),
+            runtime_options: RwLock::new(HashMap::new()),
             http: Client::new(),
         }
     }
@@ -53,23 +77,19 @@ impl ParakeetManager {
 
     /// Returns available Parakeet models.
     ///
-    /// **Platform Support**: This returns an empty list on non-macOS platforms.
-    /// Parakeet uses Apple's Neural Engine via FluidAudio, which is macOS-only.
-    ///
     /// # Platform Behavior
-    /// - **macOS**: Returns all Parakeet models with download status
-    /// - **Windows/Linux**: Returns empty vector (compile-time exclusion)
+    /// - **macOS**: CoreML models run by the Swift/FluidAudio sidecar
+    /// - **Linux**: ONNX models run on CPU by the in-process sherpa-onnx backend
+    /// - **Windows**: Returns empty vector (compile-time exclusion)
     pub fn list_models(&self) -> Vec<ParakeetModelStatus> {
-        // Parakeet Swift/FluidAudio integration is macOS-only
-        // On non-macOS platforms, this returns empty at compile time
-        #[cfg(not(target_os = "macos"))]
+        #[cfg(not(any(target_os = "macos", target_os = "linux")))]
         {
             return vec![];
         }
 
-        #[cfg(target_os = "macos")]
+        #[cfg(any(target_os = "macos", target_os = "linux"))]
         {
-            AVAILABLE_MODELS
+            platform_models()
                 .iter()
                 .map(|definition| ParakeetModelStatus {
                     name: definition.id.to_string(),
@@ -91,16 +111,84 @@ impl ParakeetManager {
         &self,
         model_name: &str,
     ) -> Option<&'static ParakeetModelDefinition> {
-        AVAILABLE_MODELS.iter().find(|m| m.id == model_name)
+        platform_models().iter().find(|m| m.id == model_name)
     }
 
     pub fn model_dir(&self, model_name: &str) -> PathBuf {
-        self.root_dir.join(model_name)
+        self.root_dir
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .join(model_name)
+    }
+
+    pub fn set_root_dir(&self, root_dir: PathBuf) {
+        info!("Parakeet model root changed to {:?}", root_dir);
+        *self
+            .root_dir
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = root_dir;
+    }
+
+    pub fn runtime_options(&self, model_name: &str) -> ParakeetRuntimeOptions {
+        self.runtime_options
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(model_name)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Replace all per-model overrides; used at startup and whenever they change
+    pub fn set_runtime_options(&self, options: HashMap<String, ParakeetRuntimeOptions>) {
+        *self
+            .runtime_options
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = options;
+    }
+
+    fn load_command(
+        &self,
+        definition: &ParakeetModelDefinition,
+        force_download: bool,
+    ) -> ParakeetCommand {
+        let options = self.runtime_options(definition.id);
+        // The ONNX backend loads from our models directory instead of a download cache
+        #[cfg(target_os = "linux")]
+        let local_path = Some(self.model_dir(definition.id).to_string_lossy().to_string());
+        #[cfg(not(target_os = "linux"))]
+        let local_path = None;
+        ParakeetCommand::LoadModel {
+            model_id: definition.id.to_string(),
+            model_version: Some(Self::model_version_for(definition).to_string()),
+            force_download: Some(force_download),
+            local_path,
+            cache_dir: None,
+            precision: options.precision.as_str().to_string(),
+            attention: options.attention.as_str().to_string(),
+            local_attention_context: options.local_attention_context,
+            chunk_duration: Some(options.chunk_duration_secs),
+            overlap_duration: Some(options.overlap_duration_secs),
+            eager_unload: Some(false),
+        }
     }
 
     /// Check if a Parakeet model is available.
-    /// FluidAudio stores models in ~/Library/Application Support/FluidAudio/Models/
+    /// FluidAudio stores models in ~/Library/Application Support/FluidAudio/Models/;
+    /// ONNX models on Linux live in the Parakeet models directory.
     pub fn is_model_downloaded(&self, definition: &ParakeetModelDefinition) -> bool {
+        #[cfg(target_os = "linux")]
+        {
+            onnx::missing_files(definition, &self.model_dir(definition.id)).is_empty()
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            self.is_sidecar_model_downloaded(definition)
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn is_sidecar_model_downloaded(&self, definition: &ParakeetModelDefinition) -> bool {
         if let Some(home) = dirs::home_dir() {
             // FluidAudio's actual model storage path
             // IMPORTANT: "Application Support" has a SPACE (macOS standard path)
@@ -137,39 +225,138 @@ impl ParakeetManager {
         &self,
         app: &AppHandle,
         model_name: &str,
-        _cancel_flag: Option<Arc<AtomicBool>>,
+        cancel_flag: Option<Arc<AtomicBool>>,
         progress_callback: impl Fn(u64, u64) + Send + 'static,
     ) -> Result<(), String> {
         let Some(definition) = self.get_model_definition(model_name) else {
             return Err(format!("Unknown Parakeet model: {model_name}"));
         };
 
+        #[cfg(target_os = "linux")]
+        {
+            let _ = app;
+            self.download_onnx_files(definition, cancel_flag.as_deref(), progress_callback)
+                .await
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            // FluidAudio downloads inside the sidecar and cannot be interrupted
+            let _ = cancel_flag;
+            self.download_with_sidecar(app, definition, progress_callback)
+                .await
+        }
+    }
+
+    /// Fetch the ONNX export file by file with the resumable Whisper downloader.
+    /// Progress is reported against the model's estimated size. The export has no
+    /// published checksums, so each file's SHA-256 is pinned when it is downloaded
+    /// and checked again before every load.
+    #[cfg(target_os = "linux")]
+    async fn download_onnx_files(
+        &self,
+        definition: &ParakeetModelDefinition,
+        cancel_flag: Option<&AtomicBool>,
+        progress_callback: impl Fn(u64, u64),
+    ) -> Result<(), String> {
+        use crate::whisper::download::{self, DownloadOptions};
+        use crate::whisper::integrity::{self, ExpectedChecksums, IntegrityStatus};
+
+        let dir = self.model_dir(definition.id);
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create Parakeet model directory: {}", e))?;
+
+        let total = definition.estimated_size;
+        let mut completed = 0u64;
+        for file in definition.files {
+            let output_path = dir.join(file.filename);
+            if !output_path.is_file() {
+                let url = format!(
+                    "https://huggingface.co/{}/resolve/main/{}",
+                    definition.repo_id, file.filename
+                );
+                info!("Downloading Parakeet file {}", url);
+                download::download_resumable(
+                    &url,
+                    &output_path,
+                    0,
+                    |size| {
+                        if size == 0 || size > total * 2 {
+                            Err(format!("Unexpected size {} for {}", size, file.filename))
+                        } else {
+                            Ok(())
+                        }
+                    },
+                    cancel_flag,
+                    &DownloadOptions::default(),
+                    |done, _| progress_callback((completed + done).min(total), total),
+                )
+                .await?;
+
+                let downloaded = output_path.clone();
+                let record = tauri::async_runtime::spawn_blocking(move || {
+                    integrity::verify_model_file(
+                        &downloaded,
+                        &ExpectedChecksums::pinned_on_first_use(),
+                        true,
+                    )
+                })
+                .await
+                .map_err(|e| e.to_string())??;
+                if record.status == IntegrityStatus::Mismatch {
+                    let _ = std::fs::remove_file(&output_path);
+                    return Err(format!(
+                        "{} does not match the copy downloaded before. Delete the model and download it again.",
+                        file.filename
+                    ));
+                }
+            }
+            completed += std::fs::metadata(&output_path)
+                .map(|m| m.len())
+                .unwrap_or(0);
+        }
+
+        progress_callback(total, total);
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    async fn download_with_sidecar(
+        &self,
+        app: &AppHandle,
+        definition: &ParakeetModelDefinition,
+        progress_callback: impl Fn(u64, u64) + Send,
+    ) -> Result<(), String> {
         // For Swift sidecar, delegate download to FluidAudio
         // Send load_model command which triggers download in Swift
-        let version = Self::model_version_for(definition);
+        let command = self.load_command(definition, true);
 
-        let command = ParakeetCommand::LoadModel {
-            model_id: definition.id.to_string(),
-            model_version: Some(version.to_string()),
-            force_download: Some(true),
-            local_path: None,
-            cache_dir: None,
-            precision: "bf16".to_string(),
-            attention: "full".to_string(),
-            local_attention_context: 256,
-            chunk_duration: Some(120.0),
-            overlap_duration: Some(15.0),
-            eager_unload: Some(false),
+        // Sidecars speaking the versioned protocol stream download progress
+        let total = definition.estimated_size;
+        let mut on_event = move |event: SidecarEvent| {
+            if let SidecarEvent::Progress {
+                progress: Some(fraction),
+                ..
+            } = event
+            {
+                progress_callback((fraction as f64 * total as f64) as u64, total);
+            }
         };
 
         // Send to sidecar and let it handle the download
-        match self.send_command(app, &command).await {
+        let response = self
+            .send_command_with_events(app, &command, &mut on_event)
+            .await;
+        match response {
             Ok(ParakeetResponse::Status {
                 loaded_model: Some(id),
                 ..
             }) if id == definition.id => {
                 // Download/load completed for the requested version
-                progress_callback(definition.estimated_size, definition.estimated_size);
+                on_event(SidecarEvent::Progress {
+                    progress: Some(1.0),
+                    message: None,
+                });
                 Ok(())
             }
             Ok(ParakeetResponse::Status {
@@ -241,19 +428,25 @@ impl ParakeetManager {
         };
 
         let version = Self::model_version_for(definition);
-        let command = ParakeetCommand::LoadModel {
-            model_id: definition.id.to_string(),
-            model_version: Some(version.to_string()),
-            force_download: Some(false),
-            local_path: None,
-            cache_dir: None,
-            precision: "bf16".into(),
-            attention: "local".into(),
-            local_attention_context: 256,
-            chunk_duration: Some(120.0),
-            overlap_duration: Some(15.0),
-            eager_unload: Some(false),
-        };
+        let command = self.load_command(definition, false);
+
+        // Refuse ONNX files that changed since their SHA-256 was pinned
+        #[cfg(target_os = "linux")]
+        for file in definition.files {
+            let path = self.model_dir(definition.id).join(file.filename);
+            if !path.is_file() {
+                continue;
+            }
+            crate::whisper::integrity::verify_before_load(
+                path,
+                crate::whisper::integrity::ExpectedChecksums::pinned_on_first_use(),
+            )
+            .await
+            .map_err(|message| ParakeetError::SidecarError {
+                code: "integrity_failed".to_string(),
+                message,
+            })?;
+        }
 
         match self.send_command(app, &command).await? {
             ParakeetResponse::Ok { .. } => Ok(()),
@@ -314,7 +507,48 @@ impl ParakeetManager {
             local_attention_context: None,
         };
 
-        self.send_command(app, &command).await
+        // Interim text from streaming sidecars, for live display
+        let mut on_event = |event: SidecarEvent| {
+            if let SidecarEvent::Partial { text } = event {
+                let _ = app.emit(
+                    PARAKEET_PARTIAL_EVENT,
+                    serde_json::json!({ "engine": "parakeet", "text": text }),
+                );
+            }
+        };
+        self.send_command_with_events(app, &command, &mut on_event)
+            .await
+    }
+
+    /// Reload `model_name` so changed runtime options take effect, if it is the
+    /// loaded model. Nothing is started just to apply them.
+    pub async fn reapply_runtime_options(
+        &self,
+        app: &AppHandle,
+        model_name: &str,
+    ) -> Result<(), ParakeetError> {
+        let Some(definition) = self.get_model_definition(model_name) else {
+            return Ok(());
+        };
+        if self.client.engine_binary(app).is_some() && !self.sidecar_health().running {
+            return Ok(());
+        }
+
+        let loaded = match self.send_command(app, &ParakeetCommand::Status {}).await? {
+            ParakeetResponse::Status { loaded_model, .. } => loaded_model,
+            _ => None,
+        };
+        if !loaded.is_some_and(|id| id.starts_with(definition.id)) {
+            return Ok(());
+        }
+
+        info!(
+            "Reloading Parakeet model {} with new runtime options",
+            model_name
+        );
+        self.send_command(app, &ParakeetCommand::UnloadModel {})
+            .await?;
+        self.load_model(app, model_name).await
     }
 
     /// Check if the Parakeet sidecar is healthy and can respond to commands
@@ -329,11 +563,29 @@ impl ParakeetManager {
         }
     }
 
+    /// Crash and restart history of the sidecar, for diagnostics
+    pub fn sidecar_health(&self) -> SidecarHealth {
+        self.client.health()
+    }
+
+    /// Stop the sidecar so the next command starts the currently registered engine
+    pub async fn restart_sidecar(&self) {
+        self.client.shutdown().await;
+    }
+
     pub async fn shutdown(&self) {
+        #[cfg(target_os = "linux")]
+        self.onnx.handle(&ParakeetCommand::Shutdown {}).ok();
         self.client.shutdown().await;
     }
 
-    fn friendly_spawn_message(details: &str) -> String {
+    fn friendly_spawn_message(external: bool, details: &str) -> String {
+        if external {
+            return format!(
+                "Parakeet is unavailable. The registered engine could not be started. Details: {}",
+                details
+            );
+        }
         format!(
             "Parakeet is unavailable. Please reinstall Verity or remove the quarantine flag by running `xattr -dr com.apple.quarantine /Applications/Verity.app`. Details: {}",
             details
@@ -351,10 +603,32 @@ impl ParakeetManager {
         app: &AppHandle,
         command: &ParakeetCommand,
     ) -> Result<ParakeetResponse, ParakeetError> {
-        match self.client.send(app, command).await {
+        self.send_command_with_events(app, command, &mut |_| {})
+            .await
+    }
+
+    async fn send_command_with_events(
+        &self,
+        app: &AppHandle,
+        command: &ParakeetCommand,
+        on_event: &mut (dyn FnMut(SidecarEvent) + Send),
+    ) -> Result<ParakeetResponse, ParakeetError> {
+        let binary = self.client.engine_binary(app);
+
+        #[cfg(target_os = "linux")]
+        if binary.is_none() {
+            let backend = self.onnx.clone();
+            let command = command.clone();
+            return tokio::task::spawn_blocking(move || backend.handle(&command))
+                .await
+                .map_err(|e| ParakeetError::SpawnError(e.to_string()))?;
+        }
+
+        let external = matches!(binary, Some(EngineBinary::External(_)));
+        match self.client.send(app, command, on_event).await {
             Ok(response) => Ok(response),
             Err(ParakeetError::SpawnError(details)) => {
-                let message = Self::friendly_spawn_message(&details);
+                let message = Self::friendly_spawn_message(external, &details);
                 Self::emit_unavailable(app, &message);
                 Err(ParakeetError::Unavailable(message))
             }
@@ -362,3 +636,18 @@ impl ParakeetManager {
         }
     }
 }
+
+/// Ping the Parakeet sidecar every `HEALTH_CHECK_INTERVAL` while nothing is being
+/// recorded, restarting it if it died or hung since the last dictation
+pub fn spawn_health_monitor(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(HEALTH_CHECK_INTERVAL).await;
+            if app.state::<AppState>().get_current_state() != RecordingState::Idle {
+                continue;
+            }
+            let manager = app.state::<ParakeetManager>();
+            manager.client.check_health(&app).await;
+        }
+    });
+}
@@ -1,3 +1,4 @@
+use crate::transcript::{Transcript, TranscriptSegment, TranscriptToken};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
@@ -59,7 +60,7 @@ pub enum ParakeetCommand {
 }
 
 fn default_precision() -> String {
-    "bf16".to_string()
+    super::options::BACKEND_PRECISION.as_str().to_string()
 }
 
 fn default_attention() -> String {
@@ -117,3 +118,122 @@ pub struct ParakeetSegment {
     #[serde(default)]
     pub tokens: Option<Vec<Value>>,
 }
+
+impl ParakeetResponse {
+    /// Map a transcription reply into the engine-neutral transcript
+    pub fn into_transcript(self) -> Result<Transcript, String> {
+        match self {
+            ParakeetResponse::Transcription {
+                text,
+                segments,
+                language,
+                duration,
+            } => Ok(Transcript {
+                text,
+                segments: segments
+                    .into_iter()
+                    .map(ParakeetSegment::into_transcript_segment)
+                    .collect(),
+                language,
+                duration,
+            }),
+            ParakeetResponse::Error { code, message, .. } => {
+                Err(format!("Parakeet transcription failed: {code} - {message}"))
+            }
+            other => Err(format!("Unexpected Parakeet response: {:?}", other)),
+        }
+    }
+}
+
+impl ParakeetSegment {
+    fn into_transcript_segment(self) -> TranscriptSegment {
+        // Token objects are passed through loosely so older sidecars still parse
+        let tokens = self
+            .tokens
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|token| {
+                let number = |key: &str| token.get(key).and_then(Value::as_f64).map(|v| v as f32);
+                let text = token
+                    .get("text")
+                    .or_else(|| token.get("token"))
+                    .and_then(Value::as_str)?;
+                Some(TranscriptToken {
+                    text: text.to_string(),
+                    start: number("start"),
+                    end: number("end"),
+                    confidence: number("confidence").map(|c| c.clamp(0.0, 1.0)),
+                })
+            })
+            .collect();
+
+        TranscriptSegment {
+            text: self.text,
+            start: self.start,
+            end: self.end,
+            confidence: None,
+            tokens,
+        }
+        .with_token_confidence()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transcription_maps_segments_and_tokens() {
+        let response: ParakeetResponse = serde_json::from_value(serde_json::json!({
+            "type": "transcription",
+            "text": "Hi there",
+            "segments": [
+                {
+                    "text": "Hi there",
+                    "start": 0.25,
+                    "end": 1.0,
+                    "tokens": [
+                        {"text": "Hi", "start": 0.25, "end": 0.5, "confidence": 0.9},
+                        {"token": " there", "start": 0.5, "end": 1.0, "confidence": 0.7},
+                        {"unexpected": true}
+                    ]
+                },
+                {"text": "no timings"}
+            ],
+            "duration": 1.2
+        }))
+        .unwrap();
+
+        let transcript = response.into_transcript().unwrap();
+        assert_eq!(transcript.text, "Hi there");
+        assert_eq!(transcript.duration, Some(1.2));
+        assert_eq!(transcript.segments.len(), 2);
+
+        let first = &transcript.segments[0];
+        assert_eq!((first.start, first.end), (Some(0.25), Some(1.0)));
+        assert_eq!(first.tokens.len(), 2);
+        assert_eq!(first.tokens[1].text, " there");
+        assert!((first.confidence.unwrap() - 0.8).abs() < 1e-6);
+
+        let second = &transcript.segments[1];
+        assert!(second.tokens.is_empty());
+        assert_eq!(second.confidence, None);
+        assert!(transcript.has_timings());
+    }
+
+    #[test]
+    fn test_non_transcription_replies_are_errors() {
+        let status: ParakeetResponse = serde_json::from_value(serde_json::json!({
+            "type": "status",
+            "loadedModel": null,
+            "modelPath": null,
+            "precision": null,
+            "attention": null
+        }))
+        .unwrap();
+        assert!(status
+            .into_transcript()
+            .unwrap_err()
+            .starts_with("Unexpected Parakeet response"));
+    }
+}
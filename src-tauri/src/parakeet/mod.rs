@@ -2,6 +2,9 @@ pub mod error;
 pub mod manager;
 pub mod messages;
 pub mod models;
+#[cfg(any(target_os = "linux", test))]
+pub mod onnx;
+pub mod options;
 pub mod sidecar;
 
 pub use manager::{ParakeetManager, ParakeetModelStatus};
@@ -21,6 +21,7 @@ pub struct ParakeetModelDefinition {
 
 // Parakeet models using Swift/FluidAudio sidecar
 // These models are macOS-only and use Apple Neural Engine for acceleration
+#[cfg(not(target_os = "linux"))]
 pub static AVAILABLE_MODELS: Lazy<Vec<ParakeetModelDefinition>> = Lazy::new(|| {
     vec![
         ParakeetModelDefinition {
@@ -84,3 +85,68 @@ pub static AVAILABLE_MODELS: Lazy<Vec<ParakeetModelDefinition>> = Lazy::new(|| {
         },
     ]
 });
+
+// Parakeet TDT ONNX exports (int8) run on CPU through sherpa-onnx on Linux.
+// Files are downloaded directly into the Parakeet models directory.
+#[cfg(any(target_os = "linux", test))]
+pub static ONNX_MODELS: Lazy<Vec<ParakeetModelDefinition>> = Lazy::new(|| {
+    vec![
+        ParakeetModelDefinition {
+            id: "parakeet-tdt-0.6b-v3",
+            display_name: "Parakeet V3",
+            repo_id: "csukuangfj/sherpa-onnx-nemo-parakeet-tdt-0.6b-v3-int8",
+            description: "Multilingual transcription on CPU via ONNX Runtime",
+            languages: &[
+                "en", "es", "fr", "de", "bg", "hr", "cs", "da", "nl", "et", "fi", "el", "hu", "it",
+                "lv", "lt", "mt", "pl", "pt", "ro", "sk", "sl", "sv", "ru", "uk",
+            ],
+            recommended: true,
+            speed_score: 8,
+            accuracy_score: 9,
+            files: ONNX_FILES,
+            estimated_size: 670_000_000,
+        },
+        ParakeetModelDefinition {
+            id: "parakeet-tdt-0.6b-v2",
+            display_name: "Parakeet V2 (English)",
+            repo_id: "csukuangfj/sherpa-onnx-nemo-parakeet-tdt-0.6b-v2-int8",
+            description: "English transcription on CPU via ONNX Runtime",
+            languages: &["en"],
+            recommended: true,
+            speed_score: 9,
+            accuracy_score: 8,
+            files: ONNX_FILES,
+            estimated_size: 660_000_000,
+        },
+    ]
+});
+
+/// Encoder, decoder and joiner of the transducer plus its token table
+#[cfg(any(target_os = "linux", test))]
+pub const ONNX_FILES: &[ParakeetModelFile] = &[
+    ParakeetModelFile {
+        filename: "encoder.int8.onnx",
+    },
+    ParakeetModelFile {
+        filename: "decoder.int8.onnx",
+    },
+    ParakeetModelFile {
+        filename: "joiner.int8.onnx",
+    },
+    ParakeetModelFile {
+        filename: "tokens.txt",
+    },
+];
+
+/// Models for the Parakeet backend of this platform
+pub fn platform_models() -> &'static [ParakeetModelDefinition] {
+    #[cfg(target_os = "linux")]
+    {
+        &ONNX_MODELS
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        &AVAILABLE_MODELS
+    }
+}
@@ -0,0 +1,927 @@
+// In-process Parakeet backend for Linux. Parakeet TDT ONNX exports are decoded on
+// CPU by sherpa-onnx; commands and responses follow the Swift sidecar's protocol so
+// the manager and callers don't care which backend is answering.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use log::{info, warn};
+
+use super::error::ParakeetError;
+use super::messages::{ParakeetCommand, ParakeetResponse, ParakeetSegment};
+use super::models::ParakeetModelDefinition;
+use super::options::{Attention, ParakeetRuntimeOptions, BACKEND_PRECISION};
+
+/// Parakeet expects 16 kHz mono audio, the same contract as Whisper
+pub const SAMPLE_RATE: u32 = 16_000;
+
+/// SentencePiece marks the start of a word with U+2581
+const WORD_START: char = '\u{2581}';
+
+/// What a recognizer heard in one stretch of audio
+#[derive(Debug, Clone, Default)]
+pub struct Recognized {
+    pub text: String,
+    /// Sub-word tokens as the model spells them
+    pub tokens: Vec<String>,
+    /// Start of each token in seconds; empty when the model has no timestamps
+    pub timestamps: Vec<f32>,
+}
+
+/// Speech recognizer over 16 kHz mono samples
+pub trait Recognizer: Send {
+    fn transcribe(&mut self, samples: &[f32]) -> Recognized;
+}
+
+/// How long recordings are split before they reach the recognizer
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Chunking {
+    duration_secs: f32,
+    overlap_secs: f32,
+}
+
+impl Default for Chunking {
+    fn default() -> Self {
+        let options = ParakeetRuntimeOptions::default();
+        Self {
+            duration_secs: options.chunk_duration_secs,
+            overlap_secs: options.overlap_duration_secs,
+        }
+    }
+}
+
+impl Chunking {
+    fn with_overrides(self, duration: Option<f32>, overlap: Option<f32>) -> Self {
+        Self {
+            duration_secs: duration.unwrap_or(self.duration_secs),
+            overlap_secs: overlap.unwrap_or(self.overlap_secs),
+        }
+    }
+}
+
+/// A token placed on the recording's timeline
+#[derive(Debug, Clone, PartialEq)]
+struct TimedToken {
+    text: String,
+    start: f32,
+    end: f32,
+}
+
+/// Creates a recognizer from a directory holding the model's files
+pub type RecognizerLoader = fn(&Path) -> Result<Box<dyn Recognizer>, String>;
+
+struct LoadedModel {
+    model_id: String,
+    model_version: Option<String>,
+    path: PathBuf,
+    chunking: Chunking,
+    recognizer: Box<dyn Recognizer>,
+}
+
+pub struct OnnxParakeet {
+    loader: RecognizerLoader,
+    loaded: Mutex<Option<LoadedModel>>,
+}
+
+impl OnnxParakeet {
+    pub fn new(loader: RecognizerLoader) -> Self {
+        Self {
+            loader,
+            loaded: Mutex::new(None),
+        }
+    }
+
+    /// Answer a sidecar command. Loading and transcription are CPU bound, so call
+    /// this from a blocking task.
+    pub fn handle(&self, command: &ParakeetCommand) -> Result<ParakeetResponse, ParakeetError> {
+        match command {
+            ParakeetCommand::LoadModel {
+                model_id,
+                model_version,
+                local_path,
+                precision,
+                attention,
+                chunk_duration,
+                overlap_duration,
+                ..
+            } => {
+                if precision != BACKEND_PRECISION.as_str() {
+                    return Err(sidecar_error(
+                        "unsupported_option",
+                        &format!("The ONNX backend cannot run at {} precision", precision),
+                    ));
+                }
+                if attention != Attention::Full.as_str() {
+                    return Err(sidecar_error(
+                        "unsupported_option",
+                        &format!("The ONNX backend cannot use {} attention", attention),
+                    ));
+                }
+                let chunking =
+                    Chunking::default().with_overrides(*chunk_duration, *overlap_duration);
+                self.load(
+                    model_id,
+                    model_version.clone(),
+                    local_path.as_deref(),
+                    chunking,
+                )
+            }
+            ParakeetCommand::Transcribe {
+                audio_path,
+                translate_to_english,
+                chunk_duration,
+                overlap_duration,
+                ..
+            } => {
+                if *translate_to_english {
+                    warn!("Parakeet cannot translate; returning the original language");
+                }
+                self.transcribe(Path::new(audio_path), *chunk_duration, *overlap_duration)
+            }
+            ParakeetCommand::Status {} => Ok(self.status()),
+            ParakeetCommand::UnloadModel {} => {
+                self.unload();
+                Ok(ok_response("unload_model"))
+            }
+            ParakeetCommand::DeleteModel { model_id, .. } => {
+                // Files are owned by the manager; only release them if they are in use
+                let mut loaded = self.lock();
+                if model_id.is_none() || loaded.as_ref().map(|m| &m.model_id) == model_id.as_ref() {
+                    loaded.take();
+                }
+                Ok(ok_response("delete_model"))
+            }
+            ParakeetCommand::Shutdown {} => {
+                self.unload();
+                Ok(ok_response("shutdown"))
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<LoadedModel>> {
+        self.loaded
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn load(
+        &self,
+        model_id: &str,
+        model_version: Option<String>,
+        local_path: Option<&str>,
+        chunking: Chunking,
+    ) -> Result<ParakeetResponse, ParakeetError> {
+        let path = PathBuf::from(local_path.ok_or_else(|| {
+            sidecar_error(
+                "missing_path",
+                "The ONNX backend needs the model directory in local_path",
+            )
+        })?);
+
+        {
+            let mut loaded = self.lock();
+            if let Some(current) = loaded.as_mut() {
+                if current.model_id == model_id && current.path == path {
+                    current.chunking = chunking;
+                    drop(loaded);
+                    return Ok(self.status());
+                }
+            }
+        }
+
+        if !path.is_dir() {
+            return Err(sidecar_error(
+                "model_not_found",
+                &format!("Model directory {} does not exist", path.display()),
+            ));
+        }
+
+        // Release the previous model first so two never sit in memory together
+        self.unload();
+        info!("Loading Parakeet ONNX model '{}' from {:?}", model_id, path);
+        let recognizer = (self.loader)(&path).map_err(|e| sidecar_error("load_failed", &e))?;
+        self.lock().replace(LoadedModel {
+            model_id: model_id.to_string(),
+            model_version,
+            path,
+            chunking,
+            recognizer,
+        });
+        Ok(self.status())
+    }
+
+    fn transcribe(
+        &self,
+        audio_path: &Path,
+        chunk_duration: Option<f32>,
+        overlap_duration: Option<f32>,
+    ) -> Result<ParakeetResponse, ParakeetError> {
+        let samples = read_wav(audio_path).map_err(|e| sidecar_error("invalid_audio", &e))?;
+        let duration = samples.len() as f32 / SAMPLE_RATE as f32;
+
+        let mut loaded = self.lock();
+        let model = loaded
+            .as_mut()
+            .ok_or_else(|| sidecar_error("model_not_loaded", "No Parakeet model is loaded"))?;
+        let chunking = model
+            .chunking
+            .with_overrides(chunk_duration, overlap_duration);
+        let tokens = transcribe_chunked(model.recognizer.as_mut(), &samples, chunking);
+
+        Ok(ParakeetResponse::Transcription {
+            text: join_tokens(&tokens),
+            segments: build_segments(&tokens),
+            language: None,
+            duration: Some(duration),
+        })
+    }
+
+    fn status(&self) -> ParakeetResponse {
+        let loaded = self.lock();
+        ParakeetResponse::Status {
+            loaded_model: loaded.as_ref().map(|m| m.model_id.clone()),
+            model_version: loaded.as_ref().and_then(|m| m.model_version.clone()),
+            model_path: loaded
+                .as_ref()
+                .map(|m| m.path.to_string_lossy().to_string()),
+            precision: loaded
+                .as_ref()
+                .map(|_| BACKEND_PRECISION.as_str().to_string()),
+            attention: loaded
+                .as_ref()
+                .map(|_| Attention::Full.as_str().to_string()),
+        }
+    }
+
+    fn unload(&self) {
+        if let Some(model) = self.lock().take() {
+            info!("Unloaded Parakeet ONNX model '{}'", model.model_id);
+        }
+    }
+}
+
+/// Recognize `samples` in overlapping chunks so long recordings don't have to fit
+/// in memory at once. Each overlap is split down the middle: tokens starting in
+/// the first half belong to the earlier chunk, the rest to the later one.
+fn transcribe_chunked(
+    recognizer: &mut dyn Recognizer,
+    samples: &[f32],
+    chunking: Chunking,
+) -> Vec<TimedToken> {
+    let rate = SAMPLE_RATE as f32;
+    let chunk_len = ((chunking.duration_secs * rate) as usize).max(SAMPLE_RATE as usize);
+    let overlap = ((chunking.overlap_secs.max(0.0) * rate) as usize).min(chunk_len / 2);
+    let step = chunk_len - overlap;
+
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    loop {
+        let end = (start + chunk_len).min(samples.len());
+        let last = end == samples.len();
+        let keep_from = if start == 0 {
+            0.0
+        } else {
+            (start + overlap / 2) as f32 / rate
+        };
+        let keep_until = if last {
+            f32::INFINITY
+        } else {
+            (end - overlap + overlap / 2) as f32 / rate
+        };
+
+        let heard = recognizer.transcribe(&samples[start..end]);
+        tokens.extend(
+            place_tokens(heard, start as f32 / rate, end as f32 / rate)
+                .into_iter()
+                .filter(|token| token.start >= keep_from && token.start < keep_until),
+        );
+        if last {
+            return tokens;
+        }
+        start += step;
+    }
+}
+
+/// Move a chunk's tokens onto the recording's timeline. A token ends where the
+/// next one starts; without timestamps the whole chunk becomes one token.
+fn place_tokens(heard: Recognized, offset: f32, chunk_end: f32) -> Vec<TimedToken> {
+    if heard.timestamps.len() != heard.tokens.len() || heard.tokens.is_empty() {
+        let text = heard.text.trim();
+        if text.is_empty() {
+            return Vec::new();
+        }
+        return vec![TimedToken {
+            text: format!("{}{}", WORD_START, text),
+            start: offset,
+            end: chunk_end,
+        }];
+    }
+
+    let starts: Vec<f32> = heard.timestamps.iter().map(|t| offset + t).collect();
+    heard
+        .tokens
+        .into_iter()
+        .enumerate()
+        .map(|(i, text)| TimedToken {
+            text,
+            start: starts[i],
+            end: starts.get(i + 1).copied().unwrap_or(chunk_end),
+        })
+        .collect()
+}
+
+fn join_tokens(tokens: &[TimedToken]) -> String {
+    let joined: String = tokens.iter().map(|token| token.text.as_str()).collect();
+    joined
+        .replace(WORD_START, " ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Group tokens into sentences. Token objects use the keys the sidecar sends, with
+/// the word-start marker turned back into a space.
+fn build_segments(tokens: &[TimedToken]) -> Vec<ParakeetSegment> {
+    let ends_sentence = |token: &TimedToken| token.text.trim_end().ends_with(['.', '?', '!']);
+
+    tokens
+        .split_inclusive(ends_sentence)
+        .filter_map(|sentence| {
+            let text = join_tokens(sentence);
+            if text.is_empty() {
+                return None;
+            }
+            Some(ParakeetSegment {
+                text,
+                start: sentence.first().map(|token| token.start),
+                end: sentence.last().map(|token| token.end),
+                tokens: Some(
+                    sentence
+                        .iter()
+                        .map(|token| {
+                            serde_json::json!({
+                                "text": token.text.replace(WORD_START, " "),
+                                "start": token.start,
+                                "end": token.end,
+                            })
+                        })
+                        .collect(),
+                ),
+            })
+        })
+        .collect()
+}
+
+/// Files of `definition` that are not present in `dir`
+pub fn missing_files(definition: &ParakeetModelDefinition, dir: &Path) -> Vec<&'static str> {
+    definition
+        .files
+        .iter()
+        .map(|file| file.filename)
+        .filter(|name| !dir.join(name).is_file())
+        .collect()
+}
+
+fn ok_response(command: &str) -> ParakeetResponse {
+    ParakeetResponse::Ok {
+        command: command.to_string(),
+        payload: Default::default(),
+    }
+}
+
+fn sidecar_error(code: &str, message: &str) -> ParakeetError {
+    ParakeetError::SidecarError {
+        code: code.to_string(),
+        message: message.to_string(),
+    }
+}
+
+/// Read a 16 kHz WAV as mono f32, averaging channels
+fn read_wav(path: &Path) -> Result<Vec<f32>, String> {
+    let mut reader =
+        hound::WavReader::open(path).map_err(|e| format!("Failed to open audio: {}", e))?;
+    let spec = reader.spec();
+    if spec.sample_rate != SAMPLE_RATE {
+        return Err(format!(
+            "Expected {} Hz audio, got {} Hz",
+            SAMPLE_RATE, spec.sample_rate
+        ));
+    }
+
+    let samples: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Float => reader
+            .samples::<f32>()
+            .collect::<Result<_, _>>()
+            .map_err(|e| format!("Failed to read audio: {}", e))?,
+        hound::SampleFormat::Int => {
+            let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|s| s.map(|s| s as f32 / scale))
+                .collect::<Result<_, _>>()
+                .map_err(|e| format!("Failed to read audio: {}", e))?
+        }
+    };
+
+    let channels = spec.channels.max(1) as usize;
+    if channels == 1 {
+        return Ok(samples);
+    }
+    Ok(samples
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect())
+}
+
+#[cfg(target_os = "linux")]
+mod sherpa {
+    //! sherpa-rs only hands back the transcript text, so the recognizer is driven
+    //! through the C API to also get the tokens and their timestamps.
+
+    use super::{Recognized, Recognizer, SAMPLE_RATE};
+    use sherpa_rs::sherpa_rs_sys as sys;
+    use std::ffi::{CStr, CString};
+    use std::path::Path;
+
+    struct SherpaRecognizer {
+        recognizer: *const sys::SherpaOnnxOfflineRecognizer,
+    }
+
+    // The recognizer is only used behind the backend's mutex
+    unsafe impl Send for SherpaRecognizer {}
+
+    impl Drop for SherpaRecognizer {
+        fn drop(&mut self) {
+            unsafe { sys::SherpaOnnxDestroyOfflineRecognizer(self.recognizer) }
+        }
+    }
+
+    impl Recognizer for SherpaRecognizer {
+        fn transcribe(&mut self, samples: &[f32]) -> Recognized {
+            unsafe {
+                let stream = sys::SherpaOnnxCreateOfflineStream(self.recognizer);
+                sys::SherpaOnnxAcceptWaveformOffline(
+                    stream,
+                    SAMPLE_RATE as i32,
+                    samples.as_ptr(),
+                    samples.len() as i32,
+                );
+                sys::SherpaOnnxDecodeOfflineStream(self.recognizer, stream);
+                let result = sys::SherpaOnnxGetOfflineStreamResult(stream);
+                let recognized = if result.is_null() {
+                    Recognized::default()
+                } else {
+                    read_result(&*result)
+                };
+                sys::SherpaOnnxDestroyOfflineRecognizerResult(result);
+                sys::SherpaOnnxDestroyOfflineStream(stream);
+                recognized
+            }
+        }
+    }
+
+    unsafe fn read_result(result: &sys::SherpaOnnxOfflineRecognizerResult) -> Recognized {
+        let string = |ptr: *const std::os::raw::c_char| {
+            if ptr.is_null() {
+                String::new()
+            } else {
+                CStr::from_ptr(ptr).to_string_lossy().into_owned()
+            }
+        };
+        let count = result.count.max(0) as usize;
+        let tokens = if result.tokens_arr.is_null() {
+            Vec::new()
+        } else {
+            (0..count)
+                .map(|i| string(*result.tokens_arr.add(i)))
+                .collect()
+        };
+        let timestamps = if result.timestamps.is_null() {
+            Vec::new()
+        } else {
+            std::slice::from_raw_parts(result.timestamps, count).to_vec()
+        };
+        Recognized {
+            text: string(result.text),
+            tokens,
+            timestamps,
+        }
+    }
+
+    /// Load a sherpa-onnx NeMo transducer export (encoder/decoder/joiner + tokens)
+    pub fn load(dir: &Path) -> Result<Box<dyn Recognizer>, String> {
+        let c_string = |value: &str| {
+            CString::new(value).map_err(|_| format!("Invalid model path {:?}", value))
+        };
+        let file = |name: &str| c_string(&dir.join(name).to_string_lossy());
+        let encoder = file("encoder.int8.onnx")?;
+        let decoder = file("decoder.int8.onnx")?;
+        let joiner = file("joiner.int8.onnx")?;
+        let tokens = file("tokens.txt")?;
+        let model_type = c_string("nemo_transducer")?;
+        let provider = c_string("cpu")?;
+        let decoding_method = c_string("greedy_search")?;
+        let threads = std::thread::available_parallelism()
+            .map(|n| n.get().min(4))
+            .unwrap_or(2);
+
+        let recognizer = unsafe {
+            // Unset fields are NULL/0, which sherpa-onnx reads as "use the default"
+            let mut config: sys::SherpaOnnxOfflineRecognizerConfig = std::mem::zeroed();
+            config.model_config.transducer.encoder = encoder.as_ptr();
+            config.model_config.transducer.decoder = decoder.as_ptr();
+            config.model_config.transducer.joiner = joiner.as_ptr();
+            config.model_config.tokens = tokens.as_ptr();
+            config.model_config.num_threads = threads as i32;
+            config.model_config.provider = provider.as_ptr();
+            config.model_config.model_type = model_type.as_ptr();
+            config.feat_config.sample_rate = SAMPLE_RATE as i32;
+            config.feat_config.feature_dim = 80;
+            config.decoding_method = decoding_method.as_ptr();
+            sys::SherpaOnnxCreateOfflineRecognizer(&config)
+        };
+        if recognizer.is_null() {
+            return Err("sherpa-onnx failed to load the model".to_string());
+        }
+        Ok(Box::new(SherpaRecognizer { recognizer }))
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub use sherpa::load as load_sherpa;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parakeet::models::ONNX_MODELS;
+    use tempfile::TempDir;
+
+    struct EchoRecognizer;
+
+    impl Recognizer for EchoRecognizer {
+        fn transcribe(&mut self, samples: &[f32]) -> Recognized {
+            Recognized {
+                text: format!(" {} samples ", samples.len()),
+                tokens: vec![
+                    format!("\u{2581}{}", samples.len()),
+                    "\u{2581}samples".into(),
+                ],
+                timestamps: vec![0.0, 0.0],
+            }
+        }
+    }
+
+    /// Says the number each second of audio is tagged with, at that second
+    struct CountingRecognizer {
+        calls: Vec<usize>,
+    }
+
+    impl Recognizer for CountingRecognizer {
+        fn transcribe(&mut self, samples: &[f32]) -> Recognized {
+            self.calls.push(samples.len());
+            let seconds: Vec<_> = samples.chunks(SAMPLE_RATE as usize).collect();
+            Recognized {
+                text: String::new(),
+                tokens: seconds
+                    .iter()
+                    .map(|second| format!("\u{2581}{}", second[0] as usize))
+                    .collect(),
+                timestamps: (0..seconds.len()).map(|i| i as f32).collect(),
+            }
+        }
+    }
+
+    fn echo_loader(dir: &Path) -> Result<Box<dyn Recognizer>, String> {
+        if dir.join("broken").exists() {
+            return Err("bad model".to_string());
+        }
+        Ok(Box::new(EchoRecognizer))
+    }
+
+    fn load_command(model_id: &str, path: Option<&Path>) -> ParakeetCommand {
+        ParakeetCommand::LoadModel {
+            model_id: model_id.to_string(),
+            model_version: Some("v3".to_string()),
+            force_download: Some(false),
+            local_path: path.map(|p| p.to_string_lossy().to_string()),
+            cache_dir: None,
+            precision: BACKEND_PRECISION.as_str().to_string(),
+            attention: "full".to_string(),
+            local_attention_context: 256,
+            chunk_duration: None,
+            overlap_duration: None,
+            eager_unload: None,
+        }
+    }
+
+    fn transcribe_command(path: &Path) -> ParakeetCommand {
+        ParakeetCommand::Transcribe {
+            audio_path: path.to_string_lossy().to_string(),
+            language: Some("en".to_string()),
+            translate_to_english: false,
+            prompt: None,
+            use_word_timestamps: Some(true),
+            chunk_duration: None,
+            overlap_duration: None,
+            attention: None,
+            local_attention_context: None,
+        }
+    }
+
+    fn write_wav(path: &Path, sample_rate: u32, channels: u16, frames: usize) {
+        let spec = hound::WavSpec {
+            channels,
+            sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(path, spec).unwrap();
+        for _ in 0..frames * channels as usize {
+            writer.write_sample(i16::MAX / 2).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    fn error_code(result: Result<ParakeetResponse, ParakeetError>) -> String {
+        match result {
+            Err(ParakeetError::SidecarError { code, .. }) => code,
+            other => panic!("expected sidecar error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_load_and_transcribe() {
+        let temp_dir = TempDir::new().unwrap();
+        let backend = OnnxParakeet::new(echo_loader);
+        let audio = temp_dir.path().join("audio.wav");
+        write_wav(&audio, SAMPLE_RATE, 1, 8_000);
+
+        let status = backend
+            .handle(&load_command("parakeet-tdt-0.6b-v3", Some(temp_dir.path())))
+            .unwrap();
+        match status {
+            ParakeetResponse::Status {
+                loaded_model,
+                model_version,
+                ..
+            } => {
+                assert_eq!(loaded_model.as_deref(), Some("parakeet-tdt-0.6b-v3"));
+                assert_eq!(model_version.as_deref(), Some("v3"));
+            }
+            other => panic!("unexpected response {:?}", other),
+        }
+
+        match backend.handle(&transcribe_command(&audio)).unwrap() {
+            ParakeetResponse::Transcription {
+                text,
+                segments,
+                duration,
+                ..
+            } => {
+                assert_eq!(text, "8000 samples");
+                assert_eq!(duration, Some(0.5));
+                assert_eq!(segments.len(), 1);
+                assert_eq!(segments[0].start, Some(0.0));
+                assert_eq!(segments[0].end, Some(0.5));
+
+                let tokens = segments[0].tokens.as_ref().unwrap();
+                assert_eq!(tokens.len(), 2);
+                assert_eq!(tokens[1]["text"], " samples");
+                assert_eq!(tokens[1]["end"], 0.5);
+            }
+            other => panic!("unexpected response {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_chunks_overlap_without_repeating_words() {
+        let samples: Vec<f32> = (0..25 * SAMPLE_RATE as usize)
+            .map(|i| (i / SAMPLE_RATE as usize) as f32)
+            .collect();
+        let mut recognizer = CountingRecognizer { calls: Vec::new() };
+        let chunking = Chunking {
+            duration_secs: 10.0,
+            overlap_secs: 4.0,
+        };
+
+        let tokens = transcribe_chunked(&mut recognizer, &samples, chunking);
+        let expected: Vec<String> = (0..25).map(|i| i.to_string()).collect();
+        assert_eq!(join_tokens(&tokens), expected.join(" "));
+        assert_eq!(tokens[7].start, 7.0);
+        assert_eq!(tokens[24].end, 25.0);
+
+        // 10 s windows stepping by 6 s, and nothing bigger reaches the recognizer
+        let chunk = 10 * SAMPLE_RATE as usize;
+        assert_eq!(
+            recognizer.calls,
+            vec![chunk, chunk, chunk, 7 * SAMPLE_RATE as usize]
+        );
+    }
+
+    #[test]
+    fn test_segments_split_at_sentence_ends() {
+        let token = |text: &str, start: f32| TimedToken {
+            text: text.replace(' ', "\u{2581}"),
+            start,
+            end: start + 0.5,
+        };
+        let tokens = vec![
+            token(" Hel", 0.0),
+            token("lo.", 0.5),
+            token(" How", 1.5),
+            token(" are", 2.0),
+            token(" you?", 2.5),
+            token(" Fine", 4.0),
+        ];
+
+        let segments = build_segments(&tokens);
+        let texts: Vec<_> = segments.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, ["Hello.", "How are you?", "Fine"]);
+        assert_eq!(segments[1].start, Some(1.5));
+        assert_eq!(segments[1].end, Some(3.0));
+
+        let transcript = ParakeetResponse::Transcription {
+            text: join_tokens(&tokens),
+            segments,
+            language: None,
+            duration: Some(4.5),
+        }
+        .into_transcript()
+        .unwrap();
+        let words: Vec<_> = transcript.segments[1]
+            .tokens
+            .iter()
+            .map(|t| (t.text.as_str(), t.start))
+            .collect();
+        assert_eq!(
+            words,
+            [
+                (" How", Some(1.5)),
+                (" are", Some(2.0)),
+                (" you?", Some(2.5))
+            ]
+        );
+        assert!(build_segments(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_short_audio_is_one_chunk() {
+        let samples = vec![3.0; 5 * SAMPLE_RATE as usize];
+        let mut recognizer = CountingRecognizer { calls: Vec::new() };
+        let tokens = transcribe_chunked(&mut recognizer, &samples, Chunking::default());
+        assert_eq!(join_tokens(&tokens), "3 3 3 3 3");
+        assert_eq!(recognizer.calls.len(), 1);
+    }
+
+    #[test]
+    fn test_stereo_is_downmixed() {
+        let temp_dir = TempDir::new().unwrap();
+        let audio = temp_dir.path().join("stereo.wav");
+        write_wav(&audio, SAMPLE_RATE, 2, 1_600);
+        let samples = read_wav(&audio).unwrap();
+        assert_eq!(samples.len(), 1_600);
+        assert!((samples[0] - 0.5).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_errors_follow_sidecar_codes() {
+        let temp_dir = TempDir::new().unwrap();
+        let backend = OnnxParakeet::new(echo_loader);
+        let audio = temp_dir.path().join("audio.wav");
+        write_wav(&audio, SAMPLE_RATE, 1, 160);
+
+        assert_eq!(
+            error_code(backend.handle(&transcribe_command(&audio))),
+            "model_not_loaded"
+        );
+        assert_eq!(
+            error_code(backend.handle(&load_command("parakeet-tdt-0.6b-v3", None))),
+            "missing_path"
+        );
+        assert_eq!(
+            error_code(backend.handle(&load_command(
+                "parakeet-tdt-0.6b-v3",
+                Some(&temp_dir.path().join("missing"))
+            ))),
+            "model_not_found"
+        );
+
+        let mut unsupported = load_command("parakeet-tdt-0.6b-v3", Some(temp_dir.path()));
+        if let ParakeetCommand::LoadModel { attention, .. } = &mut unsupported {
+            *attention = "local".to_string();
+        }
+        assert_eq!(
+            error_code(backend.handle(&unsupported)),
+            "unsupported_option"
+        );
+
+        std::fs::write(temp_dir.path().join("broken"), b"").unwrap();
+        assert_eq!(
+            error_code(
+                backend.handle(&load_command("parakeet-tdt-0.6b-v3", Some(temp_dir.path())))
+            ),
+            "load_failed"
+        );
+
+        let resampled = temp_dir.path().join("44k.wav");
+        write_wav(&resampled, 44_100, 1, 441);
+        assert_eq!(
+            error_code(backend.handle(&transcribe_command(&resampled))),
+            "invalid_audio"
+        );
+    }
+
+    #[test]
+    fn test_unload_and_delete_release_model() {
+        let temp_dir = TempDir::new().unwrap();
+        let backend = OnnxParakeet::new(echo_loader);
+        let load = load_command("parakeet-tdt-0.6b-v3", Some(temp_dir.path()));
+
+        backend.handle(&load).unwrap();
+        backend
+            .handle(&ParakeetCommand::DeleteModel {
+                model_id: Some("parakeet-tdt-0.6b-v2".to_string()),
+                model_version: None,
+            })
+            .unwrap();
+        assert!(matches!(
+            backend.handle(&ParakeetCommand::Status {}).unwrap(),
+            ParakeetResponse::Status {
+                loaded_model: Some(_),
+                ..
+            }
+        ));
+
+        backend.handle(&ParakeetCommand::UnloadModel {}).unwrap();
+        assert!(matches!(
+            backend.handle(&ParakeetCommand::Status {}).unwrap(),
+            ParakeetResponse::Status {
+                loaded_model: None,
+                ..
+            }
+        ));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_linux_lists_onnx_models() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = crate::parakeet::ParakeetManager::new(temp_dir.path().to_path_buf());
+        let models = manager.list_models();
+        assert_eq!(models.len(), ONNX_MODELS.len());
+        assert!(models.iter().all(|m| !m.downloaded));
+
+        let dir = manager.model_dir("parakeet-tdt-0.6b-v2");
+        std::fs::create_dir_all(&dir).unwrap();
+        for file in ONNX_MODELS[1].files {
+            std::fs::write(dir.join(file.filename), b"x").unwrap();
+        }
+        let downloaded: Vec<_> = manager
+            .list_models()
+            .into_iter()
+            .filter(|m| m.downloaded)
+            .map(|m| m.name)
+            .collect();
+        assert_eq!(downloaded, vec!["parakeet-tdt-0.6b-v2".to_string()]);
+    }
+
+    /// Needs a downloaded export, e.g.
+    /// `PARAKEET_ONNX_DIR=.../parakeet-tdt-0.6b-v3 cargo test -- --ignored`
+    #[cfg(target_os = "linux")]
+    #[test]
+    #[ignore]
+    fn test_sherpa_loads_a_real_model() {
+        let dir = std::env::var("PARAKEET_ONNX_DIR")
+            .expect("PARAKEET_ONNX_DIR must point at a Parakeet ONNX export");
+        let dir = Path::new(&dir);
+        assert!(missing_files(&ONNX_MODELS[0], dir).is_empty());
+
+        let mut recognizer = load_sherpa(dir).unwrap();
+        let heard = recognizer.transcribe(&vec![0.0; SAMPLE_RATE as usize]);
+        assert!(
+            heard.text.trim().is_empty(),
+            "heard {:?} in silence",
+            heard.text
+        );
+        assert_eq!(heard.tokens.len(), heard.timestamps.len());
+
+        // A tone is not speech either, but has to come back through the same path
+        let tone: Vec<f32> = (0..2 * SAMPLE_RATE as usize)
+            .map(|i| 0.1 * (i as f32 * 440.0 * std::f32::consts::TAU / SAMPLE_RATE as f32).sin())
+            .collect();
+        let heard = recognizer.transcribe(&tone);
+        assert_eq!(heard.tokens.len(), heard.timestamps.len());
+    }
+
+    #[test]
+    fn test_missing_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let definition = &ONNX_MODELS[0];
+        assert_eq!(missing_files(definition, temp_dir.path()).len(), 4);
+
+        for file in definition.files {
+            std::fs::write(temp_dir.path().join(file.filename), b"x").unwrap();
+        }
+        assert!(missing_files(definition, temp_dir.path()).is_empty());
+    }
+}
@@ -0,0 +1,165 @@
+use serde::{Deserialize, Serialize};
+
+/// Weight precision the engine runs the model at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Precision {
+    Int8,
+    Bf16,
+    Fp16,
+    Fp32,
+}
+
+impl Precision {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Precision::Int8 => "int8",
+            Precision::Bf16 => "bf16",
+            Precision::Fp16 => "fp16",
+            Precision::Fp32 => "fp32",
+        }
+    }
+}
+
+/// Full attention sees the whole chunk; local attention bounds memory on long audio
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Attention {
+    Full,
+    Local,
+}
+
+impl Attention {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Attention::Full => "full",
+            Attention::Local => "local",
+        }
+    }
+}
+
+/// Precision this platform's backend runs at. The ONNX exports are quantized to
+/// int8 and the Core ML models run at fp16; neither can switch at load time.
+#[cfg(target_os = "linux")]
+pub const BACKEND_PRECISION: Precision = Precision::Int8;
+#[cfg(not(target_os = "linux"))]
+pub const BACKEND_PRECISION: Precision = Precision::Fp16;
+
+/// Per-model options sent with `LoadModel`, stored under `parakeet_options` in the
+/// settings store keyed by model name. Options the backend cannot honour are
+/// rejected by `validate` rather than saved.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ParakeetRuntimeOptions {
+    pub precision: Precision,
+    pub attention: Attention,
+    /// Frames each side of a token that local attention looks at
+    pub local_attention_context: i32,
+    /// Long audio is transcribed in chunks of this many seconds
+    pub chunk_duration_secs: f32,
+    /// Seconds shared by neighbouring chunks so words at the seams are not lost
+    pub overlap_duration_secs: f32,
+}
+
+impl Default for ParakeetRuntimeOptions {
+    fn default() -> Self {
+        Self {
+            precision: BACKEND_PRECISION,
+            attention: Attention::Full,
+            local_attention_context: 256,
+            chunk_duration_secs: 120.0,
+            overlap_duration_secs: 15.0,
+        }
+    }
+}
+
+impl ParakeetRuntimeOptions {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.precision != BACKEND_PRECISION {
+            return Err(format!(
+                "Parakeet runs at {} precision on this platform",
+                BACKEND_PRECISION.as_str()
+            ));
+        }
+        if self.attention != Attention::Full {
+            return Err("Local attention is not supported by this Parakeet backend".to_string());
+        }
+        if !(16..=4096).contains(&self.local_attention_context) {
+            return Err("Local attention context must be between 16 and 4096".to_string());
+        }
+        if !(10.0..=1200.0).contains(&self.chunk_duration_secs) {
+            return Err("Chunk duration must be between 10 and 1200 seconds".to_string());
+        }
+        if !(0.0..=self.chunk_duration_secs / 2.0).contains(&self.overlap_duration_secs) {
+            return Err("Chunk overlap must be at most half the chunk duration".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_are_valid_and_serialize_lowercase() {
+        let options = ParakeetRuntimeOptions::default();
+        assert!(options.validate().is_ok());
+
+        let json = serde_json::to_value(&options).unwrap();
+        assert_eq!(json["precision"], BACKEND_PRECISION.as_str());
+        assert_eq!(json["attention"], "full");
+
+        // Missing fields fall back to the defaults
+        let partial: ParakeetRuntimeOptions =
+            serde_json::from_value(serde_json::json!({"chunk_duration_secs": 60.0})).unwrap();
+        assert_eq!(partial.attention, Attention::Full);
+        assert_eq!(partial.chunk_duration_secs, 60.0);
+        assert_eq!(partial.overlap_duration_secs, 15.0);
+    }
+
+    #[test]
+    fn test_validation() {
+        let base = ParakeetRuntimeOptions::default();
+        let invalid = [
+            ParakeetRuntimeOptions {
+                precision: Precision::Fp32,
+                ..base.clone()
+            },
+            ParakeetRuntimeOptions {
+                attention: Attention::Local,
+                ..base.clone()
+            },
+            ParakeetRuntimeOptions {
+                local_attention_context: 8,
+                ..base.clone()
+            },
+            ParakeetRuntimeOptions {
+                chunk_duration_secs: 5.0,
+                ..base.clone()
+            },
+            ParakeetRuntimeOptions {
+                chunk_duration_secs: f32::NAN,
+                ..base.clone()
+            },
+            ParakeetRuntimeOptions {
+                chunk_duration_secs: 20.0,
+                overlap_duration_secs: 15.0,
+                ..base.clone()
+            },
+            ParakeetRuntimeOptions {
+                overlap_duration_secs: -1.0,
+                ..base.clone()
+            },
+        ];
+        for options in invalid {
+            assert!(options.validate().is_err(), "{:?}", options);
+        }
+
+        let no_overlap = ParakeetRuntimeOptions {
+            overlap_duration_secs: 0.0,
+            ..base
+        };
+        assert!(no_overlap.validate().is_ok());
+    }
+}
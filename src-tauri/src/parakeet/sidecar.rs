@@ -1,154 +1,335 @@
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
 use super::error::ParakeetError;
 use super::messages::{ParakeetCommand, ParakeetResponse};
-use log::{error, warn};
-use tauri::async_runtime::{Receiver, RwLock};
+use crate::sidecar::connection::LineWriter;
+use crate::sidecar::registry::{EngineBinary, Launch, ShellLauncher};
+use crate::sidecar::supervisor::{RestartPolicy, SidecarHealth, Supervisor};
+use crate::sidecar::{SidecarConnection, SidecarError, SidecarEvent};
+use log::{error, info, warn};
+use tauri::async_runtime::RwLock;
 use tauri::AppHandle;
-use tauri_plugin_shell::{
-    process::{CommandChild, CommandEvent},
-    ShellExt,
-};
-use tokio::sync::RwLockWriteGuard;
+
+/// Name of the engine in the sidecar registry
+pub const ENGINE: &str = "parakeet";
+
+/// Commands every Parakeet sidecar must offer
+const REQUIRED_CAPABILITIES: &[&str] = &["load_model", "transcribe", "status"];
+
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a command may run before the sidecar is considered hung
+fn request_timeout(command: &ParakeetCommand) -> Duration {
+    match command {
+        // Downloads the model from Hugging Face first
+        ParakeetCommand::LoadModel {
+            force_download: Some(true),
+            ..
+        } => Duration::from_secs(60 * 60),
+        // First load compiles the CoreML model
+        ParakeetCommand::LoadModel { .. } => Duration::from_secs(5 * 60),
+        ParakeetCommand::Transcribe { .. } => Duration::from_secs(10 * 60),
+        _ => Duration::from_secs(30),
+    }
+}
+
+/// The process is gone or wedged and has to be replaced
+fn is_fatal(err: &SidecarError) -> bool {
+    matches!(
+        err,
+        SidecarError::Terminated
+            | SidecarError::Write(_)
+            | SidecarError::Pipe(_)
+            | SidecarError::Timeout(_)
+    )
+}
 
 pub struct ParakeetSidecar {
-    rx: Receiver<CommandEvent>,
-    child: CommandChild,
+    pid: u32,
+    connection: SidecarConnection<Box<dyn LineWriter>>,
 }
 
 impl ParakeetSidecar {
-    pub async fn spawn(app: &AppHandle, binary_name: &str) -> Result<Self, ParakeetError> {
-        // In Tauri v2, use the shell plugin and pass just the filename.
-        // The externalBin entry in tauri.conf.json must include this binary.
-        let (rx, child) = app
-            .shell()
-            .sidecar(binary_name)
-            .map_err(|e| ParakeetError::SpawnError(e.to_string()))?
-            .spawn()
-            .map_err(|e| ParakeetError::SpawnError(e.to_string()))?;
-
-        log::info!(
-            "Spawned Parakeet sidecar pid={} name={}",
-            child.pid(),
-            binary_name
-        );
-        Ok(Self { rx, child })
+    pub async fn spawn(launcher: &dyn Launch) -> Result<Self, ParakeetError> {
+        let name = launcher.name();
+        let launched = launcher.launch().map_err(ParakeetError::SpawnError)?;
+
+        let pid = launched.pid;
+        log::info!("Spawned Parakeet sidecar pid={} name={}", pid, name);
+        let mut connection = SidecarConnection::new(name, launched.rx, launched.writer);
+        if let Err(err) = connection
+            .handshake(REQUIRED_CAPABILITIES, HANDSHAKE_TIMEOUT)
+            .await
+        {
+            Self { pid, connection }.kill();
+            return Err(err.into());
+        }
+        Ok(Self { pid, connection })
     }
 
+    /// Send `command`, giving up after `timeout`
     pub async fn request(
         &mut self,
         command: &ParakeetCommand,
-    ) -> Result<ParakeetResponse, ParakeetError> {
-        let mut payload = serde_json::to_string(command)?;
-        payload.push('\n');
-        self.child
-            .write(payload.as_bytes())
-            .map_err(|e| ParakeetError::SpawnError(e.to_string()))?;
-
-        while let Some(event) = self.rx.recv().await {
-            match event {
-                CommandEvent::Stdout(line) => {
-                    let text = String::from_utf8_lossy(&line);
-                    let trimmed = text.trim();
-                    if trimmed.is_empty() {
-                        continue;
-                    }
-                    match serde_json::from_str::<ParakeetResponse>(trimmed) {
-                        Ok(response) => {
-                            if let ParakeetResponse::Error { code, message, .. } = &response {
-                                return Err(ParakeetError::SidecarError {
-                                    code: code.clone(),
-                                    message: message.clone(),
-                                });
-                            }
-                            return Ok(response);
-                        }
-                        Err(err) => {
-                            error!("Failed to parse sidecar response: {err}. raw={trimmed}");
-                            return Err(ParakeetError::InvalidResponse);
-                        }
-                    }
-                }
-                CommandEvent::Stderr(line) => {
-                    warn!(
-                        "Parakeet sidecar stderr: {}",
-                        String::from_utf8_lossy(&line)
-                    );
-                }
-                CommandEvent::Terminated(payload) => {
-                    error!(
-                        "Parakeet sidecar terminated unexpectedly code={:?}",
-                        payload.code
-                    );
-                    return Err(ParakeetError::Terminated);
-                }
-                CommandEvent::Error(err) => {
-                    error!("Error from Parakeet sidecar pipe: {err}");
-                    return Err(ParakeetError::SpawnError(err));
-                }
-                _ => {}
-            }
-        }
-
-        Err(ParakeetError::Terminated)
+        timeout: Duration,
+        on_event: &mut (dyn FnMut(SidecarEvent) + Send),
+    ) -> Result<ParakeetResponse, SidecarError> {
+        tokio::time::timeout(timeout, self.connection.request(command, on_event))
+            .await
+            .map_err(|_| SidecarError::Timeout(timeout))?
     }
 
     pub fn kill(self) {
-        if let Err(err) = self.child.kill() {
-            warn!("Failed to kill Parakeet sidecar: {err:?}");
+        if let Err(err) = self.connection.into_writer().kill() {
+            warn!("Failed to kill Parakeet sidecar: {err}");
         }
     }
 }
 
+/// Owns the Parakeet sidecar process. A crashed or hung sidecar is restarted with
+/// exponential backoff, the last loaded model is reloaded into it and the request
+/// that was in flight is retried once.
 pub struct ParakeetClient {
-    binary_name: String,
+    /// Shipped sidecar, used unless an external engine is registered
+    bundled: Option<String>,
     inner: RwLock<Option<ParakeetSidecar>>,
+    supervisor: Mutex<Supervisor>,
+    /// Last successful `LoadModel`, replayed after a restart
+    last_load: Mutex<Option<ParakeetCommand>>,
 }
 
 impl ParakeetClient {
-    pub fn new(binary_name: impl Into<String>) -> Self {
+    pub fn new(bundled: Option<&str>) -> Self {
         Self {
-            binary_name: binary_name.into(),
+            bundled: bundled.map(String::from),
             inner: RwLock::new(None),
+            supervisor: Mutex::new(Supervisor::new(RestartPolicy::default())),
+            last_load: Mutex::new(None),
         }
     }
 
+    fn supervisor(&self) -> MutexGuard<'_, Supervisor> {
+        self.supervisor.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn last_load(&self) -> MutexGuard<'_, Option<ParakeetCommand>> {
+        self.last_load.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Crash counts, restarts and the last stderr lines, for diagnostics
+    pub fn health(&self) -> SidecarHealth {
+        self.supervisor().snapshot()
+    }
+
+    /// The registered engine binary, else the bundled sidecar if there is one
+    pub fn engine_binary(&self, app: &AppHandle) -> Option<EngineBinary> {
+        EngineBinary::resolve(app, ENGINE, self.bundled.as_deref())
+    }
+
+    fn launcher(&self, app: &AppHandle) -> Result<ShellLauncher, ParakeetError> {
+        self.engine_binary(app)
+            .map(|binary| ShellLauncher::new(app, binary))
+            .ok_or_else(|| {
+                ParakeetError::SpawnError("No Parakeet engine is registered".to_string())
+            })
+    }
+
+    /// Start a sidecar in `slot` if none is running. After a crash this waits out
+    /// the backoff and reloads the last model, unless `command` loads one itself.
     async fn ensure(
         &self,
-        app: &AppHandle,
-    ) -> Result<RwLockWriteGuard<'_, Option<ParakeetSidecar>>, ParakeetError> {
-        let mut guard = self.inner.write().await;
-        if guard.is_none() {
-            let sidecar = ParakeetSidecar::spawn(app, &self.binary_name).await?;
-            guard.replace(sidecar);
+        launcher: &dyn Launch,
+        slot: &mut Option<ParakeetSidecar>,
+        command: &ParakeetCommand,
+    ) -> Result<(), ParakeetError> {
+        if slot.is_some() {
+            return Ok(());
+        }
+
+        let (restarting, delay) = {
+            let supervisor = self.supervisor();
+            (supervisor.is_restarting(), supervisor.restart_delay())
+        };
+        if !delay.is_zero() {
+            info!("Restarting Parakeet sidecar in {:?}", delay);
+            tokio::time::sleep(delay).await;
+        }
+
+        let mut sidecar = match ParakeetSidecar::spawn(launcher).await {
+            Ok(sidecar) => sidecar,
+            Err(err) => {
+                if restarting {
+                    self.supervisor()
+                        .crashed(&format!("restart failed: {err}"), None, Vec::new());
+                }
+                return Err(err);
+            }
+        };
+        self.supervisor()
+            .started(sidecar.pid, sidecar.connection.info());
+
+        let reload = match command {
+            ParakeetCommand::LoadModel { .. } => None,
+            _ => self.last_load().clone(),
+        };
+        if let Some(load) = reload {
+            info!("Reloading the last Parakeet model into the new sidecar");
+            let result = sidecar
+                .request(&load, request_timeout(&load), &mut |_| {})
+                .await;
+            match result {
+                Ok(ParakeetResponse::Error { code, message, .. }) => {
+                    warn!("Failed to reload Parakeet model: {code} - {message}");
+                }
+                Ok(_) => {}
+                Err(err) if is_fatal(&err) => {
+                    self.record_failure(sidecar, &err);
+                    return Err(err.into());
+                }
+                Err(err) => warn!("Failed to reload Parakeet model: {err}"),
+            }
+        }
+
+        slot.replace(sidecar);
+        Ok(())
+    }
+
+    /// Run `command` on the sidecar in `slot`. A sidecar that dies or hangs is
+    /// killed and taken out of the slot.
+    async fn request_in(
+        &self,
+        slot: &mut Option<ParakeetSidecar>,
+        command: &ParakeetCommand,
+        timeout: Duration,
+        on_event: &mut (dyn FnMut(SidecarEvent) + Send),
+    ) -> Result<ParakeetResponse, SidecarError> {
+        let Some(sidecar) = slot.as_mut() else {
+            return Err(SidecarError::Terminated);
+        };
+        let result = sidecar.request(command, timeout, on_event).await;
+        if let Err(err) = &result {
+            if is_fatal(err) {
+                if let Some(sidecar) = slot.take() {
+                    self.record_failure(sidecar, err);
+                }
+            }
+        }
+        result
+    }
+
+    fn record_failure(&self, sidecar: ParakeetSidecar, err: &SidecarError) {
+        let stderr = sidecar.connection.stderr_tail();
+        let exit_code = sidecar.connection.exit_code();
+        {
+            let mut supervisor = self.supervisor();
+            match err {
+                SidecarError::Timeout(_) => supervisor.timed_out(&err.to_string(), stderr.clone()),
+                _ => supervisor.crashed(&err.to_string(), exit_code, stderr.clone()),
+            }
+            let health = supervisor.snapshot();
+            error!(
+                "Parakeet sidecar pid={} failed: {} (exit code {:?}, {} crashes, {} in a row). Last stderr: {:?}",
+                sidecar.pid, err, exit_code, health.crash_count, health.consecutive_crashes, stderr
+            );
+        }
+        sidecar.kill();
+    }
+
+    /// Keep track of the model to reload after a restart
+    fn remember(&self, command: &ParakeetCommand, response: &ParakeetResponse) {
+        if matches!(response, ParakeetResponse::Error { .. }) {
+            return;
+        }
+        match command {
+            ParakeetCommand::LoadModel { .. } => {
+                let mut load = command.clone();
+                if let ParakeetCommand::LoadModel { force_download, .. } = &mut load {
+                    *force_download = Some(false);
+                }
+                self.last_load().replace(load);
+            }
+            ParakeetCommand::UnloadModel {} | ParakeetCommand::DeleteModel { .. } => {
+                self.last_load().take();
+            }
+            _ => {}
         }
-        Ok(guard)
     }
 
+    /// Send `command`, passing streamed progress/partial messages to `on_event`.
+    /// If the sidecar crashes mid-request it is restarted and the command retried once.
     pub async fn send(
         &self,
         app: &AppHandle,
         command: &ParakeetCommand,
+        on_event: &mut (dyn FnMut(SidecarEvent) + Send),
     ) -> Result<ParakeetResponse, ParakeetError> {
-        let mut guard = self.ensure(app).await?;
-        let response = match guard.as_mut() {
-            Some(sidecar) => sidecar.request(command).await,
-            None => return Err(ParakeetError::Terminated),
+        let launcher = self.launcher(app)?;
+        self.send_with(&launcher, command, request_timeout(command), on_event)
+            .await
+    }
+
+    async fn send_with(
+        &self,
+        launcher: &dyn Launch,
+        command: &ParakeetCommand,
+        timeout: Duration,
+        on_event: &mut (dyn FnMut(SidecarEvent) + Send),
+    ) -> Result<ParakeetResponse, ParakeetError> {
+        let mut guard = self.inner.write().await;
+        self.ensure(launcher, &mut guard, command).await?;
+        let mut result = self
+            .request_in(&mut guard, command, timeout, on_event)
+            .await;
+
+        let crashed = matches!(
+            result,
+            Err(SidecarError::Terminated | SidecarError::Write(_) | SidecarError::Pipe(_))
+        );
+        if crashed && !matches!(command, ParakeetCommand::Shutdown {}) {
+            info!("Retrying Parakeet command on a restarted sidecar");
+            self.ensure(launcher, &mut guard, command).await?;
+            result = self
+                .request_in(&mut guard, command, timeout, on_event)
+                .await;
+        }
+
+        let response = result?;
+        self.remember(command, &response);
+        Ok(response)
+    }
+
+    /// Ping a running sidecar with `Status`. One that is dead or hung is restarted
+    /// (reloading its model) so the next dictation does not pay for it. Skipped
+    /// while a request is in flight or when no sidecar is running.
+    pub async fn check_health(&self, app: &AppHandle) {
+        let Ok(launcher) = self.launcher(app) else {
+            return;
         };
+        self.check_health_with(&launcher, HEALTH_CHECK_TIMEOUT)
+            .await
+    }
 
-        match response {
-            Err(ParakeetError::Terminated) => {
-                let old = guard.take();
-                drop(guard);
-                if let Some(sidecar) = old {
-                    sidecar.kill();
-                }
-                let mut guard = self.ensure(app).await?;
-                if let Some(sidecar) = guard.as_mut() {
-                    sidecar.request(command).await
-                } else {
-                    Err(ParakeetError::Terminated)
-                }
+    async fn check_health_with(&self, launcher: &dyn Launch, timeout: Duration) {
+        let Ok(mut guard) = self.inner.try_write() else {
+            return;
+        };
+        if guard.is_none() {
+            return;
+        }
+
+        let status = ParakeetCommand::Status {};
+        let result = self
+            .request_in(&mut guard, &status, timeout, &mut |_| {})
+            .await;
+        let ok = !matches!(&result, Err(err) if is_fatal(err));
+        self.supervisor().health_checked(ok);
+        if !ok {
+            warn!("Parakeet sidecar failed its health check, restarting");
+            if let Err(err) = self.ensure(launcher, &mut guard, &status).await {
+                warn!("Failed to restart Parakeet sidecar: {err}");
             }
-            other => other,
         }
     }
 
@@ -156,5 +337,362 @@ impl ParakeetClient {
         if let Some(sidecar) = self.inner.write().await.take() {
             sidecar.kill();
         }
+        self.supervisor().stopped();
+        self.last_load().take();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sidecar::mock::{self, out};
+    use crate::sidecar::registry::Launched;
+    use serde_json::{json, Value};
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+    use std::time::Instant;
+    use tauri_plugin_shell::process::CommandEvent;
+
+    /// Answers request `request` of the `launch`th process, or `None` to behave
+    /// like a healthy engine
+    type Script = fn(launch: u32, request: &Value) -> Option<Vec<CommandEvent>>;
+
+    /// Starts mock Parakeet engines and records every request type they receive
+    struct MockEngine {
+        launches: AtomicU32,
+        requests: Arc<Mutex<Vec<String>>>,
+        script: Script,
+    }
+
+    impl MockEngine {
+        fn new(script: Script) -> Self {
+            Self {
+                launches: AtomicU32::new(0),
+                requests: Arc::new(Mutex::new(Vec::new())),
+                script,
+            }
+        }
+
+        fn requests(&self) -> Vec<String> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    fn healthy(request: &Value) -> Vec<CommandEvent> {
+        let id = request["id"].clone();
+        let reply = match request["type"].as_str() {
+            Some("hello") => json!({
+                "type": "hello", "id": id, "v": 1, "protocol_version": 1,
+                "capabilities": ["load_model", "transcribe", "status"]
+            }),
+            Some("load_model") => json!({
+                "type": "status", "id": id, "loadedModel": request["model_id"], "modelVersion": "v3"
+            }),
+            Some("transcribe") => json!({"type": "transcription", "id": id, "text": "hello"}),
+            _ => json!({"type": "status", "id": id, "loadedModel": null}),
+        };
+        vec![out(reply)]
+    }
+
+    impl Launch for MockEngine {
+        fn name(&self) -> String {
+            "mock-parakeet".to_string()
+        }
+
+        fn launch(&self) -> Result<Launched, String> {
+            let launch = self.launches.fetch_add(1, Ordering::SeqCst);
+            let requests = self.requests.clone();
+            let script = self.script;
+            let (rx, writer) = mock::spawn(move |request| {
+                let kind = request["type"].as_str().unwrap_or_default().to_string();
+                requests.lock().unwrap().push(kind);
+                script(launch, request).unwrap_or_else(|| healthy(request))
+            });
+            Ok(Launched {
+                pid: 100 + launch,
+                rx,
+                writer: Box::new(writer),
+            })
+        }
+    }
+
+    fn client(policy: RestartPolicy) -> ParakeetClient {
+        ParakeetClient {
+            bundled: None,
+            inner: RwLock::new(None),
+            supervisor: Mutex::new(Supervisor::new(policy)),
+            last_load: Mutex::new(None),
+        }
+    }
+
+    fn fast_restarts() -> RestartPolicy {
+        RestartPolicy {
+            initial_delay: Duration::from_millis(20),
+            max_delay: Duration::from_millis(80),
+            stable_after: Duration::from_secs(3600),
+        }
+    }
+
+    fn load(model_id: &str, force_download: bool) -> ParakeetCommand {
+        ParakeetCommand::LoadModel {
+            model_id: model_id.to_string(),
+            model_version: Some("v3".to_string()),
+            force_download: Some(force_download),
+            local_path: None,
+            cache_dir: None,
+            precision: "fp16".to_string(),
+            attention: "full".to_string(),
+            local_attention_context: 256,
+            chunk_duration: None,
+            overlap_duration: None,
+            eager_unload: None,
+        }
+    }
+
+    fn transcribe() -> ParakeetCommand {
+        ParakeetCommand::Transcribe {
+            audio_path: "/tmp/audio.wav".to_string(),
+            language: None,
+            translate_to_english: false,
+            prompt: None,
+            use_word_timestamps: None,
+            chunk_duration: None,
+            overlap_duration: None,
+            attention: None,
+            local_attention_context: None,
+        }
+    }
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    async fn send(
+        client: &ParakeetClient,
+        engine: &MockEngine,
+        command: &ParakeetCommand,
+    ) -> Result<ParakeetResponse, ParakeetError> {
+        client
+            .send_with(engine, command, TIMEOUT, &mut |_| {})
+            .await
+    }
+
+    fn transcribed_text(response: ParakeetResponse) -> String {
+        match response {
+            ParakeetResponse::Transcription { text, .. } => text,
+            other => panic!("expected a transcription, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_crashed_request_is_retried_once_after_reloading_the_model() {
+        // The first process dies in three different ways on its first transcription
+        let failures: [Script; 3] = [
+            |launch, request| {
+                (launch == 0 && request["type"] == "transcribe").then(|| vec![mock::terminated(6)])
+            },
+            |launch, request| {
+                (launch == 0 && request["type"] == "transcribe")
+                    .then(|| vec![CommandEvent::Error("broken pipe".to_string())])
+            },
+            // Exits right after loading; the next request cannot be written or
+            // only sees the exit
+            |launch, request| {
+                (launch == 0 && request["type"] == "load_model").then(|| {
+                    let mut events = healthy(request);
+                    events.push(mock::terminated(0));
+                    events
+                })
+            },
+        ];
+
+        for script in failures {
+            let engine = MockEngine::new(script);
+            let client = client(fast_restarts());
+            send(&client, &engine, &load("parakeet-tdt-0.6b-v3", true))
+                .await
+                .unwrap();
+
+            let response = send(&client, &engine, &transcribe()).await.unwrap();
+            assert_eq!(transcribed_text(response), "hello");
+            assert_eq!(engine.launches.load(Ordering::SeqCst), 2);
+
+            // The replacement got the last model before the retried request
+            let requests = engine.requests();
+            let restarted = requests.iter().rposition(|r| r == "hello").unwrap();
+            assert_eq!(requests[restarted..], ["hello", "load_model", "transcribe"]);
+
+            let health = client.health();
+            assert!(health.running);
+            assert_eq!(health.pid, Some(101));
+            assert_eq!(health.crash_count, 1);
+            assert_eq!(health.restart_count, 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replayed_load_does_not_force_a_download() {
+        let engine = MockEngine::new(|launch, request| {
+            (launch == 0 && request["type"] == "status").then(|| vec![mock::terminated(1)])
+        });
+        let client = client(fast_restarts());
+        send(&client, &engine, &load("parakeet-tdt-0.6b-v3", true))
+            .await
+            .unwrap();
+        send(&client, &engine, &ParakeetCommand::Status {})
+            .await
+            .unwrap();
+
+        match client.last_load().clone() {
+            Some(ParakeetCommand::LoadModel {
+                model_id,
+                force_download,
+                ..
+            }) => {
+                assert_eq!(model_id, "parakeet-tdt-0.6b-v3");
+                assert_eq!(force_download, Some(false));
+            }
+            other => panic!("expected the last load, got {:?}", other),
+        }
+
+        // Unloading forgets the model, so the next restart starts empty
+        send(&client, &engine, &ParakeetCommand::UnloadModel {})
+            .await
+            .unwrap();
+        assert!(client.last_load().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_request_is_not_retried_twice_or_after_engine_errors() {
+        let engine = MockEngine::new(|_, request| match request["type"].as_str() {
+            Some("transcribe") => Some(vec![mock::terminated(1)]),
+            Some("status") => Some(vec![out(
+                json!({"type": "error", "id": request["id"], "code": "busy", "message": "Busy"}),
+            )]),
+            _ => None,
+        });
+        let client = client(fast_restarts());
+
+        let err = send(&client, &engine, &transcribe()).await.unwrap_err();
+        assert!(matches!(err, ParakeetError::Terminated));
+        assert_eq!(engine.launches.load(Ordering::SeqCst), 2);
+        assert_eq!(client.health().crash_count, 2);
+
+        // An error reply leaves the process running and is not retried
+        let err = send(&client, &engine, &ParakeetCommand::Status {})
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ParakeetError::SidecarError { ref code, .. } if code == "busy"));
+        assert_eq!(engine.launches.load(Ordering::SeqCst), 3);
+        assert!(client.health().running);
+        assert_eq!(
+            engine.requests().iter().filter(|r| *r == "status").count(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_restarts_back_off_while_crashing() {
+        let engine = MockEngine::new(|_, request| {
+            (request["type"] == "transcribe").then(|| vec![mock::terminated(1)])
+        });
+        let client = client(fast_restarts());
+
+        // First start is immediate, the retry waits 20ms
+        let started = Instant::now();
+        assert!(send(&client, &engine, &transcribe()).await.is_err());
+        assert!(started.elapsed() >= Duration::from_millis(20));
+
+        // Then 40ms and 80ms (the cap)
+        let started = Instant::now();
+        assert!(send(&client, &engine, &transcribe()).await.is_err());
+        assert!(started.elapsed() >= Duration::from_millis(120));
+
+        let health = client.health();
+        assert_eq!(health.crash_count, 4);
+        assert_eq!(health.consecutive_crashes, 4);
+        assert_eq!(health.restart_count, 3);
+        assert_eq!(
+            client.supervisor().restart_delay(),
+            Duration::from_millis(80)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hung_sidecar_is_replaced_after_its_timeout() {
+        let engine = MockEngine::new(|launch, request| {
+            (launch == 0 && request["type"] == "status").then(Vec::new)
+        });
+        let client = client(fast_restarts());
+        send(&client, &engine, &load("parakeet-tdt-0.6b-v3", false))
+            .await
+            .unwrap();
+
+        // A hang is not retried: the command may be what wedged the engine
+        let err = client
+            .send_with(
+                &engine,
+                &ParakeetCommand::Status {},
+                Duration::from_millis(50),
+                &mut |_| {},
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ParakeetError::Unavailable(_)));
+        let health = client.health();
+        assert_eq!(health.timeout_count, 1);
+        assert!(!health.running);
+
+        // The next command starts a new process with the model reloaded
+        send(&client, &engine, &ParakeetCommand::Status {})
+            .await
+            .unwrap();
+        assert_eq!(engine.launches.load(Ordering::SeqCst), 2);
+        assert_eq!(engine.requests()[3..], ["hello", "load_model", "status"]);
+    }
+
+    #[tokio::test]
+    async fn test_health_check_restarts_a_hung_sidecar() {
+        let engine = MockEngine::new(|launch, request| {
+            (launch == 0 && request["type"] == "status").then(Vec::new)
+        });
+        let client = client(fast_restarts());
+
+        // Nothing to check before the first command
+        client
+            .check_health_with(&engine, Duration::from_millis(50))
+            .await;
+        assert_eq!(engine.launches.load(Ordering::SeqCst), 0);
+
+        send(&client, &engine, &load("parakeet-tdt-0.6b-v3", false))
+            .await
+            .unwrap();
+        client
+            .check_health_with(&engine, Duration::from_millis(50))
+            .await;
+
+        let health = client.health();
+        assert_eq!(health.last_health_check_ok, Some(false));
+        assert_eq!(health.restart_count, 1);
+        assert!(health.running);
+        assert_eq!(
+            engine.requests(),
+            ["hello", "load_model", "status", "hello", "load_model"]
+        );
+    }
+
+    #[test]
+    fn test_request_timeouts_depend_on_the_command() {
+        assert_eq!(
+            request_timeout(&load("parakeet-tdt-0.6b-v3", true)),
+            Duration::from_secs(60 * 60)
+        );
+        assert_eq!(
+            request_timeout(&load("parakeet-tdt-0.6b-v3", false)),
+            Duration::from_secs(5 * 60)
+        );
+        assert_eq!(request_timeout(&transcribe()), Duration::from_secs(10 * 60));
+        assert_eq!(
+            request_timeout(&ParakeetCommand::Status {}),
+            Duration::from_secs(30)
+        );
     }
 }
@@ -0,0 +1,472 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use log::{debug, error, info, warn};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tauri::async_runtime::Receiver;
+use tauri_plugin_shell::process::{CommandChild, CommandEvent};
+use thiserror::Error;
+
+use super::protocol::{self, Envelope, Hello, Message, SidecarEvent, SidecarInfo};
+
+#[derive(Debug, Error)]
+pub enum SidecarError {
+    #[error("failed to write to sidecar: {0}")]
+    Write(String),
+    #[error("failed to encode command: {0}")]
+    Encode(#[from] serde_json::Error),
+    #[error("sidecar returned error: {code} - {message}")]
+    Remote { code: String, message: String },
+    #[error("sidecar terminated unexpectedly")]
+    Terminated,
+    #[error("invalid sidecar response payload")]
+    InvalidResponse,
+    #[error("sidecar pipe error: {0}")]
+    Pipe(String),
+    #[error("sidecar did not answer within {0:?}")]
+    Timeout(Duration),
+    #[error("incompatible sidecar: {0}")]
+    Incompatible(String),
+}
+
+/// Sink for request lines; the sidecar's stdin in production, a channel in tests
+pub trait LineWriter: Send {
+    fn write_line(&mut self, line: &[u8]) -> Result<(), String>;
+
+    /// Stop the process behind the writer
+    fn kill(self: Box<Self>) -> Result<(), String>;
+}
+
+impl LineWriter for CommandChild {
+    fn write_line(&mut self, line: &[u8]) -> Result<(), String> {
+        self.write(line).map_err(|e| e.to_string())
+    }
+
+    fn kill(self: Box<Self>) -> Result<(), String> {
+        CommandChild::kill(*self).map_err(|e| e.to_string())
+    }
+}
+
+impl<W: LineWriter + ?Sized> LineWriter for Box<W> {
+    fn write_line(&mut self, line: &[u8]) -> Result<(), String> {
+        (**self).write_line(line)
+    }
+
+    fn kill(self: Box<Self>) -> Result<(), String> {
+        W::kill(*self)
+    }
+}
+
+/// Stderr lines kept for crash reports
+const STDERR_TAIL_LINES: usize = 20;
+
+/// One sidecar process: sends requests and matches replies to them by id.
+/// Requests are serialized by `&mut self`, so at most one is in flight.
+pub struct SidecarConnection<W: LineWriter> {
+    name: String,
+    rx: Receiver<CommandEvent>,
+    writer: W,
+    next_id: u64,
+    info: SidecarInfo,
+    stderr_tail: VecDeque<String>,
+    exit_code: Option<i32>,
+}
+
+impl<W: LineWriter> SidecarConnection<W> {
+    /// Wrap a freshly spawned sidecar; it is treated as legacy until `handshake`
+    pub fn new(name: impl Into<String>, rx: Receiver<CommandEvent>, writer: W) -> Self {
+        Self {
+            name: name.into(),
+            rx,
+            writer,
+            next_id: 1,
+            info: SidecarInfo::legacy(),
+            stderr_tail: VecDeque::with_capacity(STDERR_TAIL_LINES),
+            exit_code: None,
+        }
+    }
+
+    pub fn info(&self) -> &SidecarInfo {
+        &self.info
+    }
+
+    /// The most recent stderr lines, oldest first
+    pub fn stderr_tail(&self) -> Vec<String> {
+        self.stderr_tail.iter().cloned().collect()
+    }
+
+    /// Exit code, once the process has been seen to terminate
+    pub fn exit_code(&self) -> Option<i32> {
+        self.exit_code
+    }
+
+    /// Negotiate the protocol version and learn the sidecar's capabilities. A
+    /// sidecar that does not know `hello` is driven in legacy mode; any other
+    /// rejection fails, and a versioned one must list every capability in `required`.
+    pub async fn handshake(
+        &mut self,
+        required: &[&str],
+        timeout: Duration,
+    ) -> Result<&SidecarInfo, SidecarError> {
+        let id = self.send(&Hello::default(), true)?;
+        let reply = tokio::time::timeout(timeout, self.read_reply(id, false, &mut |_| {}))
+            .await
+            .map_err(|_| SidecarError::Timeout(timeout))?;
+
+        match reply {
+            Ok(value) => {
+                let info: SidecarInfo =
+                    serde_json::from_value(value).map_err(|_| SidecarError::InvalidResponse)?;
+                if !protocol::SUPPORTED_VERSIONS.contains(&info.protocol_version) {
+                    return Err(SidecarError::Incompatible(format!(
+                        "{} speaks protocol v{}, this app supports {:?}",
+                        self.name,
+                        info.protocol_version,
+                        protocol::SUPPORTED_VERSIONS
+                    )));
+                }
+                if let Some(missing) = required.iter().find(|c| !info.supports(c)) {
+                    return Err(SidecarError::Incompatible(format!(
+                        "{} does not support '{}'",
+                        self.name, missing
+                    )));
+                }
+                info!(
+                    "Sidecar {} speaks protocol v{} (engine={:?} {:?}, capabilities={:?})",
+                    self.name,
+                    info.protocol_version,
+                    info.engine,
+                    info.engine_version,
+                    info.capabilities
+                );
+                self.info = info;
+            }
+            Err(SidecarError::Remote { code, .. }) if code == protocol::UNKNOWN_COMMAND => {
+                info!(
+                    "Sidecar {} does not know the handshake, using the legacy protocol",
+                    self.name
+                );
+                self.info = SidecarInfo::legacy();
+            }
+            Err(SidecarError::Remote { code, message }) => {
+                return Err(SidecarError::Incompatible(format!(
+                    "{} rejected the handshake: {} - {}",
+                    self.name, code, message
+                )));
+            }
+            Err(e) => return Err(e),
+        }
+        Ok(&self.info)
+    }
+
+    /// Send `command` and wait for its reply. Progress and partial messages for the
+    /// request are passed to `on_event`; replies to earlier, abandoned requests are
+    /// dropped.
+    pub async fn request<C: Serialize, R: DeserializeOwned>(
+        &mut self,
+        command: &C,
+        on_event: &mut (dyn FnMut(SidecarEvent) + Send),
+    ) -> Result<R, SidecarError> {
+        let versioned = self.info.is_versioned();
+        let id = self.send(command, versioned)?;
+        let value = self.read_reply(id, versioned, on_event).await?;
+        serde_json::from_value(value).map_err(|err| {
+            error!("Failed to decode reply from sidecar {}: {err}", self.name);
+            SidecarError::InvalidResponse
+        })
+    }
+
+    /// Give back the writer, e.g. to kill the child process
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+
+    fn send<C: Serialize>(&mut self, command: &C, versioned: bool) -> Result<u64, SidecarError> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let mut payload = if versioned {
+            serde_json::to_string(&Envelope {
+                v: protocol::PROTOCOL_VERSION,
+                id,
+                command,
+            })?
+        } else {
+            serde_json::to_string(command)?
+        };
+        payload.push('\n');
+        self.writer
+            .write_line(payload.as_bytes())
+            .map_err(SidecarError::Write)?;
+        Ok(id)
+    }
+
+    async fn read_reply(
+        &mut self,
+        id: u64,
+        require_id: bool,
+        on_event: &mut (dyn FnMut(SidecarEvent) + Send),
+    ) -> Result<serde_json::Value, SidecarError> {
+        while let Some(event) = self.rx.recv().await {
+            match event {
+                CommandEvent::Stdout(line) => {
+                    let text = String::from_utf8_lossy(&line);
+                    let trimmed = text.trim();
+                    if trimmed.is_empty() {
+                        continue;
+                    }
+                    let message = protocol::parse_line(trimmed).map_err(|err| {
+                        error!(
+                            "Failed to parse response from sidecar {}: {err}. raw={trimmed}",
+                            self.name
+                        );
+                        SidecarError::InvalidResponse
+                    })?;
+
+                    // Ids are only required once the sidecar has shown it sends them
+                    if let Some(other) = message.id().filter(|other| *other != id) {
+                        debug!(
+                            "Dropping message for stale request {} from sidecar {}",
+                            other, self.name
+                        );
+                        continue;
+                    }
+                    if require_id && message.id().is_none() {
+                        warn!(
+                            "Dropping uncorrelated message from sidecar {}: {trimmed}",
+                            self.name
+                        );
+                        continue;
+                    }
+
+                    match message {
+                        Message::Event { event, .. } => on_event(event),
+                        Message::Error { code, message, .. } => {
+                            return Err(SidecarError::Remote { code, message })
+                        }
+                        Message::Reply { value, .. } => return Ok(value),
+                    }
+                }
+                CommandEvent::Stderr(line) => {
+                    let text = String::from_utf8_lossy(&line).trim_end().to_string();
+                    warn!("Sidecar {} stderr: {}", self.name, text);
+                    if self.stderr_tail.len() == STDERR_TAIL_LINES {
+                        self.stderr_tail.pop_front();
+                    }
+                    self.stderr_tail.push_back(text);
+                }
+                CommandEvent::Terminated(payload) => {
+                    error!(
+                        "Sidecar {} terminated unexpectedly code={:?}",
+                        self.name, payload.code
+                    );
+                    self.exit_code = payload.code;
+                    return Err(SidecarError::Terminated);
+                }
+                CommandEvent::Error(err) => {
+                    error!("Error from sidecar {} pipe: {err}", self.name);
+                    return Err(SidecarError::Pipe(err));
+                }
+                _ => {}
+            }
+        }
+
+        Err(SidecarError::Terminated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sidecar::mock::{self, out, ChannelWriter};
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Clone, Copy)]
+    enum MockMode {
+        Versioned,
+        Legacy,
+        /// Replies with a protocol version the host does not know
+        Future,
+        /// Knows `hello` but shares no protocol version with the host
+        Unsupported,
+    }
+
+    /// In-process stand-in for a sidecar binary. `echo` requests are answered with
+    /// their text; versioned mode first replays a stale reply and streams progress
+    /// and a partial, as a real engine would.
+    fn mock_sidecar(mode: MockMode) -> SidecarConnection<ChannelWriter> {
+        let (rx, writer) = mock::spawn(move |request| {
+            let id = request["id"].clone();
+            match (mode, request["type"].as_str()) {
+                (MockMode::Legacy, Some("hello")) => vec![out(
+                    json!({"type": "error", "code": "unknown_command", "message": "Unknown command type"}),
+                )],
+                (MockMode::Legacy, Some("echo")) => {
+                    vec![out(json!({"type": "echo", "text": request["text"]}))]
+                }
+                (MockMode::Future, Some("hello")) => vec![out(
+                    json!({"type": "hello", "id": id, "protocol_version": 9, "capabilities": []}),
+                )],
+                (MockMode::Unsupported, Some("hello")) => vec![out(json!({
+                    "type": "error", "id": id, "code": "unsupported_protocol",
+                    "message": "No common protocol version in [1]"
+                }))],
+                (_, Some("hello")) => vec![out(json!({
+                    "type": "hello", "id": id, "v": 1, "protocol_version": 1,
+                    "engine": "mock", "engine_version": "1.0",
+                    "capabilities": ["echo", "progress", "partial"]
+                }))],
+                (_, Some("echo")) => vec![
+                    out(json!({"type": "echo", "id": 999, "text": "stale"})),
+                    CommandEvent::Stderr(b"warming up".to_vec()),
+                    out(
+                        json!({"type": "progress", "id": id, "progress": 0.5, "message": "Working"}),
+                    ),
+                    out(json!({"type": "partial", "id": id, "text": "par"})),
+                    out(json!({"type": "echo", "id": id, "v": 1, "text": request["text"]})),
+                ],
+                (_, Some("fail")) => vec![out(
+                    json!({"type": "error", "id": id, "code": "boom", "message": "exploded"}),
+                )],
+                (_, Some("crash")) => vec![mock::terminated(1)],
+                _ => vec![out(
+                    json!({"type": "error", "id": id, "code": "unknown_command"}),
+                )],
+            }
+        });
+
+        SidecarConnection::new("mock", rx, writer)
+    }
+
+    #[derive(Serialize)]
+    #[serde(tag = "type", rename_all = "snake_case")]
+    enum MockCommand {
+        Echo { text: String },
+        Fail {},
+        Crash {},
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct EchoReply {
+        text: String,
+    }
+
+    fn echo(text: &str) -> MockCommand {
+        MockCommand::Echo {
+            text: text.to_string(),
+        }
+    }
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[tokio::test]
+    async fn test_versioned_request_correlates_and_streams() {
+        let mut conn = mock_sidecar(MockMode::Versioned);
+        let info = conn.handshake(&["echo"], TIMEOUT).await.unwrap();
+        assert_eq!(info.protocol_version, 1);
+        assert_eq!(info.engine.as_deref(), Some("mock"));
+
+        let mut events = Vec::new();
+        let reply: EchoReply = conn
+            .request(&echo("hello"), &mut |event| events.push(event))
+            .await
+            .unwrap();
+        // The stale reply with id 999 was skipped
+        assert_eq!(reply.text, "hello");
+        assert_eq!(
+            events,
+            vec![
+                SidecarEvent::Progress {
+                    progress: Some(0.5),
+                    message: Some("Working".to_string())
+                },
+                SidecarEvent::Partial {
+                    text: "par".to_string()
+                }
+            ]
+        );
+
+        // Ids keep increasing across requests
+        let reply: EchoReply = conn.request(&echo("again"), &mut |_| {}).await.unwrap();
+        assert_eq!(reply.text, "again");
+    }
+
+    #[tokio::test]
+    async fn test_legacy_sidecar_falls_back() {
+        let mut conn = mock_sidecar(MockMode::Legacy);
+        let info = conn.handshake(&["echo"], TIMEOUT).await.unwrap();
+        assert!(!info.is_versioned());
+
+        let reply: EchoReply = conn.request(&echo("plain"), &mut |_| {}).await.unwrap();
+        assert_eq!(reply.text, "plain");
+    }
+
+    #[tokio::test]
+    async fn test_handshake_rejects_incompatible_sidecars() {
+        let mut conn = mock_sidecar(MockMode::Future);
+        assert!(matches!(
+            conn.handshake(&[], TIMEOUT).await,
+            Err(SidecarError::Incompatible(_))
+        ));
+
+        let mut conn = mock_sidecar(MockMode::Versioned);
+        let err = conn
+            .handshake(&["echo", "diarize"], TIMEOUT)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("diarize"));
+
+        // Only a sidecar that does not know `hello` is driven in legacy mode
+        let mut conn = mock_sidecar(MockMode::Unsupported);
+        match conn.handshake(&[], TIMEOUT).await {
+            Err(SidecarError::Incompatible(message)) => {
+                assert!(message.contains("unsupported_protocol"))
+            }
+            other => panic!("expected incompatible sidecar, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_errors_and_termination() {
+        let mut conn = mock_sidecar(MockMode::Versioned);
+        conn.handshake(&[], TIMEOUT).await.unwrap();
+
+        match conn
+            .request::<_, EchoReply>(&MockCommand::Fail {}, &mut |_| {})
+            .await
+        {
+            Err(SidecarError::Remote { code, message }) => {
+                assert_eq!(code, "boom");
+                assert_eq!(message, "exploded");
+            }
+            other => panic!("expected remote error, got {:?}", other),
+        }
+
+        let _: EchoReply = conn.request(&echo("one"), &mut |_| {}).await.unwrap();
+        assert_eq!(conn.exit_code(), None);
+        assert!(matches!(
+            conn.request::<_, EchoReply>(&MockCommand::Crash {}, &mut |_| {})
+                .await,
+            Err(SidecarError::Terminated)
+        ));
+        // Kept for the crash report
+        assert_eq!(conn.exit_code(), Some(1));
+        assert_eq!(conn.stderr_tail(), vec!["warming up".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_stderr_tail_is_bounded() {
+        let mut conn = mock_sidecar(MockMode::Versioned);
+        conn.handshake(&[], TIMEOUT).await.unwrap();
+        for i in 0..STDERR_TAIL_LINES + 5 {
+            let _: EchoReply = conn
+                .request(&echo(&i.to_string()), &mut |_| {})
+                .await
+                .unwrap();
+        }
+        let tail = conn.stderr_tail();
+        assert_eq!(tail.len(), STDERR_TAIL_LINES);
+        assert!(tail.iter().all(|line| line == "warming up"));
+    }
+}
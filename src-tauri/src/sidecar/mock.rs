@@ -0,0 +1,60 @@
+// In-process stand-ins for sidecar binaries, shared by the connection and engine
+// client tests. A mock answers every request line with the events its responder
+// returns; an empty answer leaves the request hanging.
+
+use serde_json::Value;
+use tauri::async_runtime::Receiver;
+use tauri_plugin_shell::process::{CommandEvent, TerminatedPayload};
+use tokio::sync::mpsc;
+
+use super::connection::LineWriter;
+
+/// Requests written by the host, as parsed JSON
+pub struct ChannelWriter(mpsc::UnboundedSender<Value>);
+
+impl LineWriter for ChannelWriter {
+    fn write_line(&mut self, line: &[u8]) -> Result<(), String> {
+        let value = serde_json::from_slice(line).map_err(|e| e.to_string())?;
+        self.0.send(value).map_err(|e| e.to_string())
+    }
+
+    fn kill(self: Box<Self>) -> Result<(), String> {
+        // Dropping the sender stops the mock
+        Ok(())
+    }
+}
+
+/// A stdout line carrying `value`
+pub fn out(value: Value) -> CommandEvent {
+    CommandEvent::Stdout(value.to_string().into_bytes())
+}
+
+/// The process exiting with `code`
+pub fn terminated(code: i32) -> CommandEvent {
+    CommandEvent::Terminated(TerminatedPayload {
+        code: Some(code),
+        signal: None,
+    })
+}
+
+/// Start a mock sidecar answering each request with `respond(request)`
+pub fn spawn<F>(mut respond: F) -> (Receiver<CommandEvent>, ChannelWriter)
+where
+    F: FnMut(&Value) -> Vec<CommandEvent> + Send + 'static,
+{
+    let (request_tx, mut request_rx) = mpsc::unbounded_channel::<Value>();
+    let (event_tx, event_rx) = mpsc::channel(32);
+
+    tokio::spawn(async move {
+        while let Some(request) = request_rx.recv().await {
+            for reply in respond(&request) {
+                let exited = matches!(reply, CommandEvent::Terminated(_));
+                if event_tx.send(reply).await.is_err() || exited {
+                    return;
+                }
+            }
+        }
+    });
+
+    (event_rx, ChannelWriter(request_tx))
+}
@@ -0,0 +1,12 @@
+// Engine-neutral plumbing for speech-to-text sidecar processes. Engine modules
+// (e.g. `parakeet`) define their own command and reply types on top of it.
+
+pub mod connection;
+#[cfg(test)]
+pub mod mock;
+pub mod protocol;
+pub mod registry;
+pub mod supervisor;
+
+pub use connection::{SidecarConnection, SidecarError};
+pub use protocol::SidecarEvent;
@@ -0,0 +1,241 @@
+// Versioned JSON-lines protocol spoken by speech-to-text sidecars.
+//
+// Every request carries `v` (protocol version) and `id`; every message the sidecar
+// writes back for that request echoes the `id`. A request may produce any number of
+// `progress` and `partial` messages before exactly one final reply (or `error`).
+// Sidecars that predate the protocol answer the `hello` handshake with an
+// `unknown_command` error and are driven in legacy mode: no ids, the next line is
+// the reply.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Protocol revision this host speaks
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Revisions the host can drive; legacy (unversioned) sidecars are version 0
+pub const SUPPORTED_VERSIONS: &[u32] = &[1];
+
+/// Error code of a sidecar that does not know the command it was sent
+pub const UNKNOWN_COMMAND: &str = "unknown_command";
+
+/// A command wrapped with its version and correlation id
+#[derive(Serialize)]
+pub struct Envelope<'a, C: Serialize> {
+    pub v: u32,
+    pub id: u64,
+    #[serde(flatten)]
+    pub command: &'a C,
+}
+
+/// First request on a new connection
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename = "hello")]
+pub struct Hello {
+    pub client: String,
+    pub protocol_versions: Vec<u32>,
+}
+
+impl Default for Hello {
+    fn default() -> Self {
+        Self {
+            client: "verity".to_string(),
+            protocol_versions: SUPPORTED_VERSIONS.to_vec(),
+        }
+    }
+}
+
+/// What the sidecar reported during the handshake
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SidecarInfo {
+    #[serde(default)]
+    pub protocol_version: u32,
+    #[serde(default)]
+    pub engine: Option<String>,
+    #[serde(default)]
+    pub engine_version: Option<String>,
+    /// Commands and features, e.g. "transcribe", "progress", "partial"
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+}
+
+impl SidecarInfo {
+    /// A sidecar that did not understand the handshake
+    pub fn legacy() -> Self {
+        Self {
+            protocol_version: 0,
+            engine: None,
+            engine_version: None,
+            capabilities: Vec::new(),
+        }
+    }
+
+    pub fn is_versioned(&self) -> bool {
+        self.protocol_version > 0
+    }
+
+    pub fn supports(&self, capability: &str) -> bool {
+        self.capabilities.iter().any(|c| c == capability)
+    }
+}
+
+/// Streamed, non-final messages for an in-flight request
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SidecarEvent {
+    /// `progress` is a 0.0-1.0 fraction when the sidecar knows it
+    Progress {
+        progress: Option<f32>,
+        message: Option<String>,
+    },
+    /// Interim transcript text; each one replaces the previous
+    Partial { text: String },
+}
+
+/// One line from the sidecar, classified
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    Event {
+        id: Option<u64>,
+        event: SidecarEvent,
+    },
+    Error {
+        id: Option<u64>,
+        code: String,
+        message: String,
+    },
+    /// Final reply; the value still holds `type` and is decoded by the caller
+    Reply { id: Option<u64>, value: Value },
+}
+
+impl Message {
+    pub fn id(&self) -> Option<u64> {
+        match self {
+            Message::Event { id, .. } | Message::Error { id, .. } | Message::Reply { id, .. } => {
+                *id
+            }
+        }
+    }
+}
+
+pub fn parse_line(line: &str) -> Result<Message, String> {
+    let value: Value = serde_json::from_str(line).map_err(|e| e.to_string())?;
+    let object = value
+        .as_object()
+        .ok_or_else(|| "message is not a JSON object".to_string())?;
+    let id = object.get("id").and_then(Value::as_u64);
+    let field = |name: &str| object.get(name).and_then(Value::as_str).map(String::from);
+
+    let message = match object.get("type").and_then(Value::as_str) {
+        Some("progress") => Message::Event {
+            id,
+            event: SidecarEvent::Progress {
+                progress: object
+                    .get("progress")
+                    .and_then(Value::as_f64)
+                    .map(|p| p.clamp(0.0, 1.0) as f32),
+                message: field("message"),
+            },
+        },
+        Some("partial") => Message::Event {
+            id,
+            event: SidecarEvent::Partial {
+                text: field("text").unwrap_or_default(),
+            },
+        },
+        Some("error") => Message::Error {
+            id,
+            code: field("code").unwrap_or_else(|| "unknown".to_string()),
+            message: field("message").unwrap_or_default(),
+        },
+        Some(_) => Message::Reply { id, value },
+        None => return Err("message has no type".to_string()),
+    };
+    Ok(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    #[serde(tag = "type", rename_all = "snake_case")]
+    enum TestCommand {
+        Transcribe { audio_path: String },
+    }
+
+    #[test]
+    fn test_envelope_flattens_command() {
+        let command = TestCommand::Transcribe {
+            audio_path: "/tmp/a.wav".to_string(),
+        };
+        let json = serde_json::to_value(Envelope {
+            v: PROTOCOL_VERSION,
+            id: 7,
+            command: &command,
+        })
+        .unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"v": 1, "id": 7, "type": "transcribe", "audio_path": "/tmp/a.wav"})
+        );
+
+        let hello = serde_json::to_value(Hello::default()).unwrap();
+        assert_eq!(hello["type"], "hello");
+        assert_eq!(hello["protocol_versions"], serde_json::json!([1]));
+    }
+
+    #[test]
+    fn test_parse_messages() {
+        assert_eq!(
+            parse_line(r#"{"type":"progress","id":3,"progress":1.5,"message":"Downloading"}"#)
+                .unwrap(),
+            Message::Event {
+                id: Some(3),
+                event: SidecarEvent::Progress {
+                    progress: Some(1.0),
+                    message: Some("Downloading".to_string())
+                }
+            }
+        );
+        assert_eq!(
+            parse_line(r#"{"type":"partial","id":4,"text":"hello wor"}"#).unwrap(),
+            Message::Event {
+                id: Some(4),
+                event: SidecarEvent::Partial {
+                    text: "hello wor".to_string()
+                }
+            }
+        );
+        // Legacy sidecars send no id
+        assert_eq!(
+            parse_line(r#"{"type":"error","code":"unknown_command","message":"Unknown"}"#).unwrap(),
+            Message::Error {
+                id: None,
+                code: "unknown_command".to_string(),
+                message: "Unknown".to_string()
+            }
+        );
+        let reply = parse_line(r#"{"type":"status","id":5,"loadedModel":null}"#).unwrap();
+        assert_eq!(reply.id(), Some(5));
+
+        assert!(parse_line("not json").is_err());
+        assert!(parse_line(r#"{"id":1}"#).is_err());
+        assert!(parse_line("[1,2]").is_err());
+    }
+
+    #[test]
+    fn test_sidecar_info() {
+        let info: SidecarInfo = serde_json::from_value(serde_json::json!({
+            "type": "hello",
+            "protocol_version": 1,
+            "engine": "parakeet",
+            "capabilities": ["transcribe", "progress"]
+        }))
+        .unwrap();
+        assert!(info.is_versioned());
+        assert!(info.supports("progress"));
+        assert!(!info.supports("partial"));
+        assert!(!SidecarInfo::legacy().is_versioned());
+    }
+}
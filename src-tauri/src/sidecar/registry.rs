@@ -0,0 +1,214 @@
+// Engine binaries registered by the user, stored under `sidecar_engines` in the
+// settings store. A registered binary replaces the bundled sidecar for its engine
+// and has to speak the sidecar protocol.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use tauri::async_runtime::Receiver;
+use tauri::AppHandle;
+use tauri_plugin_shell::process::CommandEvent;
+use tauri_plugin_shell::ShellExt;
+
+use super::connection::LineWriter;
+use crate::commands::settings::{load_setting, save_setting};
+
+/// Engines that can run in a sidecar process
+pub const ENGINES: &[&str] = &["parakeet"];
+
+const SETTINGS_KEY: &str = "sidecar_engines";
+
+/// A user-provided engine executable
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExternalEngine {
+    pub path: PathBuf,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+impl ExternalEngine {
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.path.is_absolute() {
+            return Err("Engine binaries must be given by absolute path".to_string());
+        }
+        if !self.path.is_file() {
+            return Err(format!("Engine binary not found: {}", self.path.display()));
+        }
+        Ok(())
+    }
+}
+
+/// Registered binaries by engine name
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct EngineRegistry {
+    engines: BTreeMap<String, ExternalEngine>,
+}
+
+impl EngineRegistry {
+    pub fn load(app: &AppHandle) -> Self {
+        load_setting(app, SETTINGS_KEY)
+    }
+
+    pub fn save(&self, app: &AppHandle) -> Result<(), String> {
+        save_setting(app, SETTINGS_KEY, self)
+    }
+
+    pub fn get(&self, engine: &str) -> Option<&ExternalEngine> {
+        self.engines.get(engine)
+    }
+
+    pub fn register(&mut self, engine: &str, binary: ExternalEngine) -> Result<(), String> {
+        if !ENGINES.contains(&engine) {
+            return Err(format!("Unknown sidecar engine '{}'", engine));
+        }
+        binary.validate()?;
+        self.engines.insert(engine.to_string(), binary);
+        Ok(())
+    }
+
+    pub fn unregister(&mut self, engine: &str) -> Option<ExternalEngine> {
+        self.engines.remove(engine)
+    }
+}
+
+/// How an engine's process is started
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EngineBinary {
+    /// Shipped with the app as an `externalBin`
+    Bundled(String),
+    External(ExternalEngine),
+}
+
+impl EngineBinary {
+    /// The binary registered for `engine`, else the bundled one if the platform has it
+    pub fn resolve(app: &AppHandle, engine: &str, bundled: Option<&str>) -> Option<Self> {
+        match EngineRegistry::load(app).get(engine) {
+            Some(external) => Some(EngineBinary::External(external.clone())),
+            None => bundled.map(|name| EngineBinary::Bundled(name.to_string())),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            EngineBinary::Bundled(name) => name.clone(),
+            EngineBinary::External(external) => external.path.display().to_string(),
+        }
+    }
+}
+
+/// A freshly started sidecar process
+pub struct Launched {
+    pub pid: u32,
+    pub rx: Receiver<CommandEvent>,
+    pub writer: Box<dyn LineWriter>,
+}
+
+/// Starts sidecar processes; the shell plugin in production, mocks in tests
+pub trait Launch: Send + Sync {
+    /// Name used in logs and errors
+    fn name(&self) -> String;
+
+    fn launch(&self) -> Result<Launched, String>;
+}
+
+pub struct ShellLauncher {
+    app: AppHandle,
+    binary: EngineBinary,
+}
+
+impl ShellLauncher {
+    pub fn new(app: &AppHandle, binary: EngineBinary) -> Self {
+        Self {
+            app: app.clone(),
+            binary,
+        }
+    }
+}
+
+impl Launch for ShellLauncher {
+    fn name(&self) -> String {
+        self.binary.name()
+    }
+
+    fn launch(&self) -> Result<Launched, String> {
+        let command = match &self.binary {
+            // The externalBin entry in tauri.conf.json must include this binary
+            EngineBinary::Bundled(name) => {
+                self.app.shell().sidecar(name).map_err(|e| e.to_string())?
+            }
+            EngineBinary::External(external) => self
+                .app
+                .shell()
+                .command(&external.path)
+                .args(&external.args),
+        };
+        let (rx, child) = command.spawn().map_err(|e| e.to_string())?;
+        Ok(Launched {
+            pid: child.pid(),
+            rx,
+            writer: Box::new(child),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_register_validates_engine_and_binary() {
+        let temp_dir = TempDir::new().unwrap();
+        let binary = temp_dir.path().join("my-engine");
+        std::fs::write(&binary, b"#!/bin/sh\n").unwrap();
+
+        let mut registry = EngineRegistry::default();
+        let engine = ExternalEngine {
+            path: binary.clone(),
+            args: vec!["--stdio".to_string()],
+        };
+        assert!(registry.register("whisper", engine.clone()).is_err());
+        assert!(registry
+            .register(
+                "parakeet",
+                ExternalEngine {
+                    path: PathBuf::from("my-engine"),
+                    args: Vec::new(),
+                }
+            )
+            .unwrap_err()
+            .contains("absolute"));
+        assert!(registry
+            .register(
+                "parakeet",
+                ExternalEngine {
+                    path: temp_dir.path().join("missing"),
+                    args: Vec::new(),
+                }
+            )
+            .unwrap_err()
+            .contains("not found"));
+
+        registry.register("parakeet", engine.clone()).unwrap();
+        assert_eq!(registry.get("parakeet"), Some(&engine));
+        assert_eq!(registry.unregister("parakeet"), Some(engine));
+        assert_eq!(registry, EngineRegistry::default());
+    }
+
+    #[test]
+    fn test_registry_is_stored_as_a_map() {
+        let value = serde_json::json!({
+            "parakeet": { "path": "/opt/engines/parakeet" }
+        });
+        let registry: EngineRegistry = serde_json::from_value(value.clone()).unwrap();
+        let engine = registry.get("parakeet").unwrap();
+        assert_eq!(engine.path, PathBuf::from("/opt/engines/parakeet"));
+        assert!(engine.args.is_empty());
+        assert_eq!(
+            serde_json::to_value(&registry).unwrap(),
+            serde_json::json!({ "parakeet": { "path": "/opt/engines/parakeet", "args": [] } })
+        );
+    }
+}
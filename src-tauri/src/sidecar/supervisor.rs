@@ -0,0 +1,222 @@
+// Restart bookkeeping for supervised sidecars: crash counts, exponential backoff
+// between restarts and a snapshot for the diagnostics view. The engine client owns
+// the process itself and reports what happened to it here.
+
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use super::protocol::SidecarInfo;
+
+/// How quickly a crashed sidecar is brought back
+#[derive(Debug, Clone, Copy)]
+pub struct RestartPolicy {
+    /// Wait before the first restart after a crash
+    pub initial_delay: Duration,
+    /// Upper bound for the doubling delay
+    pub max_delay: Duration,
+    /// A sidecar that ran this long before crashing resets the backoff
+    pub stable_after: Duration,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            stable_after: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RestartPolicy {
+    /// Delay before restarting after `consecutive_crashes` crashes in a row
+    pub fn delay(&self, consecutive_crashes: u32) -> Duration {
+        if consecutive_crashes == 0 {
+            return Duration::ZERO;
+        }
+        let factor = 1u32 << (consecutive_crashes - 1).min(16);
+        self.initial_delay
+            .saturating_mul(factor)
+            .min(self.max_delay)
+    }
+}
+
+/// Sidecar health as shown in diagnostics
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SidecarHealth {
+    pub running: bool,
+    pub pid: Option<u32>,
+    pub protocol_version: Option<u32>,
+    pub engine_version: Option<String>,
+    pub crash_count: u32,
+    pub consecutive_crashes: u32,
+    pub restart_count: u32,
+    pub timeout_count: u32,
+    pub last_crash_at: Option<String>,
+    pub last_crash_reason: Option<String>,
+    pub last_exit_code: Option<i32>,
+    /// Final stderr lines of the last crashed process
+    pub last_stderr: Vec<String>,
+    pub last_health_check_at: Option<String>,
+    pub last_health_check_ok: Option<bool>,
+}
+
+pub struct Supervisor {
+    policy: RestartPolicy,
+    health: SidecarHealth,
+    started_at: Option<Instant>,
+    /// Set by a crash; the next start waits out the backoff and counts as a restart
+    pending_restart: bool,
+}
+
+impl Supervisor {
+    pub fn new(policy: RestartPolicy) -> Self {
+        Self {
+            policy,
+            health: SidecarHealth::default(),
+            started_at: None,
+            pending_restart: false,
+        }
+    }
+
+    /// Whether the next start replaces a crashed process
+    pub fn is_restarting(&self) -> bool {
+        self.pending_restart
+    }
+
+    /// How long to wait before the next start
+    pub fn restart_delay(&self) -> Duration {
+        if self.pending_restart {
+            self.policy.delay(self.health.consecutive_crashes)
+        } else {
+            Duration::ZERO
+        }
+    }
+
+    pub fn started(&mut self, pid: u32, info: &SidecarInfo) {
+        if self.pending_restart {
+            self.health.restart_count += 1;
+            self.pending_restart = false;
+        }
+        self.started_at = Some(Instant::now());
+        self.health.running = true;
+        self.health.pid = Some(pid);
+        self.health.protocol_version = Some(info.protocol_version);
+        self.health.engine_version = info.engine_version.clone();
+    }
+
+    /// Record a crash (or a failed restart, which has no process to report on)
+    pub fn crashed(&mut self, reason: &str, exit_code: Option<i32>, stderr: Vec<String>) {
+        let stable = self
+            .started_at
+            .take()
+            .is_some_and(|started| started.elapsed() >= self.policy.stable_after);
+        self.health.consecutive_crashes = if stable {
+            1
+        } else {
+            self.health.consecutive_crashes + 1
+        };
+        self.health.crash_count += 1;
+        self.health.running = false;
+        self.health.pid = None;
+        self.health.last_crash_at = Some(chrono::Utc::now().to_rfc3339());
+        self.health.last_crash_reason = Some(reason.to_string());
+        self.health.last_exit_code = exit_code;
+        if !stderr.is_empty() {
+            self.health.last_stderr = stderr;
+        }
+        self.pending_restart = true;
+    }
+
+    /// A request outlived its timeout and the process was killed
+    pub fn timed_out(&mut self, reason: &str, stderr: Vec<String>) {
+        self.health.timeout_count += 1;
+        self.crashed(reason, None, stderr);
+    }
+
+    /// Deliberate shutdown; not a crash
+    pub fn stopped(&mut self) {
+        self.started_at = None;
+        self.health.running = false;
+        self.health.pid = None;
+    }
+
+    pub fn health_checked(&mut self, ok: bool) {
+        self.health.last_health_check_at = Some(chrono::Utc::now().to_rfc3339());
+        self.health.last_health_check_ok = Some(ok);
+    }
+
+    pub fn snapshot(&self) -> SidecarHealth {
+        self.health.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_up_to_max() {
+        let policy = RestartPolicy::default();
+        assert_eq!(policy.delay(0), Duration::ZERO);
+        assert_eq!(policy.delay(1), Duration::from_millis(500));
+        assert_eq!(policy.delay(2), Duration::from_secs(1));
+        assert_eq!(policy.delay(4), Duration::from_secs(4));
+        assert_eq!(policy.delay(7), Duration::from_secs(30));
+        assert_eq!(policy.delay(u32::MAX), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_crash_loop_escalates_and_restarts_are_counted() {
+        let mut supervisor = Supervisor::new(RestartPolicy {
+            stable_after: Duration::from_secs(3600),
+            ..RestartPolicy::default()
+        });
+        let info = SidecarInfo::legacy();
+        supervisor.started(10, &info);
+        assert!(!supervisor.is_restarting());
+        assert_eq!(supervisor.restart_delay(), Duration::ZERO);
+
+        supervisor.crashed("terminated", Some(6), vec!["fatal".to_string()]);
+        assert!(supervisor.is_restarting());
+        assert_eq!(supervisor.restart_delay(), Duration::from_millis(500));
+
+        // A failed restart escalates the backoff but keeps the last stderr
+        supervisor.crashed("spawn failed", None, Vec::new());
+        assert_eq!(supervisor.restart_delay(), Duration::from_secs(1));
+
+        supervisor.started(11, &info);
+        let health = supervisor.snapshot();
+        assert!(health.running);
+        assert_eq!(health.pid, Some(11));
+        assert_eq!(health.crash_count, 2);
+        assert_eq!(health.restart_count, 1);
+        assert_eq!(health.last_exit_code, None);
+        assert_eq!(health.last_stderr, vec!["fatal".to_string()]);
+        assert_eq!(health.last_crash_reason.as_deref(), Some("spawn failed"));
+    }
+
+    #[test]
+    fn test_stable_run_resets_backoff() {
+        let mut supervisor = Supervisor::new(RestartPolicy {
+            stable_after: Duration::ZERO,
+            ..RestartPolicy::default()
+        });
+        let info = SidecarInfo::legacy();
+        for pid in 0..3 {
+            supervisor.started(pid, &info);
+            supervisor.timed_out("timeout", Vec::new());
+            assert_eq!(supervisor.restart_delay(), Duration::from_millis(500));
+        }
+        let health = supervisor.snapshot();
+        assert_eq!(health.consecutive_crashes, 1);
+        assert_eq!(health.timeout_count, 3);
+        assert!(!health.running);
+
+        supervisor.started(3, &info);
+        supervisor.stopped();
+        assert!(!supervisor.is_restarting());
+        assert_eq!(supervisor.snapshot().restart_count, 3);
+    }
+}
@@ -101,6 +101,25 @@ impl UnifiedRecordingState {
         }
     }
 
+    /// Transition only if the current state is still `expected`. Lets concurrent
+    /// paths (a hotkey press and an auto-stop) claim a transition exactly once.
+    pub fn transition_if(
+        &self,
+        expected: RecordingState,
+        new_state: RecordingState,
+    ) -> Result<bool, String> {
+        let mut guard = self.lock_or_recover()?;
+        if guard.current != expected {
+            return Ok(false);
+        }
+        guard
+            .machine
+            .transition_to(new_state)
+            .map_err(|e| e.to_string())?;
+        guard.current = new_state;
+        Ok(true)
+    }
+
     /// Lock the state, recovering from poison if necessary
     fn lock_or_recover(&self) -> Result<MutexGuard<'_, UnifiedStateInner>, String> {
         match self.inner.lock() {
@@ -165,4 +184,29 @@ mod tests {
         state.force_set(RecordingState::Recording).unwrap();
         assert_eq!(state.current(), RecordingState::Recording);
     }
+
+    #[test]
+    fn test_unified_state_transition_if_claims_once() {
+        let state = UnifiedRecordingState::new();
+        state.transition_to(RecordingState::Starting).unwrap();
+        state.transition_to(RecordingState::Recording).unwrap();
+
+        // Auto-stop and a hotkey press race to stop the same recording
+        assert_eq!(
+            state.transition_if(RecordingState::Recording, RecordingState::Stopping),
+            Ok(true)
+        );
+        assert_eq!(
+            state.transition_if(RecordingState::Recording, RecordingState::Stopping),
+            Ok(false)
+        );
+        assert_eq!(state.current(), RecordingState::Stopping);
+
+        // A matching state with an invalid target is still refused
+        state.force_set(RecordingState::Idle).unwrap();
+        assert!(state
+            .transition_if(RecordingState::Idle, RecordingState::Transcribing)
+            .is_err());
+        assert_eq!(state.current(), RecordingState::Idle);
+    }
 }
@@ -28,6 +28,9 @@ mod test_data_helpers;
 #[cfg(test)]
 mod logging_performance_tests;
 
+#[cfg(test)]
+mod model_compare;
+
 #[cfg(test)]
 mod integration_tests {
     use crate::whisper::manager::{ModelSize, WhisperManager};
@@ -67,10 +70,12 @@ mod integration_tests {
 
         let mut cache = TranscriberCache::new();
         assert_eq!(cache.size(), 0);
+        // Only the memory budget limits the default cache
+        assert_eq!(cache.capacity(), None);
 
         // Test with custom capacity
         let cache_large = TranscriberCache::with_capacity(5);
-        assert_eq!(cache_large.capacity(), 5);
+        assert_eq!(cache_large.capacity(), Some(5));
 
         // Clear should work on empty cache
         cache.clear();
@@ -33,6 +33,7 @@ mod tests {
             size: 100 * 1024 * 1024, // 100MB
             url: "https://example.com/model.bin".to_string(),
             sha256: "abc123".to_string(),
+            sha1: String::new(),
             downloaded: false,
             speed_score: 5,
             accuracy_score: 5,
@@ -50,6 +51,7 @@ mod tests {
             size: 1024, // 1KB - too small
             url: "https://example.com/model.bin".to_string(),
             sha256: "abc123".to_string(),
+            sha1: String::new(),
             downloaded: false,
             speed_score: 5,
             accuracy_score: 5,
@@ -86,6 +88,7 @@ mod tests {
             size: 100 * 1024 * 1024,
             url: "https://example.com/model.bin".to_string(),
             sha256: "abc123".to_string(),
+            sha1: String::new(),
             downloaded: true,
             speed_score: 7,
             accuracy_score: 8,
@@ -247,6 +250,63 @@ mod tests {
         assert!(!status.get("large-v3").unwrap().downloaded);
     }
 
+    #[test]
+    fn test_custom_model_registration() {
+        use crate::whisper::catalog::CustomModelEntry;
+        use crate::whisper::model_file::{ModelFileInfo, ModelFormat, WhisperHyperparams};
+
+        let temp_dir = TempDir::new().unwrap();
+        let models_dir = temp_dir.path().join("models");
+        std::fs::create_dir_all(&models_dir).unwrap();
+
+        let mut manager = WhisperManager::new_for_test(models_dir.clone());
+        let name = manager.custom_model_name("Medical Small");
+        assert_eq!(name, "custom-medical-small");
+
+        let info = ModelFileInfo {
+            format: ModelFormat::Ggml,
+            hyperparams: WhisperHyperparams {
+                n_vocab: 51865,
+                n_audio_layer: 12,
+                n_text_layer: 12,
+                ftype: 1,
+                ..Default::default()
+            },
+            multilingual: true,
+            quantization: "f16".to_string(),
+            size_class: "small".to_string(),
+            file_size: 512,
+        };
+        std::fs::write(models_dir.join(format!("{}.bin", name)), vec![0u8; 512]).unwrap();
+        let entry = CustomModelEntry::new(
+            name.clone(),
+            "Medical Small".to_string(),
+            "ggml-medical-small.bin".to_string(),
+            "hash".to_string(),
+            &info,
+        );
+        manager.register_custom_model(entry.clone()).unwrap();
+        assert!(manager.register_custom_model(entry).is_err());
+
+        assert!(manager.is_custom_model(&name));
+        assert!(manager.list_downloaded_files().contains(&name));
+        assert!(manager.get_model_path(&name).is_some());
+        // Imported models have no URL to download from
+        assert!(manager.get_model_info(&name).is_err());
+
+        // The catalog brings the model back on the next start
+        let reloaded = WhisperManager::new_for_test(models_dir.clone());
+        let status = reloaded.get_models_status();
+        assert_eq!(status.get(&name).unwrap().display_name, "Medical Small");
+        assert!(status.get(&name).unwrap().downloaded);
+
+        // Deleting removes the file and the catalog entry
+        manager.delete_model_file(&name).unwrap();
+        assert!(!manager.get_models_status().contains_key(&name));
+        let reloaded = WhisperManager::new_for_test(models_dir);
+        assert!(!reloaded.is_custom_model(&name));
+    }
+
     #[test]
     fn test_model_scores() {
         let temp_dir = TempDir::new().unwrap();
@@ -299,8 +359,32 @@ mod tests {
             assert!(model.url.contains("whisper.cpp"));
             assert!(model.url.ends_with(&format!("{}.bin", name)));
 
-            // Verify SHA256 field (actually contains SHA1) is 40 characters
-            assert_eq!(model.sha256.len(), 40);
+            // Every catalog model has a SHA-256; SHA-1 is legacy only
+            assert_eq!(model.sha256.len(), 64);
+            assert!(model.sha1.is_empty() || model.sha1.len() == 40);
         }
     }
+
+    #[test]
+    fn test_models_storage_on_removable_drive() {
+        let temp_dir = TempDir::new().unwrap();
+        let drive = temp_dir.path().join("drive/models");
+        std::fs::create_dir_all(&drive).unwrap();
+
+        let mut manager = WhisperManager::new(temp_dir.path().join("unused"));
+        assert!(manager.storage_error().is_some());
+        manager.set_models_dir(drive.clone());
+        assert!(manager.storage_error().is_none());
+        assert!(!manager.recheck_storage());
+
+        // Drive unplugged: a clear error instead of "model not found"
+        std::fs::remove_dir_all(temp_dir.path().join("drive")).unwrap();
+        assert!(manager.recheck_storage());
+        assert!(manager.storage_error().unwrap().contains("Reconnect"));
+        assert!(manager.get_model_path("base.en").is_none());
+
+        std::fs::create_dir_all(&drive).unwrap();
+        assert!(manager.recheck_storage());
+        assert!(manager.storage_error().is_none());
+    }
 }
@@ -0,0 +1,59 @@
+#[cfg(test)]
+mod tests {
+    use crate::commands::compare::CompareModel;
+    use crate::utils::word_diff::{changed_word_count, diff_words, normalize_words, DiffOp};
+
+    #[test]
+    fn test_normalize_words_ignores_case_and_punctuation() {
+        assert_eq!(
+            normalize_words("Hello, World! Don't  stop."),
+            vec!["hello", "world", "don't", "stop"]
+        );
+        assert!(normalize_words(" -- ").is_empty());
+        // Same word boundaries the benchmark scores WER on
+        assert_eq!(normalize_words("well-known"), vec!["well", "known"]);
+    }
+
+    #[test]
+    fn test_identical_transcripts_have_no_changes() {
+        let diff = diff_words("The quick brown fox.", "the quick brown fox");
+        assert_eq!(diff.len(), 1);
+        assert_eq!(diff[0].op, DiffOp::Equal);
+        assert_eq!(diff[0].text, "the quick brown fox");
+        assert_eq!(changed_word_count(&diff), 0);
+    }
+
+    #[test]
+    fn test_substitution_shows_delete_and_insert() {
+        let diff = diff_words("send the report today", "send a report today");
+        let ops: Vec<(DiffOp, &str)> = diff.iter().map(|d| (d.op, d.text.as_str())).collect();
+        assert_eq!(
+            ops,
+            vec![
+                (DiffOp::Equal, "send"),
+                (DiffOp::Delete, "the"),
+                (DiffOp::Insert, "a"),
+                (DiffOp::Equal, "report today"),
+            ]
+        );
+        assert_eq!(changed_word_count(&diff), 2);
+    }
+
+    #[test]
+    fn test_trailing_words_are_grouped() {
+        let diff = diff_words("one two", "one two three four");
+        assert_eq!(diff.last().unwrap().op, DiffOp::Insert);
+        assert_eq!(diff.last().unwrap().text, "three four");
+        assert_eq!(changed_word_count(&diff), 2);
+
+        let diff = diff_words("", "hello");
+        assert_eq!(changed_word_count(&diff), 1);
+    }
+
+    #[test]
+    fn test_compare_model_engine_is_optional() {
+        let model: CompareModel = serde_json::from_str(r#"{"name": "base.en"}"#).unwrap();
+        assert_eq!(model.name, "base.en");
+        assert!(model.engine.is_none());
+    }
+}
@@ -0,0 +1,157 @@
+// Engine-neutral transcription result. Engines map their own output into this so
+// subtitles, confidence display and history playback do not care which one ran.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Transcript {
+    pub text: String,
+    /// Empty when the engine only returns plain text
+    #[serde(default)]
+    pub segments: Vec<TranscriptSegment>,
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Audio length in seconds
+    #[serde(default)]
+    pub duration: Option<f32>,
+}
+
+/// A stretch of speech; times are seconds from the start of the recording
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptSegment {
+    pub text: String,
+    #[serde(default)]
+    pub start: Option<f32>,
+    #[serde(default)]
+    pub end: Option<f32>,
+    /// 0.0-1.0
+    #[serde(default)]
+    pub confidence: Option<f32>,
+    #[serde(default)]
+    pub tokens: Vec<TranscriptToken>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptToken {
+    pub text: String,
+    #[serde(default)]
+    pub start: Option<f32>,
+    #[serde(default)]
+    pub end: Option<f32>,
+    #[serde(default)]
+    pub confidence: Option<f32>,
+}
+
+impl Transcript {
+    /// Result from an engine without timing information
+    pub fn plain(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Whether any segment can be placed on the timeline
+    pub fn has_timings(&self) -> bool {
+        self.segments.iter().any(TranscriptSegment::is_timed)
+    }
+
+    /// Render timed segments as SubRip subtitles
+    pub fn to_srt(&self) -> String {
+        let mut srt = String::new();
+        let timed = self.segments.iter().filter(|s| s.is_timed());
+        for (index, segment) in timed.enumerate() {
+            let (Some(start), Some(end)) = (segment.start, segment.end) else {
+                continue;
+            };
+            srt.push_str(&format!(
+                "{}\n{} --> {}\n{}\n\n",
+                index + 1,
+                srt_timestamp(start),
+                srt_timestamp(end),
+                segment.text.trim()
+            ));
+        }
+        srt
+    }
+}
+
+impl TranscriptSegment {
+    fn is_timed(&self) -> bool {
+        self.start.is_some() && self.end.is_some() && !self.text.trim().is_empty()
+    }
+
+    /// Fill in the segment confidence from its tokens when the engine left it out
+    pub fn with_token_confidence(mut self) -> Self {
+        if self.confidence.is_none() {
+            let scores: Vec<f32> = self.tokens.iter().filter_map(|t| t.confidence).collect();
+            if !scores.is_empty() {
+                self.confidence = Some(scores.iter().sum::<f32>() / scores.len() as f32);
+            }
+        }
+        self
+    }
+}
+
+fn srt_timestamp(seconds: f32) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02},{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(text: &str, start: Option<f32>, end: Option<f32>) -> TranscriptSegment {
+        TranscriptSegment {
+            text: text.to_string(),
+            start,
+            end,
+            ..TranscriptSegment::default()
+        }
+    }
+
+    #[test]
+    fn test_srt_skips_untimed_segments() {
+        let transcript = Transcript {
+            text: "Hello there. General Kenobi.".to_string(),
+            segments: vec![
+                segment(" Hello there.", Some(0.0), Some(1.25)),
+                segment("untimed", None, None),
+                segment("General Kenobi.", Some(3661.5), Some(3663.0)),
+            ],
+            ..Transcript::default()
+        };
+        assert!(transcript.has_timings());
+        assert_eq!(
+            transcript.to_srt(),
+            "1\n00:00:00,000 --> 00:00:01,250\nHello there.\n\n\
+             2\n01:01:01,500 --> 01:01:03,000\nGeneral Kenobi.\n\n"
+        );
+
+        let plain = Transcript::plain("just text");
+        assert!(!plain.has_timings());
+        assert!(plain.to_srt().is_empty());
+    }
+
+    #[test]
+    fn test_segment_confidence_from_tokens() {
+        let token = |confidence| TranscriptToken {
+            text: "a".to_string(),
+            confidence,
+            ..TranscriptToken::default()
+        };
+        let mut seg = segment("a a a", Some(0.0), Some(1.0));
+        seg.tokens = vec![token(Some(0.5)), token(None), token(Some(1.0))];
+        assert_eq!(seg.clone().with_token_confidence().confidence, Some(0.75));
+
+        seg.confidence = Some(0.2);
+        assert_eq!(seg.with_token_confidence().confidence, Some(0.2));
+    }
+}
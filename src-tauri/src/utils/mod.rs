@@ -4,3 +4,4 @@ pub mod logger;
 pub mod network_diagnostics;
 pub mod onboarding_logger;
 pub mod system_monitor;
+pub mod word_diff;
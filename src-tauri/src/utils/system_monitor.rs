@@ -83,6 +83,22 @@ fn get_current_resources() -> SystemResources {
     }
 }
 
+/// Total and available physical memory in MB
+pub fn memory_mb() -> (u64, u64) {
+    let mut system = match SYSTEM.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => {
+            log::error!("System monitor lock poisoned, recovering");
+            poisoned.into_inner()
+        }
+    };
+    system.refresh_memory();
+    (
+        system.total_memory() / 1_048_576,
+        system.available_memory() / 1_048_576,
+    )
+}
+
 /// Get available disk space in GB for the current working directory
 fn get_available_disk_space() -> f64 {
     // Try to get disk space for the current directory
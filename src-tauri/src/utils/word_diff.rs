@@ -0,0 +1,94 @@
+// Word-level alignment between transcripts, used to compare model outputs
+
+use serde::{Deserialize, Serialize};
+
+use crate::benchmark::metrics::normalize_text;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffOp {
+    /// Word present in both transcripts
+    Equal,
+    /// Word only in the candidate transcript
+    Insert,
+    /// Word only in the baseline transcript
+    Delete,
+}
+
+/// A run of consecutive words with the same diff operation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WordDiff {
+    pub op: DiffOp,
+    pub text: String,
+}
+
+/// Split a transcript into comparable words, normalized the same way the
+/// benchmark scores WER so a diff and an error rate never disagree
+pub fn normalize_words(text: &str) -> Vec<String> {
+    normalize_text(text)
+        .split_whitespace()
+        .map(str::to_string)
+        .collect()
+}
+
+/// Diff two transcripts word by word (LCS alignment on normalized words)
+pub fn diff_words(baseline: &str, candidate: &str) -> Vec<WordDiff> {
+    let a = normalize_words(baseline);
+    let b = normalize_words(candidate);
+
+    // lcs[i][j] = LCS length of a[i..] and b[j..]
+    let mut lcs = vec![vec![0u32; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diffs: Vec<WordDiff> = Vec::new();
+    let mut push = |op: DiffOp, word: &str| match diffs.last_mut() {
+        Some(last) if last.op == op => {
+            last.text.push(' ');
+            last.text.push_str(word);
+        }
+        _ => diffs.push(WordDiff {
+            op,
+            text: word.to_string(),
+        }),
+    };
+
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            push(DiffOp::Equal, &a[i]);
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            push(DiffOp::Delete, &a[i]);
+            i += 1;
+        } else {
+            push(DiffOp::Insert, &b[j]);
+            j += 1;
+        }
+    }
+    for word in &a[i..] {
+        push(DiffOp::Delete, word);
+    }
+    for word in &b[j..] {
+        push(DiffOp::Insert, word);
+    }
+
+    diffs
+}
+
+/// Number of words that differ (inserted or deleted) in a diff
+pub fn changed_word_count(diffs: &[WordDiff]) -> usize {
+    diffs
+        .iter()
+        .filter(|d| d.op != DiffOp::Equal)
+        .map(|d| d.text.split(' ').count())
+        .sum()
+}
@@ -0,0 +1,505 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read};
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+use super::catalog::CustomModelEntry;
+use super::manager::{ModelInfo, WhisperManager};
+
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// Bundle manifest, written as the last tar entry once all checksums are known
+const BUNDLE_MANIFEST: &str = "bundle.json";
+const MODELS_PREFIX: &str = "models/";
+const STAGING_SUFFIX: &str = ".bin.importing";
+const MAX_MANIFEST_BYTES: u64 = 1024 * 1024;
+const COPY_BUFFER: usize = 1024 * 1024;
+
+/// One model inside a bundle
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleModel {
+    pub name: String,
+    pub display_name: String,
+    pub size: u64,
+    /// SHA-256 of the file, computed at export time
+    pub sha256: String,
+    /// Checksum from the exporting machine's catalog (SHA-1 or SHA-256), if any
+    #[serde(default)]
+    pub catalog_checksum: String,
+    /// Present for imported (non-catalog) models so they can be registered again
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom: Option<CustomModelEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub version: u32,
+    pub created_at: String,
+    pub app_version: String,
+    pub models: Vec<BundleModel>,
+}
+
+/// A downloaded model to put into a bundle
+#[derive(Clone)]
+pub struct BundleSource {
+    pub info: ModelInfo,
+    pub path: PathBuf,
+    pub custom: Option<CustomModelEntry>,
+}
+
+/// Result of unpacking a bundle into the staging area
+pub struct ExtractedBundle {
+    pub manifest: BundleManifest,
+    /// Model name -> staged file awaiting verification
+    pub staged: HashMap<String, PathBuf>,
+}
+
+impl ExtractedBundle {
+    /// Remove every staged file that has not been installed
+    pub fn discard(&self) {
+        for path in self.staged.values() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+fn is_safe_model_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.contains("..")
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+}
+
+pub fn staging_path(models_dir: &Path, model_name: &str) -> PathBuf {
+    models_dir.join(format!("{}{}", model_name, STAGING_SUFFIX))
+}
+
+/// Passes everything read through it to `inspect`, for hashing and progress
+struct InspectReader<R, F> {
+    inner: R,
+    inspect: F,
+}
+
+impl<R: Read, F: FnMut(&[u8])> Read for InspectReader<R, F> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        (self.inspect)(&buf[..read]);
+        Ok(read)
+    }
+}
+
+fn file_header(size: u64) -> tar::Header {
+    let mut header = tar::Header::new_ustar();
+    header.set_entry_type(tar::EntryType::Regular);
+    header.set_size(size);
+    header.set_mode(0o644);
+    header.set_mtime(chrono::Utc::now().timestamp().max(0) as u64);
+    header
+}
+
+/// Package model files into a tar bundle with a checksum manifest
+pub async fn write_bundle(
+    output: &Path,
+    sources: &[BundleSource],
+    progress_callback: impl Fn(u64, u64) + Send + 'static,
+) -> Result<BundleManifest, String> {
+    if sources.is_empty() {
+        return Err("No models selected for export".to_string());
+    }
+
+    let part_path = PathBuf::from(format!("{}.part", output.display()));
+    let result = {
+        let part_path = part_path.clone();
+        let sources = sources.to_vec();
+        tokio::task::spawn_blocking(move || {
+            write_bundle_blocking(&part_path, &sources, &progress_callback)
+        })
+        .await
+        .map_err(|e| format!("Bundle export task failed: {}", e))
+        .and_then(|r| r)
+    };
+
+    match result {
+        Ok(manifest) => {
+            fs::rename(&part_path, output)
+                .await
+                .map_err(|e| format!("Failed to finalize bundle: {}", e))?;
+            Ok(manifest)
+        }
+        Err(e) => {
+            let _ = fs::remove_file(&part_path).await;
+            Err(e)
+        }
+    }
+}
+
+fn write_bundle_blocking(
+    part_path: &Path,
+    sources: &[BundleSource],
+    progress_callback: &dyn Fn(u64, u64),
+) -> Result<BundleManifest, String> {
+    let write_err = |e: std::io::Error| format!("Failed to write bundle: {}", e);
+    let out = File::create(part_path).map_err(|e| format!("Failed to create bundle: {}", e))?;
+    let mut builder = tar::Builder::new(BufWriter::with_capacity(COPY_BUFFER, out));
+
+    let total: u64 = sources.iter().map(|s| s.info.size).sum();
+    let mut models = Vec::with_capacity(sources.len());
+    let mut written = 0u64;
+
+    for source in sources {
+        let file = File::open(&source.path)
+            .map_err(|e| format!("Failed to open {}: {}", source.info.name, e))?;
+        let size = file
+            .metadata()
+            .map_err(|e| format!("Failed to read {}: {}", source.info.name, e))?
+            .len();
+
+        let mut hasher = Sha256::new();
+        let mut hashed = 0u64;
+        let reader = InspectReader {
+            inner: BufReader::with_capacity(COPY_BUFFER, file).take(size),
+            inspect: |data: &[u8]| {
+                hasher.update(data);
+                hashed += data.len() as u64;
+                progress_callback((written + hashed).min(total), total);
+            },
+        };
+        let entry = format!("{}{}.bin", MODELS_PREFIX, source.info.name);
+        builder
+            .append_data(&mut file_header(size), &entry, reader)
+            .map_err(write_err)?;
+        if hashed != size {
+            return Err(format!(
+                "{} changed size while it was being exported",
+                source.info.name
+            ));
+        }
+        written += size;
+
+        models.push(BundleModel {
+            name: source.info.name.clone(),
+            display_name: source.info.display_name.clone(),
+            size,
+            sha256: format!("{:x}", hasher.finalize()),
+            catalog_checksum: source.info.reference_checksum().to_string(),
+            custom: source.custom.clone(),
+        });
+    }
+
+    let manifest = BundleManifest {
+        version: BUNDLE_VERSION,
+        created_at: chrono::Local::now().to_rfc3339(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        models,
+    };
+    let manifest_json = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize bundle manifest: {}", e))?;
+    builder
+        .append_data(
+            &mut file_header(manifest_json.len() as u64),
+            BUNDLE_MANIFEST,
+            manifest_json.as_slice(),
+        )
+        .map_err(write_err)?;
+
+    let out = builder
+        .into_inner()
+        .map_err(write_err)?
+        .into_inner()
+        .map_err(|e| write_err(e.into_error()))?;
+    out.sync_all()
+        .map_err(|e| format!("Failed to flush bundle: {}", e))?;
+    Ok(manifest)
+}
+
+/// Unpack a bundle's model files into `<models_dir>/<name>.bin.importing`.
+/// Nothing is installed yet; see `verify_staged_model`.
+pub async fn extract_bundle(
+    bundle: &Path,
+    models_dir: &Path,
+    progress_callback: impl Fn(u64, u64) + Send + 'static,
+) -> Result<ExtractedBundle, String> {
+    let bundle = bundle.to_path_buf();
+    let models_dir = models_dir.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        extract_bundle_blocking(&bundle, &models_dir, &progress_callback)
+    })
+    .await
+    .map_err(|e| format!("Bundle import task failed: {}", e))?
+}
+
+fn extract_bundle_blocking(
+    bundle: &Path,
+    models_dir: &Path,
+    progress_callback: &dyn Fn(u64, u64),
+) -> Result<ExtractedBundle, String> {
+    let input = File::open(bundle).map_err(|e| format!("Failed to open bundle: {}", e))?;
+    let total = input
+        .metadata()
+        .map_err(|e| format!("Failed to read bundle: {}", e))?
+        .len();
+    std::fs::create_dir_all(models_dir)
+        .map_err(|e| format!("Failed to create models directory: {}", e))?;
+
+    let mut extracted = ExtractedBundle {
+        manifest: BundleManifest {
+            version: 0,
+            created_at: String::new(),
+            app_version: String::new(),
+            models: Vec::new(),
+        },
+        staged: HashMap::new(),
+    };
+    let mut manifest = None;
+
+    let mut read_total = 0u64;
+    let mut archive = tar::Archive::new(InspectReader {
+        inner: BufReader::with_capacity(COPY_BUFFER, input),
+        inspect: |data: &[u8]| {
+            read_total += data.len() as u64;
+            progress_callback(read_total.min(total), total);
+        },
+    });
+    let corrupt = |e: std::io::Error| format!("Bundle is corrupt: {}", e);
+
+    let result: Result<(), String> = (|| {
+        for entry in archive.entries().map_err(corrupt)? {
+            let mut entry = entry.map_err(corrupt)?;
+            let path = String::from_utf8_lossy(&entry.path_bytes()).to_string();
+            let size = entry.size();
+            let is_file = entry.header().entry_type().is_file();
+
+            let model_name = path
+                .strip_prefix(MODELS_PREFIX)
+                .and_then(|file| file.strip_suffix(".bin"))
+                .filter(|name| is_safe_model_name(name))
+                .map(str::to_string);
+
+            if is_file && path == BUNDLE_MANIFEST {
+                if size > MAX_MANIFEST_BYTES {
+                    return Err("Bundle manifest is too large".to_string());
+                }
+                let mut bytes = Vec::with_capacity(size as usize);
+                entry.read_to_end(&mut bytes).map_err(corrupt)?;
+                if bytes.len() as u64 != size {
+                    return Err("Bundle is truncated".to_string());
+                }
+                let parsed: BundleManifest = serde_json::from_slice(&bytes)
+                    .map_err(|e| format!("Invalid bundle manifest: {}", e))?;
+                if parsed.version != BUNDLE_VERSION {
+                    return Err(format!("Unsupported bundle version {}", parsed.version));
+                }
+                manifest = Some(parsed);
+            } else if let (true, Some(name)) = (is_file, model_name) {
+                let staged = staging_path(models_dir, &name);
+                let file = File::create(&staged)
+                    .map_err(|e| format!("Failed to stage {}: {}", name, e))?;
+                extracted.staged.insert(name, staged);
+
+                let mut sink = BufWriter::with_capacity(COPY_BUFFER, file);
+                let copied = std::io::copy(&mut entry, &mut sink)
+                    .map_err(|e| format!("Failed to write model file: {}", e))?;
+                if copied != size {
+                    return Err("Bundle is truncated".to_string());
+                }
+                sink.into_inner()
+                    .map_err(|e| format!("Failed to write model file: {}", e.error()))?
+                    .sync_all()
+                    .map_err(|e| format!("Failed to flush model file: {}", e))?;
+            } else {
+                // The archive skips over the unread data
+                log::warn!("Skipping unexpected bundle entry '{}'", path);
+            }
+        }
+        Ok(())
+    })();
+
+    if let Err(e) = result {
+        extracted.discard();
+        return Err(e);
+    }
+    match manifest {
+        Some(manifest) => {
+            extracted.manifest = manifest;
+            Ok(extracted)
+        }
+        None => {
+            extracted.discard();
+            Err("Bundle has no manifest".to_string())
+        }
+    }
+}
+
+/// Check a staged file against the bundle's SHA-256 and, when the model is in
+/// this machine's catalog, against the catalog checksum too. Deletes the staged
+/// file on mismatch.
+pub async fn verify_staged_model(
+    model: &BundleModel,
+    staged: &PathBuf,
+    catalog_checksum: Option<&str>,
+) -> Result<(), String> {
+    let size = fs::metadata(staged)
+        .await
+        .map_err(|e| format!("Staged model missing: {}", e))?
+        .len();
+    if size != model.size {
+        let _ = fs::remove_file(staged).await;
+        return Err(format!(
+            "Size mismatch for {}: expected {} bytes, got {}",
+            model.name, model.size, size
+        ));
+    }
+
+    WhisperManager::verify_sha256_checksum(staged, &model.sha256).await?;
+
+    match catalog_checksum {
+        Some(expected) if expected.len() == 40 => {
+            WhisperManager::verify_sha1_checksum(staged, expected).await
+        }
+        Some(expected) if expected.len() == 64 && expected != model.sha256 => {
+            WhisperManager::verify_sha256_checksum(staged, expected).await
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn source(dir: &Path, name: &str, data: &[u8], checksum: String) -> BundleSource {
+        let path = dir.join(format!("{}.bin", name));
+        std::fs::write(&path, data).unwrap();
+        BundleSource {
+            info: ModelInfo {
+                name: name.to_string(),
+                display_name: name.to_uppercase(),
+                size: data.len() as u64,
+                url: String::new(),
+                sha256: String::new(),
+                sha1: checksum,
+                downloaded: true,
+                speed_score: 5,
+                accuracy_score: 5,
+                recommended: false,
+            },
+            path,
+            custom: None,
+        }
+    }
+
+    fn sha1_hex(data: &[u8]) -> String {
+        use sha1::Sha1;
+        format!("{:x}", Sha1::digest(data))
+    }
+
+    #[tokio::test]
+    async fn test_bundle_roundtrip() {
+        let source_dir = TempDir::new().unwrap();
+        let target_dir = TempDir::new().unwrap();
+        let base: Vec<u8> = (0..3000u32).map(|i| (i % 251) as u8).collect();
+        let small = vec![7u8; 512];
+        let sources = vec![
+            source(source_dir.path(), "base.en", &base, sha1_hex(&base)),
+            source(source_dir.path(), "small.en", &small, String::new()),
+        ];
+
+        let bundle_path = source_dir.path().join("models.tar");
+        let manifest = write_bundle(&bundle_path, &sources, |_, _| {})
+            .await
+            .unwrap();
+        assert_eq!(manifest.models.len(), 2);
+        assert_eq!(manifest.models[0].size, 3000);
+
+        let extracted = extract_bundle(&bundle_path, target_dir.path(), |_, _| {})
+            .await
+            .unwrap();
+        assert_eq!(extracted.manifest, manifest);
+        assert_eq!(extracted.staged.len(), 2);
+
+        for model in &extracted.manifest.models {
+            let staged = &extracted.staged[&model.name];
+            let catalog =
+                (!model.catalog_checksum.is_empty()).then_some(model.catalog_checksum.as_str());
+            verify_staged_model(model, staged, catalog).await.unwrap();
+        }
+        assert_eq!(std::fs::read(&extracted.staged["base.en"]).unwrap(), base);
+    }
+
+    #[tokio::test]
+    async fn test_tampered_bundle_is_rejected() {
+        let source_dir = TempDir::new().unwrap();
+        let target_dir = TempDir::new().unwrap();
+        let data = vec![1u8; 2048];
+        let sources = vec![source(source_dir.path(), "base.en", &data, sha1_hex(&data))];
+
+        let bundle_path = source_dir.path().join("models.tar");
+        write_bundle(&bundle_path, &sources, |_, _| {})
+            .await
+            .unwrap();
+
+        // Flip a byte inside the model data (after the 512-byte header block)
+        let mut bytes = std::fs::read(&bundle_path).unwrap();
+        bytes[512 + 100] ^= 0xff;
+        std::fs::write(&bundle_path, bytes).unwrap();
+
+        let extracted = extract_bundle(&bundle_path, target_dir.path(), |_, _| {})
+            .await
+            .unwrap();
+        let model = &extracted.manifest.models[0];
+        let staged = extracted.staged[&model.name].clone();
+        assert!(verify_staged_model(model, &staged, None).await.is_err());
+        // Mismatched files are removed
+        assert!(!staged.exists());
+    }
+
+    #[tokio::test]
+    async fn test_catalog_checksum_mismatch() {
+        let source_dir = TempDir::new().unwrap();
+        let target_dir = TempDir::new().unwrap();
+        let data = vec![3u8; 1024];
+        let sources = vec![source(source_dir.path(), "base.en", &data, String::new())];
+
+        let bundle_path = source_dir.path().join("models.tar");
+        write_bundle(&bundle_path, &sources, |_, _| {})
+            .await
+            .unwrap();
+        let extracted = extract_bundle(&bundle_path, target_dir.path(), |_, _| {})
+            .await
+            .unwrap();
+
+        // The receiving machine's catalog expects a different file
+        let model = &extracted.manifest.models[0];
+        let wrong = "0".repeat(40);
+        assert!(
+            verify_staged_model(model, &extracted.staged["base.en"], Some(&wrong))
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rejects_truncated_bundle() {
+        let source_dir = TempDir::new().unwrap();
+        let target_dir = TempDir::new().unwrap();
+        let data = vec![5u8; 4096];
+        let sources = vec![source(source_dir.path(), "base.en", &data, String::new())];
+
+        let bundle_path = source_dir.path().join("models.tar");
+        write_bundle(&bundle_path, &sources, |_, _| {})
+            .await
+            .unwrap();
+        let bytes = std::fs::read(&bundle_path).unwrap();
+        std::fs::write(&bundle_path, &bytes[..2048]).unwrap();
+
+        let result = extract_bundle(&bundle_path, target_dir.path(), |_, _| {}).await;
+        assert!(result.is_err());
+        // Staged leftovers are cleaned up
+        assert!(!staging_path(target_dir.path(), "base.en").exists());
+    }
+}
@@ -1,26 +1,240 @@
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, VecDeque};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use super::transcriber::Transcriber;
 use crate::utils::logger::*;
 
-/// Maximum number of models to keep in cache
-/// Only cache the current model to minimize RAM usage (1-3GB per model)
-const MAX_CACHE_SIZE: usize = 1;
+/// Load/unload events kept for diagnostics
+const MAX_EVENTS: usize = 50;
 
-/// Simple LRU cache that keeps loaded `Transcriber` models with size limits.
+/// Memory pressure: less than this share of physical RAM is available
+const PRESSURE_AVAILABLE_PERCENT: u64 = 10;
+
+/// User-facing memory settings, stored under `model_memory` in the settings store
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelMemorySettings {
+    /// Ceiling for loaded models; `None` means half of physical RAM
+    pub memory_budget_mb: Option<u64>,
+    /// Unload models unused for this long; 0 keeps them until evicted
+    pub idle_unload_minutes: u32,
+    /// Unload models when the system runs low on memory
+    pub unload_on_memory_pressure: bool,
+    /// Model kept loaded ("always warm") regardless of idle time and pressure
+    pub pinned_model: Option<String>,
+}
+
+impl Default for ModelMemorySettings {
+    fn default() -> Self {
+        Self {
+            memory_budget_mb: None,
+            idle_unload_minutes: 30,
+            unload_on_memory_pressure: true,
+            pinned_model: None,
+        }
+    }
+}
+
+impl ModelMemorySettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if matches!(self.memory_budget_mb, Some(mb) if mb < 256) {
+            return Err("Memory budget must be at least 256 MB".to_string());
+        }
+        if self.idle_unload_minutes > 24 * 60 {
+            return Err("Idle unload time must be at most 24 hours".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn policy(&self, total_ram_mb: u64) -> CachePolicy {
+        let budget_mb = self.memory_budget_mb.unwrap_or(total_ram_mb / 2);
+        CachePolicy {
+            budget_bytes: budget_mb.saturating_mul(1024 * 1024),
+            // The byte budget decides how many models fit
+            max_entries: None,
+            idle_timeout: (self.idle_unload_minutes > 0)
+                .then(|| Duration::from_secs(u64::from(self.idle_unload_minutes) * 60)),
+            unload_on_pressure: self.unload_on_memory_pressure,
+        }
+    }
+}
+
+/// Limits the cache enforces
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachePolicy {
+    pub budget_bytes: u64,
+    /// Optional cap on unpinned models; the pinned model is kept on top of it
+    pub max_entries: Option<usize>,
+    pub idle_timeout: Option<Duration>,
+    pub unload_on_pressure: bool,
+}
+
+impl Default for CachePolicy {
+    fn default() -> Self {
+        Self {
+            budget_bytes: u64::MAX,
+            max_entries: None,
+            idle_timeout: None,
+            unload_on_pressure: false,
+        }
+    }
+}
+
+/// True when less than `PRESSURE_AVAILABLE_PERCENT` of RAM is available
+pub fn is_memory_pressure(total_ram_mb: u64, available_ram_mb: u64) -> bool {
+    total_ram_mb > 0 && available_ram_mb * 100 < total_ram_mb * PRESSURE_AVAILABLE_PERCENT
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheEventKind {
+    Loaded,
+    Unloaded,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheEventReason {
+    /// Loaded for a transcription or preload
+    OnDemand,
+    /// Loaded to keep the pinned model warm
+    Pinned,
+    /// Evicted to stay under the model count
+    Lru,
+    /// Evicted to stay under the memory budget
+    Budget,
+    Idle,
+    MemoryPressure,
+}
+
+impl CacheEventReason {
+    fn as_str(&self) -> &'static str {
+        match self {
+            CacheEventReason::OnDemand => "on_demand",
+            CacheEventReason::Pinned => "pinned",
+            CacheEventReason::Lru => "cache_eviction",
+            CacheEventReason::Budget => "memory_budget",
+            CacheEventReason::Idle => "idle",
+            CacheEventReason::MemoryPressure => "memory_pressure",
+        }
+    }
+}
+
+/// A model load or unload, for the diagnostics view
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CacheEvent {
+    /// Increases by one per event, so listeners can tell what they have seen
+    pub seq: u64,
+    pub kind: CacheEventKind,
+    pub reason: CacheEventReason,
+    pub model_path: String,
+    pub size_bytes: u64,
+    pub timestamp: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedModelStatus {
+    pub model_path: String,
+    pub size_bytes: u64,
+    pub idle_secs: u64,
+    pub pinned: bool,
+    /// A transcription is holding the model right now
+    pub in_use: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheStatus {
+    pub budget_bytes: u64,
+    pub used_bytes: u64,
+    pub idle_timeout_secs: Option<u64>,
+    pub pinned_path: Option<String>,
+    pub models: Vec<CachedModelStatus>,
+    pub events: Vec<CacheEvent>,
+}
+
+/// What the unload planner needs to know about a cached model
+#[derive(Debug, Clone)]
+pub struct EntryState {
+    pub key: String,
+    pub size_bytes: u64,
+    pub idle: Duration,
+    pub pinned: bool,
+    pub in_use: bool,
+}
+
+/// Decide which models to unload. `entries` is in LRU order (oldest first) and
+/// `incoming_bytes` is the size of a model about to be loaded (0 for housekeeping);
+/// a pinned newcomer does not count against `max_entries`.
+/// Pinned models and models in use are never unloaded.
+pub fn plan_unloads(
+    entries: &[EntryState],
+    policy: &CachePolicy,
+    incoming_bytes: u64,
+    incoming_pinned: bool,
+    under_pressure: bool,
+) -> Vec<(String, CacheEventReason)> {
+    let mut unloads = Vec::new();
+    let mut remaining: Vec<&EntryState> = Vec::new();
+
+    for entry in entries {
+        if entry.pinned || entry.in_use {
+            remaining.push(entry);
+        } else if under_pressure && policy.unload_on_pressure {
+            unloads.push((entry.key.clone(), CacheEventReason::MemoryPressure));
+        } else if matches!(policy.idle_timeout, Some(timeout) if entry.idle >= timeout) {
+            unloads.push((entry.key.clone(), CacheEventReason::Idle));
+        } else {
+            remaining.push(entry);
+        }
+    }
+
+    let incoming_count = usize::from(incoming_bytes > 0 && !incoming_pinned);
+    loop {
+        let used: u64 = remaining.iter().map(|e| e.size_bytes).sum();
+        let unpinned = remaining.iter().filter(|e| !e.pinned).count();
+        let reason = if matches!(policy.max_entries, Some(max) if unpinned + incoming_count > max) {
+            CacheEventReason::Lru
+        } else if used.saturating_add(incoming_bytes) > policy.budget_bytes {
+            CacheEventReason::Budget
+        } else {
+            break;
+        };
+
+        let Some(index) = remaining.iter().position(|e| !e.pinned && !e.in_use) else {
+            break;
+        };
+        unloads.push((remaining.remove(index).key.clone(), reason));
+    }
+
+    unloads
+}
+
+struct CacheEntry {
+    transcriber: Arc<Transcriber>,
+    /// File size; the weights dominate a loaded model's footprint
+    size_bytes: u64,
+    last_used: Instant,
+}
+
+/// LRU cache that keeps loaded `Transcriber` models within a memory budget.
 ///
 /// Loading a GGML model from disk can take hundreds of milliseconds and a lot
-/// of RAM (1-3GB per model). By keeping a limited number of models in memory
-/// we balance performance with memory usage.
+/// of RAM (1-3GB per model). By keeping as many models as the budget allows in
+/// memory we balance performance with memory usage. Idle models and, under memory
+/// pressure, every model but the pinned one are unloaded by `maintain`.
 pub struct TranscriberCache {
     /// Keyed by absolute path to the `.bin` model file.
-    map: HashMap<String, Arc<Transcriber>>,
+    map: HashMap<String, CacheEntry>,
     /// Track access order for LRU eviction
     lru_order: VecDeque<String>,
-    /// Maximum number of models to cache
-    max_size: usize,
+    policy: CachePolicy,
+    /// Model kept warm regardless of idle time
+    pinned: Option<PathBuf>,
+    events: VecDeque<CacheEvent>,
+    next_seq: u64,
 }
 
 impl Default for TranscriberCache {
@@ -30,27 +244,83 @@ impl Default for TranscriberCache {
 }
 
 impl TranscriberCache {
-    /// Create an empty cache with default size limit.
+    /// Create an empty cache, limited only by the memory budget once configured.
     pub fn new() -> Self {
-        Self::with_capacity(MAX_CACHE_SIZE)
+        Self::with_policy(CachePolicy::default())
     }
 
-    /// Create a cache with a specific capacity.
+    /// Create a cache that also holds at most `max_size` unpinned models.
     pub fn with_capacity(max_size: usize) -> Self {
+        Self::with_policy(CachePolicy {
+            max_entries: Some(max_size.max(1)), // At least 1
+            ..Default::default()
+        })
+    }
+
+    fn with_policy(policy: CachePolicy) -> Self {
         Self {
             map: HashMap::new(),
             lru_order: VecDeque::new(),
-            max_size: max_size.max(1), // At least 1
+            policy,
+            pinned: None,
+            events: VecDeque::new(),
+            next_seq: 1,
         }
     }
 
+    /// Apply new limits and pinned model; models over the new limits are unloaded.
+    pub fn configure(&mut self, policy: CachePolicy, pinned: Option<PathBuf>) {
+        log::info!(
+            "Model cache configured: budget {} MB, idle timeout {:?}, pinned {:?}",
+            policy.budget_bytes / (1024 * 1024),
+            policy.idle_timeout,
+            pinned
+        );
+        self.policy = CachePolicy {
+            max_entries: policy.max_entries.map(|max| max.max(1)),
+            ..policy
+        };
+        self.pinned = pinned;
+        self.unload_planned(None, false);
+    }
+
     /// Retrieve a cached transcriber, or load and cache it if it isn't present yet.
     pub fn get_or_create(&mut self, model_path: &Path) -> Result<Arc<Transcriber>, String> {
         log::info!(
             "[TRANSCRIPTION_DEBUG] get_or_create called with path: {:?}",
             model_path
         );
+        self.load(model_path, CacheEventReason::OnDemand)
+    }
+
+    /// Load the pinned model if it is not in memory (e.g. after memory pressure).
+    pub fn warm_pinned(&mut self) -> Result<(), String> {
+        let Some(path) = self.pinned.clone() else {
+            return Ok(());
+        };
+        if self.map.contains_key(path.to_string_lossy().as_ref()) {
+            return Ok(());
+        }
+        if !path.exists() {
+            log::debug!("Pinned model {:?} is not on disk; skipping warm-up", path);
+            return Ok(());
+        }
+        self.load(&path, CacheEventReason::Pinned).map(|_| ())
+    }
+
+    /// Unload idle models, and unpinned ones when `under_pressure`.
+    pub fn maintain(&mut self, under_pressure: bool) {
+        if under_pressure && self.policy.unload_on_pressure && !self.map.is_empty() {
+            log::warn!("System memory is low; unloading unpinned models");
+        }
+        self.unload_planned(None, under_pressure);
+    }
 
+    fn load(
+        &mut self,
+        model_path: &Path,
+        reason: CacheEventReason,
+    ) -> Result<Arc<Transcriber>, String> {
         // Check if the model file exists
         if !model_path.exists() {
             let error = format!("Model file does not exist: {:?}", model_path);
@@ -63,21 +333,35 @@ impl TranscriberCache {
         let key = model_path.to_string_lossy().to_string();
 
         // Check if already cached
-        if self.map.contains_key(&key) {
+        if let Some(entry) = self.map.get_mut(&key) {
             log::info!("[TRANSCRIPTION_DEBUG] Model found in cache: {}", key);
-            // Clone the transcriber before updating LRU
-            let transcriber = self.map.get(&key).cloned();
+            entry.last_used = Instant::now();
+            let transcriber = entry.transcriber.clone();
             // Move to end of LRU order
             self.update_lru(&key);
-            if let Some(t) = transcriber {
-                return Ok(t);
-            }
+            return Ok(transcriber);
         }
 
-        // Not cached – check if we need to evict
-        if self.map.len() >= self.max_size {
-            log::info!("[TRANSCRIPTION_DEBUG] Cache full, evicting LRU model");
-            self.evict_lru();
+        // Hashing happens before the cache lock is taken (`verify_model_before_load`);
+        // here the file only has to be unchanged since it passed
+        super::integrity::require_verified(model_path)?;
+
+        // Not cached – make room under the count and byte limits
+        let size_bytes = std::fs::metadata(model_path)
+            .map(|m| m.len())
+            .unwrap_or(0)
+            .max(1);
+        self.unload_planned(Some((size_bytes, self.is_pinned(&key))), false);
+        let used = self.used_bytes();
+        if used.saturating_add(size_bytes) > self.policy.budget_bytes {
+            // Still load: refusing would fail the transcription the user asked for
+            log::warn!(
+                "Loading {} exceeds the model memory budget ({} + {} > {} bytes)",
+                key,
+                used,
+                size_bytes,
+                self.policy.budget_bytes
+            );
         }
 
         // Load the model
@@ -103,12 +387,20 @@ impl TranscriberCache {
         };
 
         // Insert into cache
-        self.map.insert(key.clone(), transcriber.clone());
+        self.map.insert(
+            key.clone(),
+            CacheEntry {
+                transcriber: transcriber.clone(),
+                size_bytes,
+                last_used: Instant::now(),
+            },
+        );
         self.lru_order.push_back(key.clone());
+        self.record_event(CacheEventKind::Loaded, reason, &key, size_bytes);
         log::info!(
-            "[TRANSCRIPTION_DEBUG] Model cached successfully. Cache size: {}/{}",
+            "[TRANSCRIPTION_DEBUG] Model cached successfully. Cache size: {} model(s), {} MB",
             self.map.len(),
-            self.max_size
+            self.used_bytes() / (1024 * 1024)
         );
 
         Ok(transcriber)
@@ -122,56 +414,159 @@ impl TranscriberCache {
         self.lru_order.push_back(key.to_string());
     }
 
-    /// Evict the least recently used model
-    fn evict_lru(&mut self) {
-        if let Some(key) = self.lru_order.pop_front() {
-            log::info!("Evicting model from cache: {}", key);
+    fn used_bytes(&self) -> u64 {
+        self.map.values().map(|e| e.size_bytes).sum()
+    }
+
+    fn is_pinned(&self, key: &str) -> bool {
+        self.pinned
+            .as_ref()
+            .is_some_and(|p| p.to_string_lossy() == key)
+    }
+
+    fn entry_states(&self) -> Vec<EntryState> {
+        let now = Instant::now();
+        self.lru_order
+            .iter()
+            .filter_map(|key| {
+                let entry = self.map.get(key)?;
+                Some(EntryState {
+                    key: key.clone(),
+                    size_bytes: entry.size_bytes,
+                    idle: now.duration_since(entry.last_used),
+                    pinned: self.is_pinned(key),
+                    // The cache holds one reference; more means a transcription has it
+                    in_use: Arc::strong_count(&entry.transcriber) > 1,
+                })
+            })
+            .collect()
+    }
 
-            // Log model cleanup with context
+    fn unload_planned(&mut self, incoming: Option<(u64, bool)>, under_pressure: bool) {
+        let (incoming_bytes, incoming_pinned) = incoming.unwrap_or((0, false));
+        let plan = plan_unloads(
+            &self.entry_states(),
+            &self.policy,
+            incoming_bytes,
+            incoming_pinned,
+            under_pressure,
+        );
+        for (key, reason) in plan {
+            self.unload(&key, reason);
+        }
+    }
+
+    /// Drop a model from the cache
+    fn unload(&mut self, key: &str, reason: CacheEventReason) {
+        log::info!("Evicting model from cache: {} ({})", key, reason.as_str());
+
+        // Log model cleanup with context
+        log_with_context(
+            log::Level::Info,
+            "Model cleanup started",
+            &[
+                ("operation", "MODEL_CLEANUP"),
+                ("model_path", key),
+                ("reason", reason.as_str()),
+                ("cache_size", &self.map.len().to_string().as_str()),
+            ],
+        );
+
+        self.lru_order.retain(|k| k != key);
+        // Remove from cache - this will drop the Arc<Transcriber>
+        if let Some(entry) = self.map.remove(key) {
+            let ref_count = Arc::strong_count(&entry.transcriber);
             log_with_context(
-                log::Level::Info,
-                "Model cleanup started",
+                log::Level::Debug,
+                "Model cleanup complete",
                 &[
                     ("operation", "MODEL_CLEANUP"),
-                    ("model_path", &key),
-                    ("reason", "cache_eviction"),
-                    ("cache_size", &self.map.len().to_string().as_str()),
+                    ("model_path", key),
+                    ("ref_count_before_drop", &ref_count.to_string().as_str()),
+                    ("cleanup_result", "success"),
                 ],
             );
-
-            // Remove from cache - this will drop the Arc<Transcriber>
-            if let Some(transcriber) = self.map.remove(&key) {
-                let ref_count = Arc::strong_count(&transcriber);
-                log_with_context(
-                    log::Level::Debug,
-                    "Model cleanup complete",
-                    &[
-                        ("operation", "MODEL_CLEANUP"),
-                        ("model_path", &key),
-                        ("ref_count_before_drop", &ref_count.to_string().as_str()),
-                        ("cleanup_result", "success"),
-                    ],
-                );
-            } else {
-                log_with_context(
-                    log::Level::Warn,
-                    "Model cleanup warning",
-                    &[
-                        ("operation", "MODEL_CLEANUP"),
-                        ("model_path", &key),
-                        ("issue", "model_not_found_in_cache"),
-                    ],
-                );
-            }
+            self.record_event(CacheEventKind::Unloaded, reason, key, entry.size_bytes);
         } else {
             log_with_context(
-                log::Level::Debug,
-                "Model cleanup skipped",
-                &[("operation", "MODEL_CLEANUP"), ("reason", "cache_empty")],
+                log::Level::Warn,
+                "Model cleanup warning",
+                &[
+                    ("operation", "MODEL_CLEANUP"),
+                    ("model_path", key),
+                    ("issue", "model_not_found_in_cache"),
+                ],
             );
         }
     }
 
+    fn record_event(
+        &mut self,
+        kind: CacheEventKind,
+        reason: CacheEventReason,
+        key: &str,
+        size_bytes: u64,
+    ) {
+        let mut metadata = HashMap::new();
+        metadata.insert("reason".to_string(), reason.as_str().to_string());
+        metadata.insert(
+            "size_mb".to_string(),
+            (size_bytes / (1024 * 1024)).to_string(),
+        );
+        let operation = match kind {
+            CacheEventKind::Loaded => "CACHE_LOAD",
+            CacheEventKind::Unloaded => "CACHE_UNLOAD",
+        };
+        log_model_operation(operation, key, "done", Some(&metadata));
+
+        if self.events.len() >= MAX_EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back(CacheEvent {
+            seq: self.next_seq,
+            kind,
+            reason,
+            model_path: key.to_string(),
+            size_bytes,
+            timestamp: chrono::Local::now().to_rfc3339(),
+        });
+        self.next_seq += 1;
+    }
+
+    /// Events newer than `seq`
+    pub fn events_since(&self, seq: u64) -> Vec<CacheEvent> {
+        self.events
+            .iter()
+            .filter(|e| e.seq > seq)
+            .cloned()
+            .collect()
+    }
+
+    /// Snapshot for diagnostics
+    pub fn status(&self) -> CacheStatus {
+        CacheStatus {
+            budget_bytes: self.policy.budget_bytes,
+            used_bytes: self.used_bytes(),
+            idle_timeout_secs: self.policy.idle_timeout.map(|d| d.as_secs()),
+            pinned_path: self
+                .pinned
+                .as_ref()
+                .map(|p| p.to_string_lossy().to_string()),
+            models: self
+                .entry_states()
+                .into_iter()
+                .map(|e| CachedModelStatus {
+                    model_path: e.key,
+                    size_bytes: e.size_bytes,
+                    idle_secs: e.idle.as_secs(),
+                    pinned: e.pinned,
+                    in_use: e.in_use,
+                })
+                .collect(),
+            events: self.events.iter().cloned().collect(),
+        }
+    }
+
     /// Manually clear the cache (e.g. to free RAM or after a model upgrade).
     #[cfg(test)]
     pub fn clear(&mut self) {
@@ -185,9 +580,138 @@ impl TranscriberCache {
         self.map.len()
     }
 
-    /// Get the maximum cache size
+    /// Get the maximum number of unpinned models, if capped
     #[cfg(test)]
-    pub fn capacity(&self) -> usize {
-        self.max_size
+    pub fn capacity(&self) -> Option<usize> {
+        self.policy.max_entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GB: u64 = 1024 * 1024 * 1024;
+
+    fn entry(key: &str, size_gb: u64, idle_mins: u64) -> EntryState {
+        EntryState {
+            key: key.to_string(),
+            size_bytes: size_gb * GB,
+            idle: Duration::from_secs(idle_mins * 60),
+            pinned: false,
+            in_use: false,
+        }
+    }
+
+    fn policy(budget_gb: u64, max_entries: Option<usize>) -> CachePolicy {
+        CachePolicy {
+            budget_bytes: budget_gb * GB,
+            max_entries,
+            idle_timeout: Some(Duration::from_secs(30 * 60)),
+            unload_on_pressure: true,
+        }
+    }
+
+    #[test]
+    fn test_budget_evicts_oldest_first() {
+        let entries = vec![entry("a", 2, 1), entry("b", 2, 0)];
+        let plan = plan_unloads(&entries, &policy(4, Some(3)), GB, false, false);
+        assert_eq!(plan, vec![("a".to_string(), CacheEventReason::Budget)]);
+
+        // Fits: nothing to do
+        assert!(plan_unloads(&entries, &policy(4, Some(3)), 0, false, false).is_empty());
+    }
+
+    #[test]
+    fn test_count_limit_ignores_pinned() {
+        let mut pinned = entry("main", 3, 90);
+        pinned.pinned = true;
+        let entries = vec![pinned, entry("other", 1, 0)];
+
+        // Pinned model survives idle time; the unpinned one makes room for the newcomer
+        let plan = plan_unloads(&entries, &policy(16, Some(1)), GB, false, false);
+        assert_eq!(plan, vec![("other".to_string(), CacheEventReason::Lru)]);
+
+        // Warming the pinned model does not push out the active one
+        let plan = plan_unloads(
+            &[entry("other", 1, 0)],
+            &policy(16, Some(1)),
+            GB,
+            true,
+            false,
+        );
+        assert!(plan.is_empty());
+    }
+
+    #[test]
+    fn test_budget_is_the_only_limit_by_default() {
+        let entries = vec![entry("a", 2, 1), entry("b", 2, 0)];
+        let policy = ModelMemorySettings {
+            memory_budget_mb: Some(8 * 1024),
+            ..Default::default()
+        }
+        .policy(16384);
+        assert_eq!(policy.max_entries, None);
+
+        // Three models fit in 8 GB, a fourth pushes out the oldest
+        assert!(plan_unloads(&entries, &policy, 2 * GB, false, false).is_empty());
+        let plan = plan_unloads(&entries, &policy, 5 * GB, false, false);
+        assert_eq!(plan, vec![("a".to_string(), CacheEventReason::Budget)]);
+    }
+
+    #[test]
+    fn test_idle_and_pressure() {
+        let entries = vec![entry("old", 1, 45), entry("recent", 1, 5)];
+        let plan = plan_unloads(&entries, &policy(16, Some(4)), 0, false, false);
+        assert_eq!(plan, vec![("old".to_string(), CacheEventReason::Idle)]);
+
+        let plan = plan_unloads(&entries, &policy(16, Some(4)), 0, false, true);
+        assert_eq!(plan.len(), 2);
+        assert!(plan
+            .iter()
+            .all(|(_, reason)| *reason == CacheEventReason::MemoryPressure));
+
+        let mut no_pressure = policy(16, Some(4));
+        no_pressure.unload_on_pressure = false;
+        no_pressure.idle_timeout = None;
+        assert!(plan_unloads(&entries, &no_pressure, 0, false, true).is_empty());
+    }
+
+    #[test]
+    fn test_models_in_use_are_kept() {
+        let mut busy = entry("busy", 4, 60);
+        busy.in_use = true;
+        let plan = plan_unloads(&[busy], &policy(2, Some(1)), GB, false, true);
+        assert!(plan.is_empty());
+    }
+
+    #[test]
+    fn test_settings_policy() {
+        let settings = ModelMemorySettings::default();
+        assert!(settings.validate().is_ok());
+        let policy = settings.policy(16384);
+        assert_eq!(policy.budget_bytes, 8 * GB);
+        assert_eq!(policy.idle_timeout, Some(Duration::from_secs(30 * 60)));
+
+        let never = ModelMemorySettings {
+            idle_unload_minutes: 0,
+            memory_budget_mb: Some(4096),
+            ..Default::default()
+        };
+        assert_eq!(never.policy(16384).idle_timeout, None);
+        assert_eq!(never.policy(16384).budget_bytes, 4 * GB);
+
+        let tiny = ModelMemorySettings {
+            memory_budget_mb: Some(10),
+            ..Default::default()
+        };
+        assert!(tiny.validate().is_err());
+    }
+
+    #[test]
+    fn test_memory_pressure_threshold() {
+        assert!(is_memory_pressure(16384, 1000));
+        assert!(!is_memory_pressure(16384, 4096));
+        assert!(!is_memory_pressure(0, 0));
     }
 }
@@ -0,0 +1,217 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use super::manager::ModelInfo;
+use super::model_file::{ModelFileInfo, ModelFormat};
+
+/// Catalog of imported models, stored next to the model files
+const CATALOG_FILE: &str = "custom_models.json";
+/// Prefix that keeps imported model names apart from the built-in table
+pub const CUSTOM_PREFIX: &str = "custom-";
+
+/// A user-imported ggml/gguf model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomModelEntry {
+    /// Registry name; the file lives at `<models_dir>/<name>.bin`
+    pub name: String,
+    pub display_name: String,
+    /// File name the model was imported from
+    pub source_file: String,
+    pub size: u64,
+    pub sha256: String,
+    pub format: ModelFormat,
+    pub n_vocab: i32,
+    pub n_audio_layer: i32,
+    pub n_text_layer: i32,
+    pub multilingual: bool,
+    pub quantization: String,
+    pub imported_at: String,
+}
+
+impl CustomModelEntry {
+    pub fn new(
+        name: String,
+        display_name: String,
+        source_file: String,
+        sha256: String,
+        info: &ModelFileInfo,
+    ) -> Self {
+        Self {
+            name,
+            display_name,
+            source_file,
+            size: info.file_size,
+            sha256,
+            format: info.format,
+            n_vocab: info.hyperparams.n_vocab,
+            n_audio_layer: info.hyperparams.n_audio_layer,
+            n_text_layer: info.hyperparams.n_text_layer,
+            multilingual: info.multilingual,
+            quantization: info.quantization.clone(),
+            imported_at: chrono::Local::now().to_rfc3339(),
+        }
+    }
+
+    pub fn to_model_info(&self) -> ModelInfo {
+        let hyperparams = super::model_file::WhisperHyperparams {
+            n_audio_layer: self.n_audio_layer,
+            n_text_layer: self.n_text_layer,
+            ftype: quantization_ftype(&self.quantization),
+            ..Default::default()
+        };
+        let (speed_score, accuracy_score) = hyperparams.estimated_scores();
+
+        ModelInfo {
+            name: self.name.clone(),
+            display_name: self.display_name.clone(),
+            size: self.size,
+            // Imported models have nothing to download
+            url: String::new(),
+            sha256: self.sha256.clone(),
+            sha1: String::new(),
+            downloaded: false,
+            speed_score,
+            accuracy_score,
+            recommended: false,
+        }
+    }
+}
+
+/// Inverse of `WhisperHyperparams::quantization`, enough for score estimates
+fn quantization_ftype(quantization: &str) -> i32 {
+    match quantization {
+        "f32" => 0,
+        "f16" | "unknown" => 1,
+        _ => 2,
+    }
+}
+
+fn catalog_path(models_dir: &Path) -> PathBuf {
+    models_dir.join(CATALOG_FILE)
+}
+
+/// Load the catalog; a missing or unreadable file yields an empty list
+pub fn load_catalog(models_dir: &Path) -> Vec<CustomModelEntry> {
+    let path = catalog_path(models_dir);
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(_) => return Vec::new(),
+    };
+    match serde_json::from_str(&content) {
+        Ok(entries) => entries,
+        Err(e) => {
+            log::warn!("Ignoring unreadable custom model catalog {:?}: {}", path, e);
+            Vec::new()
+        }
+    }
+}
+
+pub fn save_catalog(models_dir: &Path, entries: &[CustomModelEntry]) -> Result<(), String> {
+    std::fs::create_dir_all(models_dir)
+        .map_err(|e| format!("Failed to create models directory: {}", e))?;
+    let content = serde_json::to_string_pretty(entries)
+        .map_err(|e| format!("Failed to serialize model catalog: {}", e))?;
+
+    // Write then rename so a crash never leaves a half-written catalog
+    let path = catalog_path(models_dir);
+    let tmp_path = path.with_extension("json.tmp");
+    std::fs::write(&tmp_path, content)
+        .map_err(|e| format!("Failed to write model catalog: {}", e))?;
+    std::fs::rename(&tmp_path, &path).map_err(|e| format!("Failed to save model catalog: {}", e))
+}
+
+/// Registry name for a display name: `custom-<slug>`, suffixed until it is unique
+pub fn custom_model_name(display_name: &str, is_taken: impl Fn(&str) -> bool) -> String {
+    let mut slug = String::new();
+    for c in display_name.trim().to_lowercase().chars() {
+        if c.is_ascii_alphanumeric() || c == '.' || c == '_' {
+            slug.push(c);
+        } else if !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_matches(|c| c == '-' || c == '.').to_string();
+    let base = if slug.is_empty() {
+        format!("{}model", CUSTOM_PREFIX)
+    } else {
+        format!("{}{}", CUSTOM_PREFIX, slug)
+    };
+
+    let mut name = base.clone();
+    let mut suffix = 2;
+    while is_taken(&name) {
+        name = format!("{}-{}", base, suffix);
+        suffix += 1;
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::whisper::model_file::WhisperHyperparams;
+    use tempfile::TempDir;
+
+    fn sample_entry(name: &str) -> CustomModelEntry {
+        let info = ModelFileInfo {
+            format: ModelFormat::Ggml,
+            hyperparams: WhisperHyperparams {
+                n_vocab: 51865,
+                n_audio_layer: 24,
+                n_text_layer: 24,
+                ftype: 8,
+                ..Default::default()
+            },
+            multilingual: true,
+            quantization: "q5_0".to_string(),
+            size_class: "medium".to_string(),
+            file_size: 4096,
+        };
+        CustomModelEntry::new(
+            name.to_string(),
+            "Medical Medium".to_string(),
+            "ggml-medical-q5_0.bin".to_string(),
+            "abc".to_string(),
+            &info,
+        )
+    }
+
+    #[test]
+    fn test_catalog_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(load_catalog(temp_dir.path()).is_empty());
+
+        let entries = vec![sample_entry("custom-medical-medium")];
+        save_catalog(temp_dir.path(), &entries).unwrap();
+        assert_eq!(load_catalog(temp_dir.path()), entries);
+    }
+
+    #[test]
+    fn test_corrupt_catalog_is_ignored() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join(CATALOG_FILE), "{not json").unwrap();
+        assert!(load_catalog(temp_dir.path()).is_empty());
+    }
+
+    #[test]
+    fn test_custom_model_name() {
+        assert_eq!(
+            custom_model_name("Medical Medium (q5_0)", |_| false),
+            "custom-medical-medium-q5_0"
+        );
+        assert_eq!(custom_model_name("../../", |_| false), "custom-model");
+        assert_eq!(
+            custom_model_name("Tuned", |name| name == "custom-tuned"),
+            "custom-tuned-2"
+        );
+    }
+
+    #[test]
+    fn test_model_info_scores() {
+        let info = sample_entry("custom-medical-medium").to_model_info();
+        assert_eq!(info.url, "");
+        assert_eq!(info.size, 4096);
+        // medium (5, 7), quantized
+        assert_eq!((info.speed_score, info.accuracy_score), (6, 6));
+    }
+}
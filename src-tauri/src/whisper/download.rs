@@ -0,0 +1,691 @@
+use futures_util::StreamExt;
+use reqwest::header::{CONTENT_RANGE, ETAG, IF_RANGE, LAST_MODIFIED, RANGE};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tokio::fs;
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+
+/// Retry and checkpoint tuning for resumable downloads
+#[derive(Debug, Clone)]
+pub struct DownloadOptions {
+    /// Consecutive failed attempts (without progress) before giving up
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// No bytes for this long counts as a failed attempt
+    pub stall_timeout: Duration,
+    /// Bytes between fsync + state checkpoints
+    pub checkpoint_bytes: u64,
+}
+
+impl Default for DownloadOptions {
+    fn default() -> Self {
+        Self {
+            max_retries: 8,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+            stall_timeout: Duration::from_secs(60),
+            checkpoint_bytes: 8 * 1024 * 1024,
+        }
+    }
+}
+
+/// Persisted next to the `.part` file so a download can resume after an app restart.
+/// `downloaded` only advances after the data up to it has been synced to disk.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct DownloadState {
+    url: String,
+    total_size: u64,
+    /// ETag or Last-Modified, sent as If-Range so a changed file restarts from zero
+    validator: Option<String>,
+    downloaded: u64,
+}
+
+pub fn part_path(output_path: &Path) -> PathBuf {
+    append_extension(output_path, "part")
+}
+
+fn state_path(output_path: &Path) -> PathBuf {
+    append_extension(output_path, "part.json")
+}
+
+fn append_extension(path: &Path, ext: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".");
+    name.push(ext);
+    PathBuf::from(name)
+}
+
+/// Remove any partial download left for `output_path`
+pub fn discard_partial(output_path: &Path) {
+    let _ = std::fs::remove_file(part_path(output_path));
+    let _ = std::fs::remove_file(state_path(output_path));
+}
+
+/// Bytes already on disk for an interrupted download of `url`, if any
+pub fn resumable_bytes(output_path: &Path, url: &str) -> u64 {
+    load_state(output_path)
+        .filter(|state| state.url == url)
+        .map(|state| state.downloaded)
+        .unwrap_or(0)
+}
+
+fn load_state(output_path: &Path) -> Option<DownloadState> {
+    let content = std::fs::read_to_string(state_path(output_path)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+async fn save_state(output_path: &Path, state: &DownloadState) -> Result<(), String> {
+    let path = state_path(output_path);
+    let tmp = append_extension(&path, "tmp");
+    let json = serde_json::to_vec(state).map_err(|e| e.to_string())?;
+    fs::write(&tmp, json)
+        .await
+        .map_err(|e| format!("Failed to write download state: {}", e))?;
+    fs::rename(&tmp, &path)
+        .await
+        .map_err(|e| format!("Failed to write download state: {}", e))
+}
+
+/// Parse `Content-Range: bytes start-end/total`
+fn parse_content_range(value: &str) -> Option<(u64, Option<u64>)> {
+    let range = value.trim().strip_prefix("bytes ")?;
+    let (span, total) = range.split_once('/')?;
+    let (start, _) = span.split_once('-')?;
+    Some((start.parse().ok()?, total.parse().ok()))
+}
+
+fn backoff_delay(options: &DownloadOptions, failures: u32) -> Duration {
+    let factor = 2u32.saturating_pow(failures.saturating_sub(1));
+    options
+        .initial_backoff
+        .saturating_mul(factor)
+        .min(options.max_backoff)
+}
+
+fn is_cancelled(cancel_flag: Option<&AtomicBool>) -> bool {
+    cancel_flag
+        .map(|flag| flag.load(Ordering::Relaxed))
+        .unwrap_or(false)
+}
+
+/// Sleep for the backoff, waking early if the download is cancelled
+async fn sleep_unless_cancelled(delay: Duration, cancel_flag: Option<&AtomicBool>) {
+    let step = Duration::from_millis(100);
+    let mut remaining = delay;
+    while !remaining.is_zero() && !is_cancelled(cancel_flag) {
+        let nap = remaining.min(step);
+        tokio::time::sleep(nap).await;
+        remaining -= nap;
+    }
+}
+
+async fn checkpoint(
+    file: &mut fs::File,
+    output_path: &Path,
+    state: &mut DownloadState,
+    downloaded: u64,
+) -> Result<(), String> {
+    file.flush().await.map_err(|e| e.to_string())?;
+    file.sync_data()
+        .await
+        .map_err(|e| format!("Failed to sync partial download: {}", e))?;
+    state.downloaded = downloaded;
+    save_state(output_path, state).await
+}
+
+/// Download `url` to `output_path` through a `.part` file, resuming with HTTP Range
+/// requests after connection drops and across app restarts.
+///
+/// `validate_total` checks the size reported by the server before any bytes are written.
+/// On cancellation the partial file and its state are kept so the next attempt resumes.
+/// Returns the total size in bytes.
+pub async fn download_resumable(
+    url: &str,
+    output_path: &Path,
+    fallback_size: u64,
+    validate_total: impl Fn(u64) -> Result<(), String>,
+    cancel_flag: Option<&AtomicBool>,
+    options: &DownloadOptions,
+    progress_callback: impl Fn(u64, u64),
+) -> Result<u64, String> {
+    let part = part_path(output_path);
+    let client = reqwest::Client::builder()
+        .connect_timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+    // Pick up where a previous run left off, if it was downloading the same URL
+    let mut state = match load_state(output_path) {
+        Some(state) if state.url == url => {
+            let on_disk = fs::metadata(&part).await.map(|m| m.len()).unwrap_or(0);
+            let mut state = state;
+            state.downloaded = state.downloaded.min(on_disk);
+            log::info!("Resuming download of {} at {} bytes", url, state.downloaded);
+            state
+        }
+        stale => {
+            if stale.is_some() {
+                log::info!("Discarding partial download for a different URL");
+            }
+            discard_partial(output_path);
+            DownloadState {
+                url: url.to_string(),
+                total_size: 0,
+                validator: None,
+                downloaded: 0,
+            }
+        }
+    };
+
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(false)
+        .open(&part)
+        .await
+        .map_err(|e| format!("Failed to open partial download: {}", e))?;
+
+    let mut downloaded = state.downloaded;
+    let mut failures = 0u32;
+    let mut last_error = String::new();
+    let mut total_validated = false;
+
+    loop {
+        if is_cancelled(cancel_flag) {
+            log::info!(
+                "Download cancelled at {} bytes, keeping partial file",
+                downloaded
+            );
+            checkpoint(&mut file, output_path, &mut state, downloaded).await?;
+            return Err("Download cancelled by user".to_string());
+        }
+
+        if failures > 0 {
+            if failures > options.max_retries {
+                checkpoint(&mut file, output_path, &mut state, downloaded).await?;
+                return Err(format!(
+                    "Download failed after {} retries: {}",
+                    options.max_retries, last_error
+                ));
+            }
+            let delay = backoff_delay(options, failures);
+            log::warn!(
+                "Download attempt failed ({}), retrying in {:?} from byte {}",
+                last_error,
+                delay,
+                downloaded
+            );
+            sleep_unless_cancelled(delay, cancel_flag).await;
+            if is_cancelled(cancel_flag) {
+                continue;
+            }
+        }
+
+        let mut request = client.get(url);
+        if downloaded > 0 {
+            request = request.header(RANGE, format!("bytes={}-", downloaded));
+            if let Some(validator) = &state.validator {
+                request = request.header(IF_RANGE, validator.as_str());
+            }
+        }
+
+        let response = match request.send().await {
+            Ok(response) => response,
+            Err(e) => {
+                failures += 1;
+                last_error = e.to_string();
+                continue;
+            }
+        };
+
+        let status = response.status();
+        let start = match status {
+            StatusCode::PARTIAL_CONTENT => {
+                let content_range = response
+                    .headers()
+                    .get(CONTENT_RANGE)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(parse_content_range);
+                match content_range {
+                    Some((start, total)) if start == downloaded => {
+                        if let Some(total) = total {
+                            state.total_size = total;
+                        }
+                        start
+                    }
+                    _ => {
+                        // Unusable range response; start over with a plain request
+                        log::warn!("Server returned an unexpected range, restarting download");
+                        downloaded = 0;
+                        state.validator = None;
+                        failures += 1;
+                        last_error = "unexpected Content-Range".to_string();
+                        continue;
+                    }
+                }
+            }
+            StatusCode::OK => {
+                if downloaded > 0 {
+                    log::warn!("Server did not honour the range request, restarting from zero");
+                }
+                state.total_size = response.content_length().unwrap_or(fallback_size);
+                0
+            }
+            StatusCode::RANGE_NOT_SATISFIABLE
+                if state.total_size > 0 && downloaded >= state.total_size =>
+            {
+                // Everything was already on disk
+                break;
+            }
+            StatusCode::RANGE_NOT_SATISFIABLE => {
+                downloaded = 0;
+                state.validator = None;
+                failures += 1;
+                last_error = "range not satisfiable".to_string();
+                continue;
+            }
+            s if s.is_server_error() || s == StatusCode::TOO_MANY_REQUESTS => {
+                failures += 1;
+                last_error = format!("HTTP {}", s);
+                continue;
+            }
+            s => {
+                checkpoint(&mut file, output_path, &mut state, downloaded).await?;
+                return Err(format!("Download failed: HTTP {}", s));
+            }
+        };
+
+        let total_size = state.total_size;
+        if !total_validated {
+            if let Err(e) = validate_total(total_size) {
+                drop(file);
+                discard_partial(output_path);
+                return Err(e);
+            }
+            total_validated = true;
+        }
+
+        state.validator = response
+            .headers()
+            .get(ETAG)
+            .or_else(|| response.headers().get(LAST_MODIFIED))
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string());
+
+        downloaded = start;
+        file.set_len(downloaded)
+            .await
+            .map_err(|e| format!("Failed to truncate partial download: {}", e))?;
+        file.seek(std::io::SeekFrom::Start(downloaded))
+            .await
+            .map_err(|e| e.to_string())?;
+        checkpoint(&mut file, output_path, &mut state, downloaded).await?;
+
+        progress_callback(downloaded, total_size);
+        let update_threshold = (total_size / 100).max(1); // Update every 1%
+        let mut last_progress_update = downloaded;
+        let mut last_checkpoint = downloaded;
+        let attempt_start = downloaded;
+
+        let mut stream = response.bytes_stream();
+        let stream_error = loop {
+            if is_cancelled(cancel_flag) {
+                log::info!(
+                    "Download cancelled at {} bytes, keeping partial file",
+                    downloaded
+                );
+                checkpoint(&mut file, output_path, &mut state, downloaded).await?;
+                return Err("Download cancelled by user".to_string());
+            }
+
+            let chunk = match tokio::time::timeout(options.stall_timeout, stream.next()).await {
+                Ok(Some(Ok(chunk))) => chunk,
+                Ok(Some(Err(e))) => break Some(e.to_string()),
+                Ok(None) => break None,
+                Err(_) => break Some("download stalled".to_string()),
+            };
+
+            // Prevent downloading more than expected (with 1% tolerance)
+            if downloaded + chunk.len() as u64 > (total_size as f64 * 1.01) as u64 {
+                drop(file);
+                discard_partial(output_path);
+                return Err(format!(
+                    "Download exceeded expected size: downloaded {} bytes, expected {} bytes",
+                    downloaded + chunk.len() as u64,
+                    total_size
+                ));
+            }
+
+            file.write_all(&chunk).await.map_err(|e| e.to_string())?;
+            downloaded += chunk.len() as u64;
+
+            if downloaded - last_checkpoint >= options.checkpoint_bytes {
+                checkpoint(&mut file, output_path, &mut state, downloaded).await?;
+                last_checkpoint = downloaded;
+            }
+
+            // Only update progress every 1% to avoid flooding the UI
+            if downloaded - last_progress_update >= update_threshold || downloaded == total_size {
+                progress_callback(downloaded, total_size);
+                last_progress_update = downloaded;
+            }
+        };
+
+        checkpoint(&mut file, output_path, &mut state, downloaded).await?;
+
+        if stream_error.is_none() && downloaded >= total_size {
+            break;
+        }
+
+        // Progress resets the retry budget; only consecutive dead attempts count
+        if downloaded > attempt_start {
+            failures = 1;
+        } else {
+            failures += 1;
+        }
+        last_error = stream_error.unwrap_or_else(|| {
+            format!(
+                "connection closed at {} of {} bytes",
+                downloaded, total_size
+            )
+        });
+    }
+
+    // Ensure file is flushed to disk
+    file.flush().await.map_err(|e| e.to_string())?;
+    // Force OS to write to physical disk
+    file.sync_all()
+        .await
+        .map_err(|e| format!("Failed to sync file to disk: {}", e))?;
+    drop(file);
+
+    fs::rename(&part, output_path)
+        .await
+        .map_err(|e| format!("Failed to move completed download into place: {}", e))?;
+    let _ = fs::remove_file(state_path(output_path)).await;
+
+    // Also sync the parent directory to ensure directory entry is visible
+    if let Some(parent) = output_path.parent() {
+        if let Ok(dir) = std::fs::File::open(parent) {
+            let _ = dir.sync_all();
+        }
+    }
+
+    let total_size = state.total_size.max(downloaded);
+    progress_callback(total_size, total_size);
+    Ok(total_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tempfile::TempDir;
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    /// Minimal HTTP server standing in for the model host
+    #[derive(Clone)]
+    struct StandIn {
+        body: Arc<Vec<u8>>,
+        /// Close the connection after this many body bytes, once per entry
+        drop_after: Arc<Mutex<Vec<usize>>>,
+        honour_range: bool,
+        /// Range start of each request (None = full request)
+        requests: Arc<Mutex<Vec<Option<u64>>>>,
+    }
+
+    impl StandIn {
+        fn new(body: Vec<u8>) -> Self {
+            Self {
+                body: Arc::new(body),
+                drop_after: Arc::new(Mutex::new(Vec::new())),
+                honour_range: true,
+                requests: Arc::new(Mutex::new(Vec::new())),
+            }
+        }
+
+        async fn serve(self) -> String {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            tokio::spawn(async move {
+                while let Ok((mut socket, _)) = listener.accept().await {
+                    let server = self.clone();
+                    tokio::spawn(async move {
+                        let mut request = Vec::new();
+                        let mut buf = [0u8; 1024];
+                        while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                            let n = socket.read(&mut buf).await.unwrap_or(0);
+                            if n == 0 {
+                                return;
+                            }
+                            request.extend_from_slice(&buf[..n]);
+                        }
+                        let request = String::from_utf8_lossy(&request).to_lowercase();
+                        let range_start = request
+                            .lines()
+                            .find_map(|l| l.strip_prefix("range: bytes="))
+                            .and_then(|r| r.trim().trim_end_matches('-').parse::<u64>().ok());
+                        server.requests.lock().unwrap().push(range_start);
+
+                        let total = server.body.len();
+                        let (head, start) = match range_start {
+                            Some(start) if server.honour_range => (
+                                format!(
+                                    "HTTP/1.1 206 Partial Content\r\nContent-Length: {}\r\nContent-Range: bytes {}-{}/{}\r\nETag: \"v1\"\r\nConnection: close\r\n\r\n",
+                                    total - start as usize,
+                                    start,
+                                    total - 1,
+                                    total
+                                ),
+                                start as usize,
+                            ),
+                            _ => (
+                                format!(
+                                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nETag: \"v1\"\r\nConnection: close\r\n\r\n",
+                                    total
+                                ),
+                                0,
+                            ),
+                        };
+                        let _ = socket.write_all(head.as_bytes()).await;
+
+                        let limit = {
+                            let mut drops = server.drop_after.lock().unwrap();
+                            if drops.is_empty() {
+                                None
+                            } else {
+                                Some(drops.remove(0))
+                            }
+                        };
+                        let end = limit.map(|n| (start + n).min(total)).unwrap_or(total);
+                        let _ = socket.write_all(&server.body[start..end]).await;
+                        let _ = socket.shutdown().await;
+                    });
+                }
+            });
+            format!("http://{}/model.bin", addr)
+        }
+    }
+
+    fn body(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    fn fast_options() -> DownloadOptions {
+        DownloadOptions {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(20),
+            stall_timeout: Duration::from_secs(5),
+            checkpoint_bytes: 1024,
+        }
+    }
+
+    async fn download(
+        url: &str,
+        output: &Path,
+        cancel: Option<&AtomicBool>,
+    ) -> Result<u64, String> {
+        download_resumable(
+            url,
+            output,
+            0,
+            |_| Ok(()),
+            cancel,
+            &fast_options(),
+            |_, _| {},
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_fresh_download_moves_part_into_place() {
+        let temp_dir = TempDir::new().unwrap();
+        let output = temp_dir.path().join("model.bin");
+        let server = StandIn::new(body(10_000));
+        let url = server.clone().serve().await;
+
+        let total = download(&url, &output, None).await.unwrap();
+
+        assert_eq!(total, 10_000);
+        assert_eq!(std::fs::read(&output).unwrap(), *server.body);
+        assert!(!part_path(&output).exists());
+        assert!(!state_path(&output).exists());
+    }
+
+    #[tokio::test]
+    async fn test_dropped_connection_resumes_with_range() {
+        let temp_dir = TempDir::new().unwrap();
+        let output = temp_dir.path().join("model.bin");
+        let server = StandIn::new(body(10_000));
+        server.drop_after.lock().unwrap().extend([3_000, 3_000]);
+        let url = server.clone().serve().await;
+
+        download(&url, &output, None).await.unwrap();
+
+        assert_eq!(std::fs::read(&output).unwrap(), *server.body);
+        assert_eq!(
+            *server.requests.lock().unwrap(),
+            vec![None, Some(3_000), Some(6_000)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resumes_after_restart_from_persisted_state() {
+        let temp_dir = TempDir::new().unwrap();
+        let output = temp_dir.path().join("model.bin");
+        let server = StandIn::new(body(10_000));
+        let url = server.clone().serve().await;
+
+        // Simulate a previous run that synced 4000 bytes and wrote a few more
+        std::fs::write(part_path(&output), &server.body[..4_500]).unwrap();
+        let state = DownloadState {
+            url: url.clone(),
+            total_size: 10_000,
+            validator: Some("\"v1\"".to_string()),
+            downloaded: 4_000,
+        };
+        std::fs::write(state_path(&output), serde_json::to_vec(&state).unwrap()).unwrap();
+        assert_eq!(resumable_bytes(&output, &url), 4_000);
+
+        download(&url, &output, None).await.unwrap();
+
+        assert_eq!(std::fs::read(&output).unwrap(), *server.body);
+        assert_eq!(*server.requests.lock().unwrap(), vec![Some(4_000)]);
+    }
+
+    #[tokio::test]
+    async fn test_server_without_range_support_restarts() {
+        let temp_dir = TempDir::new().unwrap();
+        let output = temp_dir.path().join("model.bin");
+        let mut server = StandIn::new(body(8_000));
+        server.honour_range = false;
+        server.drop_after.lock().unwrap().push(5_000);
+        let url = server.clone().serve().await;
+
+        download(&url, &output, None).await.unwrap();
+
+        assert_eq!(std::fs::read(&output).unwrap(), *server.body);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_keeps_partial_download() {
+        let temp_dir = TempDir::new().unwrap();
+        let output = temp_dir.path().join("model.bin");
+        let server = StandIn::new(body(8_000));
+        let url = server.clone().serve().await;
+        std::fs::write(part_path(&output), &server.body[..2_000]).unwrap();
+        let state = DownloadState {
+            url: url.clone(),
+            total_size: 8_000,
+            validator: None,
+            downloaded: 2_000,
+        };
+        std::fs::write(state_path(&output), serde_json::to_vec(&state).unwrap()).unwrap();
+
+        let cancel = AtomicBool::new(true);
+        let err = download(&url, &output, Some(&cancel)).await.unwrap_err();
+
+        assert!(err.contains("cancelled"));
+        assert!(!output.exists());
+        assert_eq!(resumable_bytes(&output, &url), 2_000);
+    }
+
+    #[tokio::test]
+    async fn test_state_for_other_url_is_discarded() {
+        let temp_dir = TempDir::new().unwrap();
+        let output = temp_dir.path().join("model.bin");
+        let server = StandIn::new(body(4_000));
+        let url = server.clone().serve().await;
+        std::fs::write(part_path(&output), vec![0u8; 1_000]).unwrap();
+        let state = DownloadState {
+            url: "http://elsewhere/model.bin".to_string(),
+            total_size: 4_000,
+            validator: None,
+            downloaded: 1_000,
+        };
+        std::fs::write(state_path(&output), serde_json::to_vec(&state).unwrap()).unwrap();
+
+        download(&url, &output, None).await.unwrap();
+
+        assert_eq!(std::fs::read(&output).unwrap(), *server.body);
+        assert_eq!(*server.requests.lock().unwrap(), vec![None]);
+    }
+
+    #[tokio::test]
+    async fn test_size_validation_rejects_before_writing() {
+        let temp_dir = TempDir::new().unwrap();
+        let output = temp_dir.path().join("model.bin");
+        let url = StandIn::new(body(4_000)).serve().await;
+
+        let err = download_resumable(
+            &url,
+            &output,
+            0,
+            |total| Err(format!("bad size {}", total)),
+            None,
+            &fast_options(),
+            |_, _| {},
+        )
+        .await
+        .unwrap_err();
+
+        assert_eq!(err, "bad size 4000");
+        assert!(!part_path(&output).exists());
+    }
+
+    #[test]
+    fn test_parse_content_range() {
+        assert_eq!(
+            parse_content_range("bytes 100-199/1000"),
+            Some((100, Some(1000)))
+        );
+        assert_eq!(parse_content_range("bytes 100-199/*"), Some((100, None)));
+        assert_eq!(parse_content_range("items 1-2/3"), None);
+    }
+}
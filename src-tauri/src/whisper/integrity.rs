@@ -0,0 +1,542 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+use super::manager::ModelInfo;
+
+/// Verification results, stored next to the model files
+const CACHE_FILE: &str = "integrity.json";
+/// Unchanged files are re-hashed by the background scan after this long, to catch bit rot
+pub const REVERIFY_AFTER_DAYS: i64 = 30;
+
+/// Serializes read-modify-write cycles on the cache file
+static CACHE_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+/// Files whose last verification passed, with the size and mtime they had then
+static VERIFIED: Lazy<Mutex<HashMap<PathBuf, FileStamp>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntegrityStatus {
+    /// Matches the catalog's SHA-256 (or legacy SHA-1)
+    Verified,
+    /// No published checksum (Parakeet ONNX exports); the SHA-256 seen first is pinned
+    /// and later scans compare against it
+    Pinned,
+    /// Does not match the reference checksum: tampered or corrupted
+    Mismatch,
+}
+
+/// Reference checksums for a model file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExpectedChecksums {
+    pub sha256: String,
+    /// Legacy SHA-1 from the whisper.cpp download script
+    pub sha1: String,
+    /// Accept a file with neither checksum by pinning its SHA-256; otherwise it is refused
+    pub pin_on_first_use: bool,
+}
+
+impl ExpectedChecksums {
+    pub fn from_model(info: &ModelInfo) -> Self {
+        Self {
+            sha256: info.sha256.clone(),
+            sha1: info.sha1.clone(),
+            pin_on_first_use: false,
+        }
+    }
+
+    /// For files whose host publishes no checksum
+    pub fn pinned_on_first_use() -> Self {
+        Self {
+            pin_on_first_use: true,
+            ..Self::default()
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.sha256.is_empty() && self.sha1.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntegrityRecord {
+    pub size: u64,
+    pub mtime_secs: u64,
+    pub mtime_nanos: u32,
+    /// SHA-256 of the file as last hashed
+    pub sha256: String,
+    /// Trusted SHA-256: the catalog value, or the hash pinned on first verification
+    pub reference_sha256: Option<String>,
+    /// Legacy SHA-1 the file was checked against, if any
+    #[serde(default)]
+    pub expected_sha1: Option<String>,
+    pub status: IntegrityStatus,
+    pub checked_at: String,
+}
+
+impl IntegrityRecord {
+    fn matches_stamp(&self, stamp: &FileStamp) -> bool {
+        self.size == stamp.size
+            && self.mtime_secs == stamp.mtime_secs
+            && self.mtime_nanos == stamp.mtime_nanos
+    }
+
+    fn is_stale(&self) -> bool {
+        chrono::DateTime::parse_from_rfc3339(&self.checked_at)
+            .map(|checked| {
+                chrono::Local::now().signed_duration_since(checked)
+                    > chrono::Duration::days(REVERIFY_AFTER_DAYS)
+            })
+            .unwrap_or(true)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FileStamp {
+    size: u64,
+    mtime_secs: u64,
+    mtime_nanos: u32,
+}
+
+impl FileStamp {
+    fn of(record: &IntegrityRecord) -> Self {
+        Self {
+            size: record.size,
+            mtime_secs: record.mtime_secs,
+            mtime_nanos: record.mtime_nanos,
+        }
+    }
+}
+
+/// Remember whether the file passed, so unchanged files load without re-reading the cache
+fn remember(model_path: &Path, record: &IntegrityRecord) {
+    let mut verified = VERIFIED.lock().unwrap_or_else(|e| e.into_inner());
+    if record.status == IntegrityStatus::Mismatch {
+        verified.remove(model_path);
+    } else {
+        verified.insert(model_path.to_path_buf(), FileStamp::of(record));
+    }
+}
+
+fn file_stamp(path: &Path) -> Result<FileStamp, String> {
+    let metadata =
+        std::fs::metadata(path).map_err(|e| format!("Cannot read model file metadata: {}", e))?;
+    let mtime = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .unwrap_or_default();
+    Ok(FileStamp {
+        size: metadata.len(),
+        mtime_secs: mtime.as_secs(),
+        mtime_nanos: mtime.subsec_nanos(),
+    })
+}
+
+fn cache_path(models_dir: &Path) -> PathBuf {
+    models_dir.join(CACHE_FILE)
+}
+
+fn load_cache(models_dir: &Path) -> HashMap<String, IntegrityRecord> {
+    std::fs::read_to_string(cache_path(models_dir))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_cache(models_dir: &Path, cache: &HashMap<String, IntegrityRecord>) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(cache)
+        .map_err(|e| format!("Failed to serialize integrity cache: {}", e))?;
+    let path = cache_path(models_dir);
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, content).map_err(|e| format!("Failed to write integrity cache: {}", e))?;
+    std::fs::rename(&tmp, &path).map_err(|e| format!("Failed to save integrity cache: {}", e))
+}
+
+fn split_model_path(path: &Path) -> Result<(PathBuf, String), String> {
+    let dir = path
+        .parent()
+        .ok_or_else(|| format!("Invalid model path: {:?}", path))?
+        .to_path_buf();
+    let name = path
+        .file_name()
+        .ok_or_else(|| format!("Invalid model path: {:?}", path))?
+        .to_string_lossy()
+        .to_string();
+    Ok((dir, name))
+}
+
+/// Last recorded result for a model file, if any
+pub fn cached_record(model_path: &Path) -> Option<IntegrityRecord> {
+    let (dir, name) = split_model_path(model_path).ok()?;
+    let _guard = CACHE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    load_cache(&dir).remove(&name)
+}
+
+/// Drop the cached result for a model file (after deleting or replacing it)
+pub fn forget(model_path: &Path) {
+    VERIFIED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(model_path);
+    let Ok((dir, name)) = split_model_path(model_path) else {
+        return;
+    };
+    let _guard = CACHE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut cache = load_cache(&dir);
+    if cache.remove(&name).is_some() {
+        let _ = save_cache(&dir, &cache);
+    }
+}
+
+/// SHA-256 and SHA-1 in a single pass over the file
+pub fn hash_file(path: &Path) -> Result<(String, String), String> {
+    let mut file = std::fs::File::open(path)
+        .map_err(|e| format!("Failed to open file for checksum verification: {}", e))?;
+    let mut sha256 = Sha256::new();
+    let mut sha1 = Sha1::new();
+    let mut buffer = vec![0u8; 1024 * 1024];
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .map_err(|e| format!("Failed to read file for checksum: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        sha256.update(&buffer[..read]);
+        sha1.update(&buffer[..read]);
+    }
+    Ok((
+        format!("{:x}", sha256.finalize()),
+        format!("{:x}", sha1.finalize()),
+    ))
+}
+
+/// Decide the status of freshly computed digests
+fn evaluate(
+    expected: &ExpectedChecksums,
+    previous: Option<&IntegrityRecord>,
+    sha256: &str,
+    sha1: &str,
+) -> (IntegrityStatus, Option<String>) {
+    let verdict = |ok: bool| {
+        if ok {
+            IntegrityStatus::Verified
+        } else {
+            IntegrityStatus::Mismatch
+        }
+    };
+
+    if !expected.sha256.is_empty() {
+        let ok = expected.sha256.eq_ignore_ascii_case(sha256);
+        return (verdict(ok), Some(expected.sha256.to_lowercase()));
+    }
+    if !expected.sha1.is_empty() {
+        // Once the legacy SHA-1 checks out, the SHA-256 becomes the reference
+        let ok = expected.sha1.eq_ignore_ascii_case(sha1);
+        let reference = if ok {
+            Some(sha256.to_string())
+        } else {
+            previous.and_then(|p| p.reference_sha256.clone())
+        };
+        return (verdict(ok), reference);
+    }
+    // Only reached with `pin_on_first_use`; `verify_model_file` refuses otherwise
+    match previous.and_then(|p| p.reference_sha256.clone()) {
+        Some(pinned) if pinned == sha256 => (IntegrityStatus::Pinned, Some(pinned)),
+        Some(pinned) => (IntegrityStatus::Mismatch, Some(pinned)),
+        None => (IntegrityStatus::Pinned, Some(sha256.to_string())),
+    }
+}
+
+/// Verify a model file, reusing the cached result while size and mtime are unchanged.
+/// `force` re-hashes regardless (used after downloads and by the periodic deep scan).
+pub fn verify_model_file(
+    model_path: &Path,
+    expected: &ExpectedChecksums,
+    force: bool,
+) -> Result<IntegrityRecord, String> {
+    let (dir, name) = split_model_path(model_path)?;
+    let stamp = file_stamp(model_path)?;
+    if expected.is_empty() && !expected.pin_on_first_use {
+        return Err(format!(
+            "No published SHA-256 for {:?}; refusing to use it",
+            model_path.file_name().unwrap_or_default()
+        ));
+    }
+
+    let previous = {
+        let _guard = CACHE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        load_cache(&dir).remove(&name)
+    };
+    if let Some(record) = &previous {
+        if !force && record.matches_stamp(&stamp) {
+            return Ok(record.clone());
+        }
+    }
+
+    let (sha256, sha1) = hash_file(model_path)?;
+    let (status, reference_sha256) = evaluate(expected, previous.as_ref(), &sha256, &sha1);
+    let record = IntegrityRecord {
+        size: stamp.size,
+        mtime_secs: stamp.mtime_secs,
+        mtime_nanos: stamp.mtime_nanos,
+        sha256,
+        reference_sha256,
+        expected_sha1: (!expected.sha1.is_empty()).then(|| expected.sha1.to_lowercase()),
+        status,
+        checked_at: chrono::Local::now().to_rfc3339(),
+    };
+
+    if status == IntegrityStatus::Mismatch {
+        log::warn!(
+            "Integrity check failed for {:?}: expected {:?}, got {}",
+            model_path,
+            record.reference_sha256,
+            record.sha256
+        );
+    }
+
+    remember(model_path, &record);
+    let _guard = CACHE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut cache = load_cache(&dir);
+    cache.insert(name, record.clone());
+    save_cache(&dir, &cache)?;
+    Ok(record)
+}
+
+/// Whether the background scan should re-hash this file
+pub fn needs_scan(model_path: &Path) -> bool {
+    let Some(record) = cached_record(model_path) else {
+        return true;
+    };
+    match file_stamp(model_path) {
+        Ok(stamp) => !record.matches_stamp(&stamp) || record.is_stale(),
+        Err(_) => false,
+    }
+}
+
+/// Verify a model before it is loaded into memory. Files the scan has flagged are
+/// refused, files modified since their last verification are re-checked, and files
+/// never verified are hashed against `expected` now. Blocks while hashing; see
+/// `verify_before_load`.
+pub fn check_before_load(model_path: &Path, expected: &ExpectedChecksums) -> Result<(), String> {
+    let stamp = file_stamp(model_path)?;
+    let known = VERIFIED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(model_path)
+        .cloned();
+    if known.as_ref() == Some(&stamp) {
+        return Ok(());
+    }
+
+    let record = match cached_record(model_path) {
+        Some(record) if record.matches_stamp(&stamp) => {
+            remember(model_path, &record);
+            record
+        }
+        Some(record) => {
+            log::info!(
+                "Model {:?} changed since its last verification; re-checking before load",
+                model_path
+            );
+            let reference = ExpectedChecksums {
+                sha256: record
+                    .reference_sha256
+                    .clone()
+                    .unwrap_or_else(|| expected.sha256.clone()),
+                sha1: record
+                    .expected_sha1
+                    .clone()
+                    .unwrap_or_else(|| expected.sha1.clone()),
+                pin_on_first_use: expected.pin_on_first_use,
+            };
+            verify_model_file(model_path, &reference, true)?
+        }
+        None => {
+            log::info!("Verifying {:?} before its first load", model_path);
+            verify_model_file(model_path, expected, false)?
+        }
+    };
+
+    if record.status == IntegrityStatus::Mismatch {
+        return Err(format!(
+            "Model file {:?} failed its integrity check (tampered or corrupted). Please delete and re-download it.",
+            model_path.file_name().unwrap_or_default()
+        ));
+    }
+    Ok(())
+}
+
+/// `check_before_load` on a blocking thread; hashing a large model takes seconds
+pub async fn verify_before_load(
+    model_path: PathBuf,
+    expected: ExpectedChecksums,
+) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || check_before_load(&model_path, &expected))
+        .await
+        .map_err(|e| format!("Model verification task failed: {}", e))?
+}
+
+/// Cheap gate for the transcriber cache: the file must have passed
+/// `check_before_load` and be unchanged since
+pub fn require_verified(model_path: &Path) -> Result<(), String> {
+    let stamp = file_stamp(model_path)?;
+    let verified = VERIFIED.lock().unwrap_or_else(|e| e.into_inner());
+    if verified.get(model_path) == Some(&stamp) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Model file {:?} has not been verified since it last changed",
+            model_path.file_name().unwrap_or_default()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_model(dir: &Path, data: &[u8]) -> PathBuf {
+        let path = dir.join("base.en.bin");
+        std::fs::write(&path, data).unwrap();
+        path
+    }
+
+    fn digests(data: &[u8]) -> (String, String) {
+        (
+            format!("{:x}", Sha256::digest(data)),
+            format!("{:x}", Sha1::digest(data)),
+        )
+    }
+
+    /// Rewrite the file with same-length content and a later mtime
+    fn tamper(path: &Path, data: &[u8]) {
+        let mut data = data.to_vec();
+        data[0] ^= 0xff;
+        std::fs::write(path, &data).unwrap();
+        let file = std::fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(5))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_sha256_verification() {
+        let temp_dir = TempDir::new().unwrap();
+        let data = b"model weights".to_vec();
+        let path = write_model(temp_dir.path(), &data);
+        let (sha256, _) = digests(&data);
+        let expected = ExpectedChecksums {
+            sha256: sha256.clone(),
+            ..Default::default()
+        };
+
+        let record = verify_model_file(&path, &expected, false).unwrap();
+        assert_eq!(record.status, IntegrityStatus::Verified);
+        assert_eq!(cached_record(&path).unwrap(), record);
+        assert!(check_before_load(&path, &expected).is_ok());
+        assert!(require_verified(&path).is_ok());
+        assert!(!needs_scan(&path));
+
+        let wrong = ExpectedChecksums {
+            sha256: "0".repeat(64),
+            ..Default::default()
+        };
+        let record = verify_model_file(&path, &wrong, true).unwrap();
+        assert_eq!(record.status, IntegrityStatus::Mismatch);
+        assert!(require_verified(&path).is_err());
+        assert!(check_before_load(&path, &expected).is_err());
+    }
+
+    #[test]
+    fn test_legacy_sha1_pins_sha256() {
+        let temp_dir = TempDir::new().unwrap();
+        let data = b"legacy model".to_vec();
+        let path = write_model(temp_dir.path(), &data);
+        let (sha256, sha1) = digests(&data);
+
+        let record = verify_model_file(
+            &path,
+            &ExpectedChecksums {
+                sha1,
+                ..Default::default()
+            },
+            false,
+        )
+        .unwrap();
+        assert_eq!(record.status, IntegrityStatus::Verified);
+        assert_eq!(record.reference_sha256, Some(sha256));
+
+        // A later modification is caught at load time against the pinned SHA-256
+        tamper(&path, &data);
+        assert!(needs_scan(&path));
+        assert!(require_verified(&path).is_err());
+        assert!(check_before_load(&path, &ExpectedChecksums::default()).is_err());
+    }
+
+    #[test]
+    fn test_missing_checksum_is_refused_unless_pinning() {
+        let temp_dir = TempDir::new().unwrap();
+        let data = b"onnx export".to_vec();
+        let path = write_model(temp_dir.path(), &data);
+
+        // A catalog model without a checksum is never trusted
+        assert!(verify_model_file(&path, &ExpectedChecksums::default(), false).is_err());
+        assert!(check_before_load(&path, &ExpectedChecksums::default()).is_err());
+        assert!(cached_record(&path).is_none());
+
+        let pinned = ExpectedChecksums::pinned_on_first_use();
+        let record = verify_model_file(&path, &pinned, false).unwrap();
+        assert_eq!(record.status, IntegrityStatus::Pinned);
+
+        // Unchanged file: still pinned after a forced re-hash
+        let record = verify_model_file(&path, &pinned, true).unwrap();
+        assert_eq!(record.status, IntegrityStatus::Pinned);
+
+        tamper(&path, &data);
+        let record = verify_model_file(&path, &pinned, false).unwrap();
+        assert_eq!(record.status, IntegrityStatus::Mismatch);
+    }
+
+    #[test]
+    fn test_first_load_verifies_unscanned_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let data = b"new model".to_vec();
+        let path = write_model(temp_dir.path(), &data);
+        assert!(needs_scan(&path));
+        assert!(require_verified(&path).is_err());
+
+        // Never scanned and not matching the catalog: refused before loading
+        let wrong = ExpectedChecksums {
+            sha256: "0".repeat(64),
+            ..Default::default()
+        };
+        assert!(check_before_load(&path, &wrong).is_err());
+        forget(&path);
+
+        let (sha256, _) = digests(&data);
+        let expected = ExpectedChecksums {
+            sha256,
+            ..Default::default()
+        };
+        assert!(check_before_load(&path, &expected).is_ok());
+        assert_eq!(
+            cached_record(&path).unwrap().status,
+            IntegrityStatus::Verified
+        );
+        assert!(require_verified(&path).is_ok());
+
+        forget(&path);
+        assert!(cached_record(&path).is_none());
+        assert!(require_verified(&path).is_err());
+    }
+}
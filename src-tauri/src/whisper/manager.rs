@@ -1,14 +1,16 @@
-use futures_util::StreamExt;
-use reqwest;
 use sha1::Sha1;
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 use tokio::fs;
 use tokio::io::AsyncReadExt;
-use tokio::io::AsyncWriteExt;
+
+use super::catalog::{self, CustomModelEntry};
+use super::download::{self, DownloadOptions};
+use super::integrity;
+use super::manifest::{ManifestSource, ModelManifest};
 
 // Type-safe size validation
 #[derive(Debug, Clone, Copy)]
@@ -49,7 +51,10 @@ pub struct ModelInfo {
     pub display_name: String,
     pub size: u64,
     pub url: String,
+    /// SHA-256 of the model file
     pub sha256: String,
+    /// Legacy SHA-1 from the whisper.cpp download script, kept for older catalog entries
+    pub sha1: String,
     pub downloaded: bool,
     pub speed_score: u8,    // 1-10, 10 being fastest
     pub accuracy_score: u8, // 1-10, 10 being most accurate
@@ -61,11 +66,26 @@ impl ModelInfo {
     pub fn validated_size(&self) -> Result<ModelSize, String> {
         ModelSize::new(self.size)
     }
+
+    /// Strongest checksum the catalog has for this model (SHA-256, else legacy SHA-1)
+    pub fn reference_checksum(&self) -> &str {
+        if self.sha256.is_empty() {
+            &self.sha1
+        } else {
+            &self.sha256
+        }
+    }
 }
 
 pub struct WhisperManager {
     models_dir: PathBuf,
     models: HashMap<String, ModelInfo>,
+    /// Imported models, persisted in the catalog file
+    custom_models: HashMap<String, CustomModelEntry>,
+    manifest: ModelManifest,
+    manifest_source: ManifestSource,
+    /// Whether `models_dir` existed at the last check (it can live on a removable drive)
+    storage_available: bool,
 }
 
 impl WhisperManager {
@@ -88,101 +108,72 @@ impl WhisperManager {
     }
 
     pub fn new(models_dir: PathBuf) -> Self {
-        let mut models = HashMap::new();
-
-        // Define available models based on official whisper.cpp download script
-        // URLs match https://github.com/ggml-org/whisper.cpp/blob/master/models/download-ggml-model.sh
-
-        // OFFICIAL SHA1 CHECKSUMS from whisper.cpp repository
-        // Source: https://github.com/ggml-org/whisper.cpp/blob/master/models/download-ggml-model.sh
-        // These are SHA1 hashes (40 characters) as used by the official download script
-        // Note: The field is named 'sha256' for historical reasons but contains SHA1 values
-
-        // Multilingual models only (no .en variants)
-        // Removed tiny, small, and medium models - keeping only base and large variants
+        Self::with_manifest(
+            models_dir,
+            ModelManifest::bundled_or_empty(),
+            ManifestSource::Bundled,
+        )
+    }
 
-        models.insert(
-            "base.en".to_string(),
-            ModelInfo {
-                name: "base.en".to_string(),
-                display_name: "Base (English)".to_string(),
-                size: 148_897_792, // 142 MiB = 142 * 1024 * 1024 bytes
-                url: "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-base.en.bin"
-                    .to_string(),
-                sha256: "137c40403d78fd54d454da0f9bd998f78703390c".to_string(), // SHA1 (correct)
-                downloaded: false,
-                speed_score: 8,    // Very fast
-                accuracy_score: 5, // Basic accuracy
-                recommended: false,
-            },
-        );
+    /// Build the registry from a model manifest (see `manifest::load_active_manifest`)
+    pub fn with_manifest(
+        models_dir: PathBuf,
+        manifest: ModelManifest,
+        manifest_source: ManifestSource,
+    ) -> Self {
+        let mut manager = Self {
+            storage_available: models_dir.is_dir(),
+            models_dir,
+            models: HashMap::new(),
+            custom_models: HashMap::new(),
+            manifest,
+            manifest_source,
+        };
+        manager.rebuild_registry();
+        manager
+    }
 
-        models.insert(
-            "large-v3".to_string(),
-            ModelInfo {
-                name: "large-v3".to_string(),
-                display_name: "Large v3".to_string(),
-                size: 3_117_854_720, // 2.9 GiB = 2.9 * 1024 * 1024 * 1024 bytes
-                url: "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-large-v3.bin"
-                    .to_string(),
-                sha256: "ad82bf6a9043ceed055076d0fd39f5f186ff8062".to_string(), // SHA1 (correct)
-                downloaded: false,
-                speed_score: 2,    // Slowest
-                accuracy_score: 9, // Best accuracy
-                recommended: true, // Recommended model
-            },
-        );
+    /// Switch to another manifest at runtime; imported models are kept
+    pub fn apply_manifest(&mut self, manifest: ModelManifest, manifest_source: ManifestSource) {
+        self.manifest = manifest;
+        self.manifest_source = manifest_source;
+        self.rebuild_registry();
+    }
 
-        // Removed: large-v3-q5_0 to simplify model list
-
-        models.insert("large-v3-turbo".to_string(), ModelInfo {
-            name: "large-v3-turbo".to_string(),
-            display_name: "Large v3 Turbo".to_string(),
-            size: 1_610_612_736, // 1.5 GiB = 1.5 * 1024 * 1024 * 1024 bytes
-            url: "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-large-v3-turbo.bin".to_string(),
-            sha256: "4af2b29d7ec73d781377bfd1758ca957a807e941".to_string(), // SHA1 (correct)
-            downloaded: false,
-            speed_score: 7,       // 6x faster than large-v3
-            accuracy_score: 9,    // Comparable to large-v2
-            recommended: true,    // Recommended model
-        });
-
-        // Distil-Whisper: Knowledge-distilled model, 5-6x faster than large-v3
-        // English-only, within 0.8% WER of large-v3
-        // Source: https://huggingface.co/distil-whisper/distil-large-v3-ggml
-        models.insert("distil-large-v3".to_string(), ModelInfo {
-            name: "distil-large-v3".to_string(),
-            display_name: "Distil Large v3".to_string(),
-            size: 1_510_000_000, // ~1.5 GB
-            url: "https://huggingface.co/distil-whisper/distil-large-v3-ggml/resolve/main/ggml-distil-large-v3.bin".to_string(),
-            sha256: "".to_string(), // No official checksum available
-            downloaded: false,
-            speed_score: 9,       // 5-6x faster than large-v3
-            accuracy_score: 8,    // Within 0.8% WER of large-v3, English-only
-            recommended: true,    // Excellent speed/accuracy tradeoff
-        });
+    pub fn manifest(&self) -> &ModelManifest {
+        &self.manifest
+    }
 
-        models.insert(
-            "small.en".to_string(),
-            ModelInfo {
-                name: "small.en".to_string(),
-                display_name: "Small (English)".to_string(),
-                size: 488_505_344, // 466 MiB = 466 * 1024 * 1024 bytes
-                url: "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-small.en.bin"
-                    .to_string(),
-                sha256: "db8a495a91d927739e50b3fc1cc4c6b8f6c2d022".to_string(), // SHA1 (correct)
-                downloaded: false,
-                speed_score: 7,    // Fast for English-only
-                accuracy_score: 6, // Good accuracy for English
-                recommended: false,
-            },
-        );
+    pub fn manifest_source(&self) -> ManifestSource {
+        self.manifest_source
+    }
 
-        // Removed: large-v3-turbo-q8_0 to simplify model list
+    fn rebuild_registry(&mut self) {
+        self.models = self
+            .manifest
+            .models
+            .iter()
+            .map(|entry| (entry.name.clone(), entry.to_model_info()))
+            .collect();
+        self.custom_models.clear();
+        self.load_custom_models();
+        self.check_downloaded_models();
+    }
 
-        let mut manager = Self { models_dir, models };
-        manager.check_downloaded_models();
-        manager
+    /// Add the models from the import catalog to the registry
+    fn load_custom_models(&mut self) {
+        for entry in catalog::load_catalog(&self.models_dir) {
+            if self.models.contains_key(&entry.name) {
+                log::warn!(
+                    "Skipping custom model '{}': name clashes with a built-in model",
+                    entry.name
+                );
+                continue;
+            }
+            self.models
+                .insert(entry.name.clone(), entry.to_model_info());
+            self.custom_models.insert(entry.name.clone(), entry);
+        }
     }
 
     fn check_downloaded_models(&mut self) {
@@ -278,6 +269,13 @@ impl WhisperManager {
             model_name
         ))?;
 
+        if self.is_custom_model(model_name) {
+            return Err(format!(
+                "Model '{}' was imported from a local file and cannot be downloaded",
+                model_name
+            ));
+        }
+
         // Validate model size before downloading
         let _ = model.validated_size()?;
 
@@ -342,128 +340,78 @@ impl WhisperManager {
             model_info.name
         );
 
-        // Download the model
-        let client = reqwest::Client::new();
-        let response = client
-            .get(&model_info.url)
-            .send()
-            .await
-            .map_err(|e| e.to_string())?;
-
-        let total_size = response.content_length().unwrap_or(model_info.size);
-
-        // Validate reported size matches expected size (allow 10% variance for compression)
-        let size_variance =
-            (total_size as f64 - model_info.size as f64).abs() / model_info.size as f64;
-        if size_variance > 0.1 {
+        // Nothing to verify against; don't download a file we would have to trust blindly
+        if model_info.reference_checksum().is_empty() {
             return Err(format!(
-                "Model size mismatch: expected {} bytes, server reports {} bytes ({}% difference)",
-                model_info.size,
-                total_size,
-                (size_variance * 100.0) as u32
+                "Model '{}' has no published checksum and cannot be downloaded",
+                model_info.name
             ));
         }
 
-        // Validate the total size is within our limits
-        let _ = ModelSize::new(total_size)?;
-
-        let mut file = fs::File::create(&output_path)
-            .await
-            .map_err(|e| e.to_string())?;
-
-        let mut downloaded: u64 = 0;
-        let mut stream = response.bytes_stream();
-        let mut last_progress_update = 0u64;
-        let update_threshold = total_size / 100; // Update every 1%
-
-        while let Some(chunk) = stream.next().await {
-            // Check for cancellation
-            if let Some(ref flag) = cancel_flag {
-                if flag.load(Ordering::Relaxed) {
-                    log::info!("Download cancelled by user for model: {}", model_info.name);
-                    // Clean up partial download
-                    drop(file);
-                    let _ = fs::remove_file(&output_path).await;
-                    return Err("Download cancelled by user".to_string());
-                }
-            }
-
-            let chunk = chunk.map_err(|e| e.to_string())?;
-
-            // Prevent downloading more than expected (with 1% tolerance)
-            if downloaded + chunk.len() as u64 > (total_size as f64 * 1.01) as u64 {
-                // Clean up partial download
-                drop(file);
-                let _ = fs::remove_file(&output_path).await;
-
-                return Err(format!(
-                    "Download exceeded expected size: downloaded {} bytes, expected {} bytes",
-                    downloaded + chunk.len() as u64,
-                    total_size
-                ));
-            }
-
-            file.write_all(&chunk).await.map_err(|e| e.to_string())?;
-
-            downloaded += chunk.len() as u64;
-
-            // Only update progress every 1% to avoid flooding the UI
-            if downloaded - last_progress_update >= update_threshold || downloaded == total_size {
-                progress_callback(downloaded, total_size);
-                last_progress_update = downloaded;
-            }
+        // Download through a .part file so interrupted downloads resume where they stopped
+        let resumed_bytes = download::resumable_bytes(output_path, &model_info.url);
+        if resumed_bytes > 0 {
+            log::info!(
+                "[download_model] Resuming '{}' from {} bytes",
+                model_info.name,
+                resumed_bytes
+            );
         }
 
-        // Ensure file is flushed to disk
-        file.flush().await.map_err(|e| e.to_string())?;
-        // Force OS to write to physical disk
-        file.sync_all()
-            .await
-            .map_err(|e| format!("Failed to sync file to disk: {}", e))?;
-        drop(file);
-
-        // Also sync the parent directory to ensure directory entry is visible
-        if let Some(parent) = output_path.parent() {
-            if let Ok(dir) = std::fs::File::open(parent) {
-                let _ = dir.sync_all();
-            }
-        }
+        let expected_size = model_info.size;
+        download::download_resumable(
+            &model_info.url,
+            output_path,
+            expected_size,
+            |total_size| {
+                // Validate reported size matches expected size (allow 10% variance for compression)
+                let size_variance =
+                    (total_size as f64 - expected_size as f64).abs() / expected_size as f64;
+                if size_variance > 0.1 {
+                    return Err(format!(
+                        "Model size mismatch: expected {} bytes, server reports {} bytes ({}% difference)",
+                        expected_size,
+                        total_size,
+                        (size_variance * 100.0) as u32
+                    ));
+                }
 
-        // Ensure final 100% progress is sent
-        if downloaded < total_size {
-            progress_callback(total_size, total_size);
-        }
+                // Validate the total size is within our limits
+                ModelSize::new(total_size).map(|_| ())
+            },
+            cancel_flag.as_deref(),
+            &DownloadOptions::default(),
+            progress_callback,
+        )
+        .await?;
+
+        // Verify against the catalog checksum (SHA-256, or legacy SHA-1) and record
+        // the result so load-time checks can reuse it
+        log::info!("Verifying model checksum...");
+        let expected = integrity::ExpectedChecksums::from_model(model_info);
+        let verify_path = output_path.clone();
+        let record = tokio::task::spawn_blocking(move || {
+            integrity::verify_model_file(&verify_path, &expected, true)
+        })
+        .await
+        .map_err(|e| format!("Checksum task failed: {}", e))??;
 
-        // Verify checksum if available
-        if !model_info.sha256.is_empty() {
-            log::info!("Verifying model checksum...");
-            match model_info.sha256.len() {
-                40 => {
-                    // SHA1 checksum (legacy from whisper.cpp)
-                    Self::verify_sha1_checksum(&output_path, &model_info.sha256).await?;
-                }
-                64 => {
-                    // SHA256 checksum (preferred)
-                    Self::verify_sha256_checksum(&output_path, &model_info.sha256).await?;
-                }
-                _ => {
-                    log::warn!(
-                        "Invalid checksum length for {}. Skipping verification.",
-                        model_info.name
-                    );
-                    log::warn!(
-                        "Expected SHA1 (40 chars) or SHA256 (64 chars), got {} chars.",
-                        model_info.sha256.len()
-                    );
-                }
-            }
-        } else {
-            log::warn!(
-                "No checksum available for {}. Skipping verification.",
-                model_info.name
-            );
-            log::warn!("File integrity cannot be guaranteed without checksum verification.");
+        if record.status == integrity::IntegrityStatus::Mismatch {
+            // Delete the corrupted file
+            let _ = fs::remove_file(&output_path).await;
+            integrity::forget(output_path);
+            return Err(format!(
+                "Checksum verification failed!\nExpected: {}\nCalculated SHA-256: {}\nFile has been deleted.",
+                model_info.reference_checksum(),
+                record.sha256
+            ));
         }
+        log::info!(
+            "Model {} integrity {:?} (sha256 {})",
+            model_info.name,
+            record.status,
+            record.sha256
+        );
 
         // Log what files are in the directory after download
         log::info!("[download_model] Download complete. Listing models directory:");
@@ -479,7 +427,7 @@ impl WhisperManager {
     }
 
     /// Verify the SHA256 checksum of a downloaded file
-    async fn verify_sha256_checksum(
+    pub(crate) async fn verify_sha256_checksum(
         file_path: &PathBuf,
         expected_checksum: &str,
     ) -> Result<(), String> {
@@ -514,7 +462,7 @@ impl WhisperManager {
             // Delete the corrupted file
             let _ = fs::remove_file(file_path).await;
             return Err(format!(
-                "Checksum verification failed!\nExpected: {}\nCalculated: {}\nFile has been deleted.",
+                "Checksum verification failed!\nExpected: {}\nCalculated SHA-256: {}\nFile has been deleted.",
                 expected_checksum,
                 calculated_checksum
             ));
@@ -525,7 +473,7 @@ impl WhisperManager {
     }
 
     /// Verify the SHA1 checksum of a downloaded file (legacy support for whisper.cpp models)
-    async fn verify_sha1_checksum(
+    pub(crate) async fn verify_sha1_checksum(
         file_path: &PathBuf,
         expected_checksum: &str,
     ) -> Result<(), String> {
@@ -650,20 +598,107 @@ impl WhisperManager {
         }
 
         let path = self.models_dir.join(format!("{}.bin", model_name));
+        // Drop any interrupted download alongside the model
+        download::discard_partial(&path);
         if !path.exists() {
             return Err("Model file not found".to_string());
         }
         std::fs::remove_file(&path).map_err(|e| e.to_string())?;
+        integrity::forget(&path);
 
         // update internal flags
         if let Some(info) = self.models.get_mut(model_name) {
             info.downloaded = false;
         }
+
+        // Imported models have no source to re-download from, so drop them entirely
+        if self.custom_models.remove(model_name).is_some() {
+            self.models.remove(model_name);
+            self.save_custom_catalog()?;
+        }
+
         // Also refresh to catch any other changes
         self.refresh_downloaded_status();
         Ok(())
     }
 
+    pub fn models_dir(&self) -> &PathBuf {
+        &self.models_dir
+    }
+
+    /// Point the manager at a new models directory (after the files were moved there)
+    pub fn set_models_dir(&mut self, models_dir: PathBuf) {
+        log::info!("Models directory changed to {:?}", models_dir);
+        self.storage_available = models_dir.is_dir();
+        self.models_dir = models_dir;
+        self.rebuild_registry();
+    }
+
+    /// Re-check that the models directory is reachable, rescanning when a drive was
+    /// connected or removed. Returns true if availability changed.
+    pub fn recheck_storage(&mut self) -> bool {
+        let available = self.models_dir.is_dir();
+        if available == self.storage_available {
+            return false;
+        }
+        if available {
+            log::info!("Models directory {:?} is available again", self.models_dir);
+        } else {
+            log::warn!(
+                "Models directory {:?} is no longer available",
+                self.models_dir
+            );
+        }
+        self.storage_available = available;
+        self.rebuild_registry();
+        true
+    }
+
+    /// Message to show instead of "model not found" while the models folder is missing
+    pub fn storage_error(&self) -> Option<String> {
+        (!self.models_dir.is_dir()).then(|| super::storage::unavailable_message(&self.models_dir))
+    }
+
+    pub fn is_custom_model(&self, model_name: &str) -> bool {
+        self.custom_models.contains_key(model_name)
+    }
+
+    pub fn get_custom_models(&self) -> Vec<CustomModelEntry> {
+        self.custom_models.values().cloned().collect()
+    }
+
+    /// Unused registry name for an imported model with this display name
+    pub fn custom_model_name(&self, display_name: &str) -> String {
+        catalog::custom_model_name(display_name, |name| {
+            self.models.contains_key(name) || self.models_dir.join(format!("{}.bin", name)).exists()
+        })
+    }
+
+    /// Register an imported model whose file is already at `<models_dir>/<name>.bin`
+    pub fn register_custom_model(&mut self, entry: CustomModelEntry) -> Result<(), String> {
+        if self.models.contains_key(&entry.name) {
+            return Err(format!("Model '{}' already exists", entry.name));
+        }
+
+        self.models
+            .insert(entry.name.clone(), entry.to_model_info());
+        self.custom_models.insert(entry.name.clone(), entry.clone());
+        if let Err(e) = self.save_custom_catalog() {
+            self.models.remove(&entry.name);
+            self.custom_models.remove(&entry.name);
+            return Err(e);
+        }
+
+        self.refresh_downloaded_status();
+        Ok(())
+    }
+
+    fn save_custom_catalog(&self) -> Result<(), String> {
+        let mut entries = self.get_custom_models();
+        entries.sort_by(|a, b| a.imported_at.cmp(&b.imported_at));
+        catalog::save_catalog(&self.models_dir, &entries)
+    }
+
     /// Calculate a balanced performance score (combines speed and accuracy)
     #[allow(dead_code)]
     pub fn calculate_balanced_score(speed: u8, accuracy: u8) -> f32 {
@@ -746,6 +781,7 @@ impl WhisperManager {
                 size: 1024, // 1KB for tests
                 url: "https://test.example.com/base.en.bin".to_string(),
                 sha256: "test_hash".to_string(),
+                sha1: String::new(),
                 downloaded: false,
                 speed_score: 8,
                 accuracy_score: 5,
@@ -761,6 +797,7 @@ impl WhisperManager {
                 size: 2048, // 2KB for tests
                 url: "https://test.example.com/large-v3.bin".to_string(),
                 sha256: "test_hash_v3".to_string(),
+                sha1: String::new(),
                 downloaded: false,
                 speed_score: 2,
                 accuracy_score: 9,
@@ -776,6 +813,7 @@ impl WhisperManager {
                 size: 1536, // 1.5KB for tests
                 url: "https://test.example.com/large-v3-q5_0.bin".to_string(),
                 sha256: "test_hash_q5".to_string(),
+                sha1: String::new(),
                 downloaded: false,
                 speed_score: 4,
                 accuracy_score: 8,
@@ -783,7 +821,15 @@ impl WhisperManager {
             },
         );
 
-        let mut manager = Self { models, models_dir };
+        let mut manager = Self {
+            models,
+            models_dir,
+            custom_models: HashMap::new(),
+            manifest: ModelManifest::bundled_or_empty(),
+            manifest_source: ManifestSource::Bundled,
+            storage_available: true,
+        };
+        manager.load_custom_models();
         manager.check_downloaded_models();
         manager
     }
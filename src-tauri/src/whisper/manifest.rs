@@ -0,0 +1,500 @@
+use base64::{engine::general_purpose, Engine as _};
+use ring::signature::{UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::manager::{ModelInfo, ModelSize};
+
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// Manifest shipped inside the binary; always available as the fallback
+const BUNDLED_MANIFEST: &str = include_str!("model_manifest.json");
+const BUNDLED_SIGNATURE: &str = include_str!("model_manifest.json.sig");
+/// Base64 Ed25519 key the project signs manifests with (scripts/sign-model-manifest.js)
+const PROJECT_PUBLIC_KEY: &str = include_str!("manifest_signing_key.pub");
+/// Verified override, cached in the models directory with its `.sig` next to it
+const OVERRIDE_FILE: &str = "model_manifest.json";
+const MAX_MANIFEST_BYTES: usize = 1024 * 1024;
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// One downloadable model in the catalog
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub name: String,
+    pub display_name: String,
+    pub url: String,
+    pub size: u64,
+    /// Hex SHA-256 of the model file, as published by the host
+    pub sha256: String,
+    /// Legacy SHA-1 from the whisper.cpp download script
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha1: Option<String>,
+    /// Language codes, or `["*"]` for every language Whisper supports
+    pub languages: Vec<String>,
+    pub quantization: String,
+    pub min_ram_mb: u64,
+    pub speed_score: u8,
+    pub accuracy_score: u8,
+    #[serde(default)]
+    pub recommended: bool,
+}
+
+impl ManifestEntry {
+    pub fn to_model_info(&self) -> ModelInfo {
+        ModelInfo {
+            name: self.name.clone(),
+            display_name: self.display_name.clone(),
+            size: self.size,
+            url: self.url.clone(),
+            sha256: self.sha256.clone(),
+            sha1: self.sha1.clone().unwrap_or_default(),
+            downloaded: false,
+            speed_score: self.speed_score,
+            accuracy_score: self.accuracy_score,
+            recommended: self.recommended,
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let is_hex =
+            |s: &str, len: usize| s.len() == len && s.chars().all(|c| c.is_ascii_hexdigit());
+
+        if self.name.is_empty()
+            || self.name.starts_with(super::catalog::CUSTOM_PREFIX)
+            || !self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+            || self.name.contains("..")
+        {
+            return Err(format!("Invalid model name '{}'", self.name));
+        }
+        if !self.url.starts_with("https://") {
+            return Err(format!("Model '{}' must use an https URL", self.name));
+        }
+        ModelSize::new(self.size).map_err(|e| format!("Model '{}': {}", self.name, e))?;
+        if self.sha256.is_empty() {
+            return Err(format!("Model '{}' has no SHA-256", self.name));
+        }
+        if !is_hex(&self.sha256, 64) {
+            return Err(format!("Model '{}' has a malformed SHA-256", self.name));
+        }
+        if let Some(sha1) = &self.sha1 {
+            if !is_hex(sha1, 40) {
+                return Err(format!("Model '{}' has a malformed SHA-1", self.name));
+            }
+        }
+        if self.languages.is_empty() {
+            return Err(format!("Model '{}' lists no languages", self.name));
+        }
+        if !(1..=10).contains(&self.speed_score) || !(1..=10).contains(&self.accuracy_score) {
+            return Err(format!("Model '{}' has scores outside 1-10", self.name));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelManifest {
+    pub version: u32,
+    pub models: Vec<ManifestEntry>,
+}
+
+impl ModelManifest {
+    pub fn bundled() -> Result<Self, String> {
+        parse_signed(BUNDLED_MANIFEST.as_bytes(), BUNDLED_SIGNATURE, None)
+            .map_err(|e| format!("Bundled model manifest is invalid: {}", e))
+    }
+
+    /// The bundled manifest, or an empty catalog when it cannot be parsed so the
+    /// app still starts and imported models keep working
+    pub fn bundled_or_empty() -> Self {
+        Self::bundled().unwrap_or_else(|e| {
+            log::error!("{}", e);
+            Self {
+                version: MANIFEST_VERSION,
+                models: Vec::new(),
+            }
+        })
+    }
+
+    pub fn parse(content: &[u8]) -> Result<Self, String> {
+        if content.len() > MAX_MANIFEST_BYTES {
+            return Err("Model manifest is too large".to_string());
+        }
+        let manifest: Self = serde_json::from_slice(content)
+            .map_err(|e| format!("Invalid model manifest: {}", e))?;
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.version != MANIFEST_VERSION {
+            return Err(format!(
+                "Unsupported model manifest version {} (expected {})",
+                self.version, MANIFEST_VERSION
+            ));
+        }
+        if self.models.is_empty() {
+            return Err("Model manifest lists no models".to_string());
+        }
+        let mut names = HashSet::new();
+        for entry in &self.models {
+            entry.validate()?;
+            if !names.insert(entry.name.as_str()) {
+                return Err(format!("Duplicate model '{}' in manifest", entry.name));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ManifestSource {
+    Bundled,
+    Override,
+}
+
+/// Where to take the model catalog from, stored under "model_manifest" in settings
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ManifestSettings {
+    /// Local manifest file or https mirror URL; `None` uses the bundled manifest.
+    /// The detached signature is read from the same location with `.sig` appended.
+    pub source: Option<String>,
+    /// Base64 Ed25519 key trusted in addition to the project key, for mirrors that
+    /// sign their own manifest
+    pub public_key: Option<String>,
+}
+
+impl ManifestSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(source) = &self.source {
+            if source.starts_with("http://") {
+                return Err("Manifest mirrors must use https".to_string());
+            }
+        }
+        if let Some(key) = &self.public_key {
+            decode_public_key(key)?;
+        }
+        Ok(())
+    }
+}
+
+fn decode_public_key(key: &str) -> Result<Vec<u8>, String> {
+    let bytes = general_purpose::STANDARD
+        .decode(key.trim())
+        .map_err(|_| "Public key is not valid base64".to_string())?;
+    if bytes.len() != 32 {
+        return Err("Public key must be a 32-byte Ed25519 key".to_string());
+    }
+    Ok(bytes)
+}
+
+/// Check a detached base64 Ed25519 signature over the raw manifest bytes. The
+/// project key is always trusted; `extra_key` is the user's own key, if any.
+pub fn verify_signature(
+    content: &[u8],
+    signature: &str,
+    extra_key: Option<&str>,
+) -> Result<(), String> {
+    let signature = general_purpose::STANDARD
+        .decode(signature.trim())
+        .map_err(|_| "Manifest signature is not valid base64".to_string())?;
+    for key in std::iter::once(PROJECT_PUBLIC_KEY).chain(extra_key) {
+        let key = decode_public_key(key)?;
+        if UnparsedPublicKey::new(&ED25519, key)
+            .verify(content, &signature)
+            .is_ok()
+        {
+            return Ok(());
+        }
+    }
+    Err("Manifest signature does not match a trusted key".to_string())
+}
+
+/// Verify and parse a manifest
+pub fn parse_signed(
+    content: &[u8],
+    signature: &str,
+    extra_key: Option<&str>,
+) -> Result<ModelManifest, String> {
+    verify_signature(content, signature, extra_key)?;
+    ModelManifest::parse(content)
+}
+
+fn override_paths(models_dir: &Path) -> (PathBuf, PathBuf) {
+    let path = models_dir.join(OVERRIDE_FILE);
+    let sig_path = models_dir.join(format!("{}.sig", OVERRIDE_FILE));
+    (path, sig_path)
+}
+
+/// The cached override if it still verifies, otherwise the bundled manifest
+pub fn load_active_manifest(
+    models_dir: &Path,
+    extra_key: Option<&str>,
+) -> (ModelManifest, ManifestSource) {
+    let (path, sig_path) = override_paths(models_dir);
+    if !path.exists() {
+        return (ModelManifest::bundled_or_empty(), ManifestSource::Bundled);
+    }
+
+    let loaded = std::fs::read(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|content| {
+            let signature = std::fs::read_to_string(&sig_path)
+                .map_err(|e| format!("missing signature: {}", e))?;
+            parse_signed(&content, &signature, extra_key)
+        });
+
+    match loaded {
+        Ok(manifest) => {
+            log::info!(
+                "Using model manifest override with {} models",
+                manifest.models.len()
+            );
+            (manifest, ManifestSource::Override)
+        }
+        Err(e) => {
+            log::warn!(
+                "Ignoring model manifest override {:?}, using bundled copy: {}",
+                path,
+                e
+            );
+            (ModelManifest::bundled_or_empty(), ManifestSource::Bundled)
+        }
+    }
+}
+
+/// Cache a verified override so it is picked up on the next start
+pub fn install_override(models_dir: &Path, content: &[u8], signature: &str) -> Result<(), String> {
+    std::fs::create_dir_all(models_dir)
+        .map_err(|e| format!("Failed to create models directory: {}", e))?;
+    let (path, sig_path) = override_paths(models_dir);
+    for (target, data) in [(&path, content), (&sig_path, signature.trim().as_bytes())] {
+        let tmp = target.with_extension("tmp");
+        std::fs::write(&tmp, data).map_err(|e| format!("Failed to write manifest: {}", e))?;
+        std::fs::rename(&tmp, target).map_err(|e| format!("Failed to save manifest: {}", e))?;
+    }
+    Ok(())
+}
+
+pub fn remove_override(models_dir: &Path) {
+    let (path, sig_path) = override_paths(models_dir);
+    let _ = std::fs::remove_file(path);
+    let _ = std::fs::remove_file(sig_path);
+}
+
+/// Fetch a manifest and its `.sig` from a local path or https mirror
+pub async fn fetch_manifest(source: &str) -> Result<(Vec<u8>, String), String> {
+    let sig_source = format!("{}.sig", source);
+
+    if !source.starts_with("https://") {
+        let content = tokio::fs::read(source)
+            .await
+            .map_err(|e| format!("Failed to read manifest {}: {}", source, e))?;
+        let signature = tokio::fs::read_to_string(&sig_source)
+            .await
+            .map_err(|e| format!("Failed to read manifest signature {}: {}", sig_source, e))?;
+        return Ok((content, signature));
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+    let mut fetched = Vec::with_capacity(2);
+    for url in [source, sig_source.as_str()] {
+        let response = client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to fetch {}: {}", url, e))?;
+        if !response.status().is_success() {
+            return Err(format!(
+                "Failed to fetch {}: HTTP {}",
+                url,
+                response.status()
+            ));
+        }
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| format!("Failed to read {}: {}", url, e))?;
+        if bytes.len() > MAX_MANIFEST_BYTES {
+            return Err(format!("{} is too large", url));
+        }
+        fetched.push(bytes.to_vec());
+    }
+
+    let signature = String::from_utf8(fetched.pop().unwrap_or_default())
+        .map_err(|_| "Manifest signature is not valid text".to_string())?;
+    let content = fetched.pop().unwrap_or_default();
+    Ok((content, signature))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::rand::SystemRandom;
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+    use tempfile::TempDir;
+
+    fn key_pair() -> (Ed25519KeyPair, String) {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        let pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+        let public_key = general_purpose::STANDARD.encode(pair.public_key().as_ref());
+        (pair, public_key)
+    }
+
+    fn sign(pair: &Ed25519KeyPair, content: &[u8]) -> String {
+        general_purpose::STANDARD.encode(pair.sign(content).as_ref())
+    }
+
+    fn mirror_manifest() -> Vec<u8> {
+        let mut manifest = ModelManifest::bundled().unwrap();
+        manifest.models.truncate(1);
+        manifest.models[0].display_name = "Base (mirror)".to_string();
+        serde_json::to_vec(&manifest).unwrap()
+    }
+
+    #[test]
+    fn test_bundled_manifest_is_valid() {
+        let manifest = ModelManifest::bundled().unwrap();
+        let get = |name: &str| manifest.models.iter().find(|m| m.name == name).unwrap();
+        for entry in &manifest.models {
+            assert!(entry.min_ram_mb > 0);
+            assert!(!entry.quantization.is_empty());
+        }
+        let base = get("base.en");
+        assert_eq!(base.languages, vec!["en"]);
+        assert_eq!(get("large-v3").languages, vec!["*"]);
+        // Legacy SHA-1 stays separate from the SHA-256
+        let info = base.to_model_info();
+        assert_eq!(info.sha1.len(), 40);
+        assert_eq!(info.reference_checksum(), info.sha256);
+    }
+
+    #[test]
+    fn test_rejects_invalid_manifests() {
+        assert!(ModelManifest::parse(b"not json").is_err());
+
+        let mut manifest = ModelManifest::bundled().unwrap();
+        manifest.version = 99;
+        assert!(ModelManifest::parse(&serde_json::to_vec(&manifest).unwrap()).is_err());
+
+        let mut manifest = ModelManifest::bundled().unwrap();
+        manifest.models.push(manifest.models[0].clone());
+        let err = ModelManifest::parse(&serde_json::to_vec(&manifest).unwrap()).unwrap_err();
+        assert!(err.contains("Duplicate"));
+
+        let mut manifest = ModelManifest::bundled().unwrap();
+        manifest.models[0].name = "../evil".to_string();
+        assert!(ModelManifest::parse(&serde_json::to_vec(&manifest).unwrap()).is_err());
+
+        let mut manifest = ModelManifest::bundled().unwrap();
+        manifest.models[0].url = "http://example.com/model.bin".to_string();
+        assert!(ModelManifest::parse(&serde_json::to_vec(&manifest).unwrap()).is_err());
+
+        // Every model needs a SHA-256; there is no trust on first use
+        let mut manifest = ModelManifest::bundled().unwrap();
+        manifest.models[0].sha256.clear();
+        let err = ModelManifest::parse(&serde_json::to_vec(&manifest).unwrap()).unwrap_err();
+        assert!(err.contains("no SHA-256"));
+    }
+
+    #[test]
+    fn test_signature_verification() {
+        let (pair, public_key) = key_pair();
+        let (_, other_key) = key_pair();
+        let content = mirror_manifest();
+        let signature = sign(&pair, &content);
+
+        assert!(parse_signed(&content, &signature, Some(&public_key)).is_ok());
+        assert!(parse_signed(&content, &signature, Some(&other_key)).is_err());
+        // Only the project key is trusted unless the user adds one
+        assert!(parse_signed(&content, &signature, None).is_err());
+
+        let mut tampered = content.clone();
+        let pos = tampered.len() / 2;
+        tampered[pos] ^= 1;
+        assert!(verify_signature(&tampered, &signature, Some(&public_key)).is_err());
+    }
+
+    #[test]
+    fn test_bundled_manifest_is_signed() {
+        decode_public_key(PROJECT_PUBLIC_KEY).unwrap();
+        assert!(verify_signature(BUNDLED_MANIFEST.as_bytes(), BUNDLED_SIGNATURE, None).is_ok());
+
+        let mut tampered = BUNDLED_MANIFEST.as_bytes().to_vec();
+        tampered[0] = b' ';
+        assert!(verify_signature(&tampered, BUNDLED_SIGNATURE, None).is_err());
+    }
+
+    #[test]
+    fn test_override_falls_back_to_bundled() {
+        let temp_dir = TempDir::new().unwrap();
+        let (pair, public_key) = key_pair();
+        let content = mirror_manifest();
+
+        // Nothing installed
+        let (_, source) = load_active_manifest(temp_dir.path(), Some(&public_key));
+        assert_eq!(source, ManifestSource::Bundled);
+
+        install_override(temp_dir.path(), &content, &sign(&pair, &content)).unwrap();
+        let (manifest, source) = load_active_manifest(temp_dir.path(), Some(&public_key));
+        assert_eq!(source, ManifestSource::Override);
+        assert_eq!(manifest.models[0].display_name, "Base (mirror)");
+
+        // Signed by neither the project key nor a key the user added
+        let (_, source) = load_active_manifest(temp_dir.path(), None);
+        assert_eq!(source, ManifestSource::Bundled);
+        let (_, other_key) = key_pair();
+        let (_, source) = load_active_manifest(temp_dir.path(), Some(&other_key));
+        assert_eq!(source, ManifestSource::Bundled);
+
+        // Corrupted on disk
+        std::fs::write(temp_dir.path().join(OVERRIDE_FILE), b"{}").unwrap();
+        let (manifest, source) = load_active_manifest(temp_dir.path(), Some(&public_key));
+        assert_eq!(source, ManifestSource::Bundled);
+        assert_eq!(manifest, ModelManifest::bundled().unwrap());
+
+        remove_override(temp_dir.path());
+        assert!(!temp_dir.path().join(OVERRIDE_FILE).exists());
+    }
+
+    #[test]
+    fn test_settings_validation() {
+        let (_, public_key) = key_pair();
+        assert!(ManifestSettings::default().validate().is_ok());
+        // Mirrors of the project manifest need no extra key
+        assert!(ManifestSettings {
+            source: Some("https://mirror.example.com/models.json".to_string()),
+            public_key: None,
+        }
+        .validate()
+        .is_ok());
+        assert!(ManifestSettings {
+            source: None,
+            public_key: Some("not a key".to_string()),
+        }
+        .validate()
+        .is_err());
+        assert!(ManifestSettings {
+            source: Some("http://mirror.example.com/models.json".to_string()),
+            public_key: Some(public_key.clone()),
+        }
+        .validate()
+        .is_err());
+        assert!(ManifestSettings {
+            source: Some("/tmp/models.json".to_string()),
+            public_key: Some(public_key),
+        }
+        .validate()
+        .is_ok());
+    }
+}
@@ -1,4 +1,13 @@
+pub mod bundle;
 pub mod cache;
+pub mod catalog;
+pub mod download;
+pub mod integrity;
 pub mod languages;
 pub mod manager;
+pub mod manifest;
+pub mod model_file;
+pub mod quantize;
+pub mod recommender;
+pub mod storage;
 pub mod transcriber;
@@ -0,0 +1,467 @@
+use serde::{Deserialize, Serialize};
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+/// "ggml" magic as written by whisper.cpp (little-endian u32)
+pub(super) const GGML_MAGIC: u32 = 0x6767_6d6c;
+const GGUF_MAGIC: &[u8; 4] = b"GGUF";
+
+/// Largest vocabulary of any released Whisper model (multilingual v3 has 51866)
+const MAX_VOCAB: i32 = 100_000;
+/// English-only models have 51864 tokens; multilingual ones have more
+const MULTILINGUAL_MIN_VOCAB: i32 = 51_865;
+
+/// Guards against reading absurd lengths from a corrupt GGUF header
+const MAX_GGUF_KV: u64 = 100_000;
+const MAX_GGUF_STRING: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModelFormat {
+    Ggml,
+    Gguf,
+}
+
+/// Whisper hyperparameters from the model header
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WhisperHyperparams {
+    pub n_vocab: i32,
+    pub n_audio_ctx: i32,
+    pub n_audio_state: i32,
+    pub n_audio_head: i32,
+    pub n_audio_layer: i32,
+    pub n_text_ctx: i32,
+    pub n_text_state: i32,
+    pub n_text_head: i32,
+    pub n_text_layer: i32,
+    pub n_mels: i32,
+    pub ftype: i32,
+}
+
+impl WhisperHyperparams {
+    pub fn multilingual(&self) -> bool {
+        self.n_vocab >= MULTILINGUAL_MIN_VOCAB
+    }
+
+    /// Weight type, e.g. "f16" or "q5_0"
+    pub fn quantization(&self) -> &'static str {
+        // whisper.cpp stores the quantization version in the thousands
+        match self.ftype % 1000 {
+            0 => "f32",
+            1 => "f16",
+            2 => "q4_0",
+            3 => "q4_1",
+            7 => "q8_0",
+            8 => "q5_0",
+            9 => "q5_1",
+            10 => "q2_k",
+            11 => "q3_k",
+            12 => "q4_k",
+            13 => "q5_k",
+            14 => "q6_k",
+            _ => "unknown",
+        }
+    }
+
+    /// Architecture size inferred from the encoder depth
+    pub fn size_class(&self) -> &'static str {
+        match self.n_audio_layer {
+            ..=4 => "tiny",
+            5..=6 => "base",
+            7..=12 => "small",
+            13..=24 => "medium",
+            _ => "large",
+        }
+    }
+
+    /// Rough (speed, accuracy) scores on the same 1-10 scale as the built-in models
+    pub fn estimated_scores(&self) -> (u8, u8) {
+        let (speed, accuracy): (u8, u8) = match self.size_class() {
+            "tiny" => (10, 3),
+            "base" => (8, 5),
+            "small" => (7, 6),
+            "medium" => (5, 7),
+            _ => (2, 9),
+        };
+        // Distilled/turbo variants keep the large encoder but have a tiny decoder
+        let speed = if self.n_text_layer <= 4 && self.n_audio_layer > 24 {
+            7
+        } else {
+            speed
+        };
+        // Quantized weights run faster and lose a little accuracy
+        if self.ftype % 1000 >= 2 {
+            (
+                speed.saturating_add(1).min(10),
+                accuracy.saturating_sub(1).max(1),
+            )
+        } else {
+            (speed, accuracy)
+        }
+    }
+
+    pub(super) fn validate(&self) -> Result<(), String> {
+        let in_range = |value: i32, min: i32, max: i32| value >= min && value <= max;
+        if !in_range(self.n_vocab, 1, MAX_VOCAB)
+            || !in_range(self.n_audio_layer, 1, 64)
+            || !in_range(self.n_text_layer, 1, 64)
+            || !in_range(self.n_audio_state, 1, 8192)
+            || !in_range(self.n_text_state, 1, 8192)
+            || !in_range(self.n_audio_head, 1, 128)
+            || !in_range(self.n_text_head, 1, 128)
+            || !in_range(self.n_mels, 1, 256)
+        {
+            return Err(format!(
+                "Model header has implausible hyperparameters: {:?}",
+                self
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// What can be learned about a model file without loading it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelFileInfo {
+    pub format: ModelFormat,
+    pub hyperparams: WhisperHyperparams,
+    pub multilingual: bool,
+    pub quantization: String,
+    pub size_class: String,
+    pub file_size: u64,
+}
+
+/// Read and validate the header of a ggml or gguf Whisper model
+pub fn inspect_model_file(path: &Path) -> Result<ModelFileInfo, String> {
+    let file =
+        std::fs::File::open(path).map_err(|e| format!("Failed to open model file: {}", e))?;
+    let file_size = file
+        .metadata()
+        .map_err(|e| format!("Failed to read model file metadata: {}", e))?
+        .len();
+    let mut reader = BufReader::new(file);
+
+    let mut magic = [0u8; 4];
+    reader
+        .read_exact(&mut magic)
+        .map_err(|_| "File is too small to be a Whisper model".to_string())?;
+
+    let (format, hyperparams) = if &magic == GGUF_MAGIC {
+        (ModelFormat::Gguf, read_gguf_hparams(&mut reader)?)
+    } else if u32::from_le_bytes(magic) == GGML_MAGIC {
+        (ModelFormat::Ggml, read_ggml_hparams(&mut reader)?)
+    } else {
+        return Err("Not a ggml or gguf model file (bad magic)".to_string());
+    };
+
+    hyperparams.validate()?;
+
+    Ok(ModelFileInfo {
+        format,
+        multilingual: hyperparams.multilingual(),
+        quantization: hyperparams.quantization().to_string(),
+        size_class: hyperparams.size_class().to_string(),
+        hyperparams,
+        file_size,
+    })
+}
+
+pub(super) fn read_i32(reader: &mut impl Read) -> Result<i32, String> {
+    let mut buf = [0u8; 4];
+    reader
+        .read_exact(&mut buf)
+        .map_err(|_| "Model header is truncated".to_string())?;
+    Ok(i32::from_le_bytes(buf))
+}
+
+fn read_u32(reader: &mut impl Read) -> Result<u32, String> {
+    Ok(read_i32(reader)? as u32)
+}
+
+fn read_u64(reader: &mut impl Read) -> Result<u64, String> {
+    let mut buf = [0u8; 8];
+    reader
+        .read_exact(&mut buf)
+        .map_err(|_| "Model header is truncated".to_string())?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+/// ggml whisper header: magic followed by eleven i32 hyperparameters
+pub(super) fn read_ggml_hparams(reader: &mut impl Read) -> Result<WhisperHyperparams, String> {
+    Ok(WhisperHyperparams {
+        n_vocab: read_i32(reader)?,
+        n_audio_ctx: read_i32(reader)?,
+        n_audio_state: read_i32(reader)?,
+        n_audio_head: read_i32(reader)?,
+        n_audio_layer: read_i32(reader)?,
+        n_text_ctx: read_i32(reader)?,
+        n_text_state: read_i32(reader)?,
+        n_text_head: read_i32(reader)?,
+        n_text_layer: read_i32(reader)?,
+        n_mels: read_i32(reader)?,
+        ftype: read_i32(reader)?,
+    })
+}
+
+/// Scalar GGUF metadata value (arrays are reduced to their length)
+enum GgufValue {
+    Int(i64),
+    Str(String),
+    ArrayLen(u64),
+    Other,
+}
+
+fn read_gguf_string(reader: &mut impl Read) -> Result<String, String> {
+    let len = read_u64(reader)?;
+    if len > MAX_GGUF_STRING {
+        return Err("GGUF header contains an oversized string".to_string());
+    }
+    let mut buf = vec![0u8; len as usize];
+    reader
+        .read_exact(&mut buf)
+        .map_err(|_| "Model header is truncated".to_string())?;
+    Ok(String::from_utf8_lossy(&buf).to_string())
+}
+
+fn skip_bytes(reader: &mut impl Read, count: u64) -> Result<(), String> {
+    let skipped =
+        std::io::copy(&mut reader.take(count), &mut std::io::sink()).map_err(|e| e.to_string())?;
+    if skipped != count {
+        return Err("Model header is truncated".to_string());
+    }
+    Ok(())
+}
+
+fn read_gguf_value(reader: &mut impl Read, value_type: u32) -> Result<GgufValue, String> {
+    let mut buf = [0u8; 8];
+    let mut read_n = |n: usize, reader: &mut dyn Read| -> Result<i64, String> {
+        reader
+            .read_exact(&mut buf[..n])
+            .map_err(|_| "Model header is truncated".to_string())?;
+        Ok(match n {
+            1 => buf[0] as i64,
+            2 => u16::from_le_bytes([buf[0], buf[1]]) as i64,
+            4 => u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as i64,
+            _ => i64::from_le_bytes(buf),
+        })
+    };
+
+    Ok(match value_type {
+        0 | 1 | 7 => GgufValue::Int(read_n(1, reader)?),
+        2 | 3 => GgufValue::Int(read_n(2, reader)?),
+        4 | 5 => GgufValue::Int(read_n(4, reader)?),
+        10 | 11 => GgufValue::Int(read_n(8, reader)?),
+        6 => {
+            read_n(4, reader)?;
+            GgufValue::Other
+        }
+        12 => {
+            read_n(8, reader)?;
+            GgufValue::Other
+        }
+        8 => GgufValue::Str(read_gguf_string(reader)?),
+        9 => {
+            let element_type = read_u32(reader)?;
+            let len = read_u64(reader)?;
+            let fixed_size = match element_type {
+                0 | 1 | 7 => Some(1),
+                2 | 3 => Some(2),
+                4 | 5 | 6 => Some(4),
+                10..=12 => Some(8),
+                _ => None,
+            };
+            match fixed_size {
+                Some(size) => skip_bytes(reader, len.saturating_mul(size))?,
+                None => {
+                    for _ in 0..len {
+                        read_gguf_value(reader, element_type)?;
+                    }
+                }
+            }
+            GgufValue::ArrayLen(len)
+        }
+        other => return Err(format!("Unknown GGUF value type {}", other)),
+    })
+}
+
+/// Pull Whisper hyperparameters out of GGUF metadata. Accepts whisper.cpp-style
+/// names (`whisper.n_audio_layer`) and llama.cpp-style ones (`whisper.encoder.block_count`).
+fn read_gguf_hparams(reader: &mut impl Read) -> Result<WhisperHyperparams, String> {
+    let version = read_u32(reader)?;
+    if !(2..=3).contains(&version) {
+        return Err(format!("Unsupported GGUF version {}", version));
+    }
+    let _tensor_count = read_u64(reader)?;
+    let kv_count = read_u64(reader)?;
+    if kv_count > MAX_GGUF_KV {
+        return Err("GGUF header has too many metadata entries".to_string());
+    }
+
+    let mut architecture = None;
+    let mut hparams = WhisperHyperparams::default();
+    let mut token_count = None;
+
+    for _ in 0..kv_count {
+        let key = read_gguf_string(reader)?;
+        let value_type = read_u32(reader)?;
+        let value = read_gguf_value(reader, value_type)?;
+
+        match value {
+            GgufValue::Str(s) if key == "general.architecture" => architecture = Some(s),
+            GgufValue::ArrayLen(len) if key == "tokenizer.ggml.tokens" => token_count = Some(len),
+            GgufValue::Int(v) => {
+                let v = v as i32;
+                if key == "general.file_type" {
+                    hparams.ftype = v;
+                    continue;
+                }
+                let Some(field) = key.strip_prefix("whisper.") else {
+                    continue;
+                };
+                match field {
+                    "n_vocab" | "vocab_size" => hparams.n_vocab = v,
+                    "n_audio_ctx" | "encoder.context_length" => hparams.n_audio_ctx = v,
+                    "n_audio_state" | "encoder.embedding_length" => hparams.n_audio_state = v,
+                    "n_audio_head" | "encoder.attention.head_count" => hparams.n_audio_head = v,
+                    "n_audio_layer" | "encoder.block_count" => hparams.n_audio_layer = v,
+                    "n_text_ctx" | "decoder.context_length" => hparams.n_text_ctx = v,
+                    "n_text_state" | "decoder.embedding_length" => hparams.n_text_state = v,
+                    "n_text_head" | "decoder.attention.head_count" => hparams.n_text_head = v,
+                    "n_text_layer" | "decoder.block_count" => hparams.n_text_layer = v,
+                    "n_mels" | "mel_bins" => hparams.n_mels = v,
+                    "ftype" | "file_type" => hparams.ftype = v,
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if architecture.as_deref() != Some("whisper") {
+        return Err(format!(
+            "GGUF file is not a Whisper model (architecture: {})",
+            architecture.as_deref().unwrap_or("unknown")
+        ));
+    }
+    if hparams.n_vocab == 0 {
+        hparams.n_vocab = token_count.unwrap_or(0) as i32;
+    }
+
+    Ok(hparams)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    // large-v3 hyperparameters with q5_0 weights
+    const LARGE_V3: [i32; 11] = [51866, 1500, 1280, 20, 32, 448, 1280, 20, 32, 128, 1008];
+
+    fn write_ggml(path: &Path, hparams: &[i32]) {
+        let mut file = std::fs::File::create(path).unwrap();
+        file.write_all(&GGML_MAGIC.to_le_bytes()).unwrap();
+        for value in hparams {
+            file.write_all(&value.to_le_bytes()).unwrap();
+        }
+        file.write_all(&[0u8; 64]).unwrap();
+    }
+
+    fn gguf_string(out: &mut Vec<u8>, s: &str) {
+        out.extend((s.len() as u64).to_le_bytes());
+        out.extend(s.as_bytes());
+    }
+
+    fn gguf_u32(out: &mut Vec<u8>, key: &str, value: u32) {
+        gguf_string(out, key);
+        out.extend(4u32.to_le_bytes());
+        out.extend(value.to_le_bytes());
+    }
+
+    #[test]
+    fn test_ggml_header() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("ggml-large-v3-q5_0.bin");
+        write_ggml(&path, &LARGE_V3);
+
+        let info = inspect_model_file(&path).unwrap();
+        assert_eq!(info.format, ModelFormat::Ggml);
+        assert_eq!(info.hyperparams.n_vocab, 51866);
+        assert_eq!(info.hyperparams.n_audio_layer, 32);
+        assert!(info.multilingual);
+        assert_eq!(info.quantization, "q5_0");
+        assert_eq!(info.size_class, "large");
+    }
+
+    #[test]
+    fn test_english_only_ggml() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("ggml-base.en.bin");
+        write_ggml(&path, &[51864, 1500, 512, 8, 6, 448, 512, 8, 6, 80, 1]);
+
+        let info = inspect_model_file(&path).unwrap();
+        assert!(!info.multilingual);
+        assert_eq!(info.quantization, "f16");
+        assert_eq!(info.size_class, "base");
+        assert_eq!(info.hyperparams.estimated_scores(), (8, 5));
+    }
+
+    #[test]
+    fn test_gguf_header() {
+        let mut data = Vec::new();
+        data.extend(GGUF_MAGIC);
+        data.extend(3u32.to_le_bytes());
+        data.extend(0u64.to_le_bytes()); // tensors
+        data.extend(9u64.to_le_bytes()); // kv pairs
+        gguf_string(&mut data, "general.architecture");
+        data.extend(8u32.to_le_bytes());
+        gguf_string(&mut data, "whisper");
+        gguf_u32(&mut data, "whisper.encoder.block_count", 12);
+        gguf_u32(&mut data, "whisper.decoder.block_count", 12);
+        gguf_u32(&mut data, "whisper.encoder.embedding_length", 768);
+        gguf_u32(&mut data, "whisper.decoder.embedding_length", 768);
+        gguf_u32(&mut data, "whisper.encoder.attention.head_count", 12);
+        gguf_u32(&mut data, "whisper.decoder.attention.head_count", 12);
+        gguf_u32(&mut data, "whisper.n_mels", 80);
+        // Vocabulary only given as the token list
+        gguf_string(&mut data, "tokenizer.ggml.tokens");
+        data.extend(9u32.to_le_bytes());
+        data.extend(8u32.to_le_bytes());
+        data.extend(3u64.to_le_bytes());
+        for token in ["a", "b", "c"] {
+            gguf_string(&mut data, token);
+        }
+
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("small.gguf");
+        std::fs::write(&path, &data).unwrap();
+
+        let info = inspect_model_file(&path).unwrap();
+        assert_eq!(info.format, ModelFormat::Gguf);
+        assert_eq!(info.hyperparams.n_audio_layer, 12);
+        assert_eq!(info.hyperparams.n_vocab, 3);
+        assert_eq!(info.size_class, "small");
+    }
+
+    #[test]
+    fn test_rejects_non_whisper_files() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let text = temp_dir.path().join("notes.bin");
+        std::fs::write(&text, b"hello world, not a model").unwrap();
+        assert!(inspect_model_file(&text).unwrap_err().contains("bad magic"));
+
+        let garbage = temp_dir.path().join("garbage.bin");
+        write_ggml(&garbage, &[-1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert!(inspect_model_file(&garbage)
+            .unwrap_err()
+            .contains("implausible"));
+
+        let truncated = temp_dir.path().join("truncated.bin");
+        std::fs::write(&truncated, GGML_MAGIC.to_le_bytes()).unwrap();
+        assert!(inspect_model_file(&truncated)
+            .unwrap_err()
+            .contains("truncated"));
+    }
+}
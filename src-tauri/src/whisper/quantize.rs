@@ -0,0 +1,476 @@
+// Local quantization of ggml Whisper models, mirroring whisper.cpp's `quantize` example:
+// 2-D weight matrices are converted with ggml's own kernels (through the FFI) and
+// everything else (biases, norms, positional embeddings) is copied unchanged.
+
+use serde::{Deserialize, Serialize};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use whisper_rs::whisper_rs_sys as sys;
+
+use super::model_file::{read_ggml_hparams, read_i32, GGML_MAGIC};
+
+/// Quantization version whisper.cpp stores in the thousands of `ftype`
+const GGML_QNT_VERSION: i32 = 2;
+const GGML_QNT_VERSION_FACTOR: i32 = 1000;
+
+/// ggml tensor types (stable values from ggml.h)
+const GGML_TYPE_F32: i32 = 0;
+const GGML_TYPE_F16: i32 = 1;
+const GGML_TYPE_Q5_1: i32 = 7;
+const GGML_TYPE_Q8_0: i32 = 8;
+
+/// Elements per quantization block for q5_1 and q8_0; rows must be a multiple of it
+const QK: i64 = 32;
+
+/// Tensors whisper.cpp keeps at full precision
+const SKIP_TENSORS: &[&str] = &[
+    "encoder.conv1.bias",
+    "encoder.conv2.bias",
+    "encoder.positional_embedding",
+    "decoder.positional_embedding",
+];
+
+/// Sanity limits for fields read from the file
+const MAX_MEL_FILTER_VALUES: i64 = 256 * 1024;
+const MAX_TOKEN_LEN: u32 = 1024;
+const MAX_TENSOR_NAME_LEN: i32 = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QuantizationType {
+    #[serde(rename = "q5_1")]
+    Q5_1,
+    #[serde(rename = "q8_0")]
+    Q8_0,
+}
+
+impl QuantizationType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuantizationType::Q5_1 => "q5_1",
+            QuantizationType::Q8_0 => "q8_0",
+        }
+    }
+
+    fn ggml_type(&self) -> i32 {
+        match self {
+            QuantizationType::Q5_1 => GGML_TYPE_Q5_1,
+            QuantizationType::Q8_0 => GGML_TYPE_Q8_0,
+        }
+    }
+
+    /// whisper.cpp file type (`GGML_FTYPE_MOSTLY_*`)
+    fn ftype(&self) -> i32 {
+        match self {
+            QuantizationType::Q5_1 => 9,
+            QuantizationType::Q8_0 => 7,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuantizeSummary {
+    pub quantization: QuantizationType,
+    pub tensors: usize,
+    pub quantized_tensors: usize,
+    pub input_bytes: u64,
+    pub output_bytes: u64,
+}
+
+/// Reader that reports how many bytes have been consumed
+struct CountingReader<R> {
+    inner: R,
+    read: u64,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.read += n as u64;
+        Ok(n)
+    }
+}
+
+/// Quantize the f16/f32 ggml model at `input` into `output`. `progress` receives
+/// (bytes read, input size). GGUF and already quantized models are rejected.
+pub fn quantize_model(
+    input: &Path,
+    output: &Path,
+    quantization: QuantizationType,
+    progress: &dyn Fn(u64, u64),
+) -> Result<QuantizeSummary, String> {
+    let file = std::fs::File::open(input).map_err(|e| format!("Failed to open model: {}", e))?;
+    let input_bytes = file
+        .metadata()
+        .map_err(|e| format!("Failed to read model metadata: {}", e))?
+        .len();
+    let mut reader = CountingReader {
+        inner: BufReader::with_capacity(1024 * 1024, file),
+        read: 0,
+    };
+    let out_file =
+        std::fs::File::create(output).map_err(|e| format!("Failed to create output: {}", e))?;
+    let mut writer = BufWriter::with_capacity(1024 * 1024, out_file);
+    let write_err = |e: std::io::Error| format!("Failed to write quantized model: {}", e);
+
+    // Header
+    if read_u32(&mut reader)? != GGML_MAGIC {
+        return Err("Only ggml .bin Whisper models can be quantized".to_string());
+    }
+    let mut hparams = read_ggml_hparams(&mut reader)?;
+    hparams.validate()?;
+    if hparams.ftype % GGML_QNT_VERSION_FACTOR > 1 {
+        return Err(format!(
+            "Model is already quantized ({})",
+            hparams.quantization()
+        ));
+    }
+    hparams.ftype = GGML_QNT_VERSION * GGML_QNT_VERSION_FACTOR + quantization.ftype();
+
+    writer
+        .write_all(&GGML_MAGIC.to_le_bytes())
+        .map_err(write_err)?;
+    for value in [
+        hparams.n_vocab,
+        hparams.n_audio_ctx,
+        hparams.n_audio_state,
+        hparams.n_audio_head,
+        hparams.n_audio_layer,
+        hparams.n_text_ctx,
+        hparams.n_text_state,
+        hparams.n_text_head,
+        hparams.n_text_layer,
+        hparams.n_mels,
+        hparams.ftype,
+    ] {
+        writer.write_all(&value.to_le_bytes()).map_err(write_err)?;
+    }
+
+    // Mel filterbank: n_mel, n_fft, then n_mel * n_fft f32 values
+    let n_mel = read_i32(&mut reader)?;
+    let n_fft = read_i32(&mut reader)?;
+    let filter_values = i64::from(n_mel) * i64::from(n_fft);
+    if n_mel <= 0 || n_fft <= 0 || filter_values > MAX_MEL_FILTER_VALUES {
+        return Err("Model has an invalid mel filterbank".to_string());
+    }
+    writer.write_all(&n_mel.to_le_bytes()).map_err(write_err)?;
+    writer.write_all(&n_fft.to_le_bytes()).map_err(write_err)?;
+    copy_exact(&mut reader, &mut writer, filter_values as u64 * 4)?;
+
+    // Vocabulary: count, then length-prefixed tokens
+    let n_vocab = read_i32(&mut reader)?;
+    if n_vocab < 0 || n_vocab > hparams.n_vocab.max(0) + 1024 {
+        return Err("Model has an invalid vocabulary".to_string());
+    }
+    writer
+        .write_all(&n_vocab.to_le_bytes())
+        .map_err(write_err)?;
+    for _ in 0..n_vocab {
+        let len = read_u32(&mut reader)?;
+        if len > MAX_TOKEN_LEN {
+            return Err("Model vocabulary contains an oversized token".to_string());
+        }
+        writer.write_all(&len.to_le_bytes()).map_err(write_err)?;
+        copy_exact(&mut reader, &mut writer, u64::from(len))?;
+    }
+
+    // Tensors until end of file
+    let mut tensors = 0;
+    let mut quantized_tensors = 0;
+    while let Some(n_dims) = read_i32_or_eof(&mut reader)? {
+        let name_len = read_i32(&mut reader)?;
+        let ttype = read_i32(&mut reader)?;
+        if !(1..=4).contains(&n_dims) || !(1..=MAX_TENSOR_NAME_LEN).contains(&name_len) {
+            return Err("Model contains a malformed tensor header".to_string());
+        }
+        let mut ne = Vec::with_capacity(n_dims as usize);
+        for _ in 0..n_dims {
+            let dim = read_i32(&mut reader)?;
+            if dim <= 0 {
+                return Err("Model contains a tensor with an invalid shape".to_string());
+            }
+            ne.push(i64::from(dim));
+        }
+        let mut name = vec![0u8; name_len as usize];
+        reader
+            .read_exact(&mut name)
+            .map_err(|_| "Model file is truncated".to_string())?;
+        let name = String::from_utf8_lossy(&name).to_string();
+
+        let n_elements: i64 = ne.iter().product();
+        let element_size = match ttype {
+            GGML_TYPE_F32 => 4,
+            GGML_TYPE_F16 => 2,
+            _ => return Err(format!("Tensor '{}' has unsupported type {}", name, ttype)),
+        };
+
+        let quantize = n_dims == 2 && ne[0] % QK == 0 && !SKIP_TENSORS.contains(&name.as_str());
+        let out_type = if quantize {
+            quantization.ggml_type()
+        } else {
+            ttype
+        };
+
+        writer.write_all(&n_dims.to_le_bytes()).map_err(write_err)?;
+        writer
+            .write_all(&name_len.to_le_bytes())
+            .map_err(write_err)?;
+        writer
+            .write_all(&out_type.to_le_bytes())
+            .map_err(write_err)?;
+        for dim in &ne {
+            writer
+                .write_all(&(*dim as i32).to_le_bytes())
+                .map_err(write_err)?;
+        }
+        writer.write_all(name.as_bytes()).map_err(write_err)?;
+
+        if quantize {
+            let mut data = vec![0u8; (n_elements * element_size) as usize];
+            reader
+                .read_exact(&mut data)
+                .map_err(|_| "Model file is truncated".to_string())?;
+            let values = to_f32(&data, ttype);
+            let quantized = quantize_rows(&values, quantization, ne[0], n_elements / ne[0])?;
+            writer.write_all(&quantized).map_err(write_err)?;
+            quantized_tensors += 1;
+        } else {
+            copy_exact(&mut reader, &mut writer, (n_elements * element_size) as u64)?;
+        }
+        tensors += 1;
+        progress(reader.read, input_bytes);
+    }
+
+    writer.flush().map_err(write_err)?;
+    let out_file = writer
+        .into_inner()
+        .map_err(|e| format!("Failed to write quantized model: {}", e))?;
+    out_file.sync_all().map_err(write_err)?;
+    let output_bytes = out_file.metadata().map_err(write_err)?.len();
+
+    if quantized_tensors == 0 {
+        return Err("No tensors in this model could be quantized".to_string());
+    }
+    Ok(QuantizeSummary {
+        quantization,
+        tensors,
+        quantized_tensors,
+        input_bytes,
+        output_bytes,
+    })
+}
+
+/// Run ggml's quantization kernel over `nrows` rows of `n_per_row` values
+fn quantize_rows(
+    values: &[f32],
+    quantization: QuantizationType,
+    n_per_row: i64,
+    nrows: i64,
+) -> Result<Vec<u8>, String> {
+    let ggml_type = quantization.ggml_type() as sys::ggml_type;
+    // SAFETY: pure size computation on a valid type id
+    let row_size = unsafe { sys::ggml_row_size(ggml_type, n_per_row) };
+    let mut out = vec![0u8; row_size * nrows as usize];
+    // SAFETY: `values` holds nrows * n_per_row floats and `out` is sized by
+    // ggml_row_size for the same shape; q5_1/q8_0 need no importance matrix.
+    let written = unsafe {
+        sys::ggml_quantize_chunk(
+            ggml_type,
+            values.as_ptr(),
+            out.as_mut_ptr() as *mut std::ffi::c_void,
+            0,
+            nrows,
+            n_per_row,
+            std::ptr::null(),
+        )
+    };
+    if written != out.len() {
+        return Err(format!(
+            "Quantization produced {} bytes, expected {}",
+            written,
+            out.len()
+        ));
+    }
+    Ok(out)
+}
+
+fn to_f32(data: &[u8], ttype: i32) -> Vec<f32> {
+    if ttype == GGML_TYPE_F16 {
+        data.chunks_exact(2)
+            .map(|b| f16_to_f32(u16::from_le_bytes([b[0], b[1]])))
+            .collect()
+    } else {
+        data.chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect()
+    }
+}
+
+/// IEEE 754 half to single precision
+fn f16_to_f32(bits: u16) -> f32 {
+    let sign = u32::from(bits >> 15) << 31;
+    let exponent = u32::from((bits >> 10) & 0x1f);
+    let mantissa = u32::from(bits & 0x3ff);
+    let value = match (exponent, mantissa) {
+        (0, 0) => sign,
+        (0, _) => {
+            // Subnormal: renormalize into an f32 exponent
+            let shift = mantissa.leading_zeros() - 21;
+            let mantissa = (mantissa << shift) & 0x3ff;
+            sign | ((127 - 15 + 1 - shift) << 23) | (mantissa << 13)
+        }
+        (0x1f, _) => sign | 0x7f80_0000 | (mantissa << 13),
+        _ => sign | ((exponent + 127 - 15) << 23) | (mantissa << 13),
+    };
+    f32::from_bits(value)
+}
+
+fn read_u32(reader: &mut impl Read) -> Result<u32, String> {
+    read_i32(reader).map(|v| v as u32)
+}
+
+/// Next i32, or `None` at a clean end of file
+fn read_i32_or_eof(reader: &mut impl Read) -> Result<Option<i32>, String> {
+    let mut buf = [0u8; 4];
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) if filled == 0 => return Ok(None),
+            Ok(0) => return Err("Model file is truncated".to_string()),
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(format!("Failed to read model: {}", e)),
+        }
+    }
+    Ok(Some(i32::from_le_bytes(buf)))
+}
+
+fn copy_exact(reader: &mut impl Read, writer: &mut impl Write, count: u64) -> Result<(), String> {
+    let copied = std::io::copy(&mut reader.take(count), writer)
+        .map_err(|e| format!("Failed to copy model data: {}", e))?;
+    if copied != count {
+        return Err("Model file is truncated".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::whisper::model_file::inspect_model_file;
+    use tempfile::TempDir;
+
+    fn f16_bits(value: f32) -> u16 {
+        // Exact for the small integers used below
+        let bits = value.to_bits();
+        let sign = ((bits >> 16) & 0x8000) as u16;
+        if value == 0.0 {
+            return sign;
+        }
+        let exponent = ((bits >> 23) & 0xff) as i32 - 127 + 15;
+        let mantissa = ((bits >> 13) & 0x3ff) as u16;
+        sign | ((exponent as u16) << 10) | mantissa
+    }
+
+    fn write_tensor(out: &mut Vec<u8>, name: &str, ne: &[i32], ttype: i32, values: &[f32]) {
+        out.extend((ne.len() as i32).to_le_bytes());
+        out.extend((name.len() as i32).to_le_bytes());
+        out.extend(ttype.to_le_bytes());
+        for dim in ne {
+            out.extend(dim.to_le_bytes());
+        }
+        out.extend(name.as_bytes());
+        for value in values {
+            if ttype == GGML_TYPE_F16 {
+                out.extend(f16_bits(*value).to_le_bytes());
+            } else {
+                out.extend(value.to_le_bytes());
+            }
+        }
+    }
+
+    /// Tiny but structurally complete ggml Whisper file
+    fn write_model(path: &Path, ftype: i32) {
+        let mut out = Vec::new();
+        out.extend(GGML_MAGIC.to_le_bytes());
+        for value in [51864, 1500, 64, 1, 1, 448, 64, 1, 1, 80, ftype] {
+            out.extend(i32::to_le_bytes(value));
+        }
+        out.extend(2i32.to_le_bytes());
+        out.extend(3i32.to_le_bytes());
+        for value in [0.5f32; 6] {
+            out.extend(value.to_le_bytes());
+        }
+        out.extend(2i32.to_le_bytes());
+        for token in ["hi", "there"] {
+            out.extend((token.len() as u32).to_le_bytes());
+            out.extend(token.as_bytes());
+        }
+        let weights: Vec<f32> = (0..64).map(|i| (i % 8) as f32).collect();
+        write_tensor(
+            &mut out,
+            "encoder.blocks.0.attn.query.weight",
+            &[32, 2],
+            GGML_TYPE_F16,
+            &weights,
+        );
+        write_tensor(
+            &mut out,
+            "encoder.positional_embedding",
+            &[32, 2],
+            GGML_TYPE_F32,
+            &weights,
+        );
+        write_tensor(
+            &mut out,
+            "encoder.ln_post.bias",
+            &[32],
+            GGML_TYPE_F32,
+            &weights[..32],
+        );
+        std::fs::write(path, out).unwrap();
+    }
+
+    #[test]
+    fn test_f16_conversion() {
+        assert_eq!(f16_to_f32(0x3c00), 1.0);
+        assert_eq!(f16_to_f32(0xc000), -2.0);
+        assert_eq!(f16_to_f32(0x0000), 0.0);
+        assert_eq!(f16_to_f32(0x0001), 2f32.powi(-24));
+        assert!(f16_to_f32(0x7c00).is_infinite());
+        assert_eq!(f16_to_f32(f16_bits(7.0)), 7.0);
+    }
+
+    #[test]
+    fn test_quantize_q8_0() {
+        let temp_dir = TempDir::new().unwrap();
+        let input = temp_dir.path().join("ggml-tiny.bin");
+        let output = temp_dir.path().join("ggml-tiny-q8_0.bin");
+        write_model(&input, 1);
+
+        let summary = quantize_model(&input, &output, QuantizationType::Q8_0, &|_, _| {}).unwrap();
+        assert_eq!(summary.tensors, 3);
+        // The positional embedding and the 1-D bias stay at full precision
+        assert_eq!(summary.quantized_tensors, 1);
+
+        let info = inspect_model_file(&output).unwrap();
+        assert_eq!(info.quantization, "q8_0");
+        assert_eq!(info.hyperparams.ftype, 2007);
+
+        // f16 query weight (128 bytes) became two q8_0 rows of 34 bytes
+        assert_eq!(summary.input_bytes - summary.output_bytes, 128 - 68);
+    }
+
+    #[test]
+    fn test_rejects_quantized_and_foreign_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let input = temp_dir.path().join("ggml-q5.bin");
+        let output = temp_dir.path().join("out.bin");
+        write_model(&input, 2008);
+        let err = quantize_model(&input, &output, QuantizationType::Q5_1, &|_, _| {}).unwrap_err();
+        assert!(err.contains("already quantized"));
+
+        std::fs::write(&input, b"GGUF\x03\x00\x00\x00").unwrap();
+        let err = quantize_model(&input, &output, QuantizationType::Q5_1, &|_, _| {}).unwrap_err();
+        assert!(err.contains("Only ggml"));
+    }
+}
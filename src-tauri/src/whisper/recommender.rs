@@ -0,0 +1,707 @@
+// Hardware-aware model recommendation. Models are filtered by RAM and language, their
+// latency for a dictation-sized clip comes from timings taken on this machine (model
+// comparisons and recommendation benchmarks), scaled by model size for models that were
+// never timed, and the most accurate model that meets the latency target wins. Only
+// when nothing has been timed yet is latency estimated from the CPU/GPU profile.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Instant;
+
+use super::transcriber::{DecodingOptions, Transcriber};
+use crate::utils::system_monitor;
+
+/// CPU cost of a 10 s clip in ms per MB of weights per thread, calibrated on AVX2
+/// desktops (large-v3 takes roughly 8 s on 8 cores). Only used until a model has been
+/// timed on this machine.
+const CPU_MS_PER_MB_THREAD: f64 = 16.0;
+/// Clip length the cost above was measured on
+const REFERENCE_CLIP_SECS: f64 = 10.0;
+/// Share of the cost that is the encoder, which always runs on a full 30 s window
+const ENCODER_SHARE: f64 = 0.6;
+
+const MB: u64 = 1024 * 1024;
+
+/// What the recommender knows about this machine
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HardwareProfile {
+    pub total_ram_mb: u64,
+    pub available_ram_mb: u64,
+    pub cpu_cores: usize,
+    pub avx2: bool,
+    pub avx512: bool,
+    pub neon: bool,
+    /// GPU backend whisper.cpp tries first on this platform ("metal", "vulkan")
+    pub gpu_backend: Option<String>,
+}
+
+impl HardwareProfile {
+    pub fn detect() -> Self {
+        let (total_ram_mb, available_ram_mb) = system_monitor::memory_mb();
+
+        #[cfg(target_arch = "x86_64")]
+        let (avx2, avx512) = (
+            std::arch::is_x86_feature_detected!("avx2"),
+            std::arch::is_x86_feature_detected!("avx512f"),
+        );
+        #[cfg(not(target_arch = "x86_64"))]
+        let (avx2, avx512) = (false, false);
+
+        #[cfg(target_os = "macos")]
+        let gpu_backend = Some("metal".to_string());
+        #[cfg(target_os = "windows")]
+        let gpu_backend = std::path::Path::new("C:\\Windows\\System32\\vulkan-1.dll")
+            .exists()
+            .then(|| "vulkan".to_string());
+        #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+        let gpu_backend = None;
+
+        Self {
+            total_ram_mb,
+            available_ram_mb,
+            cpu_cores: std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(4),
+            avx2,
+            avx512,
+            // NEON is mandatory on aarch64
+            neon: cfg!(target_arch = "aarch64"),
+            gpu_backend,
+        }
+    }
+
+    fn simd_label(&self) -> &'static str {
+        if self.avx512 {
+            "AVX-512"
+        } else if self.avx2 {
+            "AVX2"
+        } else if self.neon {
+            "NEON"
+        } else {
+            "no wide SIMD"
+        }
+    }
+
+    /// Relative slowdown versus the AVX2 calibration machine
+    fn compute_factor(&self) -> f64 {
+        let simd = if self.avx512 {
+            0.8
+        } else if self.avx2 || self.neon {
+            1.0
+        } else {
+            2.0
+        };
+        let gpu = match self.gpu_backend.as_deref() {
+            Some("metal") => 0.3,
+            Some(_) => 0.5,
+            None => 1.0,
+        };
+        simd * gpu
+    }
+
+    /// Whisper uses every core but one (see `WHISPER_THREAD_COUNT`)
+    fn inference_threads(&self) -> f64 {
+        self.cpu_cores.saturating_sub(1).max(1) as f64
+    }
+}
+
+/// "Transcribe a `clip_secs` clip in under `max_latency_ms`"
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LatencyTarget {
+    pub max_latency_ms: u64,
+    pub clip_secs: u32,
+}
+
+impl Default for LatencyTarget {
+    fn default() -> Self {
+        Self {
+            max_latency_ms: 1000,
+            clip_secs: 10,
+        }
+    }
+}
+
+impl LatencyTarget {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_latency_ms == 0 {
+            return Err("Latency target must be greater than zero".to_string());
+        }
+        if !(1..=60).contains(&self.clip_secs) {
+            return Err("Clip length must be between 1 and 60 seconds".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// A transcription timed on this machine
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MeasuredLatency {
+    /// Inference time, excluding model load
+    pub latency_ms: u64,
+    pub audio_secs: f64,
+}
+
+impl MeasuredLatency {
+    /// The same model on a `clip_secs` clip. The encoder always runs on a full window,
+    /// so only the decoder share scales with length.
+    fn scaled_to(&self, clip_secs: u32) -> u64 {
+        let weight = |secs: f64| ENCODER_SHARE + (1.0 - ENCODER_SHARE) * secs / REFERENCE_CLIP_SECS;
+        let scale = weight(f64::from(clip_secs)) / weight(self.audio_secs.max(0.0));
+        (self.latency_ms as f64 * scale).round() as u64
+    }
+}
+
+/// Latest timing per model, stored under `model_timings` in the settings store
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ModelTimings {
+    timings: BTreeMap<String, MeasuredLatency>,
+}
+
+impl ModelTimings {
+    pub fn record(&mut self, model: &str, latency_ms: u64, audio_secs: f64) {
+        if audio_secs <= 0.0 {
+            return;
+        }
+        self.timings.insert(
+            model.to_string(),
+            MeasuredLatency {
+                latency_ms,
+                audio_secs,
+            },
+        );
+    }
+
+    pub fn get(&self, model: &str) -> Option<&MeasuredLatency> {
+        self.timings.get(model)
+    }
+}
+
+/// Where a latency figure comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LatencySource {
+    /// This model was timed on this machine
+    Measured,
+    /// Scaled by size from the models that were timed on this machine
+    Calibrated,
+    /// Hardware-profile estimate; no model has been timed yet
+    Estimated,
+}
+
+impl LatencySource {
+    fn label(&self) -> &'static str {
+        match self {
+            LatencySource::Measured => "measured",
+            LatencySource::Calibrated => "scaled from models timed on this machine",
+            LatencySource::Estimated => "estimated",
+        }
+    }
+}
+
+/// A model the recommender can pick from
+#[derive(Debug, Clone)]
+pub struct ModelCandidate {
+    pub name: String,
+    pub display_name: String,
+    pub size: u64,
+    pub min_ram_mb: u64,
+    /// Language codes, `["*"]` for multilingual
+    pub languages: Vec<String>,
+    pub speed_score: u8,
+    pub accuracy_score: u8,
+    pub downloaded: bool,
+}
+
+impl ModelCandidate {
+    /// RAM floor for models that are not in the manifest (imported ones)
+    pub fn estimated_min_ram_mb(size: u64) -> u64 {
+        size / MB * 5 / 4 + 256
+    }
+
+    fn supports_language(&self, language: &str) -> bool {
+        self.languages.is_empty()
+            || self
+                .languages
+                .iter()
+                .any(|l| l == "*" || l.eq_ignore_ascii_case(language))
+    }
+
+    /// Relative inference cost: weights in MB, nudged by the manifest speed score because
+    /// distilled and turbo decoders are faster than their size suggests
+    fn cost_units(&self) -> f64 {
+        let size_mb = self.size as f64 / MB as f64;
+        size_mb * (1.5 - f64::from(self.speed_score.min(10)) / 10.0)
+    }
+
+    /// Latency estimate for the target clip from the hardware profile alone
+    fn estimate_latency_ms(&self, hardware: &HardwareProfile, clip_secs: u32) -> u64 {
+        let reference_ms = self.cost_units() * CPU_MS_PER_MB_THREAD * hardware.compute_factor()
+            / hardware.inference_threads();
+        let clip_scale =
+            ENCODER_SHARE + (1.0 - ENCODER_SHARE) * f64::from(clip_secs) / REFERENCE_CLIP_SECS;
+        (reference_ms * clip_scale).round() as u64
+    }
+}
+
+/// How one model fared against the hardware and target
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelAssessment {
+    pub name: String,
+    pub display_name: String,
+    pub latency_ms: u64,
+    pub latency_source: LatencySource,
+    pub fits_memory: bool,
+    pub supports_language: bool,
+    pub meets_target: bool,
+    pub accuracy_score: u8,
+    pub downloaded: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Recommendation {
+    /// `None` when no model fits this machine
+    pub model: Option<String>,
+    pub display_name: Option<String>,
+    pub latency_ms: Option<u64>,
+    pub target: LatencyTarget,
+    pub language: String,
+    /// Human-readable explanation, in order
+    pub reasons: Vec<String>,
+    pub hardware: HardwareProfile,
+    pub assessments: Vec<ModelAssessment>,
+}
+
+/// This machine's cost per `cost_units` for a `clip_secs` clip, the median over the
+/// candidates that have been timed
+fn measured_ms_per_unit(
+    candidates: &[ModelCandidate],
+    timings: &ModelTimings,
+    clip_secs: u32,
+) -> Option<f64> {
+    let mut rates: Vec<f64> = candidates
+        .iter()
+        .filter(|candidate| candidate.cost_units() > 0.0)
+        .filter_map(|candidate| {
+            let timing = timings.get(&candidate.name)?;
+            Some(timing.scaled_to(clip_secs) as f64 / candidate.cost_units())
+        })
+        .collect();
+    if rates.is_empty() {
+        return None;
+    }
+    rates.sort_by(f64::total_cmp);
+    let mid = rates.len() / 2;
+    Some(if rates.len().is_multiple_of(2) {
+        (rates[mid - 1] + rates[mid]) / 2.0
+    } else {
+        rates[mid]
+    })
+}
+
+/// Pick the most accurate model that fits in RAM, supports `language` and meets the
+/// latency target. Models in `timings` use their own measurement; the rest are scaled
+/// from the measured ones, or estimated from `hardware` when nothing was timed. When
+/// nothing meets the target, the fastest eligible model is picked.
+pub fn recommend(
+    candidates: &[ModelCandidate],
+    hardware: &HardwareProfile,
+    target: LatencyTarget,
+    language: &str,
+    timings: &ModelTimings,
+) -> Recommendation {
+    let ms_per_unit = measured_ms_per_unit(candidates, timings, target.clip_secs);
+    let mut assessments: Vec<ModelAssessment> = candidates
+        .iter()
+        .map(|candidate| {
+            let (latency_ms, latency_source) = match (timings.get(&candidate.name), ms_per_unit) {
+                (Some(timing), _) => (timing.scaled_to(target.clip_secs), LatencySource::Measured),
+                (None, Some(rate)) => (
+                    (candidate.cost_units() * rate).round() as u64,
+                    LatencySource::Calibrated,
+                ),
+                (None, None) => (
+                    candidate.estimate_latency_ms(hardware, target.clip_secs),
+                    LatencySource::Estimated,
+                ),
+            };
+            let fits_memory = candidate.min_ram_mb <= hardware.total_ram_mb;
+            ModelAssessment {
+                name: candidate.name.clone(),
+                display_name: candidate.display_name.clone(),
+                latency_ms,
+                latency_source,
+                fits_memory,
+                supports_language: candidate.supports_language(language),
+                meets_target: latency_ms <= target.max_latency_ms,
+                accuracy_score: candidate.accuracy_score,
+                downloaded: candidate.downloaded,
+            }
+        })
+        .collect();
+    assessments.sort_by(|a, b| a.latency_ms.cmp(&b.latency_ms).then(a.name.cmp(&b.name)));
+
+    let mut reasons = vec![format!(
+        "{:.1} GB RAM ({:.1} GB free), {} CPU cores with {}, {}",
+        hardware.total_ram_mb as f64 / 1024.0,
+        hardware.available_ram_mb as f64 / 1024.0,
+        hardware.cpu_cores,
+        hardware.simd_label(),
+        match &hardware.gpu_backend {
+            Some(backend) => format!("{} GPU acceleration", backend),
+            None => "no GPU acceleration".to_string(),
+        }
+    )];
+
+    let too_large = assessments.iter().filter(|a| !a.fits_memory).count();
+    if too_large > 0 {
+        reasons.push(format!(
+            "Skipped {} model(s) that need more memory than this machine has",
+            too_large
+        ));
+    }
+    let wrong_language = assessments
+        .iter()
+        .filter(|a| a.fits_memory && !a.supports_language)
+        .count();
+    if wrong_language > 0 {
+        reasons.push(format!(
+            "Skipped {} model(s) that do not support '{}'",
+            wrong_language, language
+        ));
+    }
+
+    let eligible: Vec<&ModelAssessment> = assessments
+        .iter()
+        .filter(|a| a.fits_memory && a.supports_language)
+        .collect();
+
+    // Most accurate first; prefer what is already installed, then the faster one
+    let best_within_target = eligible
+        .iter()
+        .filter(|a| a.meets_target)
+        .max_by(|a, b| {
+            a.accuracy_score
+                .cmp(&b.accuracy_score)
+                .then(a.downloaded.cmp(&b.downloaded))
+                .then(b.latency_ms.cmp(&a.latency_ms))
+        })
+        .copied();
+
+    let chosen = match best_within_target {
+        Some(choice) => {
+            reasons.push(format!(
+                "{} should transcribe a {} s clip in about {} ms ({}), within the {} ms target",
+                choice.display_name,
+                target.clip_secs,
+                choice.latency_ms,
+                choice.latency_source.label(),
+                target.max_latency_ms
+            ));
+            if eligible
+                .iter()
+                .any(|a| a.meets_target && a.accuracy_score < choice.accuracy_score)
+            {
+                reasons.push(
+                    "It is the most accurate model that meets the target; faster models trade away accuracy"
+                        .to_string(),
+                );
+            }
+            Some(choice)
+        }
+        None => {
+            let fastest = eligible.first().copied();
+            if let Some(choice) = fastest {
+                reasons.push(format!(
+                    "No model meets the {} ms target; {} is the fastest that fits, at about {} ms ({})",
+                    target.max_latency_ms,
+                    choice.display_name,
+                    choice.latency_ms,
+                    choice.latency_source.label()
+                ));
+            } else {
+                reasons.push("No model fits this machine's memory and language".to_string());
+            }
+            fastest
+        }
+    };
+
+    if let Some(choice) = chosen {
+        let min_ram_mb = candidates
+            .iter()
+            .find(|c| c.name == choice.name)
+            .map(|c| c.min_ram_mb)
+            .unwrap_or(0);
+        if hardware.available_ram_mb < min_ram_mb {
+            reasons.push(format!(
+                "Only {:.1} GB is free right now; close other apps if loading is slow",
+                hardware.available_ram_mb as f64 / 1024.0
+            ));
+        }
+        if !choice.downloaded {
+            reasons.push("The model still needs to be downloaded".to_string());
+        }
+    }
+
+    Recommendation {
+        model: chosen.map(|c| c.name.clone()),
+        display_name: chosen.map(|c| c.display_name.clone()),
+        latency_ms: chosen.map(|c| c.latency_ms),
+        target,
+        language: language.to_string(),
+        reasons,
+        hardware: hardware.clone(),
+        assessments,
+    }
+}
+
+/// Load `model_path` and time one transcription of a synthetic `clip_secs` clip with
+/// the dictation decoding settings. Load time is excluded, as in `compare_models`.
+pub fn measure_latency(model_path: &Path, clip_secs: u32) -> Result<u64, String> {
+    let transcriber = Transcriber::new(model_path)?;
+    let clip = synthetic_clip(clip_secs);
+
+    let start = Instant::now();
+    transcriber.transcribe_samples_with_options(
+        &clip,
+        Some("en"),
+        false,
+        &DecodingOptions::default(),
+    )?;
+    Ok(start.elapsed().as_millis() as u64)
+}
+
+/// 16kHz voiced-sounding signal: a few harmonics with a syllable-rate envelope, so the
+/// decoder does real work instead of stopping on silence
+fn synthetic_clip(clip_secs: u32) -> Vec<f32> {
+    let sample_rate = 16_000.0_f32;
+    let len = (clip_secs as f32 * sample_rate) as usize;
+    (0..len)
+        .map(|i| {
+            let t = i as f32 / sample_rate;
+            let pitch = 140.0 + 20.0 * (2.0 * std::f32::consts::PI * 0.5 * t).sin();
+            let tone: f32 = (1..=4)
+                .map(|h| (2.0 * std::f32::consts::PI * pitch * h as f32 * t).sin() / h as f32)
+                .sum();
+            let envelope = (2.0 * std::f32::consts::PI * 4.0 * t).sin().max(0.0);
+            0.2 * tone * envelope
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hardware(total_ram_mb: u64, cpu_cores: usize) -> HardwareProfile {
+        HardwareProfile {
+            total_ram_mb,
+            available_ram_mb: total_ram_mb / 2,
+            cpu_cores,
+            avx2: true,
+            avx512: false,
+            neon: false,
+            gpu_backend: None,
+        }
+    }
+
+    fn candidate(name: &str, size_mb: u64, min_ram_mb: u64, multilingual: bool) -> ModelCandidate {
+        let (speed_score, accuracy_score) = match name {
+            "base.en" => (8, 5),
+            "small.en" => (7, 6),
+            "large-v3-turbo" => (7, 9),
+            _ => (2, 9),
+        };
+        ModelCandidate {
+            name: name.to_string(),
+            display_name: name.to_string(),
+            size: size_mb * MB,
+            min_ram_mb,
+            languages: vec![if multilingual { "*" } else { "en" }.to_string()],
+            speed_score,
+            accuracy_score,
+            downloaded: false,
+        }
+    }
+
+    fn catalog() -> Vec<ModelCandidate> {
+        vec![
+            candidate("base.en", 142, 1024, false),
+            candidate("small.en", 466, 2048, false),
+            candidate("large-v3-turbo", 1536, 4096, true),
+            candidate("large-v3", 2973, 6144, true),
+        ]
+    }
+
+    #[test]
+    fn test_picks_most_accurate_within_target() {
+        let rec = recommend(
+            &catalog(),
+            &hardware(16384, 8),
+            LatencyTarget::default(),
+            "en",
+            &ModelTimings::default(),
+        );
+        assert_eq!(rec.model.as_deref(), Some("small.en"));
+        assert!(rec.latency_ms.unwrap() <= 1000);
+
+        let relaxed = LatencyTarget {
+            max_latency_ms: 5000,
+            clip_secs: 10,
+        };
+        let rec = recommend(
+            &catalog(),
+            &hardware(16384, 8),
+            relaxed,
+            "en",
+            &ModelTimings::default(),
+        );
+        // large-v3 is as accurate but far too slow on CPU
+        assert_eq!(rec.model.as_deref(), Some("large-v3-turbo"));
+    }
+
+    #[test]
+    fn test_memory_and_language_filters() {
+        let relaxed = LatencyTarget {
+            max_latency_ms: 60_000,
+            clip_secs: 10,
+        };
+        let rec = recommend(
+            &catalog(),
+            &hardware(3072, 4),
+            relaxed,
+            "en",
+            &ModelTimings::default(),
+        );
+        assert_eq!(rec.model.as_deref(), Some("small.en"));
+        assert!(rec.assessments.iter().filter(|a| !a.fits_memory).count() == 2);
+
+        let rec = recommend(
+            &catalog(),
+            &hardware(3072, 4),
+            relaxed,
+            "de",
+            &ModelTimings::default(),
+        );
+        assert_eq!(rec.model, None);
+        assert!(rec.reasons.iter().any(|r| r.contains("'de'")));
+    }
+
+    #[test]
+    fn test_falls_back_to_fastest_when_target_unreachable() {
+        let tight = LatencyTarget {
+            max_latency_ms: 1,
+            clip_secs: 10,
+        };
+        let rec = recommend(
+            &catalog(),
+            &hardware(16384, 2),
+            tight,
+            "en",
+            &ModelTimings::default(),
+        );
+        assert_eq!(rec.model.as_deref(), Some("base.en"));
+        assert!(rec.reasons.iter().any(|r| r.starts_with("No model meets")));
+    }
+
+    #[test]
+    fn test_measurements_override_estimates() {
+        let mut timings = ModelTimings::default();
+        timings.record("large-v3-turbo", 600, 10.0);
+        let rec = recommend(
+            &catalog(),
+            &hardware(16384, 8),
+            LatencyTarget::default(),
+            "en",
+            &timings,
+        );
+        assert_eq!(rec.model.as_deref(), Some("large-v3-turbo"));
+        assert!(rec.reasons.iter().any(|r| r.contains("(measured)")));
+    }
+
+    #[test]
+    fn test_untimed_models_scale_from_measured_ones() {
+        let mut timings = ModelTimings::default();
+        // base.en timed on a 5 s recording from a model comparison
+        timings.record("base.en", 240, 5.0);
+        let base_ms = timings.get("base.en").unwrap().scaled_to(10);
+        assert_eq!(base_ms, 300);
+
+        let latency_of = |rec: &Recommendation, name: &str| {
+            let assessment = rec.assessments.iter().find(|a| a.name == name).unwrap();
+            (assessment.latency_ms, assessment.latency_source)
+        };
+        let slow = recommend(
+            &catalog(),
+            &hardware(16384, 2),
+            LatencyTarget::default(),
+            "en",
+            &timings,
+        );
+        let fast = recommend(
+            &catalog(),
+            &hardware(16384, 16),
+            LatencyTarget::default(),
+            "en",
+            &timings,
+        );
+
+        assert_eq!(latency_of(&slow, "base.en"), (300, LatencySource::Measured));
+        let (small_ms, source) = latency_of(&slow, "small.en");
+        assert_eq!(source, LatencySource::Calibrated);
+        let catalog = catalog();
+        let expected = 300.0 * catalog[1].cost_units() / catalog[0].cost_units();
+        assert_eq!(small_ms, expected.round() as u64);
+        // Measurements, not the core count, decide
+        assert_eq!(latency_of(&fast, "small.en"), latency_of(&slow, "small.en"));
+
+        let relaxed = LatencyTarget {
+            max_latency_ms: 2000,
+            clip_secs: 10,
+        };
+        let rec = recommend(&catalog, &hardware(16384, 2), relaxed, "en", &timings);
+        assert_eq!(rec.model.as_deref(), Some("small.en"));
+        assert!(rec
+            .reasons
+            .iter()
+            .any(|r| r.contains("(scaled from models timed on this machine)")));
+
+        // Nothing timed: the hardware estimate is all there is
+        let rec = recommend(
+            &catalog,
+            &hardware(16384, 8),
+            LatencyTarget::default(),
+            "en",
+            &ModelTimings::default(),
+        );
+        assert_eq!(latency_of(&rec, "small.en").1, LatencySource::Estimated);
+    }
+
+    #[test]
+    fn test_faster_hardware_lowers_estimates() {
+        let model = candidate("large-v3", 2973, 6144, true);
+        let cpu = hardware(16384, 8);
+        let mut gpu = cpu.clone();
+        gpu.gpu_backend = Some("metal".to_string());
+        assert!(model.estimate_latency_ms(&gpu, 10) < model.estimate_latency_ms(&cpu, 10));
+        assert!(model.estimate_latency_ms(&cpu, 5) < model.estimate_latency_ms(&cpu, 10));
+    }
+
+    #[test]
+    fn test_latency_target_validation() {
+        assert!(LatencyTarget::default().validate().is_ok());
+        assert!(LatencyTarget {
+            max_latency_ms: 0,
+            clip_secs: 10
+        }
+        .validate()
+        .is_err());
+        assert!(LatencyTarget {
+            max_latency_ms: 1000,
+            clip_secs: 0
+        }
+        .validate()
+        .is_err());
+    }
+}
@@ -0,0 +1,407 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Where models are stored, under `model_storage` in the settings store
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelStorageSettings {
+    /// Custom models directory; `None` uses `<app data>/models`
+    pub models_dir: Option<String>,
+}
+
+impl ModelStorageSettings {
+    pub fn resolve(&self, default_dir: &Path) -> PathBuf {
+        match &self.models_dir {
+            Some(dir) => PathBuf::from(dir),
+            None => default_dir.to_path_buf(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageState {
+    Available,
+    /// The folder is gone, usually because the drive holding it is disconnected
+    Unavailable,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelStorageStatus {
+    pub path: String,
+    pub default_path: String,
+    pub is_default: bool,
+    pub state: StorageState,
+    pub free_bytes: Option<u64>,
+    /// Explanation to show when the folder is unavailable
+    pub message: Option<String>,
+}
+
+impl ModelStorageStatus {
+    pub fn new(models_dir: &Path, default_dir: &Path) -> Self {
+        let available = models_dir.is_dir();
+        Self {
+            path: models_dir.to_string_lossy().to_string(),
+            default_path: default_dir.to_string_lossy().to_string(),
+            is_default: models_dir == default_dir,
+            state: if available {
+                StorageState::Available
+            } else {
+                StorageState::Unavailable
+            },
+            free_bytes: free_space(models_dir),
+            message: (!available).then(|| unavailable_message(models_dir)),
+        }
+    }
+}
+
+/// Error shown instead of "model not found" while the models folder is missing
+pub fn unavailable_message(models_dir: &Path) -> String {
+    format!(
+        "The models folder {:?} is not available. Reconnect the drive that holds it, or choose another location in Settings.",
+        models_dir
+    )
+}
+
+/// Free space on the volume that holds `path` (or would, for a path not created yet)
+pub fn free_space(path: &Path) -> Option<u64> {
+    let existing = path.ancestors().find(|p| p.exists())?;
+    let existing = existing.canonicalize().ok()?;
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    disks
+        .list()
+        .iter()
+        .filter(|disk| existing.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| disk.available_space())
+}
+
+/// A file to move, relative to the models directory
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveItem {
+    pub relative_path: PathBuf,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MovePhase {
+    Copying,
+    Verifying,
+}
+
+/// Check that `target` can receive the models in `source`
+pub fn validate_target(source: &Path, target: &Path) -> Result<(), String> {
+    if !target.is_absolute() {
+        return Err("Choose an absolute path for the models folder".to_string());
+    }
+    if target.exists() {
+        if !target.is_dir() {
+            return Err(format!("{:?} is not a folder", target));
+        }
+        let mut entries =
+            std::fs::read_dir(target).map_err(|e| format!("Cannot read target folder: {}", e))?;
+        if entries.next().is_some() {
+            return Err(format!("Target folder {:?} must be empty", target));
+        }
+    }
+
+    // Compare real paths so symlinks cannot nest one folder inside the other
+    let source = source
+        .canonicalize()
+        .map_err(|e| format!("Cannot resolve models folder: {}", e))?;
+    let target = resolve_nonexistent(target)?;
+    if target.starts_with(&source) || source.starts_with(&target) {
+        return Err(
+            "The new location cannot be inside the current models folder, or contain it"
+                .to_string(),
+        );
+    }
+    Ok(())
+}
+
+/// Canonicalize the deepest existing ancestor and re-append the rest
+fn resolve_nonexistent(path: &Path) -> Result<PathBuf, String> {
+    let existing = path
+        .ancestors()
+        .find(|p| p.exists())
+        .ok_or_else(|| format!("No part of {:?} exists", path))?;
+    let rest = path.strip_prefix(existing).unwrap_or(Path::new(""));
+    existing
+        .canonicalize()
+        .map(|base| base.join(rest))
+        .map_err(|e| format!("Cannot resolve {:?}: {}", path, e))
+}
+
+/// Every regular file under `dir`, sorted for a stable order
+pub fn plan_move(dir: &Path) -> Result<Vec<MoveItem>, String> {
+    fn walk(root: &Path, dir: &Path, items: &mut Vec<MoveItem>) -> Result<(), String> {
+        let entries =
+            std::fs::read_dir(dir).map_err(|e| format!("Failed to read {:?}: {}", dir, e))?;
+        for entry in entries {
+            let entry = entry.map_err(|e| format!("Failed to read {:?}: {}", dir, e))?;
+            let file_type = entry
+                .file_type()
+                .map_err(|e| format!("Failed to inspect {:?}: {}", entry.path(), e))?;
+            let path = entry.path();
+            if file_type.is_dir() {
+                walk(root, &path, items)?;
+            } else if file_type.is_file() {
+                let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+                items.push(MoveItem {
+                    relative_path: path.strip_prefix(root).unwrap_or(&path).to_path_buf(),
+                    size,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    let mut items = Vec::new();
+    walk(dir, dir, &mut items)?;
+    items.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+    Ok(items)
+}
+
+/// Copy every file from `source` to `target`, then re-read each copy and compare its
+/// SHA-256 with the source. On failure everything written to `target` is removed.
+/// The source is left untouched; see `remove_moved_files`.
+pub fn copy_and_verify(
+    source: &Path,
+    target: &Path,
+    items: &[MoveItem],
+    progress: &dyn Fn(MovePhase, u64, u64),
+) -> Result<(), String> {
+    let created_target = !target.exists();
+    let result = copy_all(source, target, items, progress);
+    if result.is_err() {
+        if created_target {
+            let _ = std::fs::remove_dir_all(target);
+        } else {
+            for item in items {
+                let _ = std::fs::remove_file(target.join(&item.relative_path));
+            }
+        }
+    }
+    result
+}
+
+fn copy_all(
+    source: &Path,
+    target: &Path,
+    items: &[MoveItem],
+    progress: &dyn Fn(MovePhase, u64, u64),
+) -> Result<(), String> {
+    let total: u64 = items.iter().map(|i| i.size).sum();
+    std::fs::create_dir_all(target).map_err(|e| format!("Failed to create {:?}: {}", target, e))?;
+
+    let mut digests = Vec::with_capacity(items.len());
+    let mut copied = 0;
+    for item in items {
+        let from = source.join(&item.relative_path);
+        let to = target.join(&item.relative_path);
+        if let Some(parent) = to.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+        }
+        let digest = copy_file(&from, &to, &mut |n| {
+            copied += n;
+            progress(MovePhase::Copying, copied, total);
+        })?;
+        digests.push(digest);
+    }
+
+    let mut verified = 0;
+    for (item, expected) in items.iter().zip(digests) {
+        let to = target.join(&item.relative_path);
+        let actual = hash_with_progress(&to, &mut |n| {
+            verified += n;
+            progress(MovePhase::Verifying, verified, total);
+        })?;
+        if actual != expected {
+            return Err(format!(
+                "Copy of {:?} does not match the original; nothing was moved",
+                item.relative_path
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Stream `from` into `to`, returning the SHA-256 of what was read
+fn copy_file(from: &Path, to: &Path, on_chunk: &mut dyn FnMut(u64)) -> Result<String, String> {
+    let mut input =
+        std::fs::File::open(from).map_err(|e| format!("Failed to open {:?}: {}", from, e))?;
+    let mut output =
+        std::fs::File::create(to).map_err(|e| format!("Failed to create {:?}: {}", to, e))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 1024 * 1024];
+    loop {
+        let read = input
+            .read(&mut buffer)
+            .map_err(|e| format!("Failed to read {:?}: {}", from, e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        output
+            .write_all(&buffer[..read])
+            .map_err(|e| format!("Failed to write {:?}: {}", to, e))?;
+        on_chunk(read as u64);
+    }
+    // Make sure the data is on the target drive before the source is deleted
+    output
+        .sync_all()
+        .map_err(|e| format!("Failed to flush {:?}: {}", to, e))?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn hash_with_progress(path: &Path, on_chunk: &mut dyn FnMut(u64)) -> Result<String, String> {
+    let mut file =
+        std::fs::File::open(path).map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 1024 * 1024];
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        on_chunk(read as u64);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Delete the moved files from the old location, then any folders left empty.
+/// Failures are logged; the models are already safe in the new location.
+pub fn remove_moved_files(source: &Path, items: &[MoveItem]) {
+    for item in items {
+        let path = source.join(&item.relative_path);
+        if let Err(e) = std::fs::remove_file(&path) {
+            log::warn!("Failed to remove old model file {:?}: {}", path, e);
+        }
+    }
+
+    // Deepest folders first, so parents are empty by the time we reach them
+    let mut dirs: Vec<PathBuf> = items
+        .iter()
+        .flat_map(|item| {
+            item.relative_path
+                .ancestors()
+                .skip(1)
+                .filter(|p| !p.as_os_str().is_empty())
+                .map(|p| source.join(p))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    dirs.sort();
+    dirs.dedup();
+    dirs.sort_by_key(|d| std::cmp::Reverse(d.components().count()));
+    for dir in dirs {
+        let _ = std::fs::remove_dir(dir);
+    }
+    let _ = std::fs::remove_dir(source);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use tempfile::TempDir;
+
+    fn populate(dir: &Path) {
+        std::fs::create_dir_all(dir.join("parakeet/v3")).unwrap();
+        std::fs::write(dir.join("base.en.bin"), vec![7u8; 3 * 1024 * 1024]).unwrap();
+        std::fs::write(dir.join("custom_models.json"), b"[]").unwrap();
+        std::fs::write(dir.join("parakeet/v3/model.onnx"), b"weights").unwrap();
+    }
+
+    #[test]
+    fn test_plan_lists_nested_files() {
+        let temp_dir = TempDir::new().unwrap();
+        populate(temp_dir.path());
+        let items = plan_move(temp_dir.path()).unwrap();
+        let names: Vec<_> = items
+            .iter()
+            .map(|i| i.relative_path.to_string_lossy().replace('\\', "/"))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "base.en.bin",
+                "custom_models.json",
+                "parakeet/v3/model.onnx"
+            ]
+        );
+        assert_eq!(items[0].size, 3 * 1024 * 1024);
+    }
+
+    #[test]
+    fn test_copy_verify_and_cleanup() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("models");
+        let target = temp_dir.path().join("external/models");
+        populate(&source);
+        validate_target(&source, &target).unwrap();
+
+        let items = plan_move(&source).unwrap();
+        let last = Mutex::new((None, 0, 0));
+        copy_and_verify(&source, &target, &items, &|phase, done, total| {
+            *last.lock().unwrap() = (Some(phase), done, total);
+        })
+        .unwrap();
+
+        let total: u64 = items.iter().map(|i| i.size).sum();
+        assert_eq!(
+            *last.lock().unwrap(),
+            (Some(MovePhase::Verifying), total, total)
+        );
+        assert_eq!(
+            std::fs::read(target.join("parakeet/v3/model.onnx")).unwrap(),
+            b"weights"
+        );
+
+        remove_moved_files(&source, &items);
+        assert!(!source.join("base.en.bin").exists());
+        assert!(!source.join("parakeet").exists());
+        assert!(!source.exists());
+    }
+
+    #[test]
+    fn test_target_validation() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("models");
+        populate(&source);
+
+        assert!(validate_target(&source, Path::new("relative/models")).is_err());
+        assert!(validate_target(&source, &source.join("nested")).is_err());
+        assert!(validate_target(&source.join("parakeet"), &source).is_err());
+
+        let occupied = temp_dir.path().join("occupied");
+        std::fs::create_dir_all(&occupied).unwrap();
+        std::fs::write(occupied.join("file"), b"x").unwrap();
+        assert!(validate_target(&source, &occupied).is_err());
+
+        let empty = temp_dir.path().join("empty");
+        std::fs::create_dir_all(&empty).unwrap();
+        assert!(validate_target(&source, &empty).is_ok());
+    }
+
+    #[test]
+    fn test_status_reports_missing_drive() {
+        let temp_dir = TempDir::new().unwrap();
+        let missing = temp_dir.path().join("unplugged/models");
+        let status = ModelStorageStatus::new(&missing, temp_dir.path());
+        assert_eq!(status.state, StorageState::Unavailable);
+        assert!(!status.is_default);
+        assert!(status.message.unwrap().contains("Reconnect"));
+
+        let status = ModelStorageStatus::new(temp_dir.path(), temp_dir.path());
+        assert_eq!(status.state, StorageState::Available);
+        assert!(status.is_default);
+    }
+}
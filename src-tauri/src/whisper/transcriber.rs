@@ -1,4 +1,5 @@
 use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
 use std::path::Path;
 use std::time::Instant;
 use whisper_rs::{
@@ -6,6 +7,7 @@ use whisper_rs::{
     WhisperContext, WhisperContextParameters,
 };
 
+use crate::transcript::{Transcript, TranscriptSegment, TranscriptToken};
 use crate::utils::logger::*;
 
 /// Cached thread count for Whisper - calculated once at startup to avoid
@@ -20,6 +22,44 @@ static WHISPER_THREAD_COUNT: Lazy<i32> = Lazy::new(|| {
 #[cfg(debug_assertions)]
 use crate::utils::system_monitor;
 
+/// Shortest recording Whisper will transcribe
+pub const MIN_DURATION_SECS: f32 = 0.5;
+
+/// Whisper decoding settings. Defaults match what dictation has always used.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DecodingOptions {
+    /// Beam width; 0 switches to greedy sampling
+    pub beam_size: u32,
+    /// Candidates per greedy step (ignored for beam search)
+    pub best_of: u32,
+    pub temperature: f32,
+    /// Temperature increase on fallback
+    pub temperature_inc: f32,
+    pub no_speech_threshold: f32,
+    pub entropy_threshold: f32,
+    pub logprob_threshold: f32,
+    /// Thread override; defaults to all cores but one
+    pub threads: Option<i32>,
+    pub initial_prompt: Option<String>,
+}
+
+impl Default for DecodingOptions {
+    fn default() -> Self {
+        Self {
+            beam_size: 5,
+            best_of: 1,
+            temperature: 0.2,
+            temperature_inc: 0.2,
+            no_speech_threshold: 0.6,
+            entropy_threshold: 2.4,
+            logprob_threshold: -1.0,
+            threads: None,
+            initial_prompt: None,
+        }
+    }
+}
+
 pub struct Transcriber {
     context: WhisperContext,
 }
@@ -288,6 +328,7 @@ impl Transcriber {
         translate: bool,
     ) -> Result<String, String> {
         self.transcribe_with_cancellation(audio_path, language, translate, || false)
+            .map(|transcript| transcript.text)
     }
 
     pub fn transcribe_with_cancellation<F>(
@@ -296,7 +337,7 @@ impl Transcriber {
         language: Option<&str>,
         translate: bool,
         should_cancel: F,
-    ) -> Result<String, String>
+    ) -> Result<Transcript, String>
     where
         F: Fn() -> bool,
     {
@@ -486,11 +527,90 @@ impl Transcriber {
             resampled_audio.len() as f32 / 16_000_f32
         );
 
-        // Create transcription parameters - use BeamSearch for better accuracy
-        let mut params = FullParams::new(SamplingStrategy::BeamSearch {
-            beam_size: 5,
-            patience: -1.0,
-        });
+        self.run_inference(
+            &resampled_audio,
+            language,
+            translate,
+            &DecodingOptions::default(),
+            transcription_start,
+        )
+    }
+
+    /// Transcribe audio that is already 16kHz mono f32 (e.g. from in-memory capture),
+    /// skipping the WAV read, downmix and resampling steps.
+    pub fn transcribe_samples_with_cancellation<F>(
+        &self,
+        audio: &[f32],
+        language: Option<&str>,
+        translate: bool,
+        should_cancel: F,
+    ) -> Result<Transcript, String>
+    where
+        F: Fn() -> bool,
+    {
+        let transcription_start = Instant::now();
+
+        #[cfg(debug_assertions)]
+        system_monitor::log_resources_before_operation("TRANSCRIPTION");
+
+        log_start("TRANSCRIPTION");
+        log_with_context(
+            log::Level::Debug,
+            "Starting transcription",
+            &[
+                ("source", "memory"),
+                ("samples", &audio.len().to_string().as_str()),
+                ("language", language.unwrap_or("auto")),
+                ("translate", &translate.to_string().as_str()),
+            ],
+        );
+
+        if should_cancel() {
+            log::info!("[TRANSCRIPTION_DEBUG] Transcription cancelled before starting");
+            return Err("Transcription cancelled".to_string());
+        }
+
+        self.run_inference(
+            audio,
+            language,
+            translate,
+            &DecodingOptions::default(),
+            transcription_start,
+        )
+    }
+
+    /// Transcribe 16kHz mono samples with explicit decoding settings (benchmarks, tuning)
+    pub fn transcribe_samples_with_options(
+        &self,
+        audio: &[f32],
+        language: Option<&str>,
+        translate: bool,
+        decoding: &DecodingOptions,
+    ) -> Result<String, String> {
+        self.run_inference(audio, language, translate, decoding, Instant::now())
+            .map(|transcript| transcript.text)
+    }
+
+    /// Run Whisper on 16kHz mono samples and collect the timed segments
+    fn run_inference(
+        &self,
+        resampled_audio: &[f32],
+        language: Option<&str>,
+        translate: bool,
+        decoding: &DecodingOptions,
+        transcription_start: Instant,
+    ) -> Result<Transcript, String> {
+        // Create transcription parameters - BeamSearch by default for better accuracy
+        let mut params = if decoding.beam_size > 0 {
+            FullParams::new(SamplingStrategy::BeamSearch {
+                beam_size: decoding.beam_size as i32,
+                patience: -1.0,
+            })
+        } else {
+            FullParams::new(SamplingStrategy::Greedy {
+                best_of: decoding.best_of.max(1) as i32,
+            })
+        };
 
         // Set language - use centralized validation
         log::info!("[LANGUAGE] Received language: {:?}", language);
@@ -524,15 +644,21 @@ impl Transcriber {
             params.set_translate(false);
         }
 
-        // Use cached thread count (calculated once at startup)
-        params.set_n_threads(*WHISPER_THREAD_COUNT);
-        log::debug!("[PERFORMANCE] Using {} threads for transcription", *WHISPER_THREAD_COUNT);
+        // Use cached thread count (calculated once at startup) unless overridden
+        let n_threads = decoding.threads.unwrap_or(*WHISPER_THREAD_COUNT).max(1);
+        params.set_n_threads(n_threads);
+        log::debug!(
+            "[PERFORMANCE] Using {} threads for transcription",
+            n_threads
+        );
 
         params.set_no_context(false); // Enable context for better word recognition
         params.set_print_special(false);
         params.set_print_progress(false);
         params.set_print_realtime(false);
         params.set_print_timestamps(false);
+        // Per-token times and probabilities for subtitles and confidence display
+        params.set_token_timestamps(true);
 
         // Suppress blank outputs to avoid empty transcriptions
         params.set_suppress_blank(true);
@@ -541,21 +667,22 @@ impl Transcriber {
         params.set_suppress_nst(true);
 
         // Adjust speech detection threshold
-        params.set_no_speech_thold(0.6); // Default value, as higher values can cause issues
+        params.set_no_speech_thold(decoding.no_speech_threshold); // 0.6 default, as higher values can cause issues
 
         // Quality thresholds - use more lenient values to avoid rejecting valid speech
         // Default entropy threshold is 2.4, we'll keep it default to avoid over-filtering
-        params.set_entropy_thold(2.4); // Default value - prevents filtering out valid but uncertain speech
+        params.set_entropy_thold(decoding.entropy_threshold); // 2.4 default - prevents filtering out valid but uncertain speech
 
         // Use default log probability threshold to avoid being too strict
-        params.set_logprob_thold(-1.0); // Default value - balanced probability requirements
+        params.set_logprob_thold(decoding.logprob_threshold); // -1.0 default - balanced probability requirements
 
         // Set initial prompt to help with context
-        params.set_initial_prompt(""); // Empty prompt to avoid biasing the model
+        // Empty by default to avoid biasing the model
+        params.set_initial_prompt(decoding.initial_prompt.as_deref().unwrap_or(""));
 
         // Temperature settings - slight randomness helps avoid repetitive loops
-        params.set_temperature(0.2); // Small amount of randomness instead of deterministic
-        params.set_temperature_inc(0.2); // Increase by 0.2 on fallback (default)
+        params.set_temperature(decoding.temperature); // Small amount of randomness instead of deterministic
+        params.set_temperature_inc(decoding.temperature_inc); // Increase by 0.2 on fallback (default)
         params.set_max_initial_ts(1.0); // Limit initial timestamp search
 
         // Limit segment length to prevent runaway hallucinations
@@ -574,8 +701,7 @@ impl Transcriber {
         let samples_count = resampled_audio.len();
         let duration_seconds = samples_count as f32 / 16_000_f32;
 
-        // Check minimum duration (0.5 seconds)
-        if duration_seconds < 0.5 {
+        if duration_seconds < MIN_DURATION_SECS {
             let error = format!("Recording too short");
             log::warn!("[TRANSCRIPTION_DEBUG] {}", error);
             return Err(error);
@@ -599,7 +725,7 @@ impl Transcriber {
             ],
         );
 
-        match state.full(params, &resampled_audio) {
+        match state.full(params, resampled_audio) {
             Ok(_) => {
                 let inference_time = inference_start.elapsed();
                 let inference_ms = inference_time.as_millis();
@@ -654,7 +780,9 @@ impl Transcriber {
             num_segments
         );
 
+        let eot = self.context.token_eot();
         let mut text = String::new();
+        let mut segments = Vec::with_capacity(num_segments.max(0) as usize);
         for i in 0..num_segments {
             let segment = state.full_get_segment_text(i).map_err(|e| {
                 let error = format!("Failed to get segment {}: {}", i, e);
@@ -665,6 +793,37 @@ impl Transcriber {
             log::debug!("[TRANSCRIPTION_DEBUG] Segment {}: '{}'", i, segment);
             text.push_str(&segment);
             text.push(' ');
+
+            // Timings are a bonus; a segment without them still carries its text
+            let mut tokens = Vec::new();
+            for j in 0..state.full_n_tokens(i).unwrap_or(0) {
+                let Ok(data) = state.full_get_token_data(i, j) else {
+                    continue;
+                };
+                // Timestamp and control tokens sort after end-of-text
+                if data.id >= eot {
+                    continue;
+                }
+                let Ok(token_text) = state.full_get_token_text_lossy(i, j) else {
+                    continue;
+                };
+                tokens.push(TranscriptToken {
+                    text: token_text,
+                    start: whisper_time(data.t0),
+                    end: whisper_time(data.t1),
+                    confidence: Some(data.p.clamp(0.0, 1.0)),
+                });
+            }
+            segments.push(
+                TranscriptSegment {
+                    text: segment.trim().to_string(),
+                    start: state.full_get_segment_t0(i).ok().and_then(whisper_time),
+                    end: state.full_get_segment_t1(i).ok().and_then(whisper_time),
+                    confidence: None,
+                    tokens,
+                }
+                .with_token_confidence(),
+            );
         }
 
         let result = text.trim().to_string();
@@ -739,10 +898,20 @@ impl Transcriber {
             );
         }
 
-        Ok(result)
+        Ok(Transcript {
+            text: result,
+            segments,
+            language: final_lang.map(str::to_string),
+            duration: Some(duration_seconds),
+        })
     }
 }
 
+/// Whisper reports times in 10 ms steps, negative when it has none
+fn whisper_time(centiseconds: i64) -> Option<f32> {
+    (centiseconds >= 0).then(|| centiseconds as f32 / 100.0)
+}
+
 /// Convert multi-channel audio to mono by averaging all channels
 ///
 /// # Arguments
@@ -831,4 +1000,12 @@ mod tests {
         let result = convert_multichannel_to_mono(&audio, 0);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_whisper_time_converts_centiseconds() {
+        assert_eq!(whisper_time(0), Some(0.0));
+        assert_eq!(whisper_time(1250), Some(12.5));
+        // whisper.cpp leaves -1 when it computed no time
+        assert_eq!(whisper_time(-1), None);
+    }
 }
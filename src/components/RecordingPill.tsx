@@ -165,6 +165,18 @@ export function RecordingPill() {
       ),
     );
 
+    // Listen for model fallbacks (model missing, or it failed mid-transcription)
+    unlisteners.push(
+      listen<{ requested?: string; fallback?: string }>(
+        "model-fallback",
+        (event) => {
+          const requested = event.payload?.requested || "Selected model";
+          const fallback = event.payload?.fallback || "another model";
+          setFeedbackWithTimeout(`${requested} → ${fallback}`, 3500);
+        },
+      ),
+    );
+
     // Listen for transcription errors
     unlisteners.push(
       listen<string>("transcription-error", (event) => {
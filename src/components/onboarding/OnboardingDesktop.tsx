@@ -9,6 +9,7 @@ import type { useModelManagement } from "@/hooks/useModelManagement";
 import { formatHotkey } from "@/lib/hotkey-utils";
 import { isMacOS } from "@/lib/platform";
 import { cn } from "@/lib/utils";
+import type { ModelRecommendation } from "@/types";
 import { invoke } from "@tauri-apps/api/core";
 import { open } from "@tauri-apps/plugin-shell";
 import {
@@ -65,6 +66,8 @@ export const OnboardingDesktop = function OnboardingDesktop({
     new Set(),
   );
   const [isEditingHotkey, setIsEditingHotkey] = useState(false);
+  const [recommendation, setRecommendation] =
+    useState<ModelRecommendation | null>(null);
 
   // Convert hook states to onboarding format
   const permissions = {
@@ -118,6 +121,16 @@ export const OnboardingDesktop = function OnboardingDesktop({
   const currentIndex = steps.findIndex((s) => s.id === currentStep);
   // const progress = ((currentIndex + 1) / steps.length) * 100;
 
+  // Suggest a model for this machine once the user reaches the model list
+  useEffect(() => {
+    if (currentStep !== "models" || recommendation) return;
+    invoke<ModelRecommendation>("recommend_model")
+      .then((result) => setRecommendation(result ?? null))
+      .catch((error) => {
+        console.error("[OnboardingDesktop] Model recommendation failed:", error);
+      });
+  }, [currentStep, recommendation]);
+
   useEffect(() => {
     if (currentStep !== "permissions") return;
     checkPermissions();
@@ -522,6 +535,21 @@ export const OnboardingDesktop = function OnboardingDesktop({
                   </span>
                 </div>
 
+                {recommendation?.model && (
+                  <div className="flex items-start gap-3 rounded-lg border bg-card p-3">
+                    <Star className="w-4 h-4 mt-0.5 flex-shrink-0 fill-yellow-500 text-yellow-500" />
+                    <div className="space-y-1">
+                      <p className="text-sm font-medium">
+                        Best fit for this computer:{" "}
+                        {recommendation.display_name ?? recommendation.model}
+                      </p>
+                      <p className="text-xs text-muted-foreground">
+                        {recommendation.reasons.join(". ")}.
+                      </p>
+                    </div>
+                  </div>
+                )}
+
                 <div className="bg-card rounded-lg border">
                   <div className="max-h-[220px] overflow-y-auto">
                     <div className="space-y-3 p-4">
@@ -530,7 +558,14 @@ export const OnboardingDesktop = function OnboardingDesktop({
                         if (!model) return null;
                         const progress = downloadProgress[name];
                         return (
-                          <div key={name} className="relative">
+                          <div
+                            key={name}
+                            className={cn(
+                              "relative",
+                              name === recommendation?.model &&
+                                "rounded-lg ring-2 ring-yellow-500/40",
+                            )}
+                          >
                             <ModelCard
                               name={name}
                               model={model}
@@ -37,6 +37,31 @@ export const isCloudModel = (model: ModelInfo): model is CloudModelInfo =>
 export const isLocalModel = (model: ModelInfo): model is LocalModelInfo =>
   model.kind === 'local';
 
+export type LatencySource = 'measured' | 'calibrated' | 'estimated';
+
+export interface ModelAssessment {
+  name: string;
+  display_name: string;
+  latency_ms: number;
+  latency_source: LatencySource;
+  fits_memory: boolean;
+  supports_language: boolean;
+  meets_target: boolean;
+  accuracy_score: number;
+  downloaded: boolean;
+}
+
+// Result of the `recommend_model` command
+export interface ModelRecommendation {
+  model: string | null;
+  display_name: string | null;
+  latency_ms: number | null;
+  target: { max_latency_ms: number; clip_secs: number };
+  language: string;
+  reasons: string[];
+  assessments: ModelAssessment[];
+}
+
 export type RecordingMode = 'toggle' | 'push_to_talk';
 
 export interface AppSettings {